@@ -0,0 +1,15 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use cargo_udeps::bench::Matching;
+
+fn matching(c :&mut Criterion) {
+	let mut group = c.benchmark_group("matching");
+	for members in [50, 500] {
+		let matching = Matching::new(members, 40);
+		group.bench_with_input(BenchmarkId::from_parameter(members), &matching, |b, matching| b.iter(|| matching.run()));
+	}
+	group.finish();
+}
+
+criterion_group!(benches, matching);
+criterion_main!(benches);
@@ -0,0 +1,399 @@
+//! The per-unit information the unused dependencies are determined from,
+//! and its exchange between machines.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use cargo::core::package_id::PackageId;
+use cargo::core::shell::Shell;
+use cargo::core::Package;
+use cargo::util::context::GlobalContext;
+use cargo::util::{Progress, ProgressStyle};
+use cargo::CargoResult;
+use serde::{Deserialize, Serialize};
+
+use crate::timings::Timings;
+use crate::{Backend, CmdInfo, ExecData, ShellExt};
+
+/// The name of the file `--export-analysis` writes into the given directory.
+const FILE_NAME :&str = "udeps-analysis.json";
+
+/// How often the progress of the parses is checked on.
+const PROGRESS_INTERVAL :Duration = Duration::from_millis(50);
+
+#[derive(Debug, Default)]
+pub(crate) struct Analysis {
+	/// The units of the targets that were asked for.
+	pub(crate) units :Vec<Unit>,
+	/// The units of the members as checked with `--cfg doc`, with `--doc-cfg`.
+	pub(crate) doc_cfg_units :Vec<Unit>,
+	/// The packages of the artifacts, by base name (like `libfoo-0123456789abcdef`).
+	pub(crate) lib_stems :HashMap<String, PackageId>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Unit {
+	pub(crate) pkg :PackageId,
+	/// Whether this is a build script.
+	pub(crate) custom_build :bool,
+	/// Whether this is a unit of tests, benches or examples.
+	pub(crate) dev :bool,
+	/// The target, as described to users.
+	pub(crate) target :String,
+	/// The `--target` it is compiled for, or `None` for the host.
+	pub(crate) compile_target :Option<String>,
+	pub(crate) extern_crate_names :HashSet<String>,
+	/// The extern crate names by the file stems of their `--extern` paths.
+	pub(crate) extern_stems :HashMap<String, String>,
+	/// The file stems of everything the unit was found to depend on.
+	pub(crate) used_stems :Vec<String>,
+}
+
+impl Unit {
+	pub(crate) fn collect(cmd_info :&CmdInfo, data :&ExecData, backend :Backend, shell :&mut Shell) -> CargoResult<Self> {
+		Self::announce(cmd_info, data, backend, shell)?;
+		Ok(Self::new(cmd_info, Self::used_stems(cmd_info, data, backend)?))
+	}
+
+	fn announce(cmd_info :&CmdInfo, data :&ExecData, backend :Backend, shell :&mut Shell) -> CargoResult<()> {
+		match backend {
+			Backend::Depinfo => shell.verbose_info(format_args!("Loading depinfo from {:?}", cmd_info.get_depinfo_path(&data.remap))),
+			Backend::Lint => Ok(()),
+		}
+	}
+
+	/// Reads what the unit depends on, which doesn't need the shell so that it can be done on other threads.
+	fn used_stems(cmd_info :&CmdInfo, data :&ExecData, backend :Backend) -> CargoResult<Vec<String>> {
+		Ok(match backend {
+			Backend::Depinfo => cmd_info
+				.get_depinfo(&data.remap)?
+				.iter()
+				.filter_map(|dep| Some(dep.file_stem()?.to_str()?.to_owned()))
+				.collect(),
+			// Everything that was passed with `--extern` and not reported by the lint
+			Backend::Lint => {
+				let unused = data.unused_externs.get(&cmd_info.get_artifact_base_name());
+				cmd_info.extern_stems
+					.iter()
+					.filter(|(_, name)| !unused.is_some_and(|unused| unused.contains(*name)))
+					.map(|(stem, _)| stem.clone())
+					.collect()
+			},
+		})
+	}
+
+	fn new(cmd_info :&CmdInfo, used_stems :Vec<String>) -> Self {
+		Self {
+			pkg : cmd_info.pkg,
+			custom_build : cmd_info.custom_build,
+			dev : cmd_info.dev,
+			target : cmd_info.target.clone(),
+			compile_target : cmd_info.compile_target.clone(),
+			extern_crate_names : cmd_info.extern_crate_names.clone(),
+			extern_stems : cmd_info.extern_stems.clone(),
+			used_stems,
+		}
+	}
+}
+
+/// The `.d` file of a unit that is missing, or has no rule for its artifact,
+/// so what the unit depends on is unknown.
+#[derive(Debug)]
+pub(crate) struct MissingDepinfo {
+	pkg :PackageId,
+	target :String,
+	path :PathBuf,
+	/// Whether the file is there, but without a rule for the artifact.
+	stale :bool,
+	/// The other `.d` files of the crate in the same directory.
+	candidates :Vec<String>,
+}
+
+impl MissingDepinfo {
+	pub(crate) fn new(cmd_info :&CmdInfo, path :PathBuf, stale :bool) -> Self {
+		let prefix = format!("{}-", cmd_info.crate_name);
+		let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_owned();
+		let mut candidates = path
+			.parent()
+			.and_then(|dir| std::fs::read_dir(dir).ok())
+			.into_iter()
+			.flatten()
+			.filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+			.filter(|name| name.starts_with(&prefix) && name.ends_with(".d") && *name != file_name)
+			.collect::<Vec<_>>();
+		candidates.sort();
+		Self { pkg : cmd_info.pkg, target : cmd_info.target.clone(), path, stale, candidates }
+	}
+}
+
+impl fmt::Display for MissingDepinfo {
+	fn fmt(&self, f :&mut fmt::Formatter<'_>) -> fmt::Result {
+		let what = if self.stale { "has no rule for the artifact of" } else { "is missing for" };
+		writeln!(f, "the dep-info file `{}` {} {} of `{}`", self.path.display(), what, self.target, self.pkg)?;
+		if self.candidates.is_empty() {
+			writeln!(f, "  there are no other dep-info files of the crate in its directory")?;
+		} else {
+			writeln!(f, "  other dep-info files of the crate in its directory: {}", self.candidates.join(", "))?;
+		}
+		write!(
+			f,
+			"  this happens if the build was interrupted or the target directory changed while it ran, \
+			if a `RUSTC_WORKSPACE_WRAPPER` changed the `-C extra-filename` or `--out-dir` of rustc, \
+			or if `--remap-path-prefix` doesn't match the paths of the build",
+		)
+	}
+}
+
+impl std::error::Error for MissingDepinfo {}
+
+/// Warns about the units [`Analysis::collect_parsed`] skipped.
+fn warn_skipped(skipped :&[anyhow::Error], shell :&mut Shell) -> CargoResult<()> {
+	for e in skipped {
+		shell.warn(format_args!(
+			"{}\n  the unit is skipped, so its dependencies may be reported wrongly, \
+			unless another unit uses them (`--strict` makes this an error)",
+			e,
+		))?;
+	}
+	Ok(())
+}
+
+impl Analysis {
+	pub(crate) fn collect(data :&ExecData, backend :Backend, timings :&mut Timings, shell :&mut Shell) -> CargoResult<Self> {
+		for cmd_info in data.relevant_cmd_infos.iter().chain(&data.doc_cfg_cmd_infos) {
+			Unit::announce(cmd_info, data, backend, shell)?;
+		}
+		let (analysis, skipped) = Self::collect_parsed(data, backend, timings, |_, _| Ok(()))?;
+		warn_skipped(&skipped, shell)?;
+		Ok(analysis)
+	}
+
+	/// Like [`Analysis::collect`], with a progress bar of the units parsed so far,
+	/// as there's no other sign of life between the build and the report.
+	pub(crate) fn collect_with_progress(
+		data :&ExecData,
+		backend :Backend,
+		timings :&mut Timings,
+		gctx :&GlobalContext,
+	) -> CargoResult<Self> {
+		for cmd_info in data.relevant_cmd_infos.iter().chain(&data.doc_cfg_cmd_infos) {
+			Unit::announce(cmd_info, data, backend, &mut gctx.shell())?;
+		}
+		let mut progress = Progress::with_style("Analyzing", ProgressStyle::Ratio, gctx);
+		let (analysis, skipped) = Self::collect_parsed(data, backend, timings, |parsed, total| progress.tick(parsed, total, ""))?;
+		drop(progress);
+		warn_skipped(&skipped, &mut gctx.shell())?;
+		Ok(analysis)
+	}
+
+	/// Parses the units of `data`, which have been announced already. `on_progress` is
+	/// called with the number of parsed units and of all of them every now and then.
+	/// Units whose `.d` file is missing or stale are skipped and returned, unless `--strict` is given.
+	fn collect_parsed(
+		data :&ExecData,
+		backend :Backend,
+		timings :&mut Timings,
+		mut on_progress :impl FnMut(usize, usize) -> CargoResult<()>,
+	) -> CargoResult<(Self, Vec<anyhow::Error>)> {
+		let mut skipped = Vec::new();
+		let total = data.relevant_cmd_infos.len() + data.doc_cfg_cmd_infos.len();
+		let units = Self::collect_units(&data.relevant_cmd_infos, data, backend, &mut skipped, |parsed| on_progress(parsed, total))?
+			.into_iter()
+			.zip(&data.relevant_cmd_infos)
+			.filter_map(|(unit, cmd_info)| {
+				let (unit, duration) = unit?;
+				timings.record(cmd_info.pkg, &cmd_info.crate_name, "analysis", duration);
+				Some(unit)
+			})
+			.collect();
+		let offset = data.relevant_cmd_infos.len();
+		let doc_cfg_units = Self::collect_units(&data.doc_cfg_cmd_infos, data, backend, &mut skipped, |parsed| on_progress(offset + parsed, total))?
+			.into_iter()
+			.flatten()
+			.map(|(unit, _)| unit)
+			.collect();
+		Ok((Self { units, doc_cfg_units, lib_stems : Self::lib_stems(data) }, skipped))
+	}
+
+	/// Reads the units on as many threads as there are cores, as parsing the depinfo of
+	/// large workspaces takes a while. They are returned in the order of `cmd_infos`,
+	/// with the time each of them took, or `None` if they are skipped and added to `skipped`.
+	/// `on_progress` is told how many are parsed while the threads are at it.
+	fn collect_units(
+		cmd_infos :&[CmdInfo],
+		data :&ExecData,
+		backend :Backend,
+		skipped :&mut Vec<anyhow::Error>,
+		mut on_progress :impl FnMut(usize) -> CargoResult<()>,
+	) -> CargoResult<Vec<Option<(Unit, Duration)>>> {
+		let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+		let chunk_size = cmd_infos.len().div_ceil(threads).max(1);
+		let parsed = &AtomicUsize::new(0);
+		let (progress, used_stems) = thread::scope(|scope| {
+			let handles = cmd_infos
+				.chunks(chunk_size)
+				.map(|chunk| scope.spawn(move || {
+					chunk
+						.iter()
+						.map(|cmd_info| {
+							let start = Instant::now();
+							let used_stems = Unit::used_stems(cmd_info, data, backend);
+							parsed.fetch_add(1, Ordering::Relaxed);
+							used_stems.map(|used_stems| (used_stems, start.elapsed()))
+						})
+						.collect::<Vec<_>>()
+				}))
+				.collect::<Vec<_>>();
+			let mut progress = Ok(());
+			while progress.is_ok() && !handles.iter().all(|handle| handle.is_finished()) {
+				progress = on_progress(parsed.load(Ordering::Relaxed));
+				thread::sleep(PROGRESS_INTERVAL);
+			}
+			let used_stems = handles
+				.into_iter()
+				.flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+				.collect::<Vec<_>>();
+			(progress, used_stems)
+		});
+		progress?;
+		cmd_infos
+			.iter()
+			.zip(used_stems)
+			.map(|(cmd_info, used_stems)| match used_stems {
+				Ok((used_stems, duration)) => Ok(Some((Unit::new(cmd_info, used_stems), duration))),
+				Err(e) if !data.strict && e.is::<MissingDepinfo>() => {
+					skipped.push(e);
+					Ok(None)
+				},
+				Err(e) => Err(e),
+			})
+			.collect()
+	}
+
+	/// The packages of the artifacts of everything that was built so far.
+	pub(crate) fn lib_stems(data :&ExecData) -> HashMap<String, PackageId> {
+		data.all_cmd_infos
+			.iter()
+			.map(|cmd_info| (cmd_info.get_artifact_base_name(), cmd_info.pkg))
+			.collect()
+	}
+
+	/// Writes the analysis into `dir`, in a form that doesn't depend on paths of this machine.
+	pub(crate) fn export(&self, dir :&Path, shell :&mut Shell) -> CargoResult<()> {
+		let exported = Exported {
+			units : self.units.iter().map(ExportedUnit::from).collect(),
+			doc_cfg_units : self.doc_cfg_units.iter().map(ExportedUnit::from).collect(),
+			lib_stems : self.lib_stems.iter().map(|(stem, &pkg)| (stem.clone(), pkg.into())).collect(),
+		};
+		cargo_util::paths::create_dir_all(dir)?;
+		let path = dir.join(FILE_NAME);
+		shell.info(format_args!("Writing the analysis to {:?}", path))?;
+		cargo_util::paths::write(&path, serde_json::to_string(&exported)?)
+	}
+
+	/// Reads an analysis written by [`Analysis::export`], possibly on another machine.
+	///
+	/// Packages are matched by name and version.
+	pub(crate) fn import(dir :&Path, packages :&HashMap<PackageId, &Package>, shell :&mut Shell) -> CargoResult<Self> {
+		use anyhow::Context;
+		let path = dir.join(FILE_NAME);
+		let exported :Exported = serde_json::from_str(&cargo_util::paths::read(&path)?)
+			.with_context(|| format!("could not parse the analysis `{}`", path.display()))?;
+
+		let by_key = packages
+			.keys()
+			.map(|&id| (ExportedPackage::from(id), id))
+			.collect::<HashMap<_, _>>();
+		let mut import_units = |exported_units :Vec<ExportedUnit>| -> CargoResult<Vec<Unit>> {
+			let mut units = Vec::new();
+			for unit in exported_units {
+				match by_key.get(&unit.package) {
+					Some(&pkg) => units.push(Unit {
+						pkg,
+						custom_build : unit.custom_build,
+						dev : unit.dev,
+						target : unit.target,
+						compile_target : unit.compile_target,
+						extern_crate_names : unit.extern_crate_names.into_iter().collect(),
+						extern_stems : unit.extern_stems.into_iter().collect(),
+						used_stems : unit.used_stems,
+					}),
+					None => shell.warn(format!(
+						"`{} v{}` of the analysis is not in this workspace's dependency graph, skipping",
+						unit.package.name, unit.package.version,
+					))?,
+				}
+			}
+			Ok(units)
+		};
+		let units = import_units(exported.units)?;
+		let doc_cfg_units = import_units(exported.doc_cfg_units)?;
+		let lib_stems = exported.lib_stems
+			.into_iter()
+			.filter_map(|(stem, pkg)| Some((stem, *by_key.get(&pkg)?)))
+			.collect();
+		Ok(Self { units, doc_cfg_units, lib_stems })
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct Exported {
+	units :Vec<ExportedUnit>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	doc_cfg_units :Vec<ExportedUnit>,
+	lib_stems :BTreeMap<String, ExportedPackage>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedUnit {
+	package :ExportedPackage,
+	/// Missing in analyses of older versions
+	#[serde(default)]
+	custom_build :bool,
+	/// Missing in analyses of older versions
+	#[serde(default)]
+	dev :bool,
+	/// Missing in analyses of older versions
+	#[serde(default)]
+	target :String,
+	/// Missing in analyses of older versions
+	#[serde(default)]
+	compile_target :Option<String>,
+	extern_crate_names :Vec<String>,
+	/// Missing in analyses of older versions
+	#[serde(default)]
+	extern_stems :BTreeMap<String, String>,
+	used_stems :Vec<String>,
+}
+
+impl From<&Unit> for ExportedUnit {
+	fn from(unit :&Unit) -> Self {
+		Self {
+			package : unit.pkg.into(),
+			custom_build : unit.custom_build,
+			dev : unit.dev,
+			target : unit.target.clone(),
+			compile_target : unit.compile_target.clone(),
+			extern_crate_names : unit.extern_crate_names.iter().cloned().collect(),
+			extern_stems : unit.extern_stems.iter().map(|(stem, name)| (stem.clone(), name.clone())).collect(),
+			used_stems : unit.used_stems.clone(),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct ExportedPackage {
+	name :String,
+	version :String,
+}
+
+impl From<PackageId> for ExportedPackage {
+	fn from(id :PackageId) -> Self {
+		Self { name : id.name().to_string(), version : id.version().to_string() }
+	}
+}
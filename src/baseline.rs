@@ -0,0 +1,67 @@
+//! Unused dependencies that are accepted for now, so that only new ones make a run fail.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use cargo::core::dependency::DepKind;
+use cargo::CargoResult;
+use serde::{Deserialize, Serialize};
+
+/// The contents of a `--baseline` file, as written by `--write-baseline`.
+///
+/// Packages are recorded by name only, so that the baseline stays valid across versions
+/// and machines.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Baseline {
+	unused_deps :BTreeMap<String, Deps>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Deps {
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	normal :BTreeSet<String>,
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	development :BTreeSet<String>,
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	build :BTreeSet<String>,
+}
+
+impl Deps {
+	fn of_kind(&mut self, kind :DepKind) -> &mut BTreeSet<String> {
+		match kind {
+			DepKind::Normal => &mut self.normal,
+			DepKind::Development => &mut self.development,
+			DepKind::Build => &mut self.build,
+		}
+	}
+}
+
+impl Baseline {
+	pub(crate) fn read(path :&Path) -> CargoResult<Self> {
+		use anyhow::Context;
+		serde_json::from_str(&cargo_util::paths::read(path)?)
+			.with_context(|| format!("could not parse the baseline `{}`", path.display()))
+	}
+
+	pub(crate) fn write(&self, path :&Path) -> CargoResult<()> {
+		cargo_util::paths::write(path, serde_json::to_string_pretty(self)? + "\n")
+	}
+
+	pub(crate) fn insert(&mut self, package :&str, kind :DepKind, dependency :&str) {
+		self.unused_deps.entry(package.to_owned()).or_default().of_kind(kind).insert(dependency.to_owned());
+	}
+
+	/// Removes the dependency, returning whether it was in the baseline.
+	pub(crate) fn remove(&mut self, package :&str, kind :DepKind, dependency :&str) -> bool {
+		self.unused_deps.get_mut(package).is_some_and(|deps| deps.of_kind(kind).remove(dependency))
+	}
+
+	/// The dependencies of `package`, with their kinds.
+	pub(crate) fn of_package(&self, package :&str) -> impl Iterator<Item = (DepKind, &str)> {
+		self.unused_deps
+			.get(package)
+			.into_iter()
+			.flat_map(|deps| [(DepKind::Normal, &deps.normal), (DepKind::Development, &deps.development), (DepKind::Build, &deps.build)])
+			.flat_map(|(kind, names)| names.iter().map(move |name| (kind, name.as_str())))
+	}
+}
@@ -0,0 +1,68 @@
+//! Synthetic workspaces to measure the matching of the units with, for `benches/matching.rs`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use cargo::core::{PackageId, SourceId};
+
+use crate::{analysis, DependencyNames, Usage};
+
+/// A workspace whose members each have one unit and the same normal dependencies,
+/// of which the unit uses every other one.
+pub struct Matching {
+	dependency_names :HashMap<PackageId, DependencyNames>,
+	units :Vec<analysis::Unit>,
+	lib_stems :HashMap<String, PackageId>,
+}
+
+impl Matching {
+	pub fn new(members :usize, dependencies :usize) -> Self {
+		let source_id = |name :&str| SourceId::for_path(&Path::new("/bench").join(name)).unwrap();
+		let dependency_ids = (0..dependencies)
+			.map(|i| {
+				let name = format!("dep_{}", i);
+				PackageId::try_new(&*name, "0.1.0", source_id(&name)).unwrap()
+			})
+			.collect::<Vec<_>>();
+		let stem = |i :usize| format!("libdep_{}-{:016x}", i, i);
+
+		let names = || {
+			let mut names = DependencyNames::default();
+			for &id in &dependency_ids {
+				let name = id.name();
+				names.normal.by_extern_crate_name.insert(name.as_str(), name);
+				names.normal.by_package_id.insert(id, name);
+				names.normal.by_lib_true_snakecased_name.entry(name).or_insert_with(HashSet::new).insert(name);
+			}
+			names
+		};
+		let mut dependency_names = HashMap::new();
+		let mut units = Vec::new();
+		for member in 0..members {
+			let name = format!("member_{}", member);
+			let pkg = PackageId::try_new(&*name, "0.1.0", source_id(&name)).unwrap();
+			units.push(analysis::Unit {
+				pkg,
+				custom_build : false,
+				dev : false,
+				target : "lib".to_owned(),
+				compile_target : None,
+				extern_crate_names : dependency_ids.iter().map(|id| id.name().to_string()).collect(),
+				extern_stems : (0..dependencies).map(|i| (stem(i), dependency_ids[i].name().to_string())).collect(),
+				used_stems : (0..dependencies).step_by(2).map(stem).collect(),
+			});
+			dependency_names.insert(pkg, names());
+		}
+		let lib_stems = dependency_ids.iter().enumerate().map(|(i, &id)| (stem(i), id)).collect();
+		Self { dependency_names, units, lib_stems }
+	}
+
+	/// Matches the units to the dependencies, and returns how many are unused.
+	pub fn run(&self) -> usize {
+		let mut usage = Usage::new(&self.dependency_names, true);
+		for unit in &self.units {
+			usage.add_unit(unit, &self.lib_stems, &self.dependency_names[&unit.pkg]);
+		}
+		usage.unused().count()
+	}
+}
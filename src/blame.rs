@@ -0,0 +1,71 @@
+//! Finding out when unused dependencies were added, from the history of the manifest.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use cargo::core::dependency::DepKind;
+use cargo::util::interning::InternedString;
+use cargo::CargoResult;
+use serde::{Deserialize, Serialize};
+use toml_edit::ImDocument;
+
+use crate::fix::dependency_key_span;
+use crate::history::format_timestamp;
+
+/// The commit that last touched the line of a dependency in `Cargo.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Introduced {
+	pub(crate) commit :String,
+	pub(crate) author :String,
+	/// `YYYY-MM-DD HH:MM:SS` in UTC
+	pub(crate) date :String,
+}
+
+/// Blames the lines the given dependencies are declared on.
+///
+/// Dependencies of manifests outside of git repositories, or on lines that
+/// haven't been committed yet, are left out.
+pub(crate) fn introduced<'a>(
+	manifest_path :&Path,
+	deps :impl IntoIterator<Item = (DepKind, &'a InternedString)>,
+) -> CargoResult<BTreeMap<InternedString, Introduced>> {
+	let mut introduced = BTreeMap::new();
+	let repo = match manifest_path.parent().map(git2::Repository::discover) {
+		Some(Ok(repo)) => repo,
+		_ => return Ok(introduced),
+	};
+	let relative_path = match repo.workdir().and_then(|workdir| manifest_path.strip_prefix(workdir).ok()) {
+		Some(relative_path) => relative_path,
+		None => return Ok(introduced),
+	};
+	let blame = match repo.blame_file(relative_path, None) {
+		Ok(blame) => blame,
+		// Not committed yet
+		Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(introduced),
+		Err(e) => return Err(e.into()),
+	};
+
+	// Blame the file as it is now, as lines might have been added or removed since the last commit
+	let contents = cargo_util::paths::read(manifest_path)?;
+	let blame = blame.blame_buffer(contents.as_bytes())?;
+	let doc = ImDocument::parse(&*contents)
+		.map_err(|e| anyhow::anyhow!("could not parse `{}`: {}", manifest_path.display(), e))?;
+	for (kind, name) in deps {
+		let offset = match dependency_key_span(&doc, kind, name) {
+			Some(span) => span.start,
+			None => continue,
+		};
+		let line = contents[..offset].matches('\n').count() + 1;
+		let hunk = match blame.get_line(line) {
+			Some(hunk) if !hunk.final_commit_id().is_zero() => hunk,
+			_ => continue,
+		};
+		let signature = hunk.final_signature();
+		introduced.insert(*name, Introduced {
+			commit : hunk.final_commit_id().to_string(),
+			author : String::from_utf8_lossy(signature.name_bytes()).into_owned(),
+			date : format_timestamp(signature.when().seconds().max(0) as u64),
+		});
+	}
+	Ok(introduced)
+}
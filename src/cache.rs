@@ -0,0 +1,105 @@
+//! The information of earlier builds of units, so that units cargo finds fresh
+//! don't need to be rebuilt to be analyzed.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use cargo::core::compiler::Unit;
+use cargo::CargoResult;
+use serde::{Deserialize, Serialize};
+
+use crate::{remap, CmdInfo};
+
+/// The name of the file in `<target-dir>/udeps`.
+const FILE_NAME :&str = "cache.json";
+
+pub(crate) struct Cache {
+	path :PathBuf,
+	validity :u64,
+	/// The entries of earlier runs.
+	entries :BTreeMap<String, Entry>,
+	/// The entries of this run, the only ones that are kept.
+	current :BTreeMap<String, Entry>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Entry {
+	pub(crate) cmd_info :CmdInfo,
+	/// The unit as checked with `--cfg doc`, if that worked, with `--doc-cfg`.
+	pub(crate) doc_cfg_cmd_info :Option<CmdInfo>,
+	/// With `--backend lint`.
+	pub(crate) unused_externs :Option<HashSet<String>>,
+	/// Other builds in the same target directory may overwrite the dep-info file.
+	#[serde(default)]
+	depinfo_modified :Option<SystemTime>,
+}
+
+impl Entry {
+	pub(crate) fn new(cmd_info :CmdInfo, doc_cfg_cmd_info :Option<CmdInfo>, unused_externs :Option<HashSet<String>>) -> Self {
+		Self { cmd_info, doc_cfg_cmd_info, unused_externs, depinfo_modified : None }
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stored {
+	validity :u64,
+	entries :BTreeMap<String, Entry>,
+}
+
+impl Cache {
+	/// Loads the cache of `target_dir`. Entries of runs that `validity` differs from are dropped,
+	/// as are the ones of files that can't be read.
+	pub(crate) fn load(target_dir :&Path, validity :u64) -> Self {
+		let path = target_dir.join("udeps").join(FILE_NAME);
+		let entries = std::fs::read_to_string(&path)
+			.ok()
+			.and_then(|contents| serde_json::from_str::<Stored>(&contents).ok())
+			.filter(|stored| stored.validity == validity)
+			.map(|stored| stored.entries)
+			.unwrap_or_default();
+		Self { path, validity, entries, current : BTreeMap::new() }
+	}
+
+	/// Whether there is an entry for the unit of `key`, whose dep-info file is unchanged.
+	pub(crate) fn is_fresh(&self, key :&str, remap :&remap::PathRemap) -> bool {
+		self.entries.get(key).is_some_and(|entry| {
+			entry.depinfo_modified.is_some() && entry.depinfo_modified == depinfo_modified(&entry.cmd_info, remap)
+		})
+	}
+
+	/// Takes the entry of an earlier run over to this one.
+	pub(crate) fn reuse(&mut self, key :&str) -> Option<&Entry> {
+		let entry = self.entries.remove(key)?;
+		Some(self.current.entry(key.to_owned()).or_insert(entry))
+	}
+
+	pub(crate) fn insert(&mut self, key :String, entry :Entry) {
+		self.current.insert(key, entry);
+	}
+
+	/// Writes the entries of this run.
+	pub(crate) fn save(&mut self, remap :&remap::PathRemap) -> CargoResult<()> {
+		for entry in self.current.values_mut() {
+			entry.depinfo_modified = depinfo_modified(&entry.cmd_info, remap);
+		}
+		let stored = Stored { validity : self.validity, entries : std::mem::take(&mut self.current) };
+		if let Some(dir) = self.path.parent() {
+			cargo_util::paths::create_dir_all(dir)?;
+		}
+		cargo_util::paths::write(&self.path, serde_json::to_string(&stored)?)
+	}
+}
+
+/// A key of the unit that is the same in every run with the same configuration.
+pub(crate) fn unit_key(unit :&Unit) -> String {
+	let description = format!(
+		"{} {:?} {} {:?} {:?} {:?} {:?}",
+		unit.pkg.package_id(), unit.target.kind(), unit.target.name(), unit.mode, unit.kind, unit.features, unit.profile,
+	);
+	cargo::util::short_hash(&description)
+}
+
+fn depinfo_modified(cmd_info :&CmdInfo, remap :&remap::PathRemap) -> Option<SystemTime> {
+	std::fs::metadata(cmd_info.get_depinfo_path(remap)).ok()?.modified().ok()
+}
@@ -0,0 +1,92 @@
+//! Collection of what the compiled units depend on, for other tools that drive cargo themselves.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use cargo::core::compiler::{CompileMode, DefaultExecutor, Executor, Unit};
+use cargo::core::manifest::Target;
+use cargo::core::package_id::PackageId;
+use cargo::core::shell::Shell;
+use cargo::core::{Package, PackageIdSpecQuery, Workspace};
+use cargo::ops::CompileOptions;
+use cargo::CargoResult;
+use cargo_util::ProcessBuilder;
+
+use crate::analysis::Analysis;
+use crate::timings::Timings;
+use crate::{remap, unit_cmd_info, Backend, Exec, ExecData};
+
+/// An [`Executor`] that records what the built units depend on, while passing
+/// the actual work on to another executor.
+///
+/// Use it with [`cargo::ops::compile_with_exec`] and write the result with
+/// [`Collector::export_analysis`], then `cargo udeps --import-analysis` reports
+/// on it like on a build of its own. The `GlobalContext` has to be configured
+/// with `-Z binary-dep-depinfo`.
+///
+/// Build systems that run rustc themselves can hand their invocations to
+/// [`Collector::record`] instead.
+pub struct Collector {
+	exec :Exec,
+}
+
+impl Collector {
+	/// Prepares the collection for a build of `ws` with `compile_opts`.
+	pub fn new(ws :&Workspace<'_>, compile_opts :&CompileOptions) -> CargoResult<Self> {
+		if !ws.gctx().cli_unstable().binary_dep_depinfo {
+			return Err(anyhow::anyhow!("collecting dependency information needs `-Z binary-dep-depinfo`"));
+		}
+		// Unlike `get_packages`, this resolves an empty list to the current package, like the build does
+		let specs = compile_opts.spec.to_package_id_specs(ws)?;
+		let included_packages = ws
+			.members()
+			.map(Package::package_id)
+			.filter(|&id| specs.iter().any(|spec| spec.matches(id)))
+			.collect::<HashSet<_>>();
+		let data = ExecData::new(ws, &included_packages, &compile_opts.filter, None, remap::PathRemap::default())?;
+		let exec = Exec {
+			data : Arc::new(Mutex::new(data)),
+			inner : Arc::new(DefaultExecutor),
+		};
+		Ok(Self { exec })
+	}
+
+	/// Runs the commands with `inner` instead of cargo's default executor.
+	pub fn wrapping(mut self, inner :Arc<dyn Executor>) -> Self {
+		self.exec.inner = inner;
+		self
+	}
+
+	/// Records the compiler invocation `cmd` of the unit of `target` of `id`, which
+	/// was run elsewhere, without running it again. This is what the [`Executor`] impl
+	/// records, so the invocation needs `-Z binary-dep-depinfo` and `--emit dep-info`,
+	/// and its `.d` file has to be in its `--out-dir` when the analysis is exported.
+	pub fn record(&self, cmd :&ProcessBuilder, id :PackageId, target :&Target, mode :CompileMode) -> CargoResult<()> {
+		let cmd_info = unit_cmd_info(cmd, id, target, mode)?;
+		let mut data = self.exec.data.lock().unwrap();
+		if data.is_relevant(id, target) {
+			data.relevant_cmd_infos.push(cmd_info.clone());
+		}
+		data.all_cmd_infos.push(cmd_info);
+		Ok(())
+	}
+
+	/// Writes what was collected so far into `dir`, for `cargo udeps --import-analysis`.
+	pub fn export_analysis(&self, dir :&Path, shell :&mut Shell) -> CargoResult<()> {
+		let data = self.exec.data.lock().unwrap();
+		Analysis::collect(&data, Backend::Depinfo, &mut Timings::default(), shell)?.export(dir, shell)
+	}
+}
+
+impl Executor for Collector {
+	fn exec(&self, cmd :&ProcessBuilder, id :PackageId, target :&Target,
+			mode :CompileMode, on_stdout_line :&mut dyn FnMut(&str) -> CargoResult<()>,
+			on_stderr_line :&mut dyn FnMut(&str) -> CargoResult<()>) -> CargoResult<()> {
+		self.exec.exec(cmd, id, target, mode, on_stdout_line, on_stderr_line)
+	}
+
+	fn force_rebuild(&self, unit :&Unit) -> bool {
+		self.exec.force_rebuild(unit)
+	}
+}
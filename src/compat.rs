@@ -0,0 +1,187 @@
+//! Checks for differences between the cargo `cargo-udeps` is built with and the installed one.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use cargo::core::shell::Shell;
+use cargo::core::Workspace;
+use cargo::util::context::GlobalContext;
+use cargo::CargoResult;
+use cargo_util::ProcessBuilder;
+use globset::Glob;
+
+/// The newest lockfile format the embedded cargo reads without unstable flags,
+/// `ResolveVersion::max_stable()`.
+const MAX_LOCKFILE_VERSION :i64 = 4;
+
+/// Features of newer cargo versions that the embedded one doesn't understand,
+/// with the minor version they were stabilized in, and the manifest key and value that use them.
+const NEWER_FEATURES :&[(u32, &str, &str, &str)] = &[
+	(84, "`resolver = \"3\"`", "resolver", "3"),
+	(85, "the 2024 edition", "edition", "2024"),
+];
+
+/// Warns if the workspace of `manifest` couldn't be loaded because it uses features that the
+/// installed cargo supports, but the embedded one doesn't.
+pub(crate) fn check_cargo_version(config :&GlobalContext, manifest :&Path) -> CargoResult<()> {
+	let embedded = cargo::version();
+	let embedded_minor = match minor_version(&embedded.version) {
+		Some(minor) => minor,
+		None => return Ok(()),
+	};
+	let used = workspace_manifests(manifest)
+		.into_iter()
+		.flat_map(|(path, doc)| {
+			NEWER_FEATURES
+				.iter()
+				.filter(move |&&(minor, _, key, value)| embedded_minor < minor && sets(&doc, key, value))
+				.map(move |&(minor, feature, _, _)| (path.clone(), minor, feature))
+		})
+		.collect::<Vec<_>>();
+	if used.is_empty() {
+		return Ok(());
+	}
+	let installed_minor = match installed_minor_version(config) {
+		Some(minor) => minor,
+		None => return Ok(()),
+	};
+	for (path, minor, feature) in used.into_iter().filter(|&(_, minor, _)| minor <= installed_minor) {
+		config.shell().warn(format!(
+			"`{}` uses {} (1.{}), which the installed cargo 1.{} supports, but not cargo {}, which `cargo-udeps` is built with",
+			path.display(),
+			feature,
+			minor,
+			installed_minor,
+			embedded.version,
+		))?;
+	}
+	Ok(())
+}
+
+/// Whether the package or the workspace of the manifest sets `key` to `value`.
+fn sets(doc :&toml_edit::DocumentMut, key :&str, value :&str) -> bool {
+	let package = doc.get("package");
+	let workspace = doc.get("workspace");
+	let workspace_package = workspace.and_then(|workspace| workspace.get("package"));
+	[package, workspace, workspace_package]
+		.into_iter()
+		.flatten()
+		.any(|table| table.get(key).and_then(|item| item.as_str()) == Some(value))
+}
+
+/// The manifests of the workspace of `manifest`, found without loading it: the manifest itself,
+/// the one of the workspace root above it, and those of the members of the workspace.
+fn workspace_manifests(manifest :&Path) -> Vec<(PathBuf, toml_edit::DocumentMut)> {
+	fn read(path :&Path) -> Option<toml_edit::DocumentMut> {
+		std::fs::read_to_string(path).ok()?.parse().ok()
+	}
+	let mut manifests = Vec::new();
+	let Some(doc) = read(manifest) else {
+		return manifests;
+	};
+	let root = if doc.contains_key("workspace") {
+		Some((manifest.to_owned(), doc.clone()))
+	} else {
+		manifest
+			.ancestors()
+			.skip(2)
+			.map(|dir| dir.join("Cargo.toml"))
+			.find_map(|path| read(&path).filter(|doc| doc.contains_key("workspace")).map(|doc| (path, doc)))
+	};
+	manifests.push((manifest.to_owned(), doc));
+	let Some((root, root_doc)) = root else {
+		return manifests;
+	};
+	let members = root_doc
+		.get("workspace")
+		.and_then(|workspace| workspace.get("members"))
+		.and_then(|members| members.as_array())
+		.into_iter()
+		.flatten()
+		.filter_map(|member| member.as_str())
+		.flat_map(|member| member_dirs(root.parent().unwrap_or(Path::new("")), member))
+		.map(|dir| dir.join("Cargo.toml"))
+		.collect::<Vec<_>>();
+	if root != manifest {
+		manifests.push((root, root_doc));
+	}
+	for path in members {
+		if manifests.iter().all(|(known, _)| *known != path) {
+			if let Some(doc) = read(&path) {
+				manifests.push((path, doc));
+			}
+		}
+	}
+	manifests
+}
+
+/// The directories that a `workspace.members` entry matches, like `crates/*`.
+fn member_dirs(root :&Path, member :&str) -> Vec<PathBuf> {
+	let mut dirs = vec![root.to_owned()];
+	for component in member.split('/') {
+		if !component.contains(['*', '?', '[']) {
+			dirs.iter_mut().for_each(|dir| dir.push(component));
+			continue;
+		}
+		let Ok(glob) = Glob::new(component) else {
+			return Vec::new();
+		};
+		let matcher = glob.compile_matcher();
+		dirs = dirs
+			.iter()
+			.filter_map(|dir| std::fs::read_dir(dir).ok())
+			.flatten()
+			.flatten()
+			.map(|entry| entry.path())
+			.filter(|path| path.is_dir() && path.file_name().is_some_and(|name| matcher.is_match(name)))
+			.collect();
+	}
+	dirs
+}
+
+/// The minor version of the installed cargo, which is only asked once per process and cargo,
+/// as `--watch` may check again and again.
+fn installed_minor_version(config :&GlobalContext) -> Option<u32> {
+	static INSTALLED_MINOR :Mutex<BTreeMap<OsString, Option<u32>>> = Mutex::new(BTreeMap::new());
+	let cargo = config.get_env_os(cargo::CARGO_ENV).unwrap_or_else(|| "cargo".into());
+	let mut installed_minor = INSTALLED_MINOR.lock().unwrap();
+	*installed_minor.entry(cargo).or_insert_with_key(|cargo| {
+		let output = ProcessBuilder::new(cargo).arg("-V").exec_with_output().ok()?;
+		let installed = String::from_utf8_lossy(&output.stdout).into_owned();
+		installed.split_whitespace().nth(1).and_then(minor_version)
+	})
+}
+
+/// Refuses to go on with a lockfile the embedded cargo can't read.
+pub(crate) fn check_lockfile(ws :&Workspace<'_>, allow_newer :bool, shell :&mut Shell) -> CargoResult<()> {
+	let path = ws.root().join("Cargo.lock");
+	let version = match lockfile_version(&path) {
+		Some(version) if version > MAX_LOCKFILE_VERSION => version,
+		_ => return Ok(()),
+	};
+	let message = format!(
+		"`{}` has version {}, but `cargo-udeps` is built with cargo {}, which only supports up to version {}",
+		path.display(),
+		version,
+		cargo::version().version,
+		MAX_LOCKFILE_VERSION,
+	);
+	if allow_newer {
+		shell.warn(message)
+	} else {
+		Err(anyhow::anyhow!("{}\nPass `--allow-newer-lockfile` to try anyway", message))
+	}
+}
+
+fn lockfile_version(path :&Path) -> Option<i64> {
+	let contents = std::fs::read_to_string(path).ok()?;
+	let doc = contents.parse::<toml_edit::DocumentMut>().ok()?;
+	doc.get("version")?.as_integer()
+}
+
+/// `92` of `1.92.0-nightly`.
+fn minor_version(version :&str) -> Option<u32> {
+	version.split('.').nth(1)?.parse().ok()
+}
@@ -0,0 +1,58 @@
+//! The settings of `.cargo-udeps.toml`, which come between the defaults and the command line.
+
+use std::path::{Path, PathBuf};
+
+use cargo::CargoResult;
+use serde::Deserialize;
+
+use crate::{Backend, OutputKind};
+
+pub(crate) const FILE_NAME :&str = ".cargo-udeps.toml";
+
+/// The contents of a `.cargo-udeps.toml`.
+///
+/// Options of the run are read before the workspace is loaded, like the backend, which
+/// decides how the build is done. All other keys are the ones of
+/// `[workspace.metadata.cargo-udeps]`, and take precedence over those of the table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct ConfigFile {
+	/// Like `--output`.
+	pub(crate) output :Option<OutputKind>,
+	/// Like `--backend`.
+	pub(crate) backend :Option<Backend>,
+	/// Like `--feature-set`, usually `all` to check with each set.
+	pub(crate) feature_set :Option<String>,
+	/// The names of the packages that aren't checked, unless they are selected with `-p`.
+	#[serde(default)]
+	pub(crate) exclude :Vec<String>,
+	#[serde(flatten)]
+	pub(crate) metadata :toml::Table,
+}
+
+impl ConfigFile {
+	/// Reads the file of `dir` or of its closest ancestor that has one.
+	pub(crate) fn find(dir :&Path) -> CargoResult<Option<(PathBuf, Self)>> {
+		use anyhow::Context;
+		let Some(path) = dir.ancestors().map(|dir| dir.join(FILE_NAME)).find(|path| path.is_file()) else {
+			return Ok(None);
+		};
+		let file = toml::from_str(&cargo_util::paths::read(&path)?)
+			.with_context(|| format!("could not parse `{}`", path.display()))?;
+		Ok(Some((path, file)))
+	}
+
+	/// Puts the keys of the file over those of `[workspace.metadata.cargo-udeps]` in `metadata`.
+	pub(crate) fn layer_over(&self, metadata :Option<&toml::Value>) -> toml::Value {
+		let mut metadata = metadata.cloned().unwrap_or_else(|| toml::Value::Table(toml::Table::new()));
+		if let toml::Value::Table(table) = &mut metadata {
+			let cargo_udeps = table
+				.entry("cargo-udeps")
+				.or_insert_with(|| toml::Value::Table(toml::Table::new()));
+			if let toml::Value::Table(cargo_udeps) = cargo_udeps {
+				cargo_udeps.extend(self.metadata.clone());
+			}
+		}
+		metadata
+	}
+}
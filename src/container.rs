@@ -0,0 +1,104 @@
+//! Running the compiler inside of a container, for cross-compilation with `cross`.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use crate::remap::PathRemap;
+
+use cargo::core::Workspace;
+use cargo::CargoResult;
+use cargo_util::ProcessBuilder;
+
+/// A command that rustc invocations are run through, like `docker run .. IMAGE`.
+#[derive(Clone, Debug)]
+pub(crate) struct Container {
+	command :Vec<OsString>,
+	/// The compiler of the mounted toolchain, to be used instead of a `rustup` proxy.
+	rustc :Option<PathBuf>,
+	/// Whether the command understands `--env NAME`.
+	forward_env :bool,
+	/// Maps host paths to the paths they are mounted at in the container.
+	path_map :PathRemap,
+}
+
+impl Container {
+	/// Runs the compiler in the image `cross` uses for the target.
+	///
+	/// Like `cross`, this honors `CROSS_CONTAINER_ENGINE` and
+	/// `CROSS_TARGET_<TARGET>_IMAGE`. The directories the compiler needs
+	/// are mounted at the same paths they have on the host, so paths
+	/// don't need to be translated.
+	pub(crate) fn cross(ws :&Workspace<'_>, target :&str, sysroot :&Path) -> CargoResult<Self> {
+		let gctx = ws.gctx();
+		let engine = gctx.get_env_os("CROSS_CONTAINER_ENGINE").unwrap_or_else(|| "docker".into());
+		let image_var = format!("CROSS_TARGET_{}_IMAGE", target.to_uppercase().replace(['-', '.'], "_"));
+		let image = gctx
+			.get_env_os(&image_var)
+			.unwrap_or_else(|| format!("ghcr.io/cross-rs/{}:main", target).into());
+
+		let mut command = vec![engine, "run".into(), "--rm".into()];
+		let target_dir = ws.target_dir().into_path_unlocked();
+		let mounts = [
+			(ws.root(), ""),
+			(&target_dir, ""),
+			(gctx.home().as_path_unlocked(), ":ro"),
+			(sysroot, ":ro"),
+		];
+		for (dir, mode) in mounts {
+			let mut volume = OsString::from(dir);
+			volume.push(":");
+			volume.push(dir);
+			volume.push(mode);
+			command.extend(["--volume".into(), volume]);
+		}
+		command.extend(["--workdir".into(), gctx.cwd().into()]);
+		command.push(image);
+		Ok(Self {
+			command,
+			rustc : Some(sysroot.join("bin").join("rustc")),
+			forward_env : true,
+			path_map : PathRemap::default(),
+		})
+	}
+
+	/// Runs the compiler through a user-specified command.
+	///
+	/// `path_map` contains `HOST=CONTAINER` pairs of directories that
+	/// are mounted at different paths in the container.
+	pub(crate) fn custom(command :&str, path_map :&[String]) -> CargoResult<Self> {
+		let command = command.split_whitespace().map(OsString::from).collect::<Vec<_>>();
+		if command.is_empty() {
+			return Err(anyhow::anyhow!("the container command must not be empty"));
+		}
+		let path_map = PathRemap::parse(path_map)?;
+		Ok(Self { command, rustc : None, forward_env : false, path_map })
+	}
+
+	/// Makes `cmd` run in the container.
+	///
+	/// For `cross`, the environment variables of `cmd` are forwarded by name,
+	/// which makes the container engine look them up in its own environment.
+	pub(crate) fn wrap(&self, cmd :&mut ProcessBuilder) {
+		let mut args = self.command[1..].to_vec();
+		if self.forward_env {
+			for (key, value) in cmd.get_envs() {
+				if value.is_some() {
+					args.extend(["--env".into(), key.into()]);
+				}
+			}
+		}
+		let program = cmd.get_program();
+		match &self.rustc {
+			Some(rustc) if Path::new(program).file_stem() == Some(OsStr::new("rustc")) => args.push(rustc.into()),
+			_ => args.push(self.path_map.arg(program)),
+		}
+		args.extend(cmd.get_args().map(|arg| self.path_map.arg(arg)));
+		cmd.program(&self.command[0]);
+		cmd.args_replace(&args);
+	}
+
+	/// The remapping of paths written in the container to the host.
+	pub(crate) fn to_host(&self) -> PathRemap {
+		self.path_map.inverse()
+	}
+}
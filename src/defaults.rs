@@ -0,0 +1,26 @@
+//! The special cases that apply out of the box, for `--print-default-ignores`.
+
+use std::io::{self, Write};
+
+/// The heuristics that can hide unused dependencies, and that `--no-default-ignores` turns off.
+const HEURISTICS :&[(&str, &str)] = &[
+	("lib-name-fallback", "Dependencies whose artifacts aren't known, like in analyses of older versions, \
+		are matched by their library name. Of several dependencies with the same library name, all count \
+		as used if one of them is (see `cargo udeps --explain ambiguous`)."),
+	("crate-level-allow", "Dependencies are not reported if a target that can use them allows or expects \
+		the `unused_crate_dependencies` lint of rustc in its root, like `#![allow(unused_crate_dependencies)]`, \
+		as they are kept on purpose then."),
+	("build-script-metadata", "Normal dependencies with `links` are not reported if the build script of the \
+		package mentions their `DEP_<LINKS>_` variables, as it gets their metadata through them. Only the source \
+		file of the build script is searched, not the modules it pulls in with `mod` or `include!`."),
+];
+
+pub(crate) fn print(mut stdout :impl Write) -> io::Result<()> {
+	writeln!(stdout, "Heuristics that can hide unused dependencies:")?;
+	for (name, description) in HEURISTICS {
+		writeln!(stdout, "  {}: {}", name, description)?;
+	}
+	writeln!(stdout)?;
+	writeln!(stdout, "Pass `--no-default-ignores` to turn them off.")?;
+	stdout.flush()
+}
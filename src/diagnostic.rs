@@ -0,0 +1,185 @@
+//! Rendering of the findings as rustc-style diagnostics that point at `Cargo.toml`.
+
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cargo::core::dependency::DepKind;
+use cargo::core::package_id::PackageId;
+use cargo::util::interning::InternedString;
+use serde::Serialize;
+use toml_edit::ImDocument;
+
+use crate::fix::dependency_key_span;
+use crate::{explain, Outcome};
+
+/// Prints a warning for every unused dependency, with the line it is declared on.
+///
+/// Relative manifest paths are relative to `root`, and shown relative to `cwd` if possible,
+/// so that terminals and editors can link them. Absolute ones are shown as they are.
+pub(crate) fn print(outcome :&Outcome, root :&Path, cwd :&Path, mut stdout :impl Write) -> io::Result<()> {
+	let mut count = 0;
+	let mut codes = BTreeSet::new();
+	for (member, unused) in &outcome.unused_deps {
+		let (manifest_path, shown_path) = manifest_paths(&unused.manifest_path, root, cwd);
+		let shown_path = &shown_path;
+		let contents = std::fs::read_to_string(manifest_path).ok();
+		let doc = contents.as_deref().and_then(|contents| ImDocument::parse(contents).ok());
+		for (deps, kind, kind_name) in [
+			(&unused.normal, DepKind::Normal, "dependency"),
+			(&unused.development, DepKind::Development, "dev-dependency"),
+			(&unused.build, DepKind::Build, "build-dependency"),
+		] {
+			for dep in deps {
+				writeln!(stdout, "warning[{}]: unused dependency `{}`", explain::code(kind), dep)?;
+				let span = doc.as_ref().and_then(|doc| dependency_key_span(doc, kind, dep));
+				let gutter = match (&contents, span) {
+					(Some(contents), Some(span)) => write_snippet(&mut stdout, shown_path, contents, span)?,
+					_ => {
+						writeln!(stdout, " --> {}", shown_path.display())?;
+						1
+					},
+				};
+				writeln!(
+					stdout,
+					"{:gutter$} = note: `{}` is a {} of `{} v{}`",
+					"", dep, kind_name, member.name(), member.version(),
+					gutter = gutter,
+				)?;
+				writeln!(stdout)?;
+				count += 1;
+				codes.insert(explain::code(kind));
+			}
+		}
+	}
+	if count > 0 {
+		let s = if count == 1 { "y" } else { "ies" };
+		writeln!(stdout, "warning: {} unused dependenc{}", count, s)?;
+		let codes = codes.into_iter().collect::<Vec<_>>();
+		if codes.len() > 1 {
+			writeln!(stdout, "Some warnings have detailed explanations: {}.", codes.join(", "))?;
+			writeln!(stdout, "For more information about a warning, try `cargo udeps --explain {}`.", codes[0])?;
+		} else {
+			writeln!(stdout, "For more information about this warning, try `cargo udeps --explain {}`.", codes[0])?;
+		}
+	}
+	stdout.flush()
+}
+
+/// The JSON line of an unused dependency, with a `reason` like the messages of cargo.
+#[derive(Serialize)]
+struct Message<'a> {
+	reason :&'static str,
+	package_id :PackageId,
+	manifest_path :&'a Path,
+	name :InternedString,
+	/// `normal`, `development` or `build`, like the lists of the unused dependencies.
+	kind :&'static str,
+	/// `warning` for the dependencies that only warn, and `error` otherwise.
+	level :&'static str,
+	code :&'static str,
+	span :Option<Span<'a>>,
+}
+
+/// The key of a declaration in a manifest, with the fields of the spans of rustc.
+#[derive(Serialize)]
+struct Span<'a> {
+	file_name :&'a Path,
+	byte_start :usize,
+	byte_end :usize,
+	line_start :usize,
+	line_end :usize,
+	column_start :usize,
+	column_end :usize,
+}
+
+/// Prints a JSON line for every unused dependency, for `--message-format json`.
+///
+/// Manifest paths are as in the report, so relative to `root` unless `--path-style absolute` is passed.
+/// The span is `null` if the declaration can't be found in the manifest.
+pub(crate) fn print_messages(outcome :&Outcome, root :&Path, mut stdout :impl Write) -> io::Result<()> {
+	for (member, unused) in &outcome.unused_deps {
+		let manifest_path = Path::new(&unused.manifest_path);
+		let contents = std::fs::read_to_string(root.join(manifest_path)).ok();
+		let doc = contents.as_deref().and_then(|contents| ImDocument::parse(contents).ok());
+		for (kind, kind_name) in [
+			(DepKind::Normal, "normal"),
+			(DepKind::Development, "development"),
+			(DepKind::Build, "build"),
+		] {
+			for &dep in unused.unused_deps(kind) {
+				let span = doc.as_ref().and_then(|doc| dependency_key_span(doc, kind, &dep));
+				let span = contents.as_deref().zip(span).map(|(contents, span)| {
+					let (line_start, column_start) = line_and_column(contents, span.start);
+					let (line_end, column_end) = line_and_column(contents, span.end);
+					Span {
+						file_name : manifest_path,
+						byte_start : span.start,
+						byte_end : span.end,
+						line_start,
+						line_end,
+						column_start,
+						column_end,
+					}
+				});
+				let message = Message {
+					reason : "udeps-unused-dependency",
+					package_id : *member,
+					manifest_path,
+					name : dep,
+					kind : kind_name,
+					level : if unused.warn_only.contains(kind, &dep) { "warning" } else { "error" },
+					code : explain::code(kind),
+					span,
+				};
+				serde_json::to_writer(&mut stdout, &message)?;
+				writeln!(stdout)?;
+			}
+		}
+	}
+	stdout.flush()
+}
+
+/// The path of a manifest of the outcome, and the one to show.
+///
+/// Relative paths are relative to `root`, and shown relative to `cwd` if possible.
+pub(crate) fn manifest_paths(manifest_path :&str, root :&Path, cwd :&Path) -> (PathBuf, PathBuf) {
+	let path = root.join(manifest_path);
+	let shown_path = if Path::new(manifest_path).is_relative() {
+		path.strip_prefix(cwd).unwrap_or(&path).to_owned()
+	} else {
+		path.clone()
+	};
+	(path, shown_path)
+}
+
+/// The line of the declaration of the dependency `name` in `doc`, starting at 1.
+pub(crate) fn declaration_line(contents :&str, doc :&ImDocument<&str>, kind :DepKind, name :&str) -> Option<usize> {
+	let span = dependency_key_span(doc, kind, name)?;
+	Some(line_and_column(contents, span.start).0)
+}
+
+/// Writes the location and the line of `span`, underlining it, and returns the width of the gutter.
+fn write_snippet(stdout :&mut impl Write, path :&Path, contents :&str, span :Range<usize>) -> io::Result<usize> {
+	let line_start = contents[..span.start].rfind('\n').map_or(0, |i| i + 1);
+	let line_end = contents[span.start..].find('\n').map_or(contents.len(), |i| span.start + i);
+	let line = contents[line_start..line_end].trim_end_matches('\r');
+	let (line_number, column) = line_and_column(contents, span.start);
+	let underline = contents[span.start..span.end.min(line_end)].chars().count().max(1);
+
+	let gutter = line_number.to_string().len();
+	writeln!(stdout, "{:gutter$}--> {}:{}:{}", "", path.display(), line_number, column, gutter = gutter)?;
+	writeln!(stdout, "{:gutter$} |", "", gutter = gutter)?;
+	writeln!(stdout, "{} | {}", line_number, line)?;
+	writeln!(stdout, "{:gutter$} | {}{}", "", " ".repeat(column - 1), "^".repeat(underline), gutter = gutter)?;
+	writeln!(stdout, "{:gutter$} |", "", gutter = gutter)?;
+	Ok(gutter)
+}
+
+/// The line and the column in characters of the byte `offset`, both starting at 1.
+pub(crate) fn line_and_column(contents :&str, offset :usize) -> (usize, usize) {
+	let line_start = contents[..offset].rfind('\n').map_or(0, |i| i + 1);
+	let line_number = contents[..offset].matches('\n').count() + 1;
+	(line_number, contents[line_start..offset].chars().count() + 1)
+}
@@ -0,0 +1,148 @@
+//! The doctests of the members, compiled for `--doctests`. Their dependencies are used
+//! unless rustdoc reports them as unused externs, like the lint backend does for other units.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use cargo::core::compiler::{CompileKind, CompileMode};
+use cargo::core::{PackageId, Workspace};
+use cargo::ops::{CompileFilter, CompileOptions};
+use cargo::CargoResult;
+use serde::Deserialize;
+
+use crate::analysis::Unit;
+use crate::ShellExt;
+
+/// What rustdoc prints with `--json=unused-externs` after compiling the doctests of a crate.
+#[derive(Deserialize)]
+struct UnusedExterns {
+	unused_extern_names :HashSet<String>,
+}
+
+/// Builds what the doctests of the libraries of `included_packages` depend on, and compiles
+/// them without running them. Doctests that fail to compile are skipped, with a warning.
+pub(crate) fn collect(
+	ws :&Workspace<'_>,
+	compile_opts :&CompileOptions,
+	included_packages :&HashSet<PackageId>,
+	rustc :&Path,
+) -> CargoResult<Vec<Unit>> {
+	// The rustdoc of the toolchain of rustc, as rustup might pick another one, like when
+	// `$RUSTC` is set, and the unused externs are only reported by nightly ones
+	let rustdoc = match ws.gctx().get_env_os("RUSTDOC") {
+		Some(_) => None,
+		None => rustc
+			.parent()
+			.map(|dir| dir.join("rustdoc").with_extension(std::env::consts::EXE_EXTENSION))
+			.filter(|rustdoc| rustdoc.is_file()),
+	};
+
+	let mut compile_opts = compile_opts.clone();
+	compile_opts.build_config.mode = CompileMode::Doctest;
+	compile_opts.filter = CompileFilter::lib_only();
+	let compilation = cargo::ops::compile(ws, &compile_opts)?;
+
+	let mut units = Vec::new();
+	for doctest in &compilation.to_doc_test {
+		let unit = &doctest.unit;
+		if !included_packages.contains(&unit.pkg.package_id()) {
+			continue;
+		}
+		// Like cargo, doctests are only compiled for the host
+		if let CompileKind::Target(target) = unit.kind {
+			if target.short_name() != compilation.host {
+				continue;
+			}
+		}
+		let target = format!("{} (doctests)", unit.target.description_named());
+		ws.gctx().shell().verbose_info(format_args!("Compiling the doctests of `{}`", unit.pkg.name()))?;
+
+		let mut cmd = compilation.rustdoc_process(unit, doctest.script_meta)?;
+		if let Some(rustdoc) = &rustdoc {
+			cmd.program(rustdoc);
+		}
+		for (var, value) in &doctest.env {
+			cmd.env(var, value);
+		}
+		cmd.arg("--crate-name").arg(unit.target.crate_name());
+		cmd.arg("--test");
+		cargo::util::add_path_args(ws, unit, &mut cmd);
+		for dir in [&compilation.deps_output[&unit.kind], &compilation.deps_output[&CompileKind::Host]] {
+			let mut arg = std::ffi::OsString::from("dependency=");
+			arg.push(dir);
+			cmd.arg("-L").arg(arg);
+		}
+		for dir in &compilation.native_dirs {
+			cmd.arg("-L").arg(dir);
+		}
+		cmd.args(unit.pkg.manifest().lint_rustflags());
+		// The unused externs are only reported with JSON diagnostics
+		let mut args = doctest.args.iter();
+		while let Some(arg) = args.next() {
+			if arg == "--error-format" {
+				args.next();
+			} else {
+				cmd.arg(arg);
+			}
+		}
+		if !doctest.unstable_opts {
+			cmd.arg("-Zunstable-options");
+		}
+		cmd.arg("--no-run");
+		cmd.arg("--error-format=json");
+		cmd.arg("--json=unused-externs");
+
+		let output = match cmd.exec_with_output() {
+			Ok(output) => output,
+			Err(e) => {
+				let mut shell = ws.gctx().shell();
+				shell.warn(format_args!(
+					"the doctests of `{}` don't compile, their dependencies are not analyzed \
+					(see `cargo test --doc`)",
+					unit.pkg.name(),
+				))?;
+				shell.verbose_info(format_args!("{:?}", e))?;
+				continue;
+			},
+		};
+		let unused = String::from_utf8_lossy(&output.stderr)
+			.lines()
+			.find_map(|line| serde_json::from_str::<UnusedExterns>(line).ok())
+			.map(|unused| unused.unused_extern_names)
+			.ok_or_else(|| anyhow::anyhow!("rustdoc reported no unused externs for the doctests of `{}`", unit.pkg.name()))?;
+
+		let mut extern_crate_names = HashSet::new();
+		let mut extern_stems = HashMap::new();
+		let mut args = doctest.args.iter();
+		while let Some(arg) = args.next() {
+			if arg == "--extern" {
+				if let Some(arg) = args.next() {
+					let (name, stem) = crate::parse_extern(arg)?;
+					if let Some(stem) = stem {
+						extern_stems.insert(stem, name.clone());
+					}
+					extern_crate_names.insert(name);
+				}
+			}
+		}
+		let used_stems = extern_stems
+			.iter()
+			.filter(|(_, name)| !unused.contains(*name))
+			.map(|(stem, _)| stem.clone())
+			.collect();
+		units.push(Unit {
+			pkg : unit.pkg.package_id(),
+			custom_build : false,
+			dev : true,
+			target,
+			compile_target : match unit.kind {
+				CompileKind::Host => None,
+				CompileKind::Target(target) => Some(target.rustc_target().to_string()),
+			},
+			extern_crate_names,
+			extern_stems,
+			used_stems,
+		});
+	}
+	Ok(units)
+}
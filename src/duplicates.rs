@@ -0,0 +1,49 @@
+//! Crates that are in the dependency graph in several versions, for `--check-duplicates`.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use cargo::core::{PackageId, Resolve};
+use cargo::util::interning::InternedString;
+use serde::{Deserialize, Serialize};
+
+/// A version of a crate that is in the graph in several versions.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub(crate) struct Duplicate {
+	pub(crate) package :PackageId,
+	/// The members that depend on this version, directly or through other packages.
+	pub(crate) members :Vec<PackageId>,
+}
+
+/// The crates that the members depend on in several versions, by name.
+///
+/// Two packages with the same name count as versions of the same crate even if they
+/// come from different sources, as they are built twice all the same.
+pub(crate) fn find(resolve :&Resolve, members :impl IntoIterator<Item = PackageId>) -> BTreeMap<InternedString, Vec<Duplicate>> {
+	let members = members.into_iter().collect::<BTreeSet<_>>();
+	let mut by_name = BTreeMap::<InternedString, BTreeMap<PackageId, Vec<PackageId>>>::new();
+	for &member in &members {
+		let mut seen = HashSet::new();
+		let mut queue = vec![member];
+		while let Some(id) = queue.pop() {
+			for (dep, _) in resolve.deps(id) {
+				if seen.insert(dep) {
+					queue.push(dep);
+				}
+			}
+		}
+		for id in seen.into_iter().filter(|id| !members.contains(id)) {
+			by_name.entry(id.name()).or_default().entry(id).or_default().push(member);
+		}
+	}
+	by_name
+		.into_iter()
+		.filter(|(_, versions)| versions.len() > 1)
+		.map(|(name, versions)| {
+			let versions = versions
+				.into_iter()
+				.map(|(package, members)| Duplicate { package, members })
+				.collect();
+			(name, versions)
+		})
+		.collect()
+}
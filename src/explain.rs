@@ -0,0 +1,115 @@
+//! Detailed explanations of the kinds of findings, for `--explain`.
+
+use std::io::Write;
+
+use cargo::core::dependency::DepKind;
+use cargo::CargoResult;
+
+/// The explanations, by the code of the kind of finding.
+const EXPLANATIONS :&[(&str, &str)] = &[
+	("unused-normal", r#"A dependency of `[dependencies]` is not used by any of the checked targets.
+
+`cargo-udeps` builds the targets and looks at which crates the compiler actually
+loaded. A dependency that none of them loaded is unused.
+
+Common causes of false positives:
+
+* The dependency is only used by targets that weren't checked. Pass `--all-targets`
+  to check tests, examples and benchmarks as well.
+* The dependency is only used in doc-tests. Such uses are only detected with
+  `--doctests`.
+* The dependency is only needed for its side effects when linking, like a crate
+  that only provides a native library or a global allocator, without anything
+  of it being named in the code.
+* The dependency is only used on other platforms or with other features. Pass
+  `--target` or `--features` to check those.
+
+Fix it by removing the dependency, for example with `cargo udeps apply`. To keep
+it, list it in `package.metadata.cargo-udeps.ignore.normal`, or give it a
+`policy` with `severity = "warn"` or `"allow"`.
+"#),
+	("unused-dev", r#"A dependency of `[dev-dependencies]` is not used by any of the checked targets.
+
+Dev-dependencies are available to tests, examples and benchmarks, so they can
+only be found to be used if those are checked. Pass `--all-targets`, as otherwise
+every dev-dependency looks unused.
+
+Common causes of false positives:
+
+* The dependency is only used in doc-tests. Such uses are only detected with
+  `--doctests`.
+* The dependency is used by a test or example that requires features that
+  weren't enabled.
+
+Fix it by removing the dependency. To keep it, list it in
+`package.metadata.cargo-udeps.ignore.development`.
+"#),
+	("unused-build", r#"A dependency of `[build-dependencies]` is not used by the build script.
+
+Build dependencies are only available to `build.rs`. A package without build
+script doesn't need any.
+
+Common causes of false positives:
+
+* The build script only runs a binary of the dependency, which `cargo-udeps`
+  can't see (see `non-lib`).
+
+Fix it by removing the dependency. To keep it, list it in
+`package.metadata.cargo-udeps.ignore.build`.
+"#),
+	("non-lib", r#"A dependency is a package without a library target.
+
+Packages that only have binaries can be depended on, for example to have a
+tool built for the build script. As nothing can be loaded from them,
+`cargo-udeps` regards them as unused.
+
+The same goes for packages whose library is only a `cdylib` or a `staticlib`,
+which are meant to be linked into programs written in other languages. A warning
+names each of them when the dependencies are resolved.
+
+If the dependency is needed, list it in `package.metadata.cargo-udeps.ignore`.
+"#),
+	("ambiguous", r#"Several dependencies have libraries of the same name.
+
+Usually, the artifacts of the dependencies tell them apart. If they aren't
+available, like for a report of another machine that predates them, the names
+of the libraries are used instead, and all of the dependencies with the name
+count as used if one of them is.
+
+This can hide unused dependencies, but doesn't report used ones as unused.
+Running again without `--import-analysis` gives exact results.
+"#),
+];
+
+/// The code of the kind of finding an unused dependency of `kind` is.
+pub(crate) fn code(kind :DepKind) -> &'static str {
+	match kind {
+		DepKind::Normal => "unused-normal",
+		DepKind::Development => "unused-dev",
+		DepKind::Build => "unused-build",
+	}
+}
+
+/// The explanation of `code`, whose first line is a summary.
+pub(crate) fn explanation(code :&str) -> Option<&'static str> {
+	EXPLANATIONS.iter().find(|(c, _)| *c == code).map(|(_, explanation)| *explanation)
+}
+
+/// The codes of the kinds of findings.
+pub(crate) fn codes() -> impl Iterator<Item = &'static str> {
+	EXPLANATIONS.iter().map(|(code, _)| *code)
+}
+
+pub(crate) fn explain(code :&str, mut stdout :impl Write) -> CargoResult<()> {
+	match explanation(code) {
+		Some(explanation) => {
+			write!(stdout, "{}", explanation)?;
+			stdout.flush()?;
+			Ok(())
+		},
+		None => {
+			let codes = codes().collect::<Vec<_>>();
+			Err(anyhow::anyhow!("unknown code `{}`, expected one of: {}", code, codes.join(", ")))
+		},
+	}
+}
@@ -0,0 +1,85 @@
+//! Features declared in `[features]` that nothing uses, for `--check-features`.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use cargo::core::{FeatureValue, Package};
+use cargo::util::interning::InternedString;
+
+/// The features of `package` that don't enable any dependency or feature of a dependency,
+/// aren't looked at by its sources and don't enable a feature that is used.
+///
+/// The sources are all `.rs` files in the directory of the package, apart from those of
+/// packages in subdirectories. They are searched for `feature = "NAME"`, as used in
+/// `cfg`, `cfg_attr` and `cfg!`, and for the `CARGO_FEATURE_NAME` variables of build scripts.
+pub(crate) fn unused(package :&Package, target_dir :&Path) -> Vec<InternedString> {
+	let features = package.summary().features();
+	let sources = sources(package.root(), target_dir);
+	let mut used = features
+		.iter()
+		.filter(|(name, values)| {
+			name.as_str() == "default"
+				|| values.iter().any(|value| !matches!(value, FeatureValue::Feature(_)))
+				|| referenced(&sources, name)
+		})
+		.map(|(&name, _)| name)
+		.collect::<BTreeSet<_>>();
+	// Features that enable used ones, like `full = ["a", "b"]`
+	loop {
+		let enabling = features
+			.iter()
+			.filter(|(name, _)| !used.contains(*name))
+			.filter(|(_, values)| {
+				values.iter().any(|value| matches!(value, FeatureValue::Feature(enabled) if used.contains(enabled)))
+			})
+			.map(|(&name, _)| name)
+			.collect::<Vec<_>>();
+		if enabling.is_empty() {
+			break;
+		}
+		used.extend(enabling);
+	}
+	features.keys().filter(|name| !used.contains(*name)).copied().collect()
+}
+
+/// The contents of the `.rs` files of the package.
+fn sources(root :&Path, target_dir :&Path) -> Vec<String> {
+	fn visit(dir :&Path, root :&Path, target_dir :&Path, sources :&mut Vec<String>) {
+		// Other packages, or the target directory
+		if (dir != root && dir.join("Cargo.toml").exists()) || dir == target_dir {
+			return;
+		}
+		let Ok(entries) = std::fs::read_dir(dir) else {
+			return;
+		};
+		for entry in entries.flatten() {
+			let path = entry.path();
+			if path.is_dir() {
+				if !entry.file_name().to_string_lossy().starts_with('.') {
+					visit(&path, root, target_dir, sources);
+				}
+			} else if path.extension().is_some_and(|ext| ext == "rs") {
+				if let Ok(contents) = std::fs::read_to_string(&path) {
+					sources.push(contents);
+				}
+			}
+		}
+	}
+	let mut sources = Vec::new();
+	visit(root, root, target_dir, &mut sources);
+	sources
+}
+
+fn referenced(sources :&[String], feature :&str) -> bool {
+	let quoted = format!("\"{}\"", feature);
+	let variable = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+	sources.iter().any(|source| {
+		source.contains(&variable) || source.match_indices(&quoted).any(|(start, _)| {
+			// `feature = "NAME"`, with any whitespace around the `=`
+			let before = source[..start].trim_end();
+			before
+				.strip_suffix('=')
+				.is_some_and(|before| before.trim_end().ends_with("feature"))
+		})
+	})
+}
@@ -0,0 +1,576 @@
+//! Editing of `Cargo.toml` files to remove unused dependencies.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cargo::core::dependency::DepKind;
+use cargo::core::FeatureValue;
+use cargo::CargoResult;
+use toml_edit::{Array, Decor, DocumentMut, ImDocument, InlineTable, Item, RawString, Table, TableLike, Value};
+
+/// Returns the names of the dependency tables that may contain
+/// dependencies of the given kind, including legacy spellings.
+pub(crate) fn table_names(kind :DepKind) -> &'static [&'static str] {
+	match kind {
+		DepKind::Normal => &["dependencies"],
+		DepKind::Development => &["dev-dependencies", "dev_dependencies"],
+		DepKind::Build => &["build-dependencies", "build_dependencies"],
+	}
+}
+
+/// The byte range of the key of the dependency, in the first table of `kind` it occurs in.
+pub(crate) fn dependency_key_span(doc :&ImDocument<&str>, kind :DepKind, name :&str) -> Option<Range<usize>> {
+	let in_table = |parent :&dyn TableLike| {
+		table_names(kind).iter().find_map(|&table_name| {
+			let (key, _) = parent.get(table_name)?.as_table_like()?.get_key_value(name)?;
+			key.span()
+		})
+	};
+	in_table(doc.as_table()).or_else(|| {
+		let targets = doc.get("target")?.as_table_like()?;
+		targets.iter().find_map(|(_, target)| in_table(target.as_table_like()?))
+	})
+}
+
+/// A `Cargo.toml` loaded for editing.
+pub(crate) struct Manifest {
+	path :PathBuf,
+	doc :DocumentMut,
+}
+
+impl Manifest {
+	pub(crate) fn open(path :&Path) -> CargoResult<Self> {
+		let contents = cargo_util::paths::read(path)?;
+		let doc = contents.parse::<DocumentMut>()
+			.map_err(|e| anyhow::anyhow!("could not parse `{}`: {}", path.display(), e))?;
+		Ok(Self { path : path.to_owned(), doc })
+	}
+
+	/// Removes the dependency with the given key from every table of the given kind,
+	/// including target-specific ones. Tables that become empty are removed as well.
+	///
+	/// Comments directly in front of the dependency are considered to document it
+	/// and are removed along with it. Anything separated from it by a blank line
+	/// is kept and becomes part of whatever follows.
+	///
+	/// Returns the (dotted) names of the tables it was removed from.
+	pub(crate) fn remove_dependency(&mut self, kind :DepKind, name_in_toml :&str) -> Vec<String> {
+		let mut removed_from = Vec::new();
+		let mut leftovers = Vec::new();
+		for &table_name in table_names(kind) {
+			if let Some(leftover) = remove_from(self.doc.as_table_mut(), table_name, name_in_toml) {
+				removed_from.push(table_name.to_owned());
+				leftovers.extend(leftover);
+			}
+		}
+		if let Some(targets) = self.doc.get_mut("target").and_then(|t| t.as_table_like_mut()) {
+			let mut emptied = Vec::new();
+			for (platform, target) in targets.iter_mut() {
+				let target = match target.as_table_like_mut() {
+					Some(target) => target,
+					None => continue,
+				};
+				for &table_name in table_names(kind) {
+					if let Some(leftover) = remove_from(target, table_name, name_in_toml) {
+						// Quoted like in the manifest, as in `target.'cfg(unix)'.dependencies`
+						removed_from.push(format!("target.{}.{}", platform.display_repr(), table_name));
+						leftovers.extend(leftover);
+					}
+				}
+				if target.is_empty() {
+					emptied.push(platform.get().to_owned());
+				}
+			}
+			for platform in emptied {
+				leftovers.extend(remove_table(targets, &platform));
+			}
+			if targets.is_empty() {
+				leftovers.extend(remove_table(self.doc.as_table_mut(), "target"));
+			}
+		}
+		// Later text has to end up behind earlier text
+		leftovers.sort_by_key(|l| l.after);
+		for leftover in leftovers.into_iter().rev() {
+			self.reattach(leftover);
+		}
+		removed_from
+	}
+
+	/// Removes the references `[features]` has to a dependency that
+	/// is no longer in the manifest, as well as the features that
+	/// become empty because of that.
+	///
+	/// This follows the rules of `cargo remove`.
+	/// Returns the names of the removed features.
+	pub(crate) fn remove_dangling_features(&mut self, name_in_toml :&str) -> Vec<String> {
+		let optional = match self.dep_status(name_in_toml) {
+			Some(true) => return Vec::new(),
+			Some(false) => false,
+			None => true,
+		};
+		let explicit_dep_activation = self.is_explicit_dep_activation(name_in_toml);
+		let mut emptied = self.retain_feature_values(&mut |value| !match value {
+			FeatureValue::Feature(name) => !explicit_dep_activation && name == name_in_toml,
+			FeatureValue::Dep { dep_name } => dep_name == name_in_toml,
+			// The dependency is still there, but not as an optional one
+			FeatureValue::DepFeature { dep_name, .. } => optional && dep_name == name_in_toml,
+		});
+		if !optional {
+			self.strengthen_weak_features(name_in_toml);
+		}
+
+		let mut removed_features = Vec::new();
+		while let Some(feature) = emptied.pop() {
+			if let Some(leftovers) = remove_from(self.doc.as_table_mut(), "features", &feature) {
+				leftovers.into_iter().for_each(|l| self.reattach(l));
+			}
+			emptied.extend(self.retain_feature_values(&mut |value| {
+				!matches!(value, FeatureValue::Feature(name) if **name == *feature)
+			}));
+			removed_features.push(feature);
+		}
+		removed_features
+	}
+
+	/// Returns whether the dependency is still present in some table
+	/// features can refer to, and if so, whether it is optional there.
+	///
+	/// Features can't refer to dev-dependencies.
+	fn dep_status(&self, name_in_toml :&str) -> Option<bool> {
+		self.dependency_entries(name_in_toml, &[DepKind::Normal, DepKind::Build])
+			.into_iter()
+			.map(|dep| dep.get("optional").and_then(Item::as_bool).unwrap_or(false))
+			.reduce(|a, b| a && b)
+	}
+
+	/// Returns whether some dependency table still inherits the
+	/// dependency from `[workspace.dependencies]`.
+	pub(crate) fn inherits(&self, name_in_toml :&str) -> bool {
+		self.dependency_entries(name_in_toml, &[DepKind::Normal, DepKind::Development, DepKind::Build])
+			.into_iter()
+			.any(|dep| dep.get("workspace").and_then(Item::as_bool).unwrap_or(false))
+	}
+
+	/// Removes the entry from `[workspace.dependencies]`.
+	///
+	/// Returns whether there was such an entry.
+	pub(crate) fn remove_workspace_dependency(&mut self, name_in_toml :&str) -> bool {
+		let workspace = match self.doc.get_mut("workspace").and_then(Item::as_table_like_mut) {
+			Some(workspace) => workspace,
+			None => return false,
+		};
+		match remove_from(workspace, "dependencies", name_in_toml) {
+			Some(leftovers) => {
+				leftovers.into_iter().for_each(|l| self.reattach(l));
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Returns the entries for the dependency in all dependency tables of the given kinds.
+	fn dependency_entries(&self, name_in_toml :&str, kinds :&[DepKind]) -> Vec<&Item> {
+		let targets = self.doc.get("target").and_then(Item::as_table_like).into_iter().flat_map(TableLike::iter);
+		let tables = [self.doc.as_table() as &dyn TableLike]
+			.into_iter()
+			.chain(targets.filter_map(|(_, target)| target.as_table_like()));
+		let mut entries = Vec::new();
+		for tables in tables {
+			for &kind in kinds {
+				for &table_name in table_names(kind) {
+					let dep = tables.get(table_name).and_then(Item::as_table_like).and_then(|t| t.get(name_in_toml));
+					entries.extend(dep);
+				}
+			}
+		}
+		entries
+	}
+
+	fn is_explicit_dep_activation(&self, name_in_toml :&str) -> bool {
+		self.feature_arrays()
+			.flat_map(|(_, values)| values.iter())
+			.filter_map(|v| v.as_str())
+			.any(|v| matches!(FeatureValue::new(v.into()), FeatureValue::Dep { dep_name } if dep_name == name_in_toml))
+	}
+
+	fn feature_arrays(&self) -> impl Iterator<Item = (&str, &Array)> {
+		self.doc
+			.get("features")
+			.and_then(Item::as_table_like)
+			.into_iter()
+			.flat_map(TableLike::iter)
+			.filter_map(|(name, values)| Some((name, values.as_array()?)))
+	}
+
+	/// Removes the feature values for which `keep` returns `false`.
+	///
+	/// Returns the features that became empty.
+	fn retain_feature_values(&mut self, keep :&mut dyn FnMut(&FeatureValue) -> bool) -> Vec<String> {
+		let features = match self.doc.get_mut("features").and_then(Item::as_table_like_mut) {
+			Some(features) => features,
+			None => return Vec::new(),
+		};
+		let mut emptied = Vec::new();
+		for (name, values) in features.iter_mut() {
+			let values = match values.as_array_mut() {
+				Some(values) => values,
+				None => continue,
+			};
+			let was_empty = values.is_empty();
+			let mut i = 0;
+			while i < values.len() {
+				let value = values.get(i).and_then(|v| v.as_str()).map(|v| FeatureValue::new(v.into()));
+				if value.is_some_and(|v| !keep(&v)) {
+					remove_array_index(values, i);
+				} else {
+					i += 1;
+				}
+			}
+			if !was_empty && values.is_empty() {
+				emptied.push(name.get().to_owned());
+			}
+		}
+		emptied
+	}
+
+	/// Turns `foo?/bar` into `foo/bar`, as `foo` is not optional any more.
+	fn strengthen_weak_features(&mut self, name_in_toml :&str) {
+		let values = self.doc
+			.get_mut("features")
+			.and_then(Item::as_table_like_mut)
+			.into_iter()
+			.flat_map(TableLike::iter_mut)
+			.filter_map(|(_, values)| values.as_array_mut())
+			.flat_map(Array::iter_mut);
+		for value in values {
+			let strong = match value.as_str().map(|v| FeatureValue::new(v.into())) {
+				Some(FeatureValue::DepFeature { dep_name, dep_feature, weak : true }) if dep_name == name_in_toml => {
+					format!("{}/{}", dep_name, dep_feature)
+				},
+				_ => continue,
+			};
+			let decor = value.decor().clone();
+			*value = Value::from(strong);
+			*value.decor_mut() = decor;
+		}
+	}
+
+	/// Puts text that lost its place in front of the next table header,
+	/// or at the end of the document if there is none.
+	fn reattach(&mut self, leftover :Leftover) {
+		let next = next_header_position(self.doc.as_table(), leftover.after.unwrap_or(0));
+		match next.and_then(|position| header_mut(self.doc.as_table_mut(), position)) {
+			Some(table) => prepend_prefix(table.decor_mut(), &leftover.text),
+			// Blank lines at the end of the file are of no use
+			None if leftover.text.trim().is_empty() => (),
+			None => {
+				let text = format!("{}\n", leftover.text.trim_end());
+				let trailing = join_prefix(&text, self.doc.trailing().as_str().unwrap_or_default());
+				self.doc.set_trailing(trailing);
+			},
+		}
+	}
+
+	pub(crate) fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub(crate) fn write(&self) -> CargoResult<()> {
+		cargo_util::paths::write_atomic(&self.path, self.doc.to_string())
+	}
+}
+
+/// Text that was in front of a removed entry but did not belong to it.
+struct Leftover {
+	/// Position of the table header the text has to come after.
+	after :Option<usize>,
+	text :String,
+}
+
+/// Removes `key` from the dependency table `parent[table_name]`,
+/// and the table itself if it becomes empty.
+///
+/// Returns `None` if there was no such key.
+fn remove_from(parent :&mut dyn TableLike, table_name :&str, key :&str) -> Option<Vec<Leftover>> {
+	let container = parent.get_mut(table_name)?;
+	let mut leftovers = Vec::new();
+	leftovers.extend(remove_entry(container, key)?);
+	if container.as_table_like().is_some_and(TableLike::is_empty) {
+		leftovers.extend(remove_table(parent, table_name));
+	}
+	Some(leftovers)
+}
+
+/// Removes the (possibly implicit or inline) table `key` from `parent`,
+/// keeping the text in front of its header that doesn't belong to it.
+fn remove_table(parent :&mut dyn TableLike, key :&str) -> Option<Leftover> {
+	let header = parent.get(key).and_then(Item::as_table).filter(|t| !t.is_dotted());
+	let (position, prefix) = match header {
+		Some(t) => (t.position(), raw_prefix(t.decor()).to_owned()),
+		None => (None, String::new()),
+	};
+	parent.remove(key);
+	let (detached, _) = split_detached(&prefix);
+	(!detached.is_empty()).then(|| Leftover { after : position, text : detached.to_owned() })
+}
+
+/// Removes `key` from the table or inline table `container`.
+///
+/// Returns `None` if there was no such key.
+fn remove_entry(container :&mut Item, key :&str) -> Option<Option<Leftover>> {
+	if let Some(inline) = container.as_inline_table_mut() {
+		return remove_inline_entry(inline, key).then_some(None);
+	}
+	let container_position = container.as_table().and_then(Table::position);
+	let table = container.as_table_like_mut()?;
+
+	if table.get(key).and_then(Item::as_table).is_some_and(|t| !t.is_dotted()) {
+		// A `[dependencies.foo]` style table
+		return Some(remove_table(table, key));
+	}
+
+	let mut detached = String::new();
+	with_first_line_decor(table, key, &mut |decor| {
+		detached = split_detached(raw_prefix(decor)).0.to_owned();
+	});
+	let next = table
+		.iter()
+		.filter(|(_, item)| is_body_entry(item))
+		.map(|(k, _)| k)
+		.skip_while(|&k| k != key)
+		.nth(1)
+		.map(str::to_owned);
+	table.remove(key)?;
+	if detached.is_empty() {
+		return Some(None);
+	}
+	match next {
+		Some(next) => {
+			with_first_line_decor(table, &next, &mut |decor| prepend_prefix(decor, &detached));
+			Some(None)
+		},
+		None => Some(Some(Leftover { after : container_position, text : detached })),
+	}
+}
+
+/// Removes an element from an array, keeping the layout of the others.
+fn remove_array_index(array :&mut Array, index :usize) {
+	let removed = array.remove(index);
+	let single_line = |v :&Value| !raw_prefix(v.decor()).contains('\n');
+	if let Some(next) = array.get_mut(index) {
+		// The new first element takes over the spacing after the `[`
+		if index == 0 && single_line(next) {
+			next.decor_mut().set_prefix(raw_prefix(removed.decor()).to_owned());
+		}
+	} else if let Some(last) = index.checked_sub(1).and_then(|i| array.get_mut(i)) {
+		// The new last element takes over the spacing before the `]`
+		if single_line(last) {
+			if let Some(suffix) = removed.decor().suffix() {
+				last.decor_mut().set_suffix(suffix.clone());
+			}
+		}
+	}
+}
+
+fn remove_inline_entry(table :&mut InlineTable, key :&str) -> bool {
+	let was_last = table.iter().last().is_some_and(|(k, _)| k == key);
+	let removed = match table.remove(key) {
+		Some(removed) => removed,
+		None => return false,
+	};
+	// Keep the space in front of the closing brace
+	if let (true, Some(suffix)) = (was_last, removed.decor().suffix()) {
+		if let Some((_, last)) = table.iter_mut().last() {
+			last.decor_mut().set_suffix(suffix.clone());
+		}
+	}
+	true
+}
+
+/// Whether the item is displayed as part of the key/value pairs
+/// of its table rather than under a header of its own.
+fn is_body_entry(item :&Item) -> bool {
+	item.is_value() || item.as_table().is_some_and(Table::is_dotted)
+}
+
+/// Calls `f` with the decor in front of the first line of the entry.
+///
+/// For dotted keys (`foo.version = "1"`), this is the decor of the
+/// first of the keys.
+fn with_first_line_decor(table :&mut dyn TableLike, key :&str, f :&mut dyn FnMut(&mut Decor)) {
+	let (mut key, item) = match table.get_key_value_mut(key) {
+		Some(entry) => entry,
+		None => return,
+	};
+	match item.as_table_mut().filter(|t| t.is_dotted()) {
+		Some(dotted) => {
+			let first = dotted.iter().next().map(|(k, _)| k.to_owned());
+			if let Some(first) = first {
+				with_first_line_decor(dotted, &first, f);
+			}
+		},
+		None => f(key.leaf_decor_mut()),
+	}
+}
+
+fn raw_prefix(decor :&Decor) -> &str {
+	decor.prefix().and_then(RawString::as_str).unwrap_or_default()
+}
+
+fn prepend_prefix(decor :&mut Decor, text :&str) {
+	let prefix = join_prefix(text, raw_prefix(decor));
+	decor.set_prefix(prefix);
+}
+
+/// Puts `text` in front of `prefix` without ending up with two blank lines in a row.
+fn join_prefix(text :&str, mut prefix :&str) -> String {
+	if let Some((first_line, rest)) = prefix.split_once('\n') {
+		let last_line = text.strip_suffix('\n').map(|t| t.rsplit('\n').next().unwrap_or_default());
+		if first_line.trim().is_empty() && last_line.is_some_and(|l| l.trim().is_empty()) {
+			prefix = rest;
+		}
+	}
+	format!("{}{}", text, prefix)
+}
+
+/// Splits `prefix` after its last blank line.
+///
+/// The first part is separated from the entry by a blank line and rather
+/// belongs to what precedes the entry. The second part documents the entry.
+fn split_detached(prefix :&str) -> (&str, &str) {
+	let mut split = 0;
+	let mut line_start = 0;
+	for (i, c) in prefix.char_indices() {
+		if c == '\n' {
+			if prefix[line_start..i].trim().is_empty() {
+				split = i + 1;
+			}
+			line_start = i + 1;
+		}
+	}
+	prefix.split_at(split)
+}
+
+/// Returns the smallest position of a table header after `position`.
+fn next_header_position(table :&Table, position :usize) -> Option<usize> {
+	table
+		.iter()
+		.flat_map(|(_, item)| match item {
+			Item::Table(t) => vec![t],
+			Item::ArrayOfTables(a) => a.iter().collect(),
+			_ => vec![],
+		})
+		.flat_map(|t| {
+			let own = t.position().filter(|&p| p > position && !t.is_implicit());
+			own.into_iter().chain(next_header_position(t, position))
+		})
+		.min()
+}
+
+fn header_mut(table :&mut Table, position :usize) -> Option<&mut Table> {
+	for (_, item) in table.iter_mut() {
+		let tables :Vec<&mut Table> = match item {
+			Item::Table(t) => vec![t],
+			Item::ArrayOfTables(a) => a.iter_mut().collect(),
+			_ => continue,
+		};
+		for t in tables {
+			if t.position() == Some(position) && !t.is_implicit() {
+				return Some(t);
+			}
+			if let Some(t) = header_mut(t, position) {
+				return Some(t);
+			}
+		}
+	}
+	None
+}
+
+/// Refuses to go on if any of the given manifests has uncommitted changes,
+/// or is not inside of a git repository, mirroring the semantics of `cargo fix`.
+///
+/// Like with `cargo fix`, `allow_no_vcs` skips all checks.
+pub(crate) fn check_version_control<'a>(
+	manifest_paths :impl IntoIterator<Item = &'a Path>,
+	allow_no_vcs :bool,
+	allow_dirty :bool,
+	allow_staged :bool,
+) -> CargoResult<()> {
+	if allow_no_vcs {
+		return Ok(());
+	}
+
+	let mut no_vcs_files = Vec::new();
+	let mut dirty_files = Vec::new();
+	let mut staged_files = Vec::new();
+	for manifest_path in manifest_paths {
+		let repo = manifest_path.parent().and_then(|dir| git2::Repository::discover(dir).ok());
+		let relative_path = repo
+			.as_ref()
+			.and_then(|repo| repo.workdir())
+			.and_then(|workdir| manifest_path.strip_prefix(workdir).ok());
+		let (repo, relative_path) = match (&repo, relative_path) {
+			(Some(repo), Some(relative_path)) => (repo, relative_path),
+			_ => {
+				no_vcs_files.push(manifest_path.display().to_string());
+				continue;
+			},
+		};
+		if allow_dirty && allow_staged {
+			continue;
+		}
+		let status = repo.status_file(relative_path)?;
+		let wt = git2::Status::WT_NEW
+			| git2::Status::WT_MODIFIED
+			| git2::Status::WT_DELETED
+			| git2::Status::WT_RENAMED
+			| git2::Status::WT_TYPECHANGE
+			| git2::Status::CONFLICTED;
+		let index = git2::Status::INDEX_NEW
+			| git2::Status::INDEX_MODIFIED
+			| git2::Status::INDEX_DELETED
+			| git2::Status::INDEX_RENAMED
+			| git2::Status::INDEX_TYPECHANGE;
+		if status.intersects(wt) {
+			if !allow_dirty {
+				dirty_files.push(manifest_path.display().to_string());
+			}
+		} else if status.intersects(index) && !allow_staged {
+			staged_files.push(manifest_path.display().to_string());
+		}
+	}
+
+	if !no_vcs_files.is_empty() {
+		let mut files_list = String::new();
+		for file in no_vcs_files {
+			files_list += &format!("  * {}\n", file);
+		}
+		return Err(anyhow::anyhow!(
+			"no VCS found for some manifests, and `cargo-udeps` would modify them; \
+			 if you'd like to suppress this error pass `--allow-no-vcs`:\n\
+			 \n\
+			 {}",
+			files_list,
+		));
+	}
+	if dirty_files.is_empty() && staged_files.is_empty() {
+		return Ok(());
+	}
+
+	let mut files_list = String::new();
+	for file in dirty_files {
+		files_list += &format!("  * {} (dirty)\n", file);
+	}
+	for file in staged_files {
+		files_list += &format!("  * {} (staged)\n", file);
+	}
+
+	Err(anyhow::anyhow!(
+		"some manifests have uncommitted changes, and `cargo-udeps` would \
+		 modify them; if you'd like to suppress this error pass `--allow-dirty`, \
+		 `--allow-staged`, or commit the changes to these files:\n\
+		 \n\
+		 {}",
+		files_list,
+	))
+}
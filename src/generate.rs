@@ -0,0 +1,29 @@
+//! Shell completions and the manpage, for `--generate-completion` and `--generate-manpage`.
+
+use std::io::{self, Write};
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use crate::Opt;
+
+/// Writes the completion script of `shell`, which completes `cargo udeps`.
+pub(crate) fn completion(shell :Shell, mut stdout :impl Write) -> io::Result<()> {
+	// `clap_complete` panics if it can't write
+	let mut script = Vec::new();
+	clap_complete::generate(shell, &mut Opt::command(), "cargo", &mut script);
+	stdout.write_all(&script)?;
+	stdout.flush()
+}
+
+/// Writes the manpage of `cargo-udeps` in roff.
+pub(crate) fn manpage(mut stdout :impl Write) -> io::Result<()> {
+	let udeps = Opt::command()
+		.find_subcommand("udeps")
+		.expect("`udeps` is the only subcommand")
+		.clone()
+		.name("cargo-udeps")
+		.bin_name("cargo udeps");
+	clap_mangen::Man::new(udeps).render(&mut stdout)?;
+	stdout.flush()
+}
@@ -0,0 +1,133 @@
+//! A record of the findings of past runs, to show their trend.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cargo::core::dependency::DepKind;
+use cargo::CargoResult;
+use serde::{Deserialize, Serialize};
+
+use crate::Outcome;
+
+/// A line of the history file.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Entry {
+	/// Seconds since the Unix epoch
+	timestamp :u64,
+	packages_checked :usize,
+	unused :usize,
+	normal :usize,
+	development :usize,
+	build :usize,
+}
+
+impl Entry {
+	pub(crate) fn new(outcome :&Outcome) -> Self {
+		let count = |kind| outcome.unused_deps.values().map(|deps| deps.unused_deps(kind).len()).sum::<usize>();
+		let (normal, development, build) = (count(DepKind::Normal), count(DepKind::Development), count(DepKind::Build));
+		Self {
+			timestamp : SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+			packages_checked : outcome.packages_checked,
+			unused : normal + development + build,
+			normal,
+			development,
+			build,
+		}
+	}
+
+	/// Appends the entry to the history file, creating it if necessary.
+	pub(crate) fn append(&self, path :&Path) -> CargoResult<()> {
+		use anyhow::Context;
+		let mut file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(path)
+			.with_context(|| format!("could not open the history file `{}`", path.display()))?;
+		writeln!(file, "{}", serde_json::to_string(self)?)?;
+		Ok(())
+	}
+}
+
+pub(crate) fn read(path :&Path) -> CargoResult<Vec<Entry>> {
+	use anyhow::Context;
+	cargo_util::paths::read(path)?
+		.lines()
+		.enumerate()
+		.filter(|(_, line)| !line.trim().is_empty())
+		.map(|(i, line)| {
+			serde_json::from_str(line)
+				.with_context(|| format!("could not parse line {} of `{}`", i + 1, path.display()))
+		})
+		.collect()
+}
+
+/// Prints one line per run with the change to the previous one, and a summary.
+pub(crate) fn print_trend(entries :&[Entry], mut stdout :impl Write) -> CargoResult<()> {
+	let mut previous :Option<&Entry> = None;
+	for entry in entries {
+		write!(
+			stdout,
+			"{}  {:>4} unused ({} normal, {} dev, {} build) in {} packages",
+			format_timestamp(entry.timestamp),
+			entry.unused,
+			entry.normal,
+			entry.development,
+			entry.build,
+			entry.packages_checked,
+		)?;
+		if let Some(previous) = previous {
+			write!(stdout, "  {}", format_change(previous.unused, entry.unused))?;
+		}
+		writeln!(stdout)?;
+		previous = Some(entry);
+	}
+	match (entries.first(), entries.last()) {
+		(Some(first), Some(last)) if entries.len() > 1 => {
+			let trend = match last.unused.cmp(&first.unused) {
+				std::cmp::Ordering::Less => "going down",
+				std::cmp::Ordering::Equal => "unchanged",
+				std::cmp::Ordering::Greater => "going up",
+			};
+			writeln!(
+				stdout,
+				"Trend: {} ({} over {} runs)",
+				trend,
+				format_change(first.unused, last.unused),
+				entries.len(),
+			)?;
+		},
+		(Some(_), _) => writeln!(stdout, "Trend: only one run recorded")?,
+		(None, _) => writeln!(stdout, "Trend: no runs recorded")?,
+	}
+	stdout.flush()?;
+	Ok(())
+}
+
+fn format_change(from :usize, to :usize) -> String {
+	if to >= from {
+		format!("+{}", to - from)
+	} else {
+		format!("-{}", from - to)
+	}
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub(crate) fn format_timestamp(timestamp :u64) -> String {
+	let (days, seconds) = (timestamp / 86400, timestamp % 86400);
+	// Howard Hinnant's `civil_from_days`
+	let z = days as i64 + 719468;
+	let era = z.div_euclid(146097);
+	let doe = z.rem_euclid(146097);
+	let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + i64::from(month <= 2);
+	format!(
+		"{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+		year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60,
+	)
+}
@@ -0,0 +1,128 @@
+//! A standalone HTML page of the findings, for `--output html`, to keep as an artifact of CI jobs.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+
+use cargo::core::dependency::DepKind;
+use toml_edit::ImDocument;
+
+use crate::diagnostic::declaration_line;
+use crate::sarif::uri;
+use crate::Outcome;
+
+const KINDS :[(DepKind, &str, &str); 3] = [
+	(DepKind::Normal, "normal", "dependencies"),
+	(DepKind::Development, "dev", "dev-dependencies"),
+	(DepKind::Build, "build", "build-dependencies"),
+];
+
+const STYLE :&str = "body { font-family: sans-serif; margin: 2em auto; max-width: 60em; padding: 0 1em; }
+table { border-collapse: collapse; }
+th, td { padding: 0.2em 0.8em; text-align: left; }
+td.count { text-align: right; }
+td.chart { width: 20em; }
+.bar { display: inline-block; height: 0.8em; }
+.normal { background: #d73a49; }
+.dev { background: #e36209; }
+.build { background: #6f42c1; }
+.warn { color: #b08800; }
+";
+
+/// Prints the page.
+///
+/// The links to the manifests are relative to `root` if their paths are, so the page is
+/// meant to be opened from the root of the workspace.
+pub(crate) fn print(outcome :&Outcome, root :&Path, mut stdout :impl Write) -> io::Result<()> {
+	stdout.write_all(html(outcome, root).as_bytes())?;
+	stdout.flush()
+}
+
+fn html(outcome :&Outcome, root :&Path) -> String {
+	let mut html = String::new();
+	html += "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n";
+	html += "<title>cargo-udeps report</title>\n";
+	writeln!(html, "<style>\n{}</style>\n</head>\n<body>\n<h1>cargo-udeps</h1>", STYLE).unwrap();
+	let counts = outcome.unused_deps
+		.iter()
+		.map(|(id, deps)| (id, KINDS.map(|(kind, _, _)| deps.unused_deps(kind).len())))
+		.filter(|(_, counts)| counts.iter().any(|&count| count > 0))
+		.collect::<Vec<_>>();
+	let count = counts.iter().flat_map(|(_, counts)| counts).sum::<usize>();
+	if count == 0 {
+		writeln!(html, "<p>All deps of the {} checked packages seem to have been used.</p>", outcome.packages_checked).unwrap();
+	} else {
+		writeln!(
+			html,
+			"<p>{} unused dependenc{} in {} of the {} checked packages.</p>",
+			count,
+			if count == 1 { "y" } else { "ies" },
+			counts.len(),
+			outcome.packages_checked,
+		).unwrap();
+
+		// The bars are as long as the number of unused dependencies, relative to the package with the most
+		let max = counts.iter().map(|(_, counts)| counts.iter().sum::<usize>()).max().unwrap_or(1);
+		html += "<table>\n<tr><th>Package</th><th>Normal</th><th>Development</th><th>Build</th><th></th></tr>\n";
+		for (i, (id, counts)) in counts.iter().enumerate() {
+			write!(html, "<tr><td><a href=\"#package-{}\">{} v{}</a></td>", i, escape(&id.name()), id.version()).unwrap();
+			for count in counts {
+				write!(html, "<td class=\"count\">{}</td>", count).unwrap();
+			}
+			html += "<td class=\"chart\">";
+			for ((_, class, _), &count) in KINDS.iter().zip(counts).filter(|(_, &count)| count > 0) {
+				write!(html, "<span class=\"bar {}\" style=\"width: {:.1}%\"></span>", class, 100.0 * count as f64 / max as f64).unwrap();
+			}
+			html += "</td></tr>\n";
+		}
+		html += "</table>\n";
+	}
+
+	for (i, (id, _)) in counts.iter().enumerate() {
+		let deps = &outcome.unused_deps[id];
+		let manifest_path = root.join(&deps.manifest_path);
+		let href = if Path::new(&deps.manifest_path).is_relative() {
+			uri(Path::new(&deps.manifest_path))
+		} else {
+			uri(&manifest_path)
+		};
+		let contents = std::fs::read_to_string(&manifest_path).ok();
+		let doc = contents.as_deref().and_then(|contents| ImDocument::parse(contents).ok());
+		writeln!(html, "<section id=\"package-{}\">\n<h2>{} v{}</h2>", i, escape(&id.name()), id.version()).unwrap();
+		writeln!(html, "<p><a href=\"{}\">{}</a></p>", escape(&href), escape(&deps.manifest_path)).unwrap();
+		for (kind, _, heading) in KINDS {
+			if deps.unused_deps(kind).is_empty() {
+				continue;
+			}
+			writeln!(html, "<h3>{}</h3>\n<ul>", heading).unwrap();
+			for dep in deps.unused_deps(kind) {
+				let line = contents.as_deref().zip(doc.as_ref()).and_then(|(contents, doc)| declaration_line(contents, doc, kind, dep));
+				let location = match line {
+					Some(line) => format!("Cargo.toml:{}", line),
+					None => "Cargo.toml".to_owned(),
+				};
+				let (class, warn_only) = if deps.warn_only.contains(kind, dep) {
+					(" class=\"warn\"", " (warning only)")
+				} else {
+					("", "")
+				};
+				writeln!(
+					html,
+					"<li{}><code>{}</code>{}, <a href=\"{}\">{}</a></li>",
+					class, escape(dep), warn_only, escape(&href), location,
+				).unwrap();
+			}
+			html += "</ul>\n";
+		}
+		html += "</section>\n";
+	}
+	html += "</body>\n</html>\n";
+	html
+}
+
+fn escape(text :&str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
@@ -0,0 +1,77 @@
+//! Scaffolding of the configuration, for `cargo udeps init`.
+
+use std::fmt::Write as _;
+use std::io::Write;
+
+use cargo::core::Workspace;
+use cargo::CargoResult;
+use toml_edit::DocumentMut;
+
+/// Appends a starter configuration to the root manifest of the workspace, or only
+/// prints it with `dry_run`.
+///
+/// It goes into `workspace.metadata` if the manifest has a `[workspace]` table, and
+/// into `package.metadata` otherwise.
+pub(crate) fn init(ws :&Workspace<'_>, dry_run :bool, mut stdout :impl Write) -> CargoResult<()> {
+	let path = ws.root_manifest();
+	let mut contents = cargo_util::paths::read(path)?;
+	let doc = contents.parse::<DocumentMut>()
+		.map_err(|e| anyhow::anyhow!("could not parse `{}`: {}", path.display(), e))?;
+	let parent = if doc.contains_key("workspace") { "workspace" } else { "package" };
+	let existing = doc
+		.get(parent)
+		.and_then(|parent| parent.get("metadata"))
+		.and_then(|metadata| metadata.get("cargo-udeps"));
+	if existing.is_some() {
+		return Err(anyhow::anyhow!(
+			"`{}` already has a `{}.metadata.cargo-udeps` table",
+			path.display(),
+			parent,
+		));
+	}
+
+	let table = format!("{}.metadata.cargo-udeps", parent);
+	let members = ws.members().map(|member| format!("{:?}", member.name().as_str())).collect::<Vec<_>>();
+	let block = config_block(&table, parent == "workspace", &members);
+	if dry_run {
+		write!(stdout, "{}", block)?;
+		stdout.flush()?;
+		return Ok(());
+	}
+
+	if !contents.is_empty() {
+		contents.truncate(contents.trim_end().len());
+		contents += "\n\n";
+	}
+	contents += &block;
+	cargo_util::paths::write(path, contents)?;
+	ws.gctx().shell().status("Added", format!("`[{}]` to `{}`", table, path.display()))?;
+	Ok(())
+}
+
+fn config_block(table :&str, workspace :bool, members :&[String]) -> String {
+	let mut block = String::new();
+	writeln!(block, "[{}]", table).unwrap();
+	if workspace {
+		writeln!(block, "# The teams that own the members, by package name patterns").unwrap();
+		writeln!(block, "#owners = {{ my-team = [{}] }}", members.join(", ")).unwrap();
+		writeln!(block, "# Or look the owners up in a CODEOWNERS file").unwrap();
+		writeln!(block, "#codeowners = \".github/CODEOWNERS\"").unwrap();
+	}
+	writeln!(block, "# Keep `cargo udeps apply` from removing crates of these registries").unwrap();
+	writeln!(block, "#protected-registries = []").unwrap();
+	block += r#"
+# Dependencies that are used in ways `cargo-udeps` can't detect, like only in doc-tests
+#[TABLE.ignore]
+#normal = []
+#development = []
+#build = []
+
+# Recommended for CI, with `cargo +nightly udeps --workspace --all-targets`:
+# fail on new unused dependencies, but only warn about those that are known
+# to be hard to detect, and keep `apply` from removing them
+#[TABLE.policy]
+#some-crate = { severity = "warn", auto-fix = false }
+"#;
+	block.replace("TABLE", table)
+}
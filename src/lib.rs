@@ -1,5 +1,6 @@
+use std::backtrace::BacktraceStatus;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::fmt::Write as _;
 use std::io::{self, Write};
 use std::ops::{Deref, Index, IndexMut};
@@ -7,33 +8,144 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Instant;
 use std::{env, fmt};
 
 use nu_ansi_term::Color;
-use cargo::core::compiler::{DefaultExecutor, Executor, RustcTargetData, Unit};
+use cargo::core::compiler::{BuildRunner, CompileKind, DefaultExecutor, Executor, RustcTargetData, Unit};
 use cargo::core::resolver::HasDevUnits;
-use cargo::core::resolver::features::{ForceAllTargets, CliFeatures};
+use cargo::core::resolver::features::{ForceAllTargets, CliFeatures, FeaturesFor, ResolvedFeatures};
 use cargo::core::manifest::Target;
 use cargo::core::package_id::PackageId;
 use cargo::core::shell::Shell;
-use cargo::core::{dependency, Package, Resolve, Workspace, Verbosity};
-use cargo::ops::Packages;
+use cargo::core::{dependency, Dependency, FeatureValue, Package, Resolve, SourceId, Workspace, Verbosity};
+use cargo::ops::{CompileFilter, CompileOptions, Packages, WorkspaceResolve};
 use cargo::util::command_prelude::{ArgMatchesExt, CompileMode, ProfileChecking};
 use cargo::util::context::GlobalContext;
 use cargo::util::interning::InternedString;
+use cargo::util::Rustc;
 use cargo_util::ProcessBuilder;
 use cargo::{CargoResult, CliError, CliResult};
 use serde::{Deserialize, Serialize};
-use clap::{ArgAction, ArgMatches, CommandFactory, Parser};
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory, Parser, Subcommand};
+
+mod analysis;
+mod baseline;
+#[doc(hidden)]
+pub mod bench;
+mod blame;
+mod cache;
+mod collector;
+mod compat;
+mod config_file;
+mod container;
+mod defaults;
+mod diagnostic;
+mod doctests;
+mod duplicates;
+mod explain;
+mod features;
+mod fix;
+mod generate;
+mod history;
+mod html;
+mod init;
+mod matrix;
+mod opt_out;
+mod owners;
+mod patches;
+mod readonly;
+mod remap;
+mod render;
+mod report;
+mod sarif;
+mod since;
+mod stream;
+mod summary;
+mod template;
+mod timings;
+mod toolchain;
+mod watch;
+mod why;
+mod wrap;
+
+pub use collector::Collector;
+pub use report::{CheckOptions, PackageReport, Report, UnusedDependency};
 
 pub fn run<I: IntoIterator<Item = OsString>, W: Write>(args :I, config :&mut GlobalContext, stdout: W) -> CliResult {
-	let args = args.into_iter().collect::<Vec<_>>();
-	let Opt::Udeps(opt) = Opt::try_parse_from(&args)?;
+	let args = expand_arg_files(args, config.cwd())?;
+	let Opt::Udeps(mut opt) = Opt::try_parse_from(&args)?;
+	// Nothing of the workspace is needed for these
+	if let Some(shell) = opt.generate_completion {
+		generate::completion(shell, stdout)?;
+		return Ok(());
+	}
+	if opt.generate_manpage {
+		generate::manpage(stdout)?;
+		return Ok(());
+	}
+	let clap_matches = Opt::command().try_get_matches_from(args)?;
+	let clap_matches = clap_matches.subcommand_matches("udeps").unwrap();
+	opt.apply_config_file(config.cwd(), clap_matches)?;
+	match opt.run(config, stdout, clap_matches) {
+		Ok(0) => Ok(()),
+		Ok(code) => Err(CliError::code(code)),
+		Err(err) => {
+			// Cargo only prints the chain of causes, even with `-vv`. Errors only have a backtrace
+			// if it's enabled, which the `cargo-udeps` binary does for `-vv`.
+			if opt.verbose >= 2 && err.backtrace().status() == BacktraceStatus::Captured {
+				config.shell().note(format!("backtrace of the error:\n{}", err.backtrace()))?;
+			}
+			Err(err.into())
+		},
+	}
+}
+
+/// Checks like `cargo udeps` does, but returns the findings instead of printing them.
+pub fn check(options :&CheckOptions, config :&mut GlobalContext) -> CargoResult<Report> {
+	let args = ["cargo".into(), "udeps".into()].into_iter().chain(options.args.iter().cloned());
+	let Opt::Udeps(mut opt) = Opt::try_parse_from(args.clone())?;
+	if opt.subcommand.is_some() {
+		return Err(anyhow::anyhow!("subcommands of `cargo udeps` can't be used for a check"));
+	}
+	if opt.generate_completion.is_some() || opt.generate_manpage {
+		return Err(anyhow::anyhow!("`--generate-completion` and `--generate-manpage` can't be used for a check"));
+	}
 	let clap_matches = Opt::command().try_get_matches_from(args)?;
-	match opt.run(config, stdout, clap_matches.subcommand_matches("udeps").unwrap())? {
-		0 => Ok(()),
-		code => Err(CliError::code(code)),
+	let clap_matches = clap_matches.subcommand_matches("udeps").unwrap();
+	opt.apply_config_file(config.cwd(), clap_matches)?;
+	let mut report = None;
+	opt.check(config, io::sink(), clap_matches, &mut report)?;
+	report.ok_or_else(|| anyhow::anyhow!("the options didn't ask for a check"))
+}
+
+/// The width to wrap the human output to: `$COLUMNS`, or the one of the terminal.
+fn output_width(config :&GlobalContext) -> Option<usize> {
+	use std::io::IsTerminal;
+	match env::var("COLUMNS").ok().and_then(|columns| columns.parse().ok()) {
+		Some(columns) => Some(columns),
+		// The width is only known for stderr, which usually is the same terminal
+		None if io::stdout().is_terminal() => config.shell().err_width().progress_max_width(),
+		None => None,
+	}
+}
+
+/// Replaces `@FILE` arguments with the lines of `FILE`, one argument per line.
+fn expand_arg_files(args :impl IntoIterator<Item = OsString>, cwd :&Path) -> CargoResult<Vec<OsString>> {
+	let mut expanded = Vec::new();
+	for arg in args {
+		match arg.to_str().and_then(|arg| arg.strip_prefix('@')) {
+			Some(path) => {
+				use anyhow::Context;
+				let contents = cargo_util::paths::read(&cwd.join(path))
+					.with_context(|| format!("could not read the argument file `{}`", path))?;
+				expanded.extend(contents.lines().filter(|line| !line.is_empty()).map(OsString::from));
+			},
+			None => expanded.push(arg),
+		}
 	}
+	Ok(expanded)
 }
 
 #[derive(Parser, Debug)]
@@ -56,14 +168,21 @@ current package is built. For more information on SPEC and its format, see the
 
 All packages in the workspace are checked if the `--workspace` flag is supplied. The
 `--workspace` flag is automatically assumed for a virtual manifest.
+If the workspace has `default-members`, only those are checked by default.
+`--include-excluded-members` checks the other members, like `xtask` crates, as well.
 Note that `--exclude` has to be specified in conjunction with the `--workspace` flag.
+In a virtual workspace, `--package` picks members from the workspace root, and it
+is an error to name a package that isn't a member, like a dependency.
+Members that aren't selected are only compiled if a selected one depends on them,
+and aren't reported.
 
 Compilation can be configured via the use of profiles which are configured in
 the manifest. The default profile for this command is `dev`, but passing
 the `--release` flag will use the `release` profile instead.
 
 The `--profile test` flag can be used to check unit tests with the
-`#[cfg(test)]` attribute."
+`#[cfg(test)]` attribute. Other profiles, like the custom ones of the manifest,
+are used like with `cargo check --profile`."
 		)
 	)]
 	Udeps(OptUdeps),
@@ -95,6 +214,16 @@ struct OptUdeps {
 		help("[cargo] Exclude packages from the check")
 	)]
 	exclude: Vec<String>,
+	#[arg(long, help("Also check the workspace members that are left out of `default-members`"))]
+	include_excluded_members: bool,
+	#[arg(long, help("Also list the workspace members that no other member depends on and that have no binaries"))]
+	check_workspace_members: bool,
+	#[arg(long, help("Also list the features in `[features]` that neither enable a dependency nor are used by the sources"))]
+	check_features: bool,
+	#[arg(long, help("Also list the crates that the members depend on in several versions, directly or not"))]
+	check_duplicates: bool,
+	#[arg(long, help("Also list the entries of `[patch]` and `[replace]` that no dependency resolves to"))]
+	check_patches: bool,
 	#[arg(
 		short,
 		long,
@@ -155,25 +284,52 @@ struct OptUdeps {
 	)]
 	profile: Option<String>,
 	#[arg(
+		short = 'F',
 		long,
 		value_name("FEATURES"),
 		num_args(0..),
-		help("[cargo] Space-separated list of features to activate")
+		help("[cargo] Space or comma separated list of features to activate, `PACKAGE/FEATURE` for members")
 	)]
 	features: Vec<String>,
+	#[arg(
+		long,
+		value_name("NAME"),
+		help("Check with the features of a `feature-sets` entry of the configuration, or with each of them for `all`"),
+	)]
+	feature_set: Option<String>,
+	#[arg(
+		long,
+		conflicts_with_all(["feature_set", "all-features", "no-default-features"]),
+		help("Check with no features, the default ones, all of them, each of them alone and each feature set"),
+	)]
+	check_feature_combinations: bool,
+	#[arg(long, help("Enable the features that checked targets of the members require, so that they are checked as well"))]
+	include_required_features: bool,
 	#[arg(long, help("[cargo] Activate all available features"), id = "all-features", value_parser = clap::value_parser!(bool))]
 	all_features: bool,
 	#[arg(long, help("[cargo] Do not activate the `default` feature"), id = "no-default-features", value_parser = clap::value_parser!(bool))]
 	no_default_features: bool,
-	#[arg(long, value_name("TRIPLE"), help("[cargo] Check for the target triple"))]
-	target: Option<String>,
+	#[arg(long, value_name("TRIPLE"), help("[cargo] Check for the target triple, which can be given several times"))]
+	target: Vec<String>,
 	#[arg(
 		long,
 		value_name("DIRECTORY"),
 		help("[cargo] Directory for all generated artifacts")
 	)]
 	target_dir: Option<PathBuf>,
-	#[arg(long, value_name("PATH"), id = "manifest-path", help("[cargo] Path to Cargo.toml"))]
+	#[arg(
+		long,
+		conflicts_with("target_dir"),
+		help("Build in a new temporary target directory, which is removed afterwards"),
+	)]
+	ephemeral: bool,
+	#[arg(long, help("Go on even if `Cargo.lock` is newer than the embedded cargo supports"))]
+	allow_newer_lockfile: bool,
+	#[arg(long, help("Install the nightly toolchain with rustup if it is needed and missing"))]
+	install_toolchain: bool,
+	#[arg(long, help("Rebuild every checked unit instead of reusing what earlier runs found out about fresh ones"))]
+	no_cache: bool,
+	#[arg(long, value_name("PATH"), id = "manifest-path", global = true, help("[cargo] Path to Cargo.toml"))]
 	manifest_path: Option<String>,
 	#[arg(
 		long,
@@ -206,14 +362,129 @@ struct OptUdeps {
 	locked: bool,
 	#[arg(long, help("[cargo] Run without accessing the network"), value_parser = clap::value_parser!(bool))]
 	offline: bool,
+	#[arg(long, value_name("KEY=VALUE"), help("[cargo] Override a configuration value"))]
+	config: Vec<String>,
+	#[arg(
+		short = 'Z',
+		value_name("FLAG"),
+		help("[cargo] Unstable (nightly-only) flags to Cargo, e.g. `-Z build-std`")
+	)]
+	unstable_flags: Vec<String>,
 	#[arg(
 		long,
 		value_name("OUTPUT"),
 		default_value("human"),
 		value_enum,
+		alias("report"),
 		help("Output format"))
 	]
 	output: OutputKind,
+	#[arg(
+		long,
+		value_name("STYLE"),
+		default_value("relative"),
+		value_enum,
+		help("Show manifest paths relative to the workspace root, or absolute"),
+	)]
+	path_style: PathStyle,
+	#[arg(
+		long,
+		value_name("KEY"),
+		default_value("package"),
+		value_enum,
+		help("List the unused dependencies by package, or each of them once with the packages that have it"),
+	)]
+	group_by: GroupBy,
+	#[arg(
+		long,
+		value_name("FILE"),
+		help("Template for the human output, see the README for the syntax"),
+	)]
+	format_template: Option<PathBuf>,
+	#[arg(
+		long,
+		value_name("COMMAND"),
+		help("Shell command to run with the JSON report on stdin if there are unused dependencies"),
+	)]
+	on_unused: Option<String>,
+	#[arg(
+		long,
+		value_name("[KIND=]N"),
+		help("Only fail if there are more than N unused dependencies (of KIND: normal, development or build)"),
+	)]
+	max_unused: Vec<String>,
+	#[arg(
+		long,
+		value_name("FILE"),
+		help("Append a summary of the findings to this file, see `cargo udeps trend`"),
+	)]
+	history_file: Option<PathBuf>,
+	#[arg(
+		long,
+		value_name("FILE"),
+		help("Don't report the unused dependencies that are listed in this file, see `--write-baseline`"),
+	)]
+	baseline: Option<PathBuf>,
+	#[arg(long, value_name("FILE"), help("Write the unused dependencies into this file, to be passed to `--baseline`"))]
+	write_baseline: Option<PathBuf>,
+	#[arg(long, help("Show the commit that added each unused dependency to `Cargo.toml` (git only)"))]
+	blame: bool,
+	#[arg(long, help("Show the description and repository of each unused dependency"))]
+	enrich: bool,
+	#[arg(
+		long,
+		value_name("FILE"),
+		help("Write the number of unused dependencies and errors to this file as JSON, also if the run fails"),
+	)]
+	status_file: Option<PathBuf>,
+	#[arg(long, help("Don't wrap the human output to the width of the terminal or `$COLUMNS`"))]
+	no_wrap: bool,
+	#[arg(long, help("Remove the unused dependencies from the manifests, like `cargo udeps apply`"))]
+	fix: bool,
+	#[arg(
+		long,
+		conflicts_with_all(["fix", "stream"]),
+		help("Check again whenever a manifest or source file of the workspace changes, until stopped"),
+	)]
+	watch: bool,
+	#[arg(long, requires("fix"), help("Print what `--fix` would remove without writing any manifest"))]
+	dry_run: bool,
+	#[arg(long, requires("fix"), help("Fix manifests even if they are not in a git repository"))]
+	allow_no_vcs: bool,
+	#[arg(long, requires("fix"), help("Fix manifests even if they have uncommitted changes"))]
+	allow_dirty: bool,
+	#[arg(long, requires("fix"), help("Fix manifests even if they have staged changes"))]
+	allow_staged: bool,
+	#[arg(long, help("Print nothing but the names of the unused dependencies, one per line"))]
+	print_unused_names: bool,
+	#[arg(
+		long,
+		requires("print_unused_names"),
+		help("Print the names as `PACKAGE:DEPENDENCY`, with `--print-unused-names`"),
+	)]
+	with_package: bool,
+	#[arg(
+		long,
+		value_name("CODE|DEPENDENCY"),
+		help("Explain a kind of finding in detail, like `unused-dev`, or why a dependency is found to be used or not"),
+	)]
+	explain: Option<String>,
+	#[arg(long, help("Print the heuristics that can hide unused dependencies out of the box"))]
+	print_default_ignores: bool,
+	#[arg(long, value_name("SHELL"), help("Print the completion script of a shell: bash, zsh, fish, powershell or elvish"))]
+	generate_completion: Option<clap_complete::Shell>,
+	#[arg(long, conflicts_with("generate_completion"), help("Print the manpage of `cargo udeps` in roff"))]
+	generate_manpage: bool,
+	#[arg(long, help("Turn off the special cases of `--print-default-ignores`, for raw results"))]
+	no_default_ignores: bool,
+	#[arg(long, help("List the unused dependencies that are not reported, and why"))]
+	show_ignored: bool,
+	#[arg(long, value_name("LINT"), help("Make the unused dependencies of a kind fail the run: unused-normal, unused-dev or unused-build"))]
+	deny: Vec<Lint>,
+	#[arg(long, value_name("LINT"), help("Report the unused dependencies of a kind without failing the run"))]
+	warn: Vec<Lint>,
+	#[arg(long, value_name("LINT"), help("Don't report the unused dependencies of a kind"))]
+	allow: Vec<Lint>,
 	#[arg(
 		long,
 		value_name("BACKEND"),
@@ -222,21 +493,247 @@ struct OptUdeps {
 		help("Backend to use for determining unused deps"))
 	]
 	backend :Backend,
+	/// The `exclude` of `.cargo-udeps.toml`.
+	#[arg(skip)]
+	excluded_by_config_file :Vec<String>,
 	#[arg(
 		long,
 		id = "keep-going",
-		help("Needed because the keep-going flag is asked about by cargo code"),
+		help("[cargo] Do not abort the build as soon as there is an error, and report on the units that were built"),
 		value_parser = clap::value_parser!(bool),
 	)]
 	keep_going :bool,
+	#[arg(
+		long,
+		requires("target"),
+		conflicts_with("container-command"),
+		help("Run the compiler in the container image that `cross` uses for the target"),
+		value_parser = clap::value_parser!(bool),
+	)]
+	use_cross :bool,
+	#[arg(
+		long,
+		id = "container-command",
+		value_name("COMMAND"),
+		help("Run the compiler through this command, e.g. `docker exec builder`"),
+	)]
+	container_command :Option<String>,
+	#[arg(
+		long,
+		value_name("HOST=CONTAINER"),
+		requires("container-command"),
+		help("Directory that is mounted at another path in the container"),
+	)]
+	container_path :Vec<String>,
+	#[arg(
+		long,
+		value_name("FROM=TO"),
+		help("Replace the prefix FROM of paths recorded by the build with TO when looking up its files"),
+	)]
+	remap_path_prefix :Vec<String>,
+	#[arg(
+		long,
+		value_name("DIRECTORY"),
+		help("Write the analysis into this directory, to be used with `--import-analysis`"),
+	)]
+	export_analysis :Option<PathBuf>,
+	#[arg(
+		long,
+		value_name("DIRECTORY"),
+		conflicts_with("export_analysis"),
+		help("Use the analysis written with `--export-analysis` instead of building"),
+	)]
+	import_analysis :Option<PathBuf>,
+	#[arg(
+		long,
+		value_name("FILE"),
+		help("Write the names the dependencies of each package are looked up by to this file as JSON, for debugging"),
+	)]
+	dump_name_map :Option<PathBuf>,
+	#[arg(long, help("Print what is taken from each compiler invocation, for debugging"))]
+	dump_cmd_info :bool,
+	#[arg(long, help("Fail if the dep-info file of a unit is missing or stale, instead of skipping the unit"))]
+	strict :bool,
+	#[arg(
+		long,
+		help("Check the members once more with `--cfg doc --cfg docsrs`, and report dependencies only used there as doc-only"),
+	)]
+	doc_cfg :bool,
+	#[arg(
+		long,
+		help("Also compile the doctests of the members, and regard the dependencies they use as used"),
+	)]
+	doctests :bool,
+	#[arg(
+		long,
+		help("Show the targets that use the dev-only dependencies, and suggest moving them to `[dev-dependencies]`"),
+	)]
+	show_unused_by_target :bool,
+	#[arg(
+		long,
+		value_name("K/N"),
+		help("Only check the K-th of N deterministic parts of the selected packages"),
+	)]
+	shard :Option<Shard>,
+	#[arg(
+		long,
+		value_name("REV"),
+		help("Only check the selected members with changes since this git revision, like `origin/main`"),
+	)]
+	since :Option<String>,
+	#[arg(
+		long,
+		value_name("SOURCE"),
+		help("Only report dependencies from this source: crates-io, git, path or registry:<NAME>"),
+	)]
+	source :Vec<SourceFilter>,
+	#[arg(
+		long = "timings",
+		// `timings` is cargo's own option, whose values are formats
+		id = "udeps-timings",
+		value_name("N"),
+		num_args(0..=1),
+		default_missing_value("10"),
+		help("Print how long the phases took, and the N (default 10) slowest crates"),
+	)]
+	timings :Option<usize>,
+	#[arg(long, help("Print the findings of each package as soon as it is built, as JSON lines with `--output json`"))]
+	stream :bool,
+	#[command(subcommand)]
+	subcommand :Option<UdepsSubcommand>,
+}
+
+#[derive(Subcommand, Debug)]
+enum UdepsSubcommand {
+	/// Remove the unused dependencies listed in a JSON report from the manifests
+	Apply(OptApply),
+	/// Combine the JSON reports of several `--shard`s into one
+	Merge(OptMerge),
+	/// Show how the number of unused dependencies developed over the runs of a `--history-file`
+	Trend(OptTrend),
+	/// Add a starter `cargo-udeps` configuration to the root `Cargo.toml`
+	Init(OptInit),
+	/// Print the `--target`s and `--features` that together check every declared dependency
+	CiMatrix(OptCiMatrix),
+}
+
+/// The features to check with in one run.
+struct FeatureSelection {
+	/// For the message at the start of the run, if there are several.
+	description :Option<String>,
+	features :Vec<String>,
+	all_features :bool,
+	default_features :bool,
+}
+
+/// What was found with the features of one run.
+struct FeatureRun<'gctx> {
+	ws_resolve :WorkspaceResolve<'gctx>,
+	dependency_names :Arc<HashMap<PackageId, DependencyNames>>,
+	analysis :analysis::Analysis,
+	/// The units that could not be analyzed, as the build failed with `--keep-going`.
+	unanalyzed_units :Vec<UnanalyzedUnit>,
+}
+
+#[derive(Parser, Debug)]
+struct OptApply {
+	#[arg(value_name("REPORT"), help("Path to a report produced by `--output json`"))]
+	report :PathBuf,
+	#[arg(long, help("Print what would be removed without writing any manifest"))]
+	dry_run :bool,
+	#[arg(long, help("Modify manifests even if they are not in a git repository"))]
+	allow_no_vcs :bool,
+	#[arg(long, help("Modify manifests even if they have uncommitted changes"))]
+	allow_dirty :bool,
+	#[arg(long, help("Modify manifests even if they have staged changes"))]
+	allow_staged :bool,
+	#[arg(long, help("Also remove `[workspace.dependencies]` entries that no member inherits any more"))]
+	prune_workspace_deps :bool,
+	#[arg(
+		long,
+		value_name("REGISTRY"),
+		help("Remove dependencies from this registry even if it is listed in `protected-registries`"),
+	)]
+	allow_registry :Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct OptMerge {
+	#[arg(value_name("REPORT"), required(true), help("Paths to reports produced by `--output json`"))]
+	reports :Vec<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+struct OptInit {
+	#[arg(long, help("Print the configuration instead of adding it"))]
+	dry_run :bool,
+}
+
+#[derive(Parser, Debug)]
+struct OptCiMatrix {
+	#[arg(long, value_name("TRIPLE"), help("Also consider this target, besides the host and some common ones"))]
+	candidate :Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct OptTrend {
+	#[arg(value_name("FILE"), help("Path to a file written with `--history-file`"))]
+	history_file :PathBuf,
 }
 
 impl OptUdeps {
+	/// Takes the settings of `.cargo-udeps.toml` that aren't given on the command line.
+	fn apply_config_file(&mut self, cwd :&Path, clap_matches :&ArgMatches) -> CargoResult<()> {
+		let Some((_, file)) = config_file::ConfigFile::find(cwd)? else {
+			return Ok(());
+		};
+		let on_command_line = |id| clap_matches.value_source(id) == Some(ValueSource::CommandLine);
+		if let Some(output) = file.output.filter(|_| !on_command_line("output")) {
+			self.output = output;
+		}
+		if let Some(backend) = file.backend.filter(|_| !on_command_line("backend")) {
+			self.backend = backend;
+		}
+		if self.feature_set.is_none() && !self.check_feature_combinations {
+			self.feature_set = file.feature_set;
+		}
+		self.excluded_by_config_file = file.exclude;
+		Ok(())
+	}
+
 	fn run<W: Write>(
 		&self,
 		config :&mut GlobalContext,
 		stdout :W,
 		clap_matches :&ArgMatches
+	) -> CargoResult<i32> {
+		if self.watch {
+			return watch::run(self, config, stdout, clap_matches);
+		}
+		let mut report = None;
+		let result = self.check(config, stdout, clap_matches, &mut report);
+		if let Some(path) = &self.status_file {
+			let status = serde_json::json!({
+				"unused": report.as_ref().map(Report::unused_count),
+				"errors": usize::from(result.is_err()),
+			});
+			let written = cargo_util::paths::write(config.cwd().join(path), format!("{}\n", status));
+			match (&result, written) {
+				(Ok(_), Err(e)) => return Err(e),
+				(Err(_), Err(e)) => config.shell().warn(format!("could not write the status file: {}", e))?,
+				(_, Ok(())) => (),
+			}
+		}
+		result
+	}
+
+	/// Does the work of [`OptUdeps::run`], setting `report` once the unused dependencies are known.
+	fn check<W: Write>(
+		&self,
+		config :&mut GlobalContext,
+		mut stdout :W,
+		clap_matches :&ArgMatches,
+		report :&mut Option<Report>,
 	) -> CargoResult<i32> {
 		if self.verbose > 0 {
 			let mut shell = config.shell();
@@ -247,258 +744,526 @@ impl OptUdeps {
 			shell.warn("- changes `$CARGO` to the value given from `cargo`")?;
 		}
 
+		if self.doc_cfg && self.backend == Backend::Lint {
+			return Err(anyhow::anyhow!("`--doc-cfg` needs `--backend depinfo`"));
+		}
+		let mut unstable_flags = Vec::new();
+		if self.backend == Backend::Depinfo {
+			unstable_flags.push("binary-dep-depinfo".to_string());
+		}
+		unstable_flags.extend(self.unstable_flags.iter().cloned());
+		match &self.subcommand {
+			Some(UdepsSubcommand::Merge(opt)) => return opt.run(config, stdout),
+			Some(UdepsSubcommand::Trend(opt)) => {
+				let entries = history::read(&config.cwd().join(&opt.history_file))?;
+				history::print_trend(&entries, stdout)?;
+				return Ok(0);
+			},
+			_ => (),
+		}
+		// Anything else is the name of a dependency, which is explained after the check
+		if let Some(code) = self.explain.as_deref().filter(|code| explain::explanation(code).is_some()) {
+			explain::explain(code, stdout)?;
+			return Ok(0);
+		}
+		if self.print_default_ignores {
+			defaults::print(stdout)?;
+			return Ok(0);
+		}
+
+		// Removed when dropped at the end of the run
+		let ephemeral_dir = if self.ephemeral {
+			Some(tempfile::Builder::new().prefix("cargo-udeps-").tempdir()?)
+		} else {
+			None
+		};
+		let target_dir = match &ephemeral_dir {
+			Some(dir) => Some(dir.path().to_owned()),
+			None => self.target_dir.clone(),
+		};
 		config.configure(
-			self.verbose.max(0).min(2) as u32,
+			self.verbose.min(2) as u32,
 			self.quiet,
 			self.color.as_deref(),
 			self.frozen,
 			self.locked,
 			self.offline,
-			&self.target_dir,
-			&["binary-dep-depinfo".to_string()],
-			&[],
+			&target_dir,
+			&unstable_flags,
+			&self.config,
 		)?;
 		assert!(config.nightly_features_allowed);
-		let ws = clap_matches.workspace(config)?;
-		let test = match self.profile.as_deref() {
-			None => false,
-			Some("test") => true,
-			Some(profile) => return Err(anyhow::anyhow!(
-				"unknown profile: `{}`, only `test` is currently supported",
-				profile,
-			)),
+		// Unused externs of doctests are only reported with `-Z unstable-options`
+		if !unstable_flags.is_empty() || self.doctests {
+			toolchain::ensure_nightly(config, self.install_toolchain)?;
+		}
+		toolchain::check_cargo_env(config)?;
+		let mut ws = match clap_matches.workspace(config) {
+			Ok(ws) => ws,
+			Err(err) => {
+				// The embedded cargo may not understand what the installed one does
+				if let Ok(manifest) = clap_matches.root_manifest(config) {
+					compat::check_cargo_version(config, &manifest)?;
+				}
+				return Err(err);
+			},
 		};
+		compat::check_lockfile(&ws, self.allow_newer_lockfile, &mut config.shell())?;
+		match &self.subcommand {
+			Some(UdepsSubcommand::Apply(opt)) => return opt.run(&ws),
+			Some(UdepsSubcommand::Init(opt)) => {
+				init::init(&ws, opt.dry_run, stdout)?;
+				return Ok(0);
+			},
+			Some(UdepsSubcommand::CiMatrix(opt)) => {
+				matrix::print(&ws, &opt.candidate, stdout)?;
+				return Ok(0);
+			},
+			_ => (),
+		}
+		readonly::avoid_writes(&mut ws)?;
+		let max_unused = self.max_unused
+			.iter()
+			.map(|limit| parse_max_unused(limit))
+			.collect::<CargoResult<Vec<_>>>()?;
+		let mut baseline = self.baseline
+			.as_ref()
+			.map(|path| baseline::Baseline::read(&config.cwd().join(path)))
+			.transpose()?;
+		let template = self.format_template
+			.as_ref()
+			.map(|path| {
+				use anyhow::Context;
+				let source = cargo_util::paths::read(&config.cwd().join(path))?;
+				template::Template::parse(&source)
+					.with_context(|| format!("could not parse the template `{}`", path.display()))
+			})
+			.transpose()?;
+		// Other profiles, like custom ones of `[profile.*]`, are picked up by the compile options
+		let test = self.profile.as_deref() == Some("test");
 		let mode = CompileMode::Check { test };
 		let pc = ProfileChecking::LegacyTestOnly;
-		let compile_opts = clap_matches.compile_options(config, mode, Some(&ws), pc)?;
-		let requested_kinds = &compile_opts.build_config.requested_kinds;
-		let mut target_data = RustcTargetData::new(&ws, requested_kinds)?;
-
-		let cli_features = CliFeatures::from_command_line(
-			&self.features,
-			self.all_features,
-			!self.no_default_features,
-		)?;
-		let ws_resolve = cargo::ops::resolve_ws_with_opts(
-			&ws,
-			&mut target_data,
-			requested_kinds,
-			&cli_features,
-			&Packages::All.to_package_id_specs(&ws)?,
-			HasDevUnits::Yes,
-			ForceAllTargets::No,
-		)?;
-
-		let packages = ws_resolve.pkg_set
-			.get_many(ws_resolve.pkg_set.package_ids())?
-			.into_iter()
-			.map(|p| (p.package_id(), p))
-			.collect::<HashMap<_, _>>();
-
-		let dependency_names = ws
-			.members()
-			.map(|from| {
-				let val = DependencyNames::new(from, &packages, &ws_resolve.targeted_resolve, &mut config.shell())?;
-				let key = from.package_id();
-				Ok((key, val))
-			})
-			.collect::<CargoResult<HashMap<_, _>>>()?;
-
-		let data = Arc::new(Mutex::new(ExecData::new(&ws)?));
-		let exec :Arc<dyn Executor + 'static> = Arc::new(Exec { data : data.clone() });
-		cargo::ops::compile_with_exec(&ws, &compile_opts, &exec)?;
-		let data = data.lock().unwrap();
-
-		let mut used_normal_dev_dependencies = HashSet::new();
-		let mut used_build_dependencies = HashSet::new();
-		let mut normal_dependencies = dependency_names
-			.iter()
-			.flat_map(|(&m, d)| d[dependency::DepKind::Normal].non_lib.iter().map(move |&s| (m, s)))
-			.collect::<HashSet<_>>();
-		let mut dev_dependencies = dependency_names
-			.iter()
-			.flat_map(|(&m, d)| d[dependency::DepKind::Development].non_lib.iter().map(move |&s| (m, s)))
-			.collect::<HashSet<_>>();
-		let mut build_dependencies = dependency_names
+		let mut compile_opts = clap_matches.compile_options(config, mode, Some(&ws), pc)?;
+		if self.include_excluded_members && compile_opts.spec == Packages::Default {
+			compile_opts.spec = Packages::All;
+		}
+		if let Packages::Packages(names) = &compile_opts.spec {
+			check_members(&ws, names)?;
+		}
+		// Packages that are asked for by name are checked all the same
+		let excluded_by_config_file = match &compile_opts.spec {
+			Packages::Packages(_) => &[],
+			_ => &self.excluded_by_config_file[..],
+		};
+		let changed = self.since
+			.as_ref()
+			.map(|rev| since::changed_members(&ws, rev))
+			.transpose()?;
+		let included_packages = compile_opts.spec.get_packages(&ws)?
 			.iter()
-			.flat_map(|(&m, d)| d[dependency::DepKind::Build].non_lib.iter().map(move |&s| (m, s)))
+			.map(|x|x.package_id())
+			.filter(|id| self.shard.is_none_or(|shard| shard.contains(&id.name())))
+			.filter(|id| !excluded_by_config_file.iter().any(|name| *name == *id.name()))
+			.filter(|id| changed.as_ref().is_none_or(|changed| changed.contains(id)))
 			.collect::<HashSet<_>>();
-
-		let mut lib_stem_to_pkg_id = HashMap::new();
-		for cmd_info in data.all_cmd_infos.iter() {
-			let lib_stem = cmd_info.get_artifact_base_name();
-			//println!("lib stem {} -> {}", lib_stem, cmd_info.pkg);
-			lib_stem_to_pkg_id.insert(lib_stem, cmd_info.pkg);
+		if let Some(rev) = &self.since {
+			let mut names = included_packages.iter().map(|id| format!("`{}`", id.name())).collect::<Vec<_>>();
+			names.sort();
+			if names.is_empty() {
+				config.shell().info(format_args!("No members changed since `{}`", rev))?;
+			} else {
+				config.shell().info(format_args!("Checking {}, which changed since `{}`", names.join(", "), rev))?;
+			}
 		}
-		enum BackendData {
-			Depinfo(DepInfo),
+		if self.shard.is_some() || self.since.is_some() || !excluded_by_config_file.is_empty() {
+			let names = included_packages.iter().map(|id| id.name().to_string()).collect();
+			compile_opts.spec = Packages::Packages(names);
+		}
+		let mut target_data = RustcTargetData::new(&ws, &compile_opts.build_config.requested_kinds)?;
+
+		let feature_sets = self.feature_sets(&ws, &included_packages, &compile_opts.filter)?;
+		if feature_sets.len() > 1 && (self.import_analysis.is_some() || self.export_analysis.is_some() || self.stream) {
+			return Err(anyhow::anyhow!(
+				"`--feature-set all` and `--check-feature-combinations` can't be combined with \
+				`--import-analysis`, `--export-analysis` or `--stream`",
+			));
+		}
+		let mut timings = timings::Timings::default();
+		let mut runs = Vec::new();
+		for set in &feature_sets {
+			if let Some(description) = &set.description {
+				config.shell().info(format_args!("Checking with {}", description))?;
+			}
+			compile_opts.cli_features = CliFeatures::from_command_line(
+				&set.features,
+				set.all_features,
+				set.default_features,
+			)?;
+			runs.push(self.check_features(&ws, &compile_opts, &included_packages, &mut target_data, &mut timings)?);
+		}
+		let report_start = Instant::now();
+		if let (Some(dir), [run]) = (&self.export_analysis, runs.as_slice()) {
+			run.analysis.export(&config.cwd().join(dir), &mut config.shell())?;
+		}
+		if let Some(path) = &self.dump_name_map {
+			let map = runs
+				.iter()
+				.flat_map(|run| run.dependency_names.iter())
+				.map(|(id, names)| (id.to_string(), names.dump()))
+				.collect::<BTreeMap<_, _>>();
+			let path = config.cwd().join(path);
+			config.shell().info(format_args!("Writing the name map to {:?}", path))?;
+			cargo_util::paths::write(&path, serde_json::to_string_pretty(&map)?)?;
 		}
-		for cmd_info in data.relevant_cmd_infos.iter() {
-			let backend_data = match self.backend {
-				Backend::Depinfo => {
-					let depinfo = cmd_info.get_depinfo(&mut config.shell())?;
-					BackendData::Depinfo(depinfo)
-				},
-			};
-			// may not be workspace member
-			if let Some(dependency_names) = dependency_names.get(&cmd_info.pkg) {
-				let collect_names = |
-					dnv :&DependencyNamesValue,
-					used_dependencies: &mut HashSet<(PackageId, InternedString)>,
-					dependencies: &mut HashSet<(PackageId, InternedString)>,
-				| {
-					match &backend_data {
-						BackendData::Depinfo(depinfo) => for dep in depinfo.deps_of_depfile()  {
-							let fs = if let Some(fs) = dep.file_stem() {
-								fs
-							} else {
-								continue
-							};
-							let fs :String = match fs.to_str() {
-								Some(v) => v.to_string(),
-								_ => continue,
-							};
-							// The file names are like cratename-hash.rmeta or .rlib,
-							// where "hash" is a hash string that cargo calls "metadata"
-							// internally and computes in its "compute_metadata" function,
-							// and cratename is the snakecased crate name.
-
-							// First, we continue if there is no - in the filename.
-							// it's likely a source file or some other artifact we aren't
-							// interested in. This is obviously only a stupid heuristic.
-							let lib_name = match fs.split_once('-') {
-								None => continue,
-								Some((lib_name, _)) => lib_name
-							};
-
-							// The metadata hash is not available through cargo's api
-							// outside of the Executor trait impl. We do our best to obtain
-							// the hashes from that impl, but the executor is not called
-							// for anything but crates that have to be recompiled.
-							// Thus, any crates that weren't recompiled we don't know the
-							// metadata hash of. So we perform a check: if we know the metadata
-							// hash, we use it, otherwise we don't.
-							// This gives a bit surprising behaviour when re-running
-							// cargo-udeps but at least sometimes the results are more accurate.
-
-							if let Some(pkg_id) = lib_stem_to_pkg_id.get(&fs) {
-								if let Some(dependency_name) = dnv.by_package_id.get(pkg_id) {
-									used_dependencies.insert((cmd_info.pkg, *dependency_name));
-								}
-							} else {
-								// TODO this is a hack as we unconditionally strip the prefix.
-								// It won't work for proc macro crates that start with "lib".
-								// See maybe_lib in the code above.
-								let lib_name = lib_name.strip_prefix("lib").unwrap_or(lib_name);
-								if let Some(dependency_names) = dnv.by_lib_true_snakecased_name.get(lib_name) {
-									for dependency_name in dependency_names {
-										used_dependencies.insert((cmd_info.pkg, *dependency_name));
-									}
-								}
-							}
-						},
-					}
 
-					for extern_crate_name in &cmd_info.extern_crate_names {
-						// We ignore:
-						// 1. the `lib` that `bin`s, `example`s, and `test`s in the same `Package` depend on
-						// 2. crates bundled with `rustc` such as `proc-macro`
-						if let Some(dependency_name) = dnv.by_extern_crate_name.get(&**extern_crate_name) {
-							dependencies.insert((cmd_info.pkg, *dependency_name));
-						}
-					}
-				};
+		let mut packages = HashMap::new();
+		for run in &runs {
+			let pkg_set = &run.ws_resolve.pkg_set;
+			packages.extend(pkg_set.get_many(pkg_set.package_ids())?.into_iter().map(|p| (p.package_id(), p)));
+		}
 
-				collect_names(
-					&dependency_names.normal,
-					&mut used_normal_dev_dependencies,
-					&mut normal_dependencies,
-				);
-				collect_names(
-					&dependency_names.development,
-					&mut used_normal_dev_dependencies,
-					&mut dev_dependencies,
-				);
-				collect_names(
-					&dependency_names.build,
-					&mut used_build_dependencies,
-					&mut build_dependencies,
-				);
+		// A dependency is used if it is used with any of the feature sets
+		let all_names = runs.iter().flat_map(|run| run.dependency_names.iter());
+		let mut usage = Usage::new(all_names.clone(), !self.no_default_ignores);
+		if self.explain.is_some() {
+			usage.trace = Some(why::Trace::default());
+		}
+		for run in &runs {
+			for unit in &run.analysis.units {
+				// may not be workspace member
+				if let Some(dependency_names) = run.dependency_names.get(&unit.pkg) {
+					usage.add_unit(unit, &run.analysis.lib_stems, dependency_names);
+				}
 			}
 		}
+		// The usage when also checked with `--cfg doc`
+		let doc_usage = if runs.iter().all(|run| run.analysis.doc_cfg_units.is_empty()) {
+			None
+		} else {
+			let mut doc_usage = Usage::new(all_names.clone(), !self.no_default_ignores);
+			for run in &runs {
+				for unit in run.analysis.units.iter().chain(&run.analysis.doc_cfg_units) {
+					if let Some(dependency_names) = run.dependency_names.get(&unit.pkg) {
+						doc_usage.add_unit(unit, &run.analysis.lib_stems, dependency_names);
+					}
+				}
+			}
+			Some(doc_usage)
+		};
+		// The dependencies that are only found to be used when checked with `--cfg doc`
+		let doc_only = match &doc_usage {
+			None => HashSet::new(),
+			Some(doc_usage) => {
+				let still_unused = doc_usage.unused().collect::<HashSet<_>>();
+				usage.unused().filter(|unused| !still_unused.contains(unused)).collect()
+			},
+		};
+		// The normal dependencies that could be dev-dependencies instead, unless building the docs needs them
+		let mut dev_only = usage.dev_only().collect::<BTreeSet<_>>();
+		if let Some(doc_usage) = &doc_usage {
+			let doc_dev_only = doc_usage.dev_only().collect::<HashSet<_>>();
+			dev_only.retain(|dev_only| doc_dev_only.contains(dev_only));
+		}
 
-		use anyhow::Context;
-		let workspace_ignore = ws
-			.custom_metadata()
-			.map::<CargoResult<_>, _>(|workspace_metadata| {
-				let PackageMetadata {
-					cargo_udeps: PackageMetadataCargoUdeps { ignore },
-				} = workspace_metadata
-					.clone()
-					.try_into()
-					.context("could not parse `workspace.metadata.cargo-udeps`")?;
-					Ok(ignore)
-			})
-			.transpose()?;
+		let workspace_metadata = PackageMetadataCargoUdeps::of_workspace(&ws)?;
+		let lint_levels = self.lint_levels();
+
+		if let (Some(name), Some(trace)) = (&self.explain, &usage.trace) {
+			let mut checked = included_packages.iter().map(|id| packages[id]).collect::<Vec<_>>();
+			checked.sort_by_key(|package| package.package_id());
+			let non_lib = runs.iter().flat_map(|run| run.dependency_names.iter()).collect::<Vec<_>>();
+			why::print(name, &checked, trace, self.backend, |kind, id, dependency| {
+				let package_metadata = PackageMetadataCargoUdeps::of_package(packages[&id])?;
+				let severity = Severity::of_unused(package_metadata.as_ref(), workspace_metadata.as_ref(), &lint_levels, kind, dependency);
+				Ok(why::Status {
+					used : usage.is_used(kind, id, dependency),
+					checked : usage.is_checked(kind, id, dependency),
+					non_lib : non_lib
+						.iter()
+						.any(|&(&member, names)| member == id && names[kind].non_lib.contains(&dependency)),
+					doc_only : doc_only.contains(&(kind, id, dependency)),
+					ignored : severity.is_none(),
+				})
+			}, |id, through, dependency| {
+				let resolved = |name| runs
+					.iter()
+					.flat_map(|run| run.ws_resolve.targeted_resolve.deps(id))
+					.find(|(_, deps)| deps.iter().any(|d| d.name_in_toml() == name))
+					.map(|(dependency_id, _)| dependency_id);
+				match (resolved(through), resolved(dependency)) {
+					(Some(from), Some(to)) => runs.iter().any(|run| loads(&run.ws_resolve.targeted_resolve, from, to)),
+					_ => false,
+				}
+			}, stdout)?;
+			if let Some(top) = self.timings {
+				timings.phase("report", report_start);
+				timings.print(top, &mut config.shell())?;
+			}
+			return Ok(0);
+		}
 
-		let mut outcome = Outcome::default();
+		let mut outcome = Outcome {
+			packages_checked : included_packages.len(),
+			provenance : Some(Provenance::new(&target_data.rustc, self.backend)),
+			resolver : Some(ws.resolve_behavior().to_manifest()),
+			features : included_packages
+				.iter()
+				.map(|&id| {
+					let features = runs
+						.iter()
+						.flat_map(|run| run.ws_resolve.resolved_features.activated_features(id, FeaturesFor::default()))
+						.collect::<BTreeSet<_>>();
+					(id, features.into_iter().collect())
+				})
+				.collect(),
+			..Outcome::default()
+		};
 
-		let included_packages = compile_opts.spec.get_packages(&ws)?
+		// The packages are already downloaded for the build, so this needs no network
+		let dependency_package = |kind, id, dependency| runs
 			.iter()
-			.map(|x|x.package_id())
-			.collect::<HashSet<_>>();
-		for (dependencies, used_dependencies, kind) in &[
-			(&normal_dependencies, &used_normal_dev_dependencies, dependency::DepKind::Normal),
-			(&dev_dependencies, &used_normal_dev_dependencies, dependency::DepKind::Development),
-			(&build_dependencies, &used_build_dependencies, dependency::DepKind::Build),
-		] {
-			for &(id, dependency) in *dependencies {
-				// This package may have been explicitly excluded via flags.
-				if !included_packages.contains(&id) {
+			.flat_map(|run| run.ws_resolve.targeted_resolve.deps(id))
+			.find(|(_, deps)| deps.iter().any(|d| d.kind() == kind && d.name_in_toml() == dependency))
+			.map(|(dependency_id, _)| packages[&dependency_id]);
+
+		// Everything that is reported, including what is in the baseline
+		let mut found = baseline::Baseline::default();
+		let mut baselined = 0;
+		// The unused dependencies that are not reported, for `--show-ignored`
+		let mut ignored = Vec::new();
+		for (kind, id, dependency) in usage.unused() {
+			// This package may have been explicitly excluded via flags.
+			if !included_packages.contains(&id) {
+				continue;
+			}
+
+			let package = packages[&id];
+			let source_id = dependency_source(package, kind, dependency);
+			if !SourceFilter::any_matches(&self.source, source_id) {
+				config.shell().info(format_args!("Not reporting `{}` ({:?}), it isn't from a `--source`", dependency, kind))?;
+				continue;
+			}
+			let package_metadata = PackageMetadataCargoUdeps::of_package(package)?;
+			let severity = match Severity::of_unused(package_metadata.as_ref(), workspace_metadata.as_ref(), &lint_levels, kind, dependency) {
+				Some(severity) => severity,
+				None => {
+					config.shell().info(format_args!("Ignoring `{}` ({:?})", dependency, kind))?;
+					let reason = ignore_reason(package_metadata.as_ref(), workspace_metadata.as_ref(), kind, dependency);
+					ignored.push((id, IgnoredUnused::new(kind, dependency, reason)));
+					continue;
+				},
+			};
+			let reason = (!self.no_default_ignores)
+				.then(|| default_ignore_reason(package, kind, dependency_package(kind, id, dependency)))
+				.flatten();
+			if let Some(reason) = reason {
+				config.shell().info(format_args!("Not reporting `{}` ({:?}), {}", dependency, kind, reason))?;
+				ignored.push((id, IgnoredUnused::new(kind, dependency, reason)));
+				continue;
+			}
+			if !doc_only.contains(&(kind, id, dependency)) {
+				found.insert(&id.name(), kind, &dependency);
+				if baseline.as_mut().is_some_and(|baseline| baseline.remove(&id.name(), kind, &dependency)) {
+					baselined += 1;
 					continue;
 				}
-
-				let ignore = ws_resolve
-					.pkg_set
-					.get_one(id)?
-					.manifest()
-					.custom_metadata()
-					.map::<CargoResult<_>, _>(|package_metadata| {
-						let PackageMetadata {
-							cargo_udeps: PackageMetadataCargoUdeps { ignore },
-						} = package_metadata
-							.clone()
-							.try_into()
-							.context("could not parse `package.metadata.cargo-udeps`")?;
-						Ok(ignore)
-					})
-					.transpose()?;
-
-				if !used_dependencies.contains(&(id, dependency)) {
-					if ignore.map_or(false, |ignore| ignore.contains(*kind, dependency)) ||
-						workspace_ignore.as_ref().map_or(false, |ignore| ignore.contains(*kind, dependency))
-					{
-						config.shell().info(format_args!("Ignoring `{}` ({:?})", dependency, kind))?;
-					} else {
-						outcome
-							.unused_deps
-							.entry(id)
-							.or_insert(OutcomeUnusedDeps::new(packages[&id].manifest_path())?)
-							.unused_deps_mut(*kind)
-							.insert(dependency);
-					}
+			}
+			let unused_deps = outcome
+				.unused_deps
+				.entry(id)
+				.or_insert(OutcomeUnusedDeps::new(packages[&id].manifest_path())?);
+			if doc_only.contains(&(kind, id, dependency)) {
+				// Removing them would break building the docs
+				unused_deps.doc_only.insert(dependency);
+				continue;
+			}
+			unused_deps.unused_deps_mut(kind).insert(dependency);
+			if severity == Severity::Warn {
+				unused_deps.warn_only.insert(kind, dependency);
+			}
+			if let Some(package_name) = renamed_package(package, kind, dependency) {
+				unused_deps.renamed.insert(dependency, package_name);
+			}
+			if let Some(platforms) = dependency_platforms(package, kind, dependency) {
+				unused_deps.platforms.insert(dependency, platforms);
+			}
+			let features = enabling_features(package, kind, dependency);
+			if !features.is_empty() {
+				unused_deps.enabled_by.insert(dependency, features);
+			}
+			if let Some(source_id) = source_id.filter(|s| s.is_registry() && !s.is_crates_io()) {
+				unused_deps.registries.insert(dependency, source_id.display_registry_name());
+			}
+			if self.enrich {
+				if let Some(dependency_package) = dependency_package(kind, id, dependency) {
+					let metadata = dependency_package.manifest().metadata();
+					unused_deps.info.insert(dependency, DependencyInfo {
+						description : metadata.description.as_ref().map(|d| d.split_whitespace().collect::<Vec<_>>().join(" ")),
+						repository : metadata.repository.clone(),
+					});
 				}
 			}
 		}
 
-		outcome.success = outcome
-			.unused_deps
-			.values()
-			.all(|OutcomeUnusedDeps { normal, development, build, .. }| {
-				normal.is_empty() && development.is_empty() && build.is_empty()
-			});
-
-		if !outcome.success {
+		if self.show_ignored {
+			for (id, ignored) in ignored {
+				outcome.ignored.entry(id).or_default().push(ignored);
+			}
+			for ignored in outcome.ignored.values_mut() {
+				ignored.sort();
+			}
+		}
+
+		if let Some(baseline) = &baseline {
+			config.shell().info(format_args!("{} unused dependencies are in the baseline and not reported", baselined))?;
+			// What is left of the checked packages has been used or removed since
+			for &id in &included_packages {
+				for (kind, dependency) in baseline.of_package(&id.name()) {
+					config.shell().info(format_args!(
+						"`{}` ({:?}) of `{}` is in the baseline, but no longer unused",
+						dependency,
+						kind,
+						id.name(),
+					))?;
+				}
+			}
+		}
+		if let Some(path) = &self.write_baseline {
+			let path = config.cwd().join(path);
+			config.shell().info(format_args!("Writing the baseline to {:?}", path))?;
+			found.write(&path)?;
+		}
+
+		for (id, dependency) in dev_only {
+			if !included_packages.contains(&id) {
+				continue;
+			}
+			let package = packages[&id];
+			let source_id = dependency_source(package, dependency::DepKind::Normal, dependency);
+			if !SourceFilter::any_matches(&self.source, source_id) {
+				continue;
+			}
+			let package_metadata = PackageMetadataCargoUdeps::of_package(package)?;
+			let severity = Severity::of_unused(
+				package_metadata.as_ref(),
+				workspace_metadata.as_ref(),
+				&lint_levels,
+				dependency::DepKind::Normal,
+				dependency,
+			);
+			if severity.is_none() {
+				continue;
+			}
+			let unused_deps = outcome
+				.unused_deps
+				.entry(id)
+				.or_insert(OutcomeUnusedDeps::new(package.manifest_path())?);
+			unused_deps.dev_only.insert(dependency);
+			if self.show_unused_by_target {
+				unused_deps.dev_only_targets.insert(dependency, usage.dev_targets(id, dependency).cloned().collect());
+			}
+		}
+
+		let owners = match &workspace_metadata {
+			Some(metadata) => owners::Owners::new(&metadata.owners, metadata.codeowners.as_deref(), ws.root())?,
+			None => owners::Owners::default(),
+		};
+		for (id, unused_deps) in &mut outcome.unused_deps {
+			let manifest_path = packages[id].manifest_path();
+			let relative_path = manifest_path.strip_prefix(ws.root()).unwrap_or(manifest_path);
+			unused_deps.owner = owners.owner_of(&id.name(), relative_path);
+		}
+
+		let mut unanalyzed_units = runs.iter().flat_map(|run| &run.unanalyzed_units).cloned().collect::<Vec<_>>();
+		unanalyzed_units.sort();
+		unanalyzed_units.dedup();
+		outcome.unanalyzed_units = unanalyzed_units;
+
+		// A partial report doesn't pass, even if nothing is reported
+		outcome.success = outcome
+			.unused_deps
+			.values()
+			.all(|unused_deps| unused_deps.failing().next().is_none())
+			&& outcome.unanalyzed_units.is_empty();
+
+		if self.blame {
+			for unused_deps in outcome.unused_deps.values_mut() {
+				let deps = [dependency::DepKind::Normal, dependency::DepKind::Development, dependency::DepKind::Build]
+					.into_iter()
+					.flat_map(|kind| unused_deps.unused_deps(kind).iter().map(move |dep| (kind, dep)));
+				unused_deps.introduced = blame::introduced(Path::new(&unused_deps.manifest_path), deps)?;
+			}
+		}
+
+		if self.check_workspace_members {
+			outcome.unused_members = unused_members(&ws, &runs[0].ws_resolve.targeted_resolve, &included_packages);
+		}
+		if self.check_features {
+			let target_dir = ws.target_dir().into_path_unlocked();
+			outcome.unused_features = ws
+				.members()
+				.filter(|member| included_packages.contains(&member.package_id()))
+				.map(|member| (member.package_id(), features::unused(member, &target_dir)))
+				.filter(|(_, features)| !features.is_empty())
+				.collect();
+		}
+		if self.check_duplicates {
+			let members = ws.members().map(Package::package_id).filter(|id| included_packages.contains(id));
+			outcome.duplicates = duplicates::find(&runs[0].ws_resolve.targeted_resolve, members);
+		}
+		if self.check_patches {
+			// The one of the whole workspace, as the patches apply to all of it
+			let ws_resolve = &runs[0].ws_resolve;
+			let resolve = ws_resolve.workspace_resolve.as_ref().unwrap_or(&ws_resolve.targeted_resolve);
+			outcome.unused_patches = patches::find(&ws, resolve)?;
+		}
+
+		// With several `--target`s, what is unused when only looking at the units of each of them
+		let triples = compile_opts.build_config.requested_kinds
+			.iter()
+			.filter_map(|kind| match kind {
+				CompileKind::Target(target) => Some(target),
+				CompileKind::Host => None,
+			})
+			.collect::<Vec<_>>();
+		if triples.len() > 1 {
+			for target in triples {
+				let mut target_usage = Usage::new(all_names.clone(), !self.no_default_ignores);
+				for run in &runs {
+					// Build scripts and proc macros are compiled for the host, whatever the `--target`
+					let units = run.analysis.units
+						.iter()
+						.filter(|unit| unit.compile_target.as_deref().is_none_or(|t| t == target.rustc_target().as_str()));
+					for unit in units {
+						if let Some(dependency_names) = run.dependency_names.get(&unit.pkg) {
+							target_usage.add_unit(unit, &run.analysis.lib_stems, dependency_names);
+						}
+					}
+				}
+				let mut unused = Vec::new();
+				for (kind, id, dependency) in target_usage.unused() {
+					if !included_packages.contains(&id) {
+						continue;
+					}
+					let package_metadata = PackageMetadataCargoUdeps::of_package(packages[&id])?;
+					if Severity::of_unused(package_metadata.as_ref(), workspace_metadata.as_ref(), &lint_levels, kind, dependency).is_none() {
+						continue;
+					}
+					let kind = match kind {
+						dependency::DepKind::Normal => "normal",
+						dependency::DepKind::Development => "development",
+						dependency::DepKind::Build => "build",
+					};
+					unused.push(TargetUnused { package : id, kind : kind.to_owned(), name : dependency });
+				}
+				if !unused.is_empty() {
+					unused.sort();
+					outcome.unused_by_target.insert(target.short_name().to_owned(), unused);
+				}
+			}
+		}
+
+		if !outcome.unused_deps.is_empty() {
 			let mut note = "".to_owned();
 
 			if !self.all_targets {
@@ -518,33 +1283,829 @@ impl OptUdeps {
 				}
 			}
 
-			if dependency_names.values().any(DependencyNames::has_non_lib) {
+			if runs.iter().flat_map(|run| run.dependency_names.values()).any(DependencyNames::has_non_lib) {
 				note += "Note: Some dependencies are non-library packages.\n";
 				note += "      `cargo-udeps` regards them as unused.\n";
 			}
 
+			if outcome.unused_deps.values().any(|unused_deps| !unused_deps.dev_only_targets.is_empty()) {
+				note += "Note: The dev-only dependencies can be moved to `[dev-dependencies]`.\n";
+			}
+
 			note += "Note: They might be false-positive.\n";
-			note += "      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.\n";
+			if !self.doctests {
+				note += "      For example, crates that are only used in doc-tests are only detected with `--doctests`.\n";
+			}
 			note += "      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.\n";
 
 			outcome.note = Some(note);
 		}
 
-		outcome.print(self.output, stdout)?;
-		Ok(if outcome.success { 0 } else { 1 })
+		if self.path_style == PathStyle::Relative {
+			for unused_deps in outcome.unused_deps.values_mut() {
+				unused_deps.relativize(ws.root());
+			}
+		}
+		if self.group_by == GroupBy::Dependency {
+			outcome.group_by_dependency();
+		}
+		// After the messages of cargo, so that its consumers pick up the findings as well
+		let json_messages = self.message_format.iter().any(|f| f.eq_ignore_ascii_case("json"));
+		if json_messages {
+			diagnostic::print_messages(&outcome, ws.root(), &mut stdout)?;
+		}
+		// Every line on stdout is JSON then, so reports of other formats go to stderr,
+		// like the human messages of cargo
+		let report_to_stderr = json_messages && (self.print_unused_names || !matches!(self.output, OutputKind::Json));
+		let mut stderr_report = Vec::new();
+		let report_out :&mut dyn Write = if report_to_stderr { &mut stderr_report } else { &mut stdout };
+
+		let width = if self.no_wrap {
+			None
+		} else {
+			output_width(config)
+		};
+		// With `--color`, `CARGO_TERM_COLOR`, or `NO_COLOR` and whether stdout is a terminal
+		let color = if report_to_stderr {
+			config.shell().err_supports_color()
+		} else {
+			config.shell().out_supports_color()
+		};
+		let quiet = config.shell().verbosity() == Verbosity::Quiet;
+		let format = render::Format {
+			output : self.output,
+			template : template.as_ref(),
+			names : self.print_unused_names.then_some(self.with_package),
+			short : self.message_format.iter().any(|f| f.eq_ignore_ascii_case("short")),
+			quiet,
+			layout : render::Layout { width, verbose : self.verbose > 0, color },
+		};
+		outcome.print_report(&format, ws.root(), config.cwd(), report_out)?;
+		if report_to_stderr {
+			config.shell().err().write_all(&stderr_report)?;
+		}
+		if let Some(top) = self.timings {
+			timings.phase("report", report_start);
+			timings.print(top, &mut config.shell())?;
+		}
+		// Before `--fix` changes the manifests
+		*report = Some(Report::new(&outcome, ws.root()));
+		if self.fix && !outcome.unused_deps.is_empty() {
+			let options = ApplyOptions {
+				dry_run : self.dry_run,
+				allow_no_vcs : self.allow_no_vcs,
+				allow_dirty : self.allow_dirty,
+				allow_staged : self.allow_staged,
+				prune_workspace_deps : false,
+				allow_registry : &[],
+			};
+			apply(&ws, &outcome, &options)?;
+		}
+		// Set in GitHub Actions jobs
+		if let Some(path) = config.get_env_os("GITHUB_STEP_SUMMARY").filter(|path| !path.is_empty()) {
+			if let Err(e) = summary::append(&outcome, Path::new(&path)) {
+				config.shell().warn(format!("could not write the job summary: {}", e))?;
+			}
+		}
+		if let Some(path) = &self.history_file {
+			history::Entry::new(&outcome).append(&config.cwd().join(path))?;
+		}
+		if let (false, Some(command)) = (outcome.unused_deps.is_empty(), &self.on_unused) {
+			outcome.notify(command, &ws)?;
+		}
+		if outcome.success {
+			return Ok(0);
+		}
+		if max_unused.is_empty() || !outcome.unanalyzed_units.is_empty() {
+			return Ok(1);
+		}
+		let mut exceeded = false;
+		for &(kind, max) in &max_unused {
+			let kinds = match kind {
+				Some(kind) => vec![kind],
+				None => vec![dependency::DepKind::Normal, dependency::DepKind::Development, dependency::DepKind::Build],
+			};
+			let count = outcome.unused_deps
+				.values()
+				.flat_map(|deps| deps.failing().filter(|(k, _)| kinds.contains(k)))
+				.count();
+			let what = match kind {
+				Some(kind) => format!("{:?} dependencies", kind).to_lowercase(),
+				None => "dependencies".to_owned(),
+			};
+			if count > max {
+				config.shell().error(format!("{} unused {} exceed the limit of {}", count, what, max))?;
+				exceeded = true;
+			} else {
+				config.shell().info(format_args!("{} unused {} are within the limit of {}", count, what, max))?;
+			}
+		}
+		Ok(if exceeded { 1 } else { 0 })
+	}
+
+	/// What the cached information of units depends on, besides the units themselves.
+	fn cache_validity(&self, ws :&Workspace<'_>, target_data :&RustcTargetData<'_>, requested_kinds :&[CompileKind]) -> u64 {
+		let lockfile = std::fs::read_to_string(ws.root().join("Cargo.lock")).unwrap_or_default();
+		let rustflags = [CompileKind::Host]
+			.iter()
+			.chain(requested_kinds)
+			.map(|&kind| target_data.info(kind).rustflags.clone())
+			.collect::<Vec<_>>();
+		cargo::util::hash_u64((
+			env!("CARGO_PKG_VERSION"),
+			&target_data.rustc.verbose_version,
+			lockfile,
+			format!("{:?}", self.backend),
+			self.doc_cfg,
+			rustflags,
+		))
+	}
+
+	/// The levels of `--deny`, `--warn` and `--allow`, where the strictest one of a lint wins.
+	fn lint_levels(&self) -> HashMap<Lint, Severity> {
+		[(&self.allow, Severity::Allow), (&self.warn, Severity::Warn), (&self.deny, Severity::Error)]
+			.into_iter()
+			.flat_map(|(lints, severity)| lints.iter().map(move |&lint| (lint, severity)))
+			.collect()
+	}
+
+	/// The features of each run.
+	///
+	/// The features of a set are added to the ones of `--features`, and so are the ones
+	/// of `--include-required-features`.
+	fn feature_sets(
+		&self,
+		ws :&Workspace<'_>,
+		included_packages :&HashSet<PackageId>,
+		filter :&CompileFilter,
+	) -> CargoResult<Vec<FeatureSelection>> {
+		let required = if self.include_required_features {
+			required_features(ws, included_packages, filter)?
+		} else {
+			Vec::new()
+		};
+		let selection = |description :Option<String>, features :&[String], all_features, default_features| FeatureSelection {
+			description,
+			features : self.features.iter().chain(&required).chain(features).cloned().collect(),
+			all_features,
+			default_features,
+		};
+		if self.feature_set.is_none() && !self.check_feature_combinations {
+			return Ok(vec![selection(None, &[], self.all_features, !self.no_default_features)]);
+		}
+		let metadata = match PackageMetadataCargoUdeps::of_workspace(ws)? {
+			Some(metadata) => Some(metadata),
+			None => ws.current_opt().map(PackageMetadataCargoUdeps::of_package).transpose()?.flatten(),
+		};
+		let sets = metadata.map(|metadata| metadata.feature_sets).unwrap_or_default();
+		let with_set = |(name, features) :(&String, &Vec<String>)| {
+			selection(Some(format!("the feature set `{}`", name)), features, self.all_features, !self.no_default_features)
+		};
+		let name = match &self.feature_set {
+			Some(name) => name,
+			None => {
+				// Like `cargo hack --each-feature`, and the sets of the configuration
+				let mut selections = vec![
+					selection(Some("no features".to_owned()), &[], false, false),
+					selection(Some("the default features".to_owned()), &[], false, true),
+					selection(Some("all features".to_owned()), &[], true, true),
+				];
+				let mut members = ws.members().filter(|pkg| included_packages.contains(&pkg.package_id())).collect::<Vec<_>>();
+				members.sort_by_key(|pkg| pkg.name());
+				for pkg in members {
+					for feature in pkg.summary().features().keys().filter(|feature| **feature != "default") {
+						let feature = format!("{}/{}", pkg.name(), feature);
+						selections.push(selection(Some(format!("the feature `{}`", feature)), &[feature], false, false));
+					}
+				}
+				selections.extend(sets.iter().map(with_set));
+				return Ok(selections);
+			},
+		};
+		if name == "all" {
+			if sets.is_empty() {
+				return Err(anyhow::anyhow!("`--feature-set all` needs `feature-sets` in the `cargo-udeps` metadata"));
+			}
+			return Ok(sets.iter().map(with_set).collect());
+		}
+		match sets.get_key_value(name) {
+			Some(set) => Ok(vec![with_set(set)]),
+			None => Err(anyhow::anyhow!(
+				"no feature set `{}`, expected `all` or one of: {}",
+				name,
+				sets.keys().map(String::as_str).collect::<Vec<_>>().join(", "),
+			)),
+		}
+	}
+
+	/// Resolves, builds and analyzes the included packages with the features of `compile_opts`.
+	fn check_features<'gctx>(
+		&self,
+		ws :&Workspace<'gctx>,
+		compile_opts :&CompileOptions,
+		included_packages :&HashSet<PackageId>,
+		target_data :&mut RustcTargetData<'gctx>,
+		timings :&mut timings::Timings,
+	) -> CargoResult<FeatureRun<'gctx>> {
+		let config = ws.gctx();
+		let requested_kinds = &compile_opts.build_config.requested_kinds;
+		// Resolve like the build does, so that with `resolver = "2"`, optional dependencies
+		// are only regarded if they are enabled for the targets and packages that are built.
+		let specs = if included_packages.is_empty() {
+			// An empty shard, which would otherwise mean the default packages
+			Packages::All
+		} else {
+			compile_opts.spec.clone()
+		};
+		let has_dev_units = if compile_opts.filter.need_dev_deps(compile_opts.build_config.mode) {
+			HasDevUnits::Yes
+		} else {
+			HasDevUnits::No
+		};
+		let start = Instant::now();
+		let ws_resolve = cargo::ops::resolve_ws_with_opts(
+			ws,
+			target_data,
+			requested_kinds,
+			&compile_opts.cli_features,
+			&specs.to_package_id_specs(ws)?,
+			has_dev_units,
+			ForceAllTargets::No,
+		)?;
+
+		let packages = ws_resolve.pkg_set
+			.get_many(ws_resolve.pkg_set.package_ids())?
+			.into_iter()
+			.map(|p| (p.package_id(), p))
+			.collect::<HashMap<_, _>>();
+
+		// Looked up once, rather than for every dependency edge
+		let lib_targets = packages
+			.iter()
+			.filter_map(|(&id, package)| Some((id, package.targets().iter().find(|t| t.is_lib())?)))
+			.collect::<HashMap<_, _>>();
+
+		let dependency_names = ws
+			.members()
+			.filter(|from| included_packages.contains(&from.package_id()))
+			.map(|from| {
+				let val = DependencyNames::new(
+					from,
+					&lib_targets,
+					&ws_resolve.targeted_resolve,
+					&ws_resolve.resolved_features,
+					target_data,
+					requested_kinds,
+					&mut config.shell(),
+				)?;
+				let key = from.package_id();
+				Ok((key, val))
+			})
+			.collect::<CargoResult<HashMap<_, _>>>()?;
+		let dependency_names = Arc::new(dependency_names);
+		timings.phase("resolve", start);
+
+		let container = if self.use_cross {
+			let target = match requested_kinds.as_slice() {
+				[kind @ CompileKind::Target(target)] => (target.short_name(), kind),
+				_ => return Err(anyhow::anyhow!("`--use-cross` needs exactly one `--target`")),
+			};
+			let sysroot = &target_data.info(*target.1).sysroot;
+			Some(container::Container::cross(ws, target.0, sysroot)?)
+		} else if let Some(command) = &self.container_command {
+			Some(container::Container::custom(command, &self.container_path)?)
+		} else {
+			None
+		};
+
+		let mut remap = remap::PathRemap::parse(&self.remap_path_prefix)?;
+		if let Some(metadata) = PackageMetadataCargoUdeps::of_workspace(ws)? {
+			remap.extend(remap::PathRemap::parse(&metadata.remap_path_prefix)?);
+		}
+		if let Some(container) = &container {
+			remap.extend(container.to_host());
+		}
+
+		let mut unanalyzed_units = Vec::new();
+		let analysis = if let Some(dir) = &self.import_analysis {
+			analysis::Analysis::import(&config.cwd().join(dir), &packages, &mut config.shell())?
+		} else if included_packages.is_empty() {
+			// The shard is empty, and building no packages would mean the default ones
+			analysis::Analysis::default()
+		} else {
+			let mut data = ExecData::new(ws, included_packages, &compile_opts.filter, container, remap)?;
+			data.dump_cmd_info = self.dump_cmd_info;
+			data.strict = self.strict;
+			data.doc_cfg = self.doc_cfg;
+			data.backend = self.backend;
+			data.rustc_wrapper = target_data.rustc.wrapper.clone();
+			// Streaming reports on the units as they are built, and paths in containers differ
+			if !self.no_cache && !self.stream && data.container.is_none() {
+				data.cache = Some(cache::Cache::load(&ws.target_dir().into_path_unlocked(), self.cache_validity(ws, target_data, &compile_opts.build_config.requested_kinds)));
+			}
+			if self.stream {
+				let json = matches!(self.output, OutputKind::Json);
+				let root = (self.path_style == PathStyle::Relative).then(|| ws.root().to_owned());
+				data.stream = Some(stream::Stream::new(json, self, ws, &ws_resolve.targeted_resolve, &packages, root, dependency_names.clone())?);
+			}
+			let data = Arc::new(Mutex::new(data));
+			let exec :Arc<dyn Executor + 'static> = Arc::new(Exec { data : data.clone(), inner : Arc::new(DefaultExecutor) });
+			let start = Instant::now();
+			let result = cargo::ops::compile_with_exec(ws, compile_opts, &exec);
+			timings.phase("build", start);
+			let mut data = data.lock().unwrap();
+			if let Err(e) = result {
+				// Without `--keep-going`, cargo stops before the other units are built
+				if !self.keep_going || (data.failed_units.is_empty() && data.unbuilt_units.is_empty()) {
+					return Err(e);
+				}
+				unanalyzed_units = data.failed_units
+					.iter()
+					.map(|(package, target)| UnanalyzedUnit { package : *package, target : target.clone(), failed : true })
+					.chain(data.unbuilt_units.iter().map(|(package, target)| UnanalyzedUnit { package : *package, target : target.clone(), failed : false }))
+					.collect::<Vec<_>>();
+				unanalyzed_units.sort();
+				config.shell().warn(format_args!(
+					"{:#}\n  the report only covers the units that were built, \
+					so the dependencies that only the others use are reported as unused",
+					e,
+				))?;
+				// Their `.d` files may be left from an earlier build
+				let unanalyzed = data.failed_units.clone();
+				data.relevant_cmd_infos.retain(|cmd_info| !unanalyzed.contains(&(cmd_info.pkg, cmd_info.target.clone())));
+			}
+			data.finish_cache(&mut config.shell())?;
+			timings.extend(std::mem::take(&mut data.timings));
+			let start = Instant::now();
+			let mut analysis = analysis::Analysis::collect_with_progress(&data, self.backend, timings, config)?;
+			timings.phase("analysis", start);
+			if self.doctests {
+				let start = Instant::now();
+				analysis.units.extend(doctests::collect(ws, compile_opts, included_packages, &target_data.rustc.path)?);
+				timings.phase("doctests", start);
+			}
+			analysis
+		};
+		Ok(FeatureRun { ws_resolve, dependency_names, analysis, unanalyzed_units })
+	}
+}
+
+impl OptApply {
+	fn run(&self, ws :&Workspace<'_>) -> CargoResult<i32> {
+		use anyhow::Context;
+		let report = cargo_util::paths::read(&ws.gctx().cwd().join(&self.report))?;
+		let outcome :Outcome = serde_json::from_str(&report)
+			.with_context(|| format!("could not parse the report `{}`", self.report.display()))?;
+		let options = ApplyOptions {
+			dry_run : self.dry_run,
+			allow_no_vcs : self.allow_no_vcs,
+			allow_dirty : self.allow_dirty,
+			allow_staged : self.allow_staged,
+			prune_workspace_deps : self.prune_workspace_deps,
+			allow_registry : &self.allow_registry,
+		};
+		apply(ws, &outcome, &options)?;
+		Ok(0)
+	}
+}
+
+/// How [`apply`] edits the manifests, set with the flags of `apply` or `--fix`.
+struct ApplyOptions<'a> {
+	dry_run :bool,
+	allow_no_vcs :bool,
+	allow_dirty :bool,
+	allow_staged :bool,
+	prune_workspace_deps :bool,
+	allow_registry :&'a [String],
+}
+
+/// Removes the unused dependencies of the report from the manifests of the members.
+fn apply(ws :&Workspace<'_>, outcome :&Outcome, options :&ApplyOptions<'_>) -> CargoResult<()> {
+	let workspace_metadata = PackageMetadataCargoUdeps::of_workspace(ws)?;
+	let workspace_protected = workspace_metadata
+		.as_ref()
+		.map(|m| m.protected_registries.clone())
+		.unwrap_or_default();
+
+	let mut shell = ws.gctx().shell();
+	let mut modified_manifests = Vec::new();
+	let mut no_longer_inherited = BTreeSet::new();
+	for (id, unused_deps) in &outcome.unused_deps {
+		// The report may have been produced on another machine, so
+		// the source paths in it generally don't match ours.
+		let member = ws
+			.members()
+			.find(|m| m.name() == id.name() && m.version() == id.version());
+		let member = match member {
+			Some(member) => member,
+			None => {
+				shell.warn(format!("`{}` is not a member of this workspace, skipping", id))?;
+				continue;
+			},
+		};
+		let package_metadata = PackageMetadataCargoUdeps::of_package(member)?;
+		let protected = package_metadata
+			.as_ref()
+			.map(|m| m.protected_registries.clone())
+			.unwrap_or_default();
+		let mut manifest = fix::Manifest::open(member.manifest_path())?;
+		let mut modified = false;
+		for kind in [dependency::DepKind::Normal, dependency::DepKind::Development, dependency::DepKind::Build] {
+			for dep in unused_deps.unused_deps(kind) {
+				let policy = DependencyPolicy::get(package_metadata.as_ref(), workspace_metadata.as_ref(), dep);
+				if policy.is_some_and(|p| !p.auto_fix) {
+					shell.warn(format!(
+						"not removing `{}` from `{}` as its policy disables `auto-fix`",
+						dep, member.name(),
+					))?;
+					continue;
+				}
+				if let Some(registry) = unused_deps.registries.get(dep) {
+					let is_protected = protected.contains(registry) || workspace_protected.contains(registry);
+					if is_protected && !options.allow_registry.contains(registry) {
+						shell.warn(format!(
+							"not removing `{}` from `{}` as the registry `{}` is protected, pass `--allow-registry {}` to remove it",
+							dep, member.name(), registry, registry,
+						))?;
+						continue;
+					}
+				}
+				let inherited = manifest.inherits(dep);
+				let removed_from = manifest.remove_dependency(kind, dep);
+				if inherited && !manifest.inherits(dep) {
+					no_longer_inherited.insert(*dep);
+				}
+				if removed_from.is_empty() {
+					shell.warn(format!("could not find `{}` in `{}`", dep, member.manifest_path().display()))?;
+				}
+				for table in removed_from {
+					shell.status("Removing", format!("{} from {} of `{}`", dep, table, member.name()))?;
+					modified = true;
+				}
+				for feature in manifest.remove_dangling_features(dep) {
+					shell.status("Removing", format!("feature {} of `{}`", feature, member.name()))?;
+				}
+			}
+		}
+		if modified {
+			modified_manifests.push(manifest);
+		}
+	}
+	if options.prune_workspace_deps && !no_longer_inherited.is_empty() {
+		let mut unmodified_members = Vec::new();
+		for member in ws.members() {
+			if !modified_manifests.iter().any(|m| m.path() == member.manifest_path()) {
+				unmodified_members.push(fix::Manifest::open(member.manifest_path())?);
+			}
+		}
+		let unused = no_longer_inherited
+			.into_iter()
+			.filter(|dep| !modified_manifests.iter().chain(&unmodified_members).any(|m| m.inherits(dep)))
+			.collect::<Vec<_>>();
+		let root_index = modified_manifests.iter().position(|m| m.path() == ws.root_manifest());
+		let mut root = match root_index {
+			Some(i) => modified_manifests.remove(i),
+			None => fix::Manifest::open(ws.root_manifest())?,
+		};
+		let mut modified = root_index.is_some();
+		for dep in unused {
+			if root.remove_workspace_dependency(&dep) {
+				shell.status("Removing", format!("{} from workspace.dependencies", dep))?;
+				modified = true;
+			}
+		}
+		if modified {
+			modified_manifests.push(root);
+		}
+	}
+	if options.dry_run {
+		shell.warn("aborting apply due to dry run")?;
+		return Ok(());
+	}
+	drop(shell);
+	fix::check_version_control(
+		modified_manifests.iter().map(fix::Manifest::path),
+		options.allow_no_vcs,
+		options.allow_dirty,
+		options.allow_staged,
+	)?;
+	let originals = modified_manifests
+		.iter()
+		.map(|manifest| cargo_util::paths::read(manifest.path()))
+		.collect::<CargoResult<Vec<_>>>()?;
+	if let Err(e) = write_and_relock(ws, &modified_manifests) {
+		// Rather than leaving them half-edited, like when the lock file can't be updated with `--locked`
+		for (manifest, original) in modified_manifests.iter().zip(&originals) {
+			cargo_util::paths::write_atomic(manifest.path(), original)?;
+		}
+		return Err(e.context("could not apply the report, the manifests were left unchanged"));
+	}
+	Ok(())
+}
+
+/// Writes the manifests, and updates the lock file to them.
+fn write_and_relock(ws :&Workspace<'_>, modified_manifests :&[fix::Manifest]) -> CargoResult<()> {
+	for manifest in modified_manifests {
+		manifest.write()?;
+	}
+
+	// Make sure the lock file doesn't reference the removed dependencies
+	// any more, so that `--locked` builds keep working right away.
+	// Libraries without a lock file don't get one created.
+	let lock_file = ws.root().join("Cargo.lock");
+	if !modified_manifests.is_empty() && lock_file.exists() {
+		let ws = Workspace::new(ws.root_manifest(), ws.gctx())?;
+		cargo::ops::resolve_ws(&ws)?;
+	}
+	Ok(())
+}
+
+impl OptMerge {
+	fn run<W: Write>(&self, config :&GlobalContext, stdout :W) -> CargoResult<i32> {
+		use anyhow::Context;
+		let mut merged = Outcome::default();
+		for path in &self.reports {
+			let report = cargo_util::paths::read(&config.cwd().join(path))?;
+			let mut outcome :Outcome = serde_json::from_str(&report)
+				.with_context(|| format!("could not parse the report `{}`", path.display()))?;
+			// Shards check disjoint sets of packages, but other runs may overlap
+			let checked_before = merged.features.keys().chain(merged.unused_deps.keys()).copied().collect::<HashSet<_>>();
+			let checked = outcome.features.keys().chain(outcome.unused_deps.keys()).copied().collect::<HashSet<_>>();
+			for &id in checked.intersection(&checked_before) {
+				// A dependency is only unused if every report that checked the package says so
+				match (merged.unused_deps.get_mut(&id), outcome.unused_deps.remove(&id)) {
+					(Some(unused_deps), Some(other)) => {
+						unused_deps.intersect(&other);
+						if unused_deps.is_empty() {
+							merged.unused_deps.remove(&id);
+						}
+					},
+					(Some(_), None) => {
+						merged.unused_deps.remove(&id);
+					},
+					(None, _) => (),
+				}
+			}
+			merged.packages_checked += outcome.packages_checked.saturating_sub(checked.intersection(&checked_before).count());
+			merged.note = merged.note.or(outcome.note);
+			merged.resolver = merged.resolver.or(outcome.resolver);
+			merged.provenance = merged.provenance.or(outcome.provenance);
+			merged.features.extend(outcome.features);
+			merged.unused_deps.extend(outcome.unused_deps);
+			merged.unanalyzed_units.extend(outcome.unanalyzed_units);
+		}
+		merged.unanalyzed_units.sort();
+		merged.unanalyzed_units.dedup();
+		// Like for a single run
+		merged.success = merged
+			.unused_deps
+			.values()
+			.all(|unused_deps| unused_deps.failing().next().is_none())
+			&& merged.unanalyzed_units.is_empty();
+		merged.print_json(stdout)?;
+		Ok(if merged.success { 0 } else { 1 })
+	}
+}
+
+/// Parses a `--max-unused` value, `N` or `KIND=N`.
+fn parse_max_unused(limit :&str) -> CargoResult<(Option<dependency::DepKind>, usize)> {
+	let (kind, max) = match limit.split_once('=') {
+		Some(("normal", max)) => (Some(dependency::DepKind::Normal), max),
+		Some(("development", max)) => (Some(dependency::DepKind::Development), max),
+		Some(("build", max)) => (Some(dependency::DepKind::Build), max),
+		Some((kind, _)) => return Err(anyhow::anyhow!(
+			"unknown dependency kind `{}`, expected `normal`, `development` or `build`", kind,
+		)),
+		None => (None, limit),
+	};
+	let max = max.parse().map_err(|_| anyhow::anyhow!("invalid limit `{}` for `--max-unused`", limit))?;
+	Ok((kind, max))
+}
+
+/// A deterministic part of the workspace members, for splitting up the work across CI jobs.
+#[derive(Clone, Copy, Debug)]
+struct Shard {
+	/// Zero-based
+	index :u64,
+	count :u64,
+}
+
+impl Shard {
+	fn contains(&self, package_name :&str) -> bool {
+		// FNV-1a, which unlike the std hashers is guaranteed to be stable
+		let hash = package_name.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+			(hash ^ u64::from(b)).wrapping_mul(0x100000001b3)
+		});
+		hash % self.count == self.index
+	}
+}
+
+impl FromStr for Shard {
+	type Err = String;
+
+	fn from_str(s: &str) -> std::result::Result<Self, String> {
+		let err = || format!("expected `K/N` with 1 <= K <= N, found `{}`", s);
+		let (k, n) = s.split_once('/').ok_or_else(err)?;
+		let (k, n) = (k.parse::<u64>().map_err(|_| err())?, n.parse::<u64>().map_err(|_| err())?);
+		if k == 0 || k > n {
+			return Err(err());
+		}
+		Ok(Self { index : k - 1, count : n })
+	}
+}
+
+/// A kind of source of dependencies, for `--source`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SourceFilter {
+	CratesIo,
+	Git,
+	Path,
+	/// An alternative registry, by name
+	Registry(String),
+}
+
+impl SourceFilter {
+	fn matches(&self, source_id :SourceId) -> bool {
+		match self {
+			Self::CratesIo => source_id.is_crates_io(),
+			Self::Git => source_id.is_git(),
+			Self::Path => source_id.is_path(),
+			Self::Registry(name) => source_id.is_registry() && source_id.display_registry_name() == *name,
+		}
+	}
+
+	/// Whether a dependency from `source_id` is reported with the `--source`s in `filters`.
+	fn any_matches(filters :&[Self], source_id :Option<SourceId>) -> bool {
+		filters.is_empty() || source_id.is_some_and(|source_id| filters.iter().any(|f| f.matches(source_id)))
+	}
+}
+
+impl FromStr for SourceFilter {
+	type Err = String;
+
+	fn from_str(s: &str) -> std::result::Result<Self, String> {
+		match s {
+			"crates-io" => Ok(Self::CratesIo),
+			"git" => Ok(Self::Git),
+			"path" => Ok(Self::Path),
+			_ => match s.strip_prefix("registry:") {
+				Some(name) if !name.is_empty() => Ok(Self::Registry(name.to_owned())),
+				_ => Err(format!("expected `crates-io`, `git`, `path` or `registry:<NAME>`, found `{}`", s)),
+			},
+		}
+	}
+}
+
+/// The included members without binaries that no other member depends on, for `--check-workspace-members`.
+fn unused_members(ws :&Workspace<'_>, resolve :&Resolve, included_packages :&HashSet<PackageId>) -> Vec<PackageId> {
+	let members = ws.members().map(Package::package_id).collect::<HashSet<_>>();
+	let depended_on = members
+		.iter()
+		.flat_map(|&member| resolve.deps(member).map(|(id, _)| id))
+		.filter(|id| members.contains(id))
+		.collect::<HashSet<_>>();
+	let mut unused = ws
+		.members()
+		.filter(|member| included_packages.contains(&member.package_id()))
+		.filter(|member| !depended_on.contains(&member.package_id()))
+		.filter(|member| !member.targets().iter().any(Target::is_bin))
+		.map(Package::package_id)
+		.collect::<Vec<_>>();
+	unused.sort();
+	unused
+}
+
+/// The source of the dependency of `package` with the name and kind.
+fn dependency_source(package :&Package, kind :dependency::DepKind, name_in_toml :InternedString) -> Option<SourceId> {
+	package
+		.dependencies()
+		.iter()
+		.find(|d| d.kind() == kind && d.name_in_toml() == name_in_toml)
+		.map(|d| d.source_id())
+}
+
+/// The name of the package of the dependency of `package`, if it is renamed with `package = "…"`.
+fn renamed_package(package :&Package, kind :dependency::DepKind, name_in_toml :InternedString) -> Option<InternedString> {
+	package
+		.dependencies()
+		.iter()
+		.find(|d| d.kind() == kind && d.name_in_toml() == name_in_toml && d.explicit_name_in_toml().is_some())
+		.map(|d| d.package_name())
+}
+
+/// The platforms the dependency of `package` is declared for, unless it is declared for all of them.
+fn dependency_platforms(package :&Package, kind :dependency::DepKind, name_in_toml :InternedString) -> Option<String> {
+	let platforms = package
+		.dependencies()
+		.iter()
+		.filter(|d| d.kind() == kind && d.name_in_toml() == name_in_toml)
+		.map(|d| d.platform().map(ToString::to_string))
+		.collect::<Option<BTreeSet<_>>>()?;
+	(!platforms.is_empty()).then(|| platforms.into_iter().collect::<Vec<_>>().join(", "))
+}
+
+/// The features in `[features]` of `package` that refer to the dependency, if it is an optional one.
+fn enabling_features(package :&Package, kind :dependency::DepKind, name_in_toml :InternedString) -> Vec<String> {
+	let optional = package
+		.dependencies()
+		.iter()
+		.any(|d| d.kind() == kind && d.name_in_toml() == name_in_toml && d.is_optional());
+	if !optional {
+		return Vec::new();
+	}
+	let feature_map = package.summary().features();
+	// Not the implicit features of optional dependencies, which are not in `Cargo.toml`
+	let declared = package.manifest().original_toml().features.iter().flatten();
+	declared
+		.filter(|(feature, _)| {
+			feature_map.get(&InternedString::new(feature)).into_iter().flatten().any(|value| match value {
+				// The implicit feature of the dependency
+				FeatureValue::Feature(feature) => *feature == name_in_toml,
+				FeatureValue::Dep { dep_name } | FeatureValue::DepFeature { dep_name, .. } => *dep_name == name_in_toml,
+			})
+		})
+		.map(|(feature, _)| feature.to_string())
+		.collect()
+}
+
+/// Why an unused dependency of `kind` of `package`, which resolves to `dependency`, is not reported,
+/// by one of the default ignores that `--no-default-ignores` turns off. The report and `--stream`
+/// both go by it.
+fn default_ignore_reason(package :&Package, kind :dependency::DepKind, dependency :Option<&Package>) -> Option<String> {
+	if let Some(root) = opt_out::allowing_root(package, kind) {
+		return Some(format!("`{}` allows `unused_crate_dependencies`", root.display()));
 	}
+	if kind != dependency::DepKind::Normal {
+		return None;
+	}
+	let prefix = build_script_metadata(package, dependency?)?;
+	Some(format!("it is used indirectly: the build script reads its `links` metadata (`{}*`)", prefix))
+}
+
+/// The prefix of the `DEP_<LINKS>_<KEY>` variables that cargo passes from `dependency`, a package
+/// with `links`, to the build script of `package`, if the source file of the build script mentions it.
+/// The dependency is used through this metadata then, which the analysis can't see.
+///
+/// Only the source file itself is searched, so the metadata that modules of the build script read,
+/// with `mod` or `include!`, is missed.
+fn build_script_metadata(package :&Package, dependency :&Package) -> Option<String> {
+	let links = dependency.manifest().links()?;
+	let prefix = format!("DEP_{}_", links.to_uppercase().replace('-', "_"));
+	let build_script = package.targets().iter().find(|target| target.is_custom_build())?;
+	let source = std::fs::read_to_string(build_script.src_path().path()?).ok()?;
+	source.contains(&prefix).then_some(prefix)
 }
 
 struct ExecData {
 	cargo_exe :OsString,
 	supports_color :bool,
 	workspace_members :Vec<PackageId>,
+	/// The members that were selected with `--package` and similar flags.
+	included_packages :HashSet<PackageId>,
+	filter :CompileFilter,
+	container :Option<container::Container>,
+	/// Translates paths recorded during the build into local ones.
+	remap :remap::PathRemap,
 	relevant_cmd_infos :Vec<CmdInfo>,
 	all_cmd_infos :Vec<CmdInfo>,
+	/// How long the compiler took for the units.
+	timings :timings::Timings,
+	/// Set with `--stream`.
+	stream :Option<stream::Stream>,
+	/// Set with `--dump-cmd-info`.
+	dump_cmd_info :bool,
+	/// Set with `--strict`, so that units with a missing or stale `.d` file fail the run.
+	strict :bool,
+	/// Set with `--doc-cfg`.
+	doc_cfg :bool,
+	/// The units of the members as checked with `--cfg doc`.
+	doc_cfg_cmd_infos :Vec<CmdInfo>,
+	backend :Backend,
+	/// The extern crates the `unused_crate_dependencies` lint reported, by artifact base name,
+	/// with `--backend lint`.
+	unused_externs :HashMap<String, HashSet<String>>,
+	/// Unless `--no-cache` is given.
+	cache :Option<cache::Cache>,
+	/// The keys of the relevant units that are built, by their `-C extra-filename`.
+	unit_keys :HashMap<String, String>,
+	/// The keys of the relevant units that cargo found fresh, and whose information is cached.
+	cached_units :BTreeSet<String>,
+	/// `RUSTC_WRAPPER`, or `build.rustc-wrapper` of the cargo configuration.
+	rustc_wrapper :Option<PathBuf>,
+	/// The relevant units cargo is going to build, by package and target, until they are built.
+	unbuilt_units :HashSet<(PackageId, String)>,
+	/// The relevant units that failed to compile.
+	failed_units :HashSet<(PackageId, String)>,
 }
 
 impl ExecData {
-	fn new(ws :&Workspace<'_>) -> CargoResult<Self> {
+	fn new(
+		ws :&Workspace<'_>,
+		included_packages :&HashSet<PackageId>,
+		filter :&CompileFilter,
+		container :Option<container::Container>,
+		remap :remap::PathRemap,
+	) -> CargoResult<Self> {
 		// `$CARGO` should be present when `cargo-udeps` is executed as `cargo udeps ..` or `cargo run -- udeps ..`.
 		let cargo_exe = env::var_os(cargo::CARGO_ENV)
 			.map(Ok::<_, anyhow::Error>)
@@ -564,14 +2125,108 @@ impl ExecData {
 			cargo_exe,
 			supports_color :ws.gctx().shell().err_supports_color(),
 			workspace_members :ws.members().map(Package::package_id).collect(),
+			included_packages :included_packages.clone(),
+			filter :filter.clone(),
+			container,
+			remap,
 			relevant_cmd_infos : Vec::new(),
 			all_cmd_infos : Vec::new(),
+			timings : timings::Timings::default(),
+			stream : None,
+			dump_cmd_info : false,
+			strict : false,
+			doc_cfg : false,
+			doc_cfg_cmd_infos : Vec::new(),
+			backend : Backend::Depinfo,
+			unused_externs : HashMap::new(),
+			cache : None,
+			unit_keys : HashMap::new(),
+			cached_units : BTreeSet::new(),
+			rustc_wrapper : None,
+			unbuilt_units : HashSet::new(),
+			failed_units : HashSet::new(),
 		})
 	}
+
+	/// Whether the unit belongs to the targets that were asked for.
+	///
+	/// Other members may still get compiled as dependencies of these,
+	/// but there is no need to rebuild or analyze them.
+	fn is_relevant(&self, id :PackageId, target :&Target) -> bool {
+		// Build scripts are needed to tell whether build-dependencies are used
+		self.included_packages.contains(&id) && (target.is_custom_build() || self.filter.target_run(target))
+	}
+
+	/// Adds the cached information of the units that were not built, and saves the cache.
+	fn finish_cache(&mut self, shell :&mut Shell) -> CargoResult<()> {
+		let Some(mut cache) = self.cache.take() else {
+			return Ok(());
+		};
+		for key in std::mem::take(&mut self.cached_units) {
+			let Some(entry) = cache.reuse(&key) else {
+				continue;
+			};
+			self.relevant_cmd_infos.push(entry.cmd_info.clone());
+			self.all_cmd_infos.push(entry.cmd_info.clone());
+			self.doc_cfg_cmd_infos.extend(entry.doc_cfg_cmd_info.clone());
+			if let Some(unused_externs) = &entry.unused_externs {
+				self.unused_externs.insert(entry.cmd_info.get_artifact_base_name(), unused_externs.clone());
+			}
+		}
+		// Only costs a rebuild the next time
+		if let Err(e) = cache.save(&self.remap) {
+			shell.warn(format!("could not save the cache: {}", e))?;
+		}
+		Ok(())
+	}
+}
+
+/// The extern crate of an `unused_crate_dependencies` diagnostic of rustc.
+fn unused_extern(line :&str) -> Option<String> {
+	let diagnostic = serde_json::from_str::<serde_json::Value>(line).ok()?;
+	if diagnostic["code"]["code"] != "unused_crate_dependencies" {
+		return None;
+	}
+	// Like "extern crate `foo` is unused in crate `bar`"
+	let message = diagnostic["message"].as_str()?;
+	let (_, rest) = message.split_once('`')?;
+	let (name, _) = rest.split_once('`')?;
+	Some(name.to_owned())
+}
+
+/// What is taken from the compiler invocation `cmd` of the unit of `target` of `id`,
+/// with the kind of the unit and its description for users.
+fn unit_cmd_info(cmd :&ProcessBuilder, id :PackageId, target :&Target, mode :CompileMode) -> CargoResult<CmdInfo> {
+	let mut cmd_info = cmd_info(id, target.is_custom_build(), cmd)?;
+	cmd_info.dev = mode.is_any_test() || target.is_test() || target.is_bench() || target.is_example();
+	cmd_info.target = unit_target(target, mode);
+	Ok(cmd_info)
+}
+
+/// The target of a unit, as described to users.
+fn unit_target(target :&Target, mode :CompileMode) -> String {
+	if mode.is_any_test() && (target.is_lib() || target.is_bin()) {
+		format!("{} (unit tests)", target.description_named())
+	} else {
+		target.description_named()
+	}
 }
 
 struct Exec {
 	data :Arc<Mutex<ExecData>>,
+	/// The executor that actually runs the commands.
+	inner :Arc<dyn Executor>,
+}
+
+impl Exec {
+	/// Notes that the unit failed to compile, if it is one of the checked ones.
+	fn failed(&self, id :PackageId, target :&Target, cmd_info :&CmdInfo) {
+		let mut bt = self.data.lock().unwrap();
+		if bt.is_relevant(id, target) {
+			bt.unbuilt_units.remove(&(id, cmd_info.target.clone()));
+			bt.failed_units.insert((id, cmd_info.target.clone()));
+		}
+	}
 }
 
 impl Executor for Exec {
@@ -579,14 +2234,32 @@ impl Executor for Exec {
 			mode :CompileMode, on_stdout_line :&mut dyn FnMut(&str) -> CargoResult<()>,
 			on_stderr_line :&mut dyn FnMut(&str) -> CargoResult<()>) -> CargoResult<()> {
 
-		let cmd_info = cmd_info(id, target.is_custom_build(), cmd).unwrap_or_else(|e| {
-			panic!("Couldn't obtain crate info {:?}: {:?}", id, e);
-		});
+		let cmd_info = match unit_cmd_info(cmd, id, target, mode) {
+			Ok(cmd_info) => cmd_info,
+			Err(e) => {
+				// The unit is still built, so that units depending on it can be analyzed
+				let supports_color = self.data.lock().unwrap().supports_color;
+				on_stderr_line(&format!(
+					"{} couldn't read the arguments of {} ({}), its dependencies are not analyzed \
+					and may be reported wrongly",
+					if supports_color {
+						Color::Yellow.bold().paint("warning:").to_string()
+					} else {
+						"warning:".to_owned()
+					},
+					target.description_named(),
+					e,
+				))?;
+				return self.inner.exec(cmd, id, target, mode, on_stdout_line, on_stderr_line);
+			},
+		};
 
 		let mut cmd = cmd.clone();
 
 		let is_path = id.source_id().is_path();
 		let is_workspace_member;
+		let mut doc_cfg = None;
+		let lint;
 		{
 			// TODO unwrap used
 			let mut bt = self.data.lock().unwrap();
@@ -594,18 +2267,31 @@ impl Executor for Exec {
 			is_workspace_member = bt.workspace_members.contains(&id);
 
 			bt.all_cmd_infos.push(cmd_info.clone());
+			if bt.dump_cmd_info {
+				on_stderr_line(&format!(
+					"{} captured {}",
+					if bt.supports_color {
+						Color::Cyan.bold().paint("info:").to_string()
+					} else {
+						"info:".to_owned()
+					},
+					cmd_info.dump(),
+				))?;
+			}
 
-			// If the crate is not a in the workspace,
+			// If the crate is not one of the targets we were asked about,
 			// we are not interested in its information.
-			if is_workspace_member {
+			if bt.is_relevant(id, target) {
 				bt.relevant_cmd_infos.push(cmd_info.clone());
 			}
 			assert!(
-				!(!is_path && is_workspace_member),
+				is_path || !is_workspace_member,
 				"`{}` is a workspace member but is not from a filesystem path",
 				id,
 			);
-			if (!cmd_info.cap_lints_allow) != is_path {
+			// Units of the standard library built by `-Z build-std` come from
+			// a path source but are never linted.
+			if cmd_info.cap_lints_allow == is_path && !cmd_info.is_std {
 				on_stderr_line(&format!(
 					"{} (!cap_lints_allow)={} differs from is_path={} for id={}",
 					if bt.supports_color {
@@ -619,35 +2305,203 @@ impl Executor for Exec {
 				))?;
 			}
 			cmd.env(cargo::CARGO_ENV, &bt.cargo_exe);
+			if let Some(wrapper) = bt.rustc_wrapper.as_deref().filter(|_| bt.is_relevant(id, target)) {
+				unwrap_rustc(&mut cmd, wrapper);
+			}
+			lint = bt.backend == Backend::Lint && bt.is_relevant(id, target);
+			if lint {
+				// Not overridden by `allow`s in the code
+				cmd.arg("--force-warn").arg("unused-crate-dependencies");
+			}
+			if bt.doc_cfg && is_workspace_member && !target.is_custom_build() && bt.is_relevant(id, target) {
+				let doc_cmd = doc_cfg_cmd(&cmd);
+				let mut doc_cmd_info = crate::cmd_info(id, false, &doc_cmd)?;
+				doc_cmd_info.dev = cmd_info.dev;
+				doc_cmd_info.target = cmd_info.target.clone();
+				doc_cfg = Some((doc_cmd, doc_cmd_info));
+			}
+			if let Some(container) = &bt.container {
+				container.wrap(&mut cmd);
+				if let Some((doc_cmd, _)) = &mut doc_cfg {
+					container.wrap(doc_cmd);
+				}
+			}
 		}
-		if is_workspace_member {
-			// This reduces the save analysis files that are being created a little
-			std::env::set_var("RUST_SAVE_ANALYSIS_CONFIG",
-				r#"{ "reachable_only": false, "full_docs": false, "pub_only": false, "distro_crate": false, "signatures": false, "borrow_data": false }"#);
+		let start = Instant::now();
+		let mut unused_externs = None;
+		if lint {
+			let mut unused = HashSet::new();
+			self.inner.exec(&cmd, id, target, mode, on_stdout_line, &mut |line| {
+				match unused_extern(line) {
+					Some(name) => {
+						unused.insert(name);
+						Ok(())
+					},
+					None => on_stderr_line(line),
+				}
+			}).inspect_err(|_| self.failed(id, target, &cmd_info))?;
+			self.data.lock().unwrap().unused_externs.insert(cmd_info.get_artifact_base_name(), unused.clone());
+			unused_externs = Some(unused);
+		} else {
+			self.inner
+				.exec(&cmd, id, target, mode, on_stdout_line, on_stderr_line)
+				.inspect_err(|_| self.failed(id, target, &cmd_info))?;
+		}
+		let duration = start.elapsed();
+		let mut doc_cfg_cmd_info = None;
+		if let Some((doc_cmd, doc_cmd_info)) = doc_cfg {
+			let start = Instant::now();
+			let result = doc_cmd.exec_with_output();
+			let mut bt = self.data.lock().unwrap();
+			bt.timings.record(id, &cmd_info.crate_name, "doc-cfg", start.elapsed());
+			match result {
+				Ok(_) => {
+					bt.doc_cfg_cmd_infos.push(doc_cmd_info.clone());
+					doc_cfg_cmd_info = Some(doc_cmd_info);
+				},
+				// Code behind `cfg(doc)` doesn't need to compile for the build, so this is no error
+				Err(_) => on_stderr_line(&format!(
+					"{} could not check `{}` with `--cfg doc`, so no dependencies of it are found to be doc-only",
+					if bt.supports_color {
+						Color::Yellow.bold().paint("warning:").to_string()
+					} else {
+						"warning:".to_owned()
+					},
+					cmd_info.crate_name,
+				))?,
+			}
+		}
+		let lines = {
+			let mut bt = self.data.lock().unwrap();
+			bt.timings.record(id, &cmd_info.crate_name, "rustc", duration);
+			if let Some(key) = bt.unit_keys.get(&cmd_info.extra_filename).cloned() {
+				if let Some(cache) = &mut bt.cache {
+					cache.insert(key, cache::Entry::new(cmd_info.clone(), doc_cfg_cmd_info, unused_externs));
+				}
+			}
+			if bt.is_relevant(id, target) {
+				bt.unbuilt_units.remove(&(id, cmd_info.target.clone()));
+				stream::unit_built(&mut bt, id)?
+			} else {
+				Vec::new()
+			}
+		};
+		for line in lines {
+			match line {
+				stream::Line::Stdout(line) => on_stdout_line(&line)?,
+				stream::Line::Stderr(line) => on_stderr_line(&line)?,
+			}
+		}
+		Ok(())
+	}
+	fn init(&self, build_runner :&BuildRunner<'_, '_>, unit :&Unit) {
+		{
+			let mut bt = self.data.lock().unwrap();
+			if bt.is_relevant(unit.pkg.package_id(), &unit.target) {
+				bt.unbuilt_units.insert((unit.pkg.package_id(), unit_target(&unit.target, unit.mode)));
+				if let Some(stream) = &mut bt.stream {
+					stream.expect(unit.pkg.package_id());
+				}
+				if bt.cache.is_some() {
+					// Cargo found it dirty after all
+					let key = cache::unit_key(unit);
+					bt.cached_units.remove(&key);
+					if build_runner.files().use_extra_filename(unit) {
+						bt.unit_keys.insert(format!("-{}", build_runner.files().metadata(unit)), key);
+					}
+				}
+			}
 		}
-		DefaultExecutor.exec(&cmd, id, target, mode, on_stdout_line, on_stderr_line)?;
-		Ok(())
+		self.inner.init(build_runner, unit)
 	}
 	fn force_rebuild(&self, unit :&Unit) -> bool {
-		let bt = self.data.lock().unwrap();
-		bt.workspace_members.contains(&unit.pkg.package_id())
+		let mut bt = self.data.lock().unwrap();
+		if !bt.is_relevant(unit.pkg.package_id(), &unit.target) {
+			return self.inner.force_rebuild(unit);
+		}
+		let key = cache::unit_key(unit);
+		let cached = bt.cache.as_ref().is_some_and(|cache| cache.is_fresh(&key, &bt.remap));
+		if cached {
+			bt.cached_units.insert(key);
+		}
+		!cached || self.inner.force_rebuild(unit)
+	}
+}
+
+/// Fails if a package that is asked for by name isn't a workspace member.
+///
+/// `cargo check -p` builds dependencies just as well, but only members are reported on,
+/// so the run would otherwise succeed without checking anything.
+fn check_members(ws :&Workspace<'_>, names :&[String]) -> CargoResult<()> {
+	use cargo::core::{PackageIdSpec, PackageIdSpecQuery};
+	let not_members = names
+		.iter()
+		// Patterns that match nothing are reported by cargo
+		.filter(|name| !name.contains(['*', '?', '[']))
+		.filter(|name| match PackageIdSpec::parse(name) {
+			Ok(spec) => !ws.members().any(|member| spec.matches(member.package_id())),
+			Err(_) => false,
+		})
+		.map(|name| format!("`{}`", name))
+		.collect::<Vec<_>>();
+	if not_members.is_empty() {
+		return Ok(());
 	}
+	let mut members = ws.members().map(|member| format!("`{}`", member.name())).collect::<Vec<_>>();
+	members.sort();
+	let subject = match not_members.as_slice() {
+		[name] => format!("package {} is not a member", name),
+		_ => format!("packages {} are not members", not_members.join(", ")),
+	};
+	Err(anyhow::anyhow!(
+		"{} of the workspace `{}`, and only members can be checked\n  the members are {}",
+		subject,
+		ws.root().display(),
+		members.join(", "),
+	))
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct CmdInfo {
 	pkg :PackageId,
-	#[allow(dead_code)]
 	custom_build :bool,
+	/// Whether this is a unit of tests, benches or examples, which may use dev-dependencies.
+	dev :bool,
+	/// The target, as described to users.
+	target :String,
+	/// The `--target` it is compiled for, or `None` for the host.
+	compile_target :Option<String>,
 	crate_name :String,
 	crate_type :String,
 	extra_filename :String,
 	cap_lints_allow :bool,
+	/// Whether this is a unit of the standard library, built with `-Z build-std`.
+	is_std :bool,
 	out_dir :String,
 	extern_crate_names :HashSet<String>,
+	/// The extern crate names by the file stems of their `--extern` paths.
+	extern_stems :HashMap<String, String>,
 }
 
 impl CmdInfo {
+	/// The information as JSON, sorted so that dumps can be compared.
+	fn dump(&self) -> serde_json::Value {
+		serde_json::json!({
+			"package_id" : self.pkg.to_string(),
+			"custom_build" : self.custom_build,
+			"dev" : self.dev,
+			"target" : self.target,
+			"compile_target" : self.compile_target,
+			"crate_name" : self.crate_name,
+			"crate_type" : self.crate_type,
+			"extra_filename" : self.extra_filename,
+			"cap_lints_allow" : self.cap_lints_allow,
+			"is_std" : self.is_std,
+			"out_dir" : self.out_dir,
+			"extern_crate_names" : self.extern_crate_names.iter().collect::<BTreeSet<_>>(),
+			"extern_stems" : self.extern_stems.iter().collect::<BTreeMap<_, _>>(),
+		})
+	}
 	fn get_artifact_base_name(&self) -> String {
 		let maybe_lib = if self.crate_type.ends_with("lib") ||
 				self.crate_type == "proc-macro" {
@@ -660,69 +2514,61 @@ impl CmdInfo {
 	fn get_depinfo_filename(&self) -> String {
 		self.crate_name.clone() + &self.extra_filename + ".d"
 	}
-	fn get_depinfo_path(&self) -> PathBuf {
-		Path::new(&self.out_dir)
+	fn get_depinfo_path(&self, remap :&remap::PathRemap) -> PathBuf {
+		remap.path(Path::new(&self.out_dir))
 			.join(self.get_depinfo_filename())
 	}
-	fn get_depinfo(&self, shell :&mut Shell) -> CargoResult<DepInfo> {
-		let p = self.get_depinfo_path();
-		shell.info(format_args!("Loading depinfo from {:?}", p))?;
-		let di = parse_rustc_dep_info(&p)?;
-		let di = di.iter()
-			.map(|(v, w)| {
-				let w = w.iter().map(PathBuf::from).collect::<Vec<_>>();
-				(PathBuf::from(v), w)
-			})
-			.collect::<Vec<_>>();
-		Ok(DepInfo { di, f_name : self.get_depinfo_filename() })
-	}
-}
-
-struct DepInfo {
-	di :Vec<(PathBuf, Vec<PathBuf>)>,
-	f_name :String,
-}
-
-impl DepInfo {
-	fn deps_of_depfile(&self) -> Vec<PathBuf> {
-		self.di.iter()
-			.find(|(v, _w)| {
-				v.file_name() == Some(&std::ffi::OsString::from(&self.f_name))
-			})
-			.map(|v| v.1.clone())
-			.unwrap_or_default()
+	/// The files the unit depends on, according to its `.d` file. If the file is missing or
+	/// has no rule for the artifact, the error is an [`analysis::MissingDepinfo`].
+	fn get_depinfo(&self, remap :&remap::PathRemap) -> CargoResult<Vec<PathBuf>> {
+		let p = self.get_depinfo_path(remap);
+		let deps = match parse_rustc_dep_info(&p, &self.get_depinfo_filename()) {
+			Ok(Some(deps)) => deps,
+			Ok(None) => return Err(analysis::MissingDepinfo::new(self, p, true).into()),
+			Err(e) if e.downcast_ref::<io::Error>().is_some_and(|e| e.kind() == io::ErrorKind::NotFound) => {
+				return Err(analysis::MissingDepinfo::new(self, p, false).into());
+			},
+			Err(e) => return Err(e),
+		};
+		Ok(deps.iter().map(|w| remap.path(Path::new(w))).collect())
 	}
 }
 
 // Bases on function with same name from cargo source src/cargo/core/compiler/fingerprint.rs
 /// Parse the `.d` dep-info file generated by rustc.
 ///
-/// Result is a Vec of `(target, prerequisites)` tuples where `target` is the
-/// rule name, and `prerequisites` is a list of files that it depends on.
-fn parse_rustc_dep_info(rustc_dep_info :&Path) -> CargoResult<Vec<(String, Vec<String>)>> {
-	let contents = std::fs::read_to_string(rustc_dep_info)?;
-	contents
-		.lines()
-		.filter_map(|l| l.find(": ").map(|i| (l, i)))
-		.map(|(line, pos)| {
-			let target = &line[..pos];
-			let mut deps = line[pos + 2..].split_whitespace();
-
-			let mut ret = Vec::new();
-			while let Some(s) = deps.next() {
-				let mut file = s.to_string();
-				while file.ends_with('\\') {
-					file.pop();
-					file.push(' ');
-					file.push_str(deps.next().ok_or_else(|| {
-						anyhow::anyhow!("malformed dep-info format, trailing \\".to_string())
-					})?);
-				}
-				ret.push(file);
+/// Only the prerequisites of the rule whose target has the file name `target_file_name`
+/// are returned, or `None` if there is no such rule. There is a rule for every output of rustc,
+/// each listing all crates and source files again, so the others aren't split up and the
+/// file is read line by line.
+fn parse_rustc_dep_info(rustc_dep_info :&Path, target_file_name :&str) -> CargoResult<Option<Vec<String>>> {
+	use std::io::BufRead;
+	let file = io::BufReader::new(std::fs::File::open(rustc_dep_info)?);
+	for line in file.lines() {
+		let line = line?;
+		let Some(pos) = line.find(": ") else {
+			continue;
+		};
+		if Path::new(&line[..pos]).file_name() != Some(OsStr::new(target_file_name)) {
+			continue;
+		}
+		let mut deps = line[pos + 2..].split_whitespace();
+
+		let mut ret = Vec::new();
+		while let Some(s) = deps.next() {
+			let mut file = s.to_string();
+			while file.ends_with('\\') {
+				file.pop();
+				file.push(' ');
+				file.push_str(deps.next().ok_or_else(|| {
+					anyhow::anyhow!("malformed dep-info format, trailing \\".to_string())
+				})?);
 			}
-			Ok((target.to_string(), ret))
-		})
-		.collect()
+			ret.push(file);
+		}
+		return Ok(Some(ret));
+	}
+	Ok(None)
 }
 
 fn cmd_info(id :PackageId, custom_build :bool, cmd :&ProcessBuilder) -> CargoResult<CmdInfo> {
@@ -731,32 +2577,27 @@ fn cmd_info(id :PackageId, custom_build :bool, cmd :&ProcessBuilder) -> CargoRes
 	let mut crate_type = None;
 	let mut extra_filename = None;
 	let mut cap_lints_allow = false;
+	let mut is_std = false;
 	let mut out_dir = None;
+	let mut compile_target = None;
 	let mut extern_crate_names = HashSet::new();
+	let mut extern_stems = HashMap::new();
 	while let Some(v) = args_iter.next() {
 		if v == "--extern" {
 			if let Some(arg) = args_iter.next() {
-				let splitter = arg
-					.to_str()
-					.expect("non-utf8 paths not supported atm")
-					.split('=')
-					.collect::<Vec<_>>();
-				match *splitter {
-					[name] | [name, _] => extern_crate_names.insert(name.to_owned()),
-					_ => panic!("invalid format for extern arg: {:?}", arg),
-				};
+				let (name, stem) = parse_extern(arg)?;
+				if let Some(stem) = stem {
+					extern_stems.insert(stem, name.clone());
+				}
+				extern_crate_names.insert(name);
 			}
 		} else if v == "--crate-name" {
 			if let Some(name) = args_iter.next() {
-				crate_name = Some(name.to_str()
-					.expect("non-utf8 crate names not supported")
-					.to_owned());
+				crate_name = Some(utf8_arg(name, "crate name")?);
 			}
 		} else if v == "--crate-type" {
 			if let Some(ty) = args_iter.next() {
-				crate_type = Some(ty.to_str()
-					.expect("non-utf8 crate names not supported")
-					.to_owned());
+				crate_type = Some(utf8_arg(ty, "crate type")?);
 			}
 		} else if v == "--cap-lints" {
 			if let Some(c) = args_iter.next() {
@@ -764,20 +2605,24 @@ fn cmd_info(id :PackageId, custom_build :bool, cmd :&ProcessBuilder) -> CargoRes
 					cap_lints_allow = true;
 				}
 			}
+		} else if v == "--target" {
+			if let Some(t) = args_iter.next() {
+				compile_target = Some(utf8_arg(t, "target")?);
+			}
 		} else if v == "--out-dir" {
 			if let Some(d) = args_iter.next() {
-				out_dir = Some(d.to_str()
-					.expect("non-utf8 crate names not supported")
-					.to_owned());
+				out_dir = Some(utf8_arg(d, "output directory")?);
+			}
+		} else if v == "-Z" {
+			// Cargo passes this to units of the standard library only
+			if args_iter.next().is_some_and(|z| z == "force-unstable-if-unmarked") {
+				is_std = true;
 			}
 		} else if v == "-C" {
-			if let Some(arg) = args_iter.next() {
-				let arg = arg.to_str().expect("non-utf8 args not supported atm");
-				let mut splitter = arg.split('=');
-				if let (Some(n), Some(p)) = (splitter.next(), splitter.next()) {
-					if n == "extra-filename" {
-						extra_filename = Some(p.to_owned());
-					}
+			// Codegen options that aren't UTF-8, like paths of `-C linker`, aren't needed
+			if let Some(arg) = args_iter.next().and_then(|arg| arg.to_str()) {
+				if let Some(("extra-filename", p)) = arg.split_once('=') {
+					extra_filename = Some(p.to_owned());
 				}
 			}
 		}
@@ -791,15 +2636,359 @@ fn cmd_info(id :PackageId, custom_build :bool, cmd :&ProcessBuilder) -> CargoRes
 	Ok(CmdInfo {
 		pkg,
 		custom_build,
+		dev : false,
+		target : String::new(),
+		compile_target,
 		crate_name,
 		crate_type,
 		extra_filename,
 		cap_lints_allow,
+		is_std,
 		out_dir,
 		extern_crate_names,
+		extern_stems,
 	})
 }
 
+/// The extern crate name of an `--extern` argument, and the file stem of its path if it has one.
+fn parse_extern(arg :&OsStr) -> CargoResult<(String, Option<String>)> {
+	// Only the name has to be UTF-8, the path can contain anything, even `=`
+	let bytes = arg.as_encoded_bytes();
+	let (name, path) = match bytes.iter().position(|&b| b == b'=') {
+		Some(i) => (&bytes[..i], Some(String::from_utf8_lossy(&bytes[i + 1..]))),
+		None => (bytes, None),
+	};
+	let name = std::str::from_utf8(name)
+		.map_err(|_| anyhow::anyhow!("the extern crate name in {:?} is not UTF-8", arg))?;
+	// Options like `priv` and `noprelude` (used for `-Z build-std`) precede the name
+	let name = strip_extern_opts(name);
+	if name.is_empty() {
+		anyhow::bail!("invalid format for extern arg: {:?}", arg);
+	}
+	let stem = path.as_deref().map(Path::new).and_then(Path::file_stem).and_then(OsStr::to_str);
+	Ok((name.to_owned(), stem.map(str::to_owned)))
+}
+
+/// The command to check the unit once more with `--cfg doc` and `--cfg docsrs`, for `--doc-cfg`.
+///
+/// Only the dep-info is emitted, next to that of the build but with another `extra-filename`,
+/// and incremental compilation is turned off, so that nothing of the build is overwritten.
+fn doc_cfg_cmd(cmd :&ProcessBuilder) -> ProcessBuilder {
+	let mut args = Vec::new();
+	let mut args_iter = cmd.get_args();
+	while let Some(arg) = args_iter.next() {
+		if arg.to_str().is_some_and(|arg| arg.starts_with("--emit=")) {
+			args.push(OsString::from("--emit=dep-info"));
+		} else if arg == "-C" {
+			let Some(value) = args_iter.next() else {
+				args.push(arg.clone());
+				continue;
+			};
+			match value.to_str() {
+				Some(value) if value.starts_with("incremental=") => (),
+				Some(value) if value.starts_with("extra-filename=") => {
+					args.push(arg.clone());
+					args.push(OsString::from(format!("{}-doc-cfg", value)));
+				},
+				_ => {
+					args.push(arg.clone());
+					args.push(value.clone());
+				},
+			}
+		} else {
+			args.push(arg.clone());
+		}
+	}
+	args.extend(["--cfg", "doc", "--cfg", "docsrs"].map(OsString::from));
+	let mut doc_cmd = cmd.clone();
+	doc_cmd.args_replace(&args);
+	doc_cmd
+}
+
+/// Drops `wrapper` from the command if it is the outermost program, like `sccache` of `RUSTC_WRAPPER`.
+///
+/// Caching wrappers may restore the dep-info and diagnostics of earlier builds, made with other paths
+/// or lint levels, so the units that are analyzed are compiled by rustc itself. A
+/// `RUSTC_WORKSPACE_WRAPPER` is kept.
+fn unwrap_rustc(cmd :&mut ProcessBuilder, wrapper :&Path) {
+	if Path::new(cmd.get_program()) != wrapper {
+		return;
+	}
+	let args = cmd.get_args().cloned().collect::<Vec<_>>();
+	let Some((program, args)) = args.split_first() else {
+		return;
+	};
+	// The wrapper becomes the program, and the inner wrappers are the first arguments
+	cmd.args_replace(args);
+	cmd.program(program);
+}
+
+/// An argument of rustc that has to be UTF-8, like the crate name.
+fn utf8_arg(arg :&OsStr, what :&str) -> CargoResult<String> {
+	arg.to_str()
+		.map(str::to_owned)
+		.ok_or_else(|| anyhow::anyhow!("the {} {:?} is not UTF-8", what, arg))
+}
+
+/// Strips the options from the name part of an `--extern [OPTIONS:]NAME=PATH` argument.
+fn strip_extern_opts(name :&str) -> &str {
+	name.split_once(':').map_or(name, |(_, name)| name)
+}
+
+/// The features of the included members that their targets to check list in `required-features`,
+/// as `PACKAGE/FEATURE`.
+///
+/// Cargo skips these targets unless the features are enabled, so the dependencies only they use
+/// would be reported. Features of dependencies, like `dep/feature`, are left out.
+fn required_features(ws :&Workspace<'_>, included_packages :&HashSet<PackageId>, filter :&CompileFilter) -> CargoResult<Vec<String>> {
+	let mut members = ws.members().filter(|pkg| included_packages.contains(&pkg.package_id())).collect::<Vec<_>>();
+	members.sort_by_key(|pkg| pkg.name());
+	let mut required = Vec::new();
+	for pkg in members {
+		let features = pkg.targets()
+			.iter()
+			// Like `cargo check`, which only checks the library and the binaries by default
+			.filter(|target| match filter {
+				CompileFilter::Default { .. } => target.is_bin(),
+				CompileFilter::Only { .. } => filter.target_run(target),
+			})
+			.flat_map(|target| target.required_features().into_iter().flatten())
+			.filter(|feature| !feature.contains('/'))
+			.collect::<BTreeSet<_>>();
+		if features.is_empty() {
+			continue;
+		}
+		ws.gctx().shell().info(format_args!(
+			"Enabling {} of `{}`, which its targets require",
+			features.iter().map(|feature| format!("`{}`", feature)).collect::<Vec<_>>().join(", "),
+			pkg.name(),
+		))?;
+		required.extend(features.into_iter().map(|feature| format!("{}/{}", pkg.name(), feature)));
+	}
+	Ok(required)
+}
+
+/// Whether a crate of `from` is linked against `to`, through its normal dependencies,
+/// so that rustc loads `to` for any unit that uses `from`.
+fn loads(resolve :&Resolve, from :PackageId, to :PackageId) -> bool {
+	let mut seen = HashSet::new();
+	let mut pending = vec![from];
+	while let Some(id) = pending.pop() {
+		for (dependency_id, deps) in resolve.deps(id) {
+			if !deps.iter().any(|d| d.kind() == dependency::DepKind::Normal) {
+				continue;
+			}
+			if dependency_id == to {
+				return true;
+			}
+			if seen.insert(dependency_id) {
+				pending.push(dependency_id);
+			}
+		}
+	}
+	false
+}
+
+/// Which dependencies of the members were found to be used by their units.
+#[derive(Debug, Default)]
+struct Usage {
+	/// Whether dependencies are matched by library name if their artifacts are unknown.
+	lib_name_fallback :bool,
+	used_normal_dev :HashSet<(PackageId, InternedString)>,
+	used_build :HashSet<(PackageId, InternedString)>,
+	/// The normal dependencies used by units of tests, benches and examples, with these targets.
+	used_normal_by_dev :HashMap<(PackageId, InternedString), BTreeSet<String>>,
+	/// The normal dependencies used by the other units, except build scripts.
+	used_normal_by_non_dev :HashSet<(PackageId, InternedString)>,
+	/// The packages with units that are not of tests, benches, examples or build scripts.
+	non_dev_packages :HashSet<PackageId>,
+	normal :HashSet<(PackageId, InternedString)>,
+	development :HashSet<(PackageId, InternedString)>,
+	build :HashSet<(PackageId, InternedString)>,
+	/// How the dependencies were found to be used, if kept for `--explain DEPENDENCY`.
+	trace :Option<why::Trace>,
+}
+
+impl Usage {
+	/// Starts out with the non-library dependencies, which are never found to be used.
+	fn new<'a>(
+		dependency_names :impl IntoIterator<Item = (&'a PackageId, &'a DependencyNames)>,
+		lib_name_fallback :bool,
+	) -> Self {
+		let mut this = Self { lib_name_fallback, ..Self::default() };
+		for (&member, names) in dependency_names {
+			this.normal.extend(names.normal.non_lib.iter().map(|&name| (member, name)));
+			this.development.extend(names.development.non_lib.iter().map(|&name| (member, name)));
+			this.build.extend(names.build.non_lib.iter().map(|&name| (member, name)));
+		}
+		this
+	}
+
+	fn add_unit(
+		&mut self,
+		unit :&analysis::Unit,
+		lib_stem_to_pkg_id :&HashMap<String, PackageId>,
+		dependency_names :&DependencyNames,
+	) {
+		let lib_name_fallback = self.lib_name_fallback;
+		let tracing = self.trace.is_some();
+		// The dependencies the unit used, with how they were found if that is traced, and those it was passed
+		let collect_names = |dnv :&DependencyNamesValue| {
+			let mut used_dependencies = Vec::new();
+			let mut evidence = Vec::new();
+			for fs in &unit.used_stems {
+				// The file names are like cratename-hash.rmeta or .rlib,
+				// where "hash" is a hash string that cargo calls "metadata"
+				// internally and computes in its "compute_metadata" function,
+				// and cratename is the snakecased crate name.
+
+				// First, we continue if there is no - in the filename.
+				// it's likely a source file or some other artifact we aren't
+				// interested in. This is obviously only a stupid heuristic.
+				let lib_name = match fs.split_once('-') {
+					None => continue,
+					Some((lib_name, _)) => lib_name
+				};
+				// Only copied for `--explain`, as there is one for every used crate of every unit
+				let mut found = |dependency_name, extern_crate_name :Option<&String>, matched| {
+					used_dependencies.push(dependency_name);
+					if tracing {
+						evidence.push(why::Evidence {
+							target : unit.target.clone(),
+							extern_crate_name : extern_crate_name.cloned(),
+							stem : fs.clone(),
+							matched,
+						});
+					}
+				};
+
+				// The metadata hash is not available through cargo's api
+				// outside of the Executor trait impl. We do our best to obtain
+				// the hashes from that impl, but the executor is not called
+				// for anything but crates that have to be recompiled.
+				// Thus, any crates that weren't recompiled we don't know the
+				// metadata hash of. So we perform a check: if we know the metadata
+				// hash, we use it, otherwise we don't.
+				// This gives a bit surprising behaviour when re-running
+				// cargo-udeps but at least sometimes the results are more accurate.
+
+				// The `--extern` paths tell apart different versions of the same crate
+				let extern_name = unit.extern_stems
+					.get(fs)
+					.and_then(|name| Some((name, dnv.by_extern_crate_name.get(&**name)?)));
+				if let Some((name, &dependency_name)) = extern_name {
+					found(dependency_name, Some(name), why::Matched::ExternPath);
+				} else if let Some(pkg_id) = lib_stem_to_pkg_id.get(fs) {
+					if let Some(&dependency_name) = dnv.by_package_id.get(pkg_id) {
+						found(dependency_name, None, why::Matched::PackageId);
+					}
+				} else if lib_name_fallback {
+					// Libraries carry a "lib" prefix, except proc macros on some platforms
+					// (`foo.dll`), so a proc macro crate whose name starts with "lib"
+					// is only found by its whole stem. See maybe_lib in the code above.
+					let dependency_names = lib_name
+						.strip_prefix("lib")
+						.and_then(|stripped| dnv.by_lib_true_snakecased_name.get(stripped))
+						.or_else(|| dnv.by_lib_true_snakecased_name.get(lib_name));
+					for &dependency_name in dependency_names.into_iter().flatten() {
+						found(dependency_name, None, why::Matched::LibName);
+					}
+				}
+			}
+
+			// We ignore:
+			// 1. the `lib` that `bin`s, `example`s, and `test`s in the same `Package` depend on
+			// 2. crates bundled with `rustc` such as `proc-macro`
+			let dependencies = unit.extern_crate_names
+				.iter()
+				.filter_map(|extern_crate_name| dnv.by_extern_crate_name.get(&**extern_crate_name).copied())
+				.collect::<Vec<_>>();
+			(used_dependencies, evidence, dependencies)
+		};
+
+		let mut record = |kind, used :&[InternedString], evidence :Vec<why::Evidence>, passed :&[InternedString]| {
+			if let Some(trace) = &mut self.trace {
+				trace.record(kind, unit.pkg, &unit.target, used.iter().copied().zip(evidence), passed);
+			}
+			let (used_dependencies, dependencies) = match kind {
+				dependency::DepKind::Normal => (&mut self.used_normal_dev, &mut self.normal),
+				dependency::DepKind::Development => (&mut self.used_normal_dev, &mut self.development),
+				dependency::DepKind::Build => (&mut self.used_build, &mut self.build),
+			};
+			used_dependencies.extend(used.iter().map(|&dependency| (unit.pkg, dependency)));
+			dependencies.extend(passed.iter().map(|&dependency| (unit.pkg, dependency)));
+		};
+
+		let (used_normal, evidence, passed_normal) = collect_names(&dependency_names.normal);
+		record(dependency::DepKind::Normal, &used_normal, evidence, &passed_normal);
+		let (used_development, evidence, passed_development) = collect_names(&dependency_names.development);
+		record(dependency::DepKind::Development, &used_development, evidence, &passed_development);
+		let (used_build, evidence, passed_build) = collect_names(&dependency_names.build);
+		record(dependency::DepKind::Build, &used_build, evidence, &passed_build);
+
+		if !unit.custom_build {
+			let used_normal = used_normal.iter().map(|&dependency| (unit.pkg, dependency)).collect::<HashSet<_>>();
+			if unit.dev {
+				for dependency in used_normal {
+					self.used_normal_by_dev.entry(dependency).or_default().insert(unit.target.clone());
+				}
+			} else {
+				self.used_normal_by_non_dev.extend(used_normal);
+				self.non_dev_packages.insert(unit.pkg);
+			}
+		}
+	}
+
+	/// The dependencies that none of the units used, with their kind.
+	fn unused(&self) -> impl Iterator<Item = (dependency::DepKind, PackageId, InternedString)> + '_ {
+		[
+			(&self.normal, &self.used_normal_dev, dependency::DepKind::Normal),
+			(&self.development, &self.used_normal_dev, dependency::DepKind::Development),
+			(&self.build, &self.used_build, dependency::DepKind::Build),
+		]
+			.into_iter()
+			.flat_map(|(dependencies, used, kind)| {
+				dependencies
+					.iter()
+					.filter(move |dependency| !used.contains(dependency))
+					.map(move |&(id, dependency)| (kind, id, dependency))
+			})
+	}
+
+	fn is_used(&self, kind :dependency::DepKind, id :PackageId, dependency :InternedString) -> bool {
+		match kind {
+			dependency::DepKind::Normal | dependency::DepKind::Development => self.used_normal_dev.contains(&(id, dependency)),
+			dependency::DepKind::Build => self.used_build.contains(&(id, dependency)),
+		}
+	}
+
+	/// Whether the dependency was passed to any of the units, or can't be used at all.
+	fn is_checked(&self, kind :dependency::DepKind, id :PackageId, dependency :InternedString) -> bool {
+		let dependencies = match kind {
+			dependency::DepKind::Normal => &self.normal,
+			dependency::DepKind::Development => &self.development,
+			dependency::DepKind::Build => &self.build,
+		};
+		dependencies.contains(&(id, dependency))
+	}
+
+	/// The normal dependencies that only units of tests, benches and examples used,
+	/// of the packages whose other targets were checked as well.
+	fn dev_only(&self) -> impl Iterator<Item = (PackageId, InternedString)> + '_ {
+		self.used_normal_by_dev
+			.keys()
+			.filter(move |&&(id, dependency)| {
+				self.non_dev_packages.contains(&id) && !self.used_normal_by_non_dev.contains(&(id, dependency))
+			})
+			.copied()
+	}
+
+	/// The targets of tests, benches and examples that used the normal dependency.
+	fn dev_targets(&self, id :PackageId, dependency :InternedString) -> impl Iterator<Item = &String> + '_ {
+		self.used_normal_by_dev.get(&(id, dependency)).into_iter().flatten()
+	}
+}
+
 #[derive(Debug, Default)]
 struct DependencyNames {
 	normal: DependencyNamesValue,
@@ -810,8 +2999,11 @@ struct DependencyNames {
 impl DependencyNames {
 	fn new(
 		from :&Package,
-		packages :&HashMap<PackageId, &Package>,
+		lib_targets :&HashMap<PackageId, &Target>,
 		resolve :&Resolve,
+		features :&ResolvedFeatures,
+		target_data :&RustcTargetData<'_>,
+		requested_kinds :&[CompileKind],
 		shell :&mut Shell,
 	) -> CargoResult<Self> {
 		let mut this = Self::default();
@@ -819,38 +3011,55 @@ impl DependencyNames {
 		let from = from.package_id();
 
 		for (to_pkg, deps) in resolve.deps(from) {
-			let to_pkg = packages.get(&to_pkg).unwrap_or_else(|| panic!("could not find `{}`", to_pkg));
-
 			// Not all dependencies contain `lib` targets as it is OK to append non-library packages to `Cargo.toml`.
 			// Their `bin` targets can be built with `cargo build --bins -p <SPEC>` and are available in build scripts.
-			if let Some(to_lib) = to_pkg
-				.targets()
-				.iter()
-				.find(|t| t.is_lib())
-			{
-				let extern_crate_name = resolve.extern_crate_name_and_dep_name(from, to_pkg.package_id(), to_lib)?.0.as_str();
-				let lib_true_snakecased_name = to_lib.crate_name();
+			// Libraries that are only `cdylib`s or `staticlib`s aren't passed to rustc either.
+			if let Some(&to_lib) = lib_targets.get(&to_pkg).filter(|lib| lib.is_linkable()) {
+				let extern_crate_name = resolve.extern_crate_name_and_dep_name(from, to_pkg, to_lib)?.0.as_str();
+				let lib_true_snakecased_name = InternedString::new(&to_lib.crate_name());
 
 				for dep in deps {
 					assert_eq!(dep.package_name(), to_pkg.name());
 					let names = &mut this[dep.kind()];
 					names.by_extern_crate_name.insert(extern_crate_name, dep.name_in_toml());
-					let r = names.by_package_id.insert(to_pkg.package_id(), dep.name_in_toml());
-					if r.is_some() {
-						shell.warn(format!("duplicate package mentioned in toml {}. {:?}", to_pkg.package_id(), r))?;
+					let r = names.by_package_id.insert(to_pkg, dep.name_in_toml());
+					// The same dependency may be declared once more in a table of a platform
+					if r.is_some_and(|previous| previous != dep.name_in_toml()) {
+						shell.warn(format!("duplicate package mentioned in toml {}. {:?}", to_pkg, r))?;
 					}
 
 					// Two `Dependenc`ies with the same name point at the same `Package`.
 					names
 						.by_lib_true_snakecased_name
-						.entry(lib_true_snakecased_name.clone())
+						.entry(lib_true_snakecased_name)
 						.or_insert_with(HashSet::new)
 						.insert(dep.name_in_toml());
 				}
 			} else {
-				for dep in deps {
+				// Library dependencies only show up in the externs of the platforms and
+				// features they are activated for. Do the same for the others, so that
+				// dependencies of other platforms (e.g. of an embedded target) or optional
+				// ones that are only enabled by other packages (with `resolver = "2"`) are not reported.
+				let activated = |dep :&&Dependency| {
+					let platform_activated = match dep.kind() {
+						dependency::DepKind::Build => target_data.dep_platform_activated(dep, CompileKind::Host),
+						_ => requested_kinds.iter().any(|&k| target_data.dep_platform_activated(dep, k)),
+					};
+					platform_activated
+						&& (!dep.is_optional() || features.is_dep_activated(from, FeaturesFor::default(), dep.name_in_toml()))
+				};
+				for dep in deps.iter().filter(activated) {
 					this[dep.kind()].non_lib.insert(dep.name_in_toml());
 				}
+				if let Some(to_lib) = lib_targets.get(&to_pkg).filter(|_| deps.iter().any(|dep| activated(&dep))) {
+					let crate_types = to_lib.rustc_crate_types().iter().map(|ty| format!("`{}`", ty)).collect::<Vec<_>>();
+					shell.warn(format!(
+						"the library of `{}` is only a {}, which Rust code can't use, so it is regarded as unused by `{}`",
+						to_pkg,
+						crate_types.join(" and "),
+						from,
+					))?;
+				}
 			}
 		}
 
@@ -895,12 +3104,34 @@ impl DependencyNames {
 					}
 				}
 			}
-			shell.warn(msg.trim_end())?;
+			msg += "See `cargo udeps --explain ambiguous` for details.";
+			shell.warn(msg)?;
 		}
 
 		Ok(this)
 	}
 
+	/// The maps as JSON, sorted so that dumps can be compared.
+	fn dump(&self) -> serde_json::Value {
+		let dump = |names :&DependencyNamesValue| serde_json::json!({
+			"by_extern_crate_name" : names.by_extern_crate_name.iter().collect::<BTreeMap<_, _>>(),
+			"by_lib_name" : names.by_lib_true_snakecased_name
+				.iter()
+				.map(|(lib_name, names)| (lib_name, names.iter().collect::<BTreeSet<_>>()))
+				.collect::<BTreeMap<_, _>>(),
+			"by_package_id" : names.by_package_id
+				.iter()
+				.map(|(id, name)| (id.to_string(), name))
+				.collect::<BTreeMap<_, _>>(),
+			"non_lib" : names.non_lib.iter().collect::<BTreeSet<_>>(),
+		});
+		serde_json::json!({
+			"normal" : dump(&self.normal),
+			"development" : dump(&self.development),
+			"build" : dump(&self.build),
+		})
+	}
+
 	fn has_non_lib(&self) -> bool {
 		[dependency::DepKind::Normal, dependency::DepKind::Development, dependency::DepKind::Build]
 			.iter()
@@ -933,7 +3164,7 @@ impl IndexMut<dependency::DepKind> for DependencyNames {
 #[derive(Debug, Default)]
 struct DependencyNamesValue {
 	by_extern_crate_name :HashMap<&'static str, InternedString>,
-	by_lib_true_snakecased_name :HashMap<String, HashSet<InternedString>>,
+	by_lib_true_snakecased_name :HashMap<InternedString, HashSet<InternedString>>,
 	by_package_id :HashMap<PackageId, InternedString>,
 	non_lib :HashSet<InternedString>,
 }
@@ -946,9 +3177,169 @@ struct PackageMetadata {
 }
 
 #[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 struct PackageMetadataCargoUdeps {
 	#[serde(default)]
 	ignore: PackageMetadataCargoUdepsIgnore,
+	/// Registries whose crates `apply` leaves alone unless explicitly allowed.
+	#[serde(default)]
+	protected_registries: HashSet<String>,
+	/// `FROM=TO` pairs like the ones of `--remap-path-prefix`.
+	#[serde(default)]
+	remap_path_prefix: Vec<String>,
+	/// Special treatment of dependencies, by name.
+	#[serde(default)]
+	policy: HashMap<String, DependencyPolicy>,
+	/// Package name patterns, by the team that owns the packages.
+	#[serde(default)]
+	owners: BTreeMap<String, Vec<String>>,
+	/// A `CODEOWNERS` file to look the owners of packages up in, relative to the workspace root.
+	codeowners: Option<PathBuf>,
+	/// Named lists of features, for `--feature-set`.
+	#[serde(default)]
+	feature_sets: BTreeMap<String, Vec<String>>,
+	/// The levels of the kinds of unused dependencies, like `--deny`, `--warn` and `--allow`.
+	#[serde(default)]
+	lints: HashMap<Lint, Severity>,
+}
+
+impl PackageMetadataCargoUdeps {
+	fn of_package(package :&Package) -> CargoResult<Option<Self>> {
+		Self::parse(package.manifest().custom_metadata(), "package.metadata.cargo-udeps")
+	}
+
+	/// The table of the workspace, with the keys of `.cargo-udeps.toml` over its own.
+	fn of_workspace(ws :&Workspace<'_>) -> CargoResult<Option<Self>> {
+		match config_file::ConfigFile::find(ws.gctx().cwd())? {
+			Some((path, file)) => Self::parse(Some(&file.layer_over(ws.custom_metadata())), &path.display().to_string()),
+			None => Self::parse(ws.custom_metadata(), "workspace.metadata.cargo-udeps"),
+		}
+	}
+
+	fn parse<T>(metadata :Option<&T>, table :&str) -> CargoResult<Option<Self>>
+	where
+		T: Clone + for<'de> serde::Deserializer<'de>,
+		for<'de> <T as serde::Deserializer<'de>>::Error: Send + Sync + 'static,
+	{
+		use anyhow::Context;
+		metadata
+			.map(|metadata| {
+				let PackageMetadata { cargo_udeps } = PackageMetadata::deserialize(metadata.clone())
+					.with_context(|| format!("could not parse `{}`", table))?;
+				Ok(cargo_udeps)
+			})
+			.transpose()
+	}
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DependencyPolicy {
+	/// If unset, the one of the kind of the dependency applies.
+	severity: Option<Severity>,
+	/// Whether `apply` may remove the dependency.
+	#[serde(default = "default_true", alias = "auto_fix")]
+	auto_fix: bool,
+}
+
+fn default_true() -> bool {
+	true
+}
+
+impl DependencyPolicy {
+	/// The policy for a dependency, where the one of the package takes precedence.
+	fn get(
+		package :Option<&PackageMetadataCargoUdeps>,
+		workspace :Option<&PackageMetadataCargoUdeps>,
+		name_in_toml :&str,
+	) -> Option<Self> {
+		package
+			.and_then(|m| m.policy.get(name_in_toml))
+			.or_else(|| workspace.and_then(|m| m.policy.get(name_in_toml)))
+			.copied()
+	}
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Severity {
+	/// Unused dependencies make the run fail
+	#[default]
+	Error,
+	/// Unused dependencies are reported, but don't make the run fail
+	Warn,
+	/// Unused dependencies are ignored
+	Allow,
+}
+
+impl Severity {
+	/// How an unused dependency is treated, or `None` if it is ignored.
+	///
+	/// The policy of the dependency takes precedence over the `levels` of the command line,
+	/// which take precedence over the `lints` of the package and then of the workspace.
+	fn of_unused(
+		package :Option<&PackageMetadataCargoUdeps>,
+		workspace :Option<&PackageMetadataCargoUdeps>,
+		levels :&HashMap<Lint, Severity>,
+		kind :dependency::DepKind,
+		name_in_toml :InternedString,
+	) -> Option<Self> {
+		let ignored = [package, workspace]
+			.into_iter()
+			.flatten()
+			.any(|metadata| metadata.ignore.contains(kind, name_in_toml));
+		let lint = Lint::of(kind);
+		let severity = DependencyPolicy::get(package, workspace, &name_in_toml)
+			.and_then(|p| p.severity)
+			.or_else(|| levels.get(&lint).copied())
+			.or_else(|| [package, workspace].into_iter().flatten().find_map(|m| m.lints.get(&lint).copied()))
+			.unwrap_or(Severity::Error);
+		match severity {
+			_ if ignored => None,
+			Severity::Allow => None,
+			severity => Some(severity),
+		}
+	}
+}
+
+/// Why [`Severity::of_unused`] ignores an unused dependency, for `--show-ignored`.
+fn ignore_reason(
+	package :Option<&PackageMetadataCargoUdeps>,
+	workspace :Option<&PackageMetadataCargoUdeps>,
+	kind :dependency::DepKind,
+	name_in_toml :InternedString,
+) -> String {
+	if package.is_some_and(|metadata| metadata.ignore.contains(kind, name_in_toml)) {
+		"it is in `package.metadata.cargo-udeps.ignore`".to_owned()
+	} else if workspace.is_some_and(|metadata| metadata.ignore.contains(kind, name_in_toml)) {
+		"it is in `workspace.metadata.cargo-udeps.ignore`".to_owned()
+	} else {
+		"its level is `allow`".to_owned()
+	}
+}
+
+/// The kinds of unused dependencies, whose severity can be set like the levels of rustc lints.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+enum Lint {
+	#[value(name = "unused-normal")]
+	#[serde(rename = "unused-normal")]
+	Normal,
+	#[value(name = "unused-dev")]
+	#[serde(rename = "unused-dev")]
+	Dev,
+	#[value(name = "unused-build")]
+	#[serde(rename = "unused-build")]
+	Build,
+}
+
+impl Lint {
+	fn of(kind :dependency::DepKind) -> Self {
+		match kind {
+			dependency::DepKind::Normal => Self::Normal,
+			dependency::DepKind::Development => Self::Dev,
+			dependency::DepKind::Build => Self::Build,
+		}
+	}
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -972,78 +3363,267 @@ impl PackageMetadataCargoUdepsIgnore {
 	}
 }
 
-#[derive(Default, Debug, Serialize)]
+#[derive(Default, Debug, Serialize, Deserialize)]
 struct Outcome {
 	success: bool,
+	/// The number of packages that were checked, so that a successful run
+	/// can be told apart from one that didn't check anything.
+	#[serde(default)]
+	packages_checked: usize,
+	/// The versions of the tools the report was made with.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	provenance: Option<Provenance>,
+	/// The `resolver` version that was in effect.
+	#[serde(default)]
+	resolver: Option<String>,
+	/// The features the checked packages were built with.
+	#[serde(default)]
+	features: BTreeMap<PackageId, Vec<InternedString>>,
 	unused_deps: BTreeMap<PackageId, OutcomeUnusedDeps>,
+	/// With several `--target`s, the dependencies that are unused when only the units
+	/// of one of them are looked at, by target.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	unused_by_target: BTreeMap<String, Vec<TargetUnused>>,
+	/// Each unused dependency once, with the packages that have it, most common first,
+	/// with `--group-by dependency`.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	by_dependency: Vec<DependencyGroup>,
+	/// The members that no other member depends on and that have no binaries, with `--check-workspace-members`.
+	/// They don't make the run fail.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	unused_members: Vec<PackageId>,
+	/// The features that don't enable anything and that the sources don't look at, with `--check-features`.
+	/// They don't make the run fail.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	unused_features: BTreeMap<PackageId, Vec<InternedString>>,
+	/// The crates the members depend on in several versions, by name, with `--check-duplicates`.
+	/// They don't make the run fail.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	duplicates: BTreeMap<InternedString, Vec<duplicates::Duplicate>>,
+	/// The entries of `[patch]` and `[replace]` that have no effect, with `--check-patches`.
+	/// They don't make the run fail.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	unused_patches: Vec<patches::UnusedPatch>,
+	/// The unused dependencies that are not reported, with why, with `--show-ignored`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	ignored: BTreeMap<PackageId, Vec<IgnoredUnused>>,
+	/// The checked units that could not be analyzed, as the build failed with `--keep-going`.
+	/// The dependencies that only they use are reported as unused.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	unanalyzed_units: Vec<UnanalyzedUnit>,
 	note: Option<String>,
 }
 
-impl Outcome {
-	fn print(&self, output: OutputKind, stdout: impl Write) -> io::Result<()> {
-		match output {
-			OutputKind::Human => self.print_human(stdout),
-			OutputKind::Json => self.print_json(stdout),
-		}
-	}
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct TargetUnused {
+	package: PackageId,
+	/// `normal`, `development` or `build`, like the lists of the unused dependencies.
+	kind: String,
+	name: InternedString,
+}
 
-	fn print_human(&self, mut stdout: impl Write) -> io::Result<()> {
-		if self.success {
-			writeln!(stdout, "All deps seem to have been used.")?;
-		} else {
-			writeln!(stdout, "unused dependencies:")?;
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct UnanalyzedUnit {
+	package: PackageId,
+	/// The target, as described to users.
+	target: String,
+	/// Whether it failed to compile, rather than not being built as something it needs failed.
+	failed: bool,
+}
 
-			for (member, OutcomeUnusedDeps { normal, development, build, .. }) in &self.unused_deps {
-				fn edge_and_joint(p: bool) -> (char, char) {
-					if p {
-						(' ', '└')
-					} else {
-						('│', '├')
-					}
-				}
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct IgnoredUnused {
+	name: InternedString,
+	/// `normal`, `development` or `build`, like the lists of the unused dependencies.
+	kind: String,
+	/// Why it is not reported.
+	reason: String,
+}
 
-				writeln!(stdout, "`{}`", member)?;
-
-				for (deps, (edge, joint), prefix) in &[
-					(normal, edge_and_joint(development.is_empty() && build.is_empty()), ""),
-					(development, edge_and_joint(build.is_empty()), "dev-"),
-					(build, (' ', '└'), "build-"),
-				] {
-					if !deps.is_empty() {
-						writeln!(stdout, "{}─── {}dependencies", joint, prefix)?;
-						let mut deps = deps.iter().peekable();
-						while let Some(dep) = deps.next() {
-							let joint = if deps.peek().is_some() {
-								'├'
-							} else {
-								'└'
-							};
-							writeln!(stdout, "{}    {}─── {:?}", edge, joint, dep)?;
-						}
-					}
+impl IgnoredUnused {
+	fn new(kind: dependency::DepKind, name: InternedString, reason: String) -> Self {
+		let kind = match kind {
+			dependency::DepKind::Normal => "normal",
+			dependency::DepKind::Development => "development",
+			dependency::DepKind::Build => "build",
+		};
+		Self { name, kind : kind.to_owned(), reason }
+	}
+}
+
+/// An unused dependency with the packages that have it unused.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct DependencyGroup {
+	name: InternedString,
+	/// `normal`, `development` or `build`, like the lists of the unused dependencies.
+	kind: String,
+	/// The number of packages.
+	count: usize,
+	packages: Vec<PackageId>,
+}
+
+impl Outcome {
+	/// Groups the unused dependencies by name and kind, for `--group-by dependency`.
+	fn group_by_dependency(&mut self) {
+		let mut groups = BTreeMap::<_, Vec<PackageId>>::new();
+		for (&member, unused_deps) in &self.unused_deps {
+			for (deps, kind) in [(&unused_deps.normal, "normal"), (&unused_deps.development, "development"), (&unused_deps.build, "build")] {
+				for &dep in deps {
+					groups.entry((dep, kind)).or_default().push(member);
 				}
 			}
+		}
+		self.by_dependency = groups
+			.into_iter()
+			.map(|((name, kind), packages)| DependencyGroup { name, kind : kind.to_owned(), count : packages.len(), packages })
+			.collect();
+		// The most common first, as removing them cleans up the most, and by name otherwise
+		self.by_dependency.sort_by_key(|group| std::cmp::Reverse(group.count));
+	}
 
-			if let Some(note) = &self.note {
-				write!(stdout, "{}", note)?;
-			}
+	/// Runs the `--on-unused` command with the JSON report on stdin.
+	///
+	/// The report is also written to a file in the target directory, whose
+	/// path is given in `$CARGO_UDEPS_REPORT`. The output of the command is
+	/// only shown if it fails, which is just warned about so that it can't
+	/// hide the findings.
+	fn notify(&self, command: &str, ws: &Workspace<'_>) -> CargoResult<()> {
+		let mut report = Vec::new();
+		self.print_json(&mut report)?;
+		let report_path = ws.target_dir().into_path_unlocked().join("udeps-report.json");
+		cargo_util::paths::create_dir_all(report_path.parent().unwrap())?;
+		cargo_util::paths::write(&report_path, &report)?;
+
+		let mut process = if cfg!(windows) {
+			let mut process = ProcessBuilder::new("cmd");
+			process.arg("/C");
+			process
+		} else {
+			let mut process = ProcessBuilder::new("sh");
+			process.arg("-c");
+			process
+		};
+		process
+			.arg(command)
+			.cwd(ws.gctx().cwd())
+			.env("CARGO_UDEPS_REPORT", &report_path)
+			.stdin(report);
+		if let Err(e) = process.exec_with_output() {
+			ws.gctx().shell().warn(format!("the `--on-unused` command failed: {:#}", e))?;
 		}
-		stdout.flush()
+		Ok(())
 	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Provenance {
+	cargo_udeps: String,
+	/// The version of cargo `cargo-udeps` was built with.
+	cargo: String,
+	rustc: String,
+	rustc_commit: Option<String>,
+	backend: Backend,
+}
 
-	fn print_json(&self, mut stdout: impl Write) -> io::Result<()> {
-		let json = serde_json::to_string(self).expect("should not fail");
-		writeln!(stdout, "{}", json)?;
-		stdout.flush()
+impl Provenance {
+	fn new(rustc: &Rustc, backend: Backend) -> Self {
+		Self {
+			cargo_udeps : env!("CARGO_PKG_VERSION").to_owned(),
+			cargo : cargo::version().to_string(),
+			rustc : rustc.version.to_string(),
+			rustc_commit : rustc.commit_hash.clone(),
+			backend,
+		}
 	}
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct OutcomeUnusedDeps {
 	manifest_path: String,
 	normal: BTreeSet<InternedString>,
 	development: BTreeSet<InternedString>,
 	build: BTreeSet<InternedString>,
+	/// The registries of the unused dependencies that are not from crates.io.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	registries: BTreeMap<InternedString, String>,
+	/// The packages of the unused dependencies that are renamed with `package = "…"`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	renamed: BTreeMap<InternedString, InternedString>,
+	/// The unused dependencies whose policy is to only warn about them.
+	#[serde(default, skip_serializing_if = "WarnOnly::is_empty")]
+	warn_only: WarnOnly,
+	/// The dependencies that are only used with `cfg(doc)` or `cfg(docsrs)`, with `--doc-cfg`.
+	/// They are not among the unused ones and don't make the run fail.
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	doc_only: BTreeSet<InternedString>,
+	/// The normal dependencies that are only used by tests, benches and examples,
+	/// and could be dev-dependencies instead. They don't make the run fail either.
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	dev_only: BTreeSet<InternedString>,
+	/// The targets that use the dev-only dependencies, with `--show-unused-by-target`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	dev_only_targets: BTreeMap<InternedString, BTreeSet<String>>,
+	/// The `cfg`s or targets of the unused dependencies that are only declared for some platforms.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	platforms: BTreeMap<InternedString, String>,
+	/// The features that enable the unused dependencies that are optional.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	enabled_by: BTreeMap<InternedString, Vec<String>>,
+	/// The commits that added the unused dependencies, with `--blame`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	introduced: BTreeMap<InternedString, blame::Introduced>,
+	/// The team that owns the package.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	owner: Option<String>,
+	/// What the manifests of the unused dependencies say about them, with `--enrich`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	info: BTreeMap<InternedString, DependencyInfo>,
+}
+
+/// The unused dependencies that are only warned about, by kind, as a dependency
+/// may be declared as several kinds with different severities.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WarnOnly {
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	normal: BTreeSet<InternedString>,
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	development: BTreeSet<InternedString>,
+	#[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+	build: BTreeSet<InternedString>,
+}
+
+impl WarnOnly {
+	fn deps(&self, kind: dependency::DepKind) -> &BTreeSet<InternedString> {
+		match kind {
+			dependency::DepKind::Normal => &self.normal,
+			dependency::DepKind::Development => &self.development,
+			dependency::DepKind::Build => &self.build,
+		}
+	}
+
+	fn contains(&self, kind: dependency::DepKind, dep: &str) -> bool {
+		self.deps(kind).contains(dep)
+	}
+
+	fn insert(&mut self, kind: dependency::DepKind, dep: InternedString) {
+		match kind {
+			dependency::DepKind::Normal => &mut self.normal,
+			dependency::DepKind::Development => &mut self.development,
+			dependency::DepKind::Build => &mut self.build,
+		}.insert(dep);
+	}
+
+	fn is_empty(&self) -> bool {
+		self.normal.is_empty() && self.development.is_empty() && self.build.is_empty()
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DependencyInfo {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	description: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	repository: Option<String>,
 }
 
 impl OutcomeUnusedDeps {
@@ -1058,9 +3638,79 @@ impl OutcomeUnusedDeps {
 			normal: BTreeSet::new(),
 			development: BTreeSet::new(),
 			build: BTreeSet::new(),
+			registries: BTreeMap::new(),
+			renamed: BTreeMap::new(),
+			warn_only: WarnOnly::default(),
+			doc_only: BTreeSet::new(),
+			dev_only: BTreeSet::new(),
+			dev_only_targets: BTreeMap::new(),
+			platforms: BTreeMap::new(),
+			enabled_by: BTreeMap::new(),
+			introduced: BTreeMap::new(),
+			owner: None,
+			info: BTreeMap::new(),
 		})
 	}
 
+	/// Makes the manifest path relative to `root`, if it is inside of it.
+	fn relativize(&mut self, root: &Path) {
+		if let Ok(relative_path) = Path::new(&self.manifest_path).strip_prefix(root) {
+			self.manifest_path = relative_path.to_string_lossy().into_owned();
+		}
+	}
+
+	fn unused_deps(&self, kind: dependency::DepKind) -> &BTreeSet<InternedString> {
+		match kind {
+			dependency::DepKind::Normal => &self.normal,
+			dependency::DepKind::Development => &self.development,
+			dependency::DepKind::Build => &self.build,
+		}
+	}
+
+	/// Keeps only what is unused in `other` as well, for `merge` of reports that checked the same package.
+	fn intersect(&mut self, other: &Self) {
+		self.normal.retain(|dep| other.normal.contains(dep));
+		self.development.retain(|dep| other.development.contains(dep));
+		self.build.retain(|dep| other.build.contains(dep));
+		self.doc_only.retain(|dep| other.doc_only.contains(dep));
+		self.dev_only.retain(|dep| other.dev_only.contains(dep));
+		let listed = self.normal
+			.iter()
+			.chain(&self.development)
+			.chain(&self.build)
+			.chain(&self.doc_only)
+			.chain(&self.dev_only)
+			.copied()
+			.collect::<BTreeSet<_>>();
+		// Only warns if every report only warns about it
+		self.warn_only.normal.retain(|dep| other.warn_only.normal.contains(dep));
+		self.warn_only.development.retain(|dep| other.warn_only.development.contains(dep));
+		self.warn_only.build.retain(|dep| other.warn_only.build.contains(dep));
+		self.registries.retain(|dep, _| listed.contains(dep));
+		self.renamed.retain(|dep, _| listed.contains(dep));
+		self.dev_only_targets.retain(|dep, _| listed.contains(dep));
+		self.platforms.retain(|dep, _| listed.contains(dep));
+		self.enabled_by.retain(|dep, _| listed.contains(dep));
+		self.introduced.retain(|dep, _| listed.contains(dep));
+		self.info.retain(|dep, _| listed.contains(dep));
+	}
+
+	fn is_empty(&self) -> bool {
+		self.normal.is_empty()
+			&& self.development.is_empty()
+			&& self.build.is_empty()
+			&& self.doc_only.is_empty()
+			&& self.dev_only.is_empty()
+	}
+
+	/// The unused dependencies that make the run fail.
+	fn failing(&self) -> impl Iterator<Item = (dependency::DepKind, InternedString)> + '_ {
+		[dependency::DepKind::Normal, dependency::DepKind::Development, dependency::DepKind::Build]
+			.into_iter()
+			.flat_map(move |kind| self.unused_deps(kind).iter().map(move |&dep| (kind, dep)))
+			.filter(|&(kind, dep)| !self.warn_only.contains(kind, &dep))
+	}
+
 	fn unused_deps_mut(&mut self, kind: dependency::DepKind) -> &mut BTreeSet<InternedString> {
 		match kind {
 			dependency::DepKind::Normal => &mut self.normal,
@@ -1070,10 +3720,19 @@ impl OutcomeUnusedDeps {
 	}
 }
 
-#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum OutputKind {
 	Human,
 	Json,
+	Diagnostic,
+	Sarif,
+	/// The markdown of the job summary, to paste into pull requests
+	Markdown,
+	/// A line with the number of unused dependencies, for scripts
+	Count,
+	/// A standalone page, for the artifacts of CI jobs
+	Html,
 }
 
 impl FromStr for OutputKind {
@@ -1083,14 +3742,38 @@ impl FromStr for OutputKind {
 		match s {
 			"human" => Ok(Self::Human),
 			"json" => Ok(Self::Json),
-			_ => Err(r#"expected "human" or "json" (you should not see this message)"#),
+			"diagnostic" => Ok(Self::Diagnostic),
+			"sarif" => Ok(Self::Sarif),
+			"markdown" => Ok(Self::Markdown),
+			"count" => Ok(Self::Count),
+			"html" => Ok(Self::Html),
+			_ => Err(r#"expected "human", "json", "diagnostic", "sarif", "markdown", "count" or "html" (you should not see this message)"#),
 		}
 	}
 }
 
-#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum PathStyle {
+	/// Relative to the workspace root, so that reports don't depend on where it is checked out
+	Relative,
+	Absolute,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum GroupBy {
+	/// Each package with its unused dependencies
+	Package,
+	/// Each unused dependency with the packages that have it, most common first
+	Dependency,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 enum Backend {
+	/// The crates in the `.d` files of `-Z binary-dep-depinfo`, which needs a nightly rustc.
 	Depinfo,
+	/// The `unused_crate_dependencies` lint of rustc, which works with any toolchain.
+	Lint,
 }
 
 impl FromStr for Backend {
@@ -1099,13 +3782,16 @@ impl FromStr for Backend {
 	fn from_str(s: &str) -> std::result::Result<Self, &'static str> {
 		match s {
 			"depinfo" => Ok(Self::Depinfo),
-			_ => Err(r#"expected "depinfo" (you should not see this message)"#),
+			"lint" => Ok(Self::Lint),
+			_ => Err(r#"expected "depinfo" or "lint" (you should not see this message)"#),
 		}
 	}
 }
 
 trait ShellExt {
 	fn info<T: fmt::Display>(&mut self, message: T) -> CargoResult<()>;
+	/// Like [`ShellExt::info`], but only with `--verbose`, for the details of every unit.
+	fn verbose_info<T: fmt::Display>(&mut self, message: T) -> CargoResult<()>;
 }
 
 impl ShellExt for Shell {
@@ -1126,4 +3812,11 @@ impl ShellExt for Shell {
 			)
 		}
 	}
+
+	fn verbose_info<T: fmt::Display>(&mut self, message: T) -> CargoResult<()> {
+		match self.verbosity() {
+			Verbosity::Verbose => self.info(message),
+			_ => Ok(()),
+		}
+	}
 }
@@ -1,11 +1,65 @@
+use std::backtrace::Backtrace;
+use std::ffi::OsString;
 use std::{env, io};
+use std::process::Command;
 
 use cargo::core::shell::Shell;
 
 fn main() {
+	let args = env::args_os().collect::<Vec<_>>();
+	if very_verbose(&args) {
+		enable_backtraces();
+	}
+	use_cargo_of_rustup_toolchain();
 	let mut config = cargo::util::context::GlobalContext::default()
 		.unwrap_or_else(|e| cargo::exit_with_error(e.into(), &mut Shell::new()));
-	if let Err(err) = cargo_udeps::run(env::args_os(), &mut config, io::stdout()) {
+	if let Err(err) = cargo_udeps::run(args, &mut config, io::stdout()) {
 		cargo::exit_with_error(err, &mut config.shell());
 	}
 }
+
+/// Makes build scripts and the like get the cargo of the toolchain chosen with
+/// `$RUSTUP_TOOLCHAIN` (like with `cargo +nightly udeps`) as `$CARGO`, if it isn't set already.
+/// Otherwise, cargo would point them to the current executable, `cargo-udeps`.
+///
+/// This is done before the context is created, which only reads the environment once.
+fn use_cargo_of_rustup_toolchain() {
+	if env::var_os(cargo::CARGO_ENV).is_some() || env::var_os("RUSTUP_TOOLCHAIN").is_none() {
+		return;
+	}
+	// `rustup which` honors `$RUSTUP_TOOLCHAIN`
+	let output = match Command::new("rustup").args(["which", "cargo"]).output() {
+		Ok(output) if output.status.success() => output,
+		_ => return,
+	};
+	if let Ok(cargo) = String::from_utf8(output.stdout) {
+		env::set_var(cargo::CARGO_ENV, cargo.trim());
+	}
+}
+
+/// Whether `-vv` is given, looked for before the arguments are parsed.
+fn very_verbose(args :&[OsString]) -> bool {
+	let verbose = args.iter()
+		.skip(1)
+		.map_while(|arg| arg.to_str().filter(|&arg| arg != "--"))
+		.map(|arg| match arg.strip_prefix('-') {
+			Some("-verbose") => 1,
+			Some(flags) if flags.bytes().all(|flag| flag == b'v') => flags.len(),
+			_ => 0,
+		})
+		.sum::<usize>();
+	verbose >= 2
+}
+
+/// Makes errors capture a backtrace, so that `-vv` can show it.
+///
+/// This has to happen before the context is created, as the first error decides it for the
+/// whole process. The variable is removed again, so that rustc and build scripts don't get it.
+fn enable_backtraces() {
+	if env::var_os("RUST_BACKTRACE").is_some() || env::var_os("RUST_LIB_BACKTRACE").is_some() {
+		return;
+	}
+	env::set_var("RUST_LIB_BACKTRACE", "1");
+	let _ = Backtrace::capture();
+	env::remove_var("RUST_LIB_BACKTRACE");
+}
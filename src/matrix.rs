@@ -0,0 +1,205 @@
+//! Advice on the configurations to run in CI, for `cargo udeps ci-matrix`.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+
+use cargo::core::compiler::{CompileKind, CompileTarget, RustcTargetData};
+use cargo::core::dependency::DepKind;
+use cargo::core::{Dependency, FeatureValue, Package, Workspace};
+use cargo::util::interning::InternedString;
+use cargo::CargoResult;
+
+/// The targets that are tried for platform specific dependencies, besides the host
+/// and the ones passed with `--candidate`.
+const CANDIDATES :&[&str] = &[
+	"x86_64-unknown-linux-gnu",
+	"aarch64-unknown-linux-gnu",
+	"x86_64-pc-windows-msvc",
+	"i686-pc-windows-msvc",
+	"x86_64-apple-darwin",
+	"aarch64-apple-darwin",
+	"aarch64-linux-android",
+	"aarch64-apple-ios",
+	"x86_64-unknown-freebsd",
+	"wasm32-unknown-unknown",
+	"wasm32-wasip1",
+];
+
+/// A declaration of a dependency that only some configurations check.
+struct Conditional {
+	member :InternedString,
+	name :InternedString,
+	/// The `cfg` or target it is declared for.
+	platform :String,
+	/// The candidates it is activated on.
+	targets :BTreeSet<usize>,
+}
+
+/// Prints the fewest `--target`s, with the `--features` that enable all optional
+/// dependencies, that together check every declared dependency of the members.
+pub(crate) fn print(ws :&Workspace<'_>, extra_candidates :&[String], mut stdout :impl Write) -> CargoResult<()> {
+	let mut shell = ws.gctx().shell();
+	let host_data = RustcTargetData::new(ws, &[CompileKind::Host])?;
+	let host = host_data.rustc.host.to_string();
+
+	let mut names = vec![host.clone()];
+	for candidate in extra_candidates.iter().map(String::as_str).chain(CANDIDATES.iter().copied()) {
+		if !names.iter().any(|name| name == candidate) {
+			names.push(candidate.to_owned());
+		}
+	}
+	// Targets this rustc doesn't know are skipped
+	let mut targets = Vec::new();
+	for name in names {
+		let kind = if name == host { CompileKind::Host } else { CompileKind::Target(CompileTarget::new(&name)?) };
+		match RustcTargetData::new(ws, &[kind]) {
+			Ok(data) => targets.push((name, kind, data)),
+			Err(e) => shell.warn(format!("skipping the target `{}`: {}", name, e))?,
+		}
+	}
+	drop(shell);
+
+	let mut features = BTreeSet::new();
+	let mut conditionals = Vec::new();
+	let mut host_only = Vec::new();
+	for member in ws.members() {
+		let enabled_by_default = enabled_deps(member, "default");
+		for dep in member.dependencies() {
+			if dep.is_optional() && !enabled_by_default.contains(&dep.name_in_toml()) {
+				match enabling_feature(member, dep.name_in_toml()) {
+					Some(feature) => { features.insert(format!("{}/{}", member.name(), feature)); },
+					None => ws.gctx().shell().warn(format!(
+						"no feature of `{}` enables the optional dependency `{}`",
+						member.name(), dep.name_in_toml(),
+					))?,
+				}
+			}
+			let platform = match dep.platform() {
+				Some(platform) => platform.to_string(),
+				None => continue,
+			};
+			if dep.kind() == DepKind::Build {
+				// The platforms of build dependencies are matched against the host, whatever the `--target`
+				if !host_data.dep_platform_activated(dep, CompileKind::Host) {
+					host_only.push(describe(member, dep, &platform));
+				}
+				continue;
+			}
+			conditionals.push(Conditional {
+				member : member.name(),
+				name : dep.name_in_toml(),
+				platform,
+				targets : targets
+					.iter()
+					.enumerate()
+					.filter(|(_, (_, kind, data))| data.dep_platform_activated(dep, *kind))
+					.map(|(i, _)| i)
+					.collect(),
+			});
+		}
+	}
+
+	// Greedily, which is good enough for the handful of platforms of real workspaces
+	let mut runs :Vec<(usize, Vec<&Conditional>)> = vec![(0, Vec::new())];
+	let mut uncovered = conditionals.iter().filter(|c| !c.targets.is_empty()).collect::<Vec<_>>();
+	let unreachable = conditionals.iter().filter(|c| c.targets.is_empty()).collect::<Vec<_>>();
+	loop {
+		let (covered, rest) :(Vec<_>, Vec<_>) = uncovered.into_iter().partition(|c| {
+			runs.iter().any(|(target, _)| c.targets.contains(target))
+		});
+		for conditional in covered {
+			let run = runs.iter_mut().find(|(target, _)| conditional.targets.contains(target)).unwrap();
+			run.1.push(conditional);
+		}
+		uncovered = rest;
+		if uncovered.is_empty() {
+			break;
+		}
+		let best = (0..targets.len())
+			.max_by_key(|&target| {
+				let count = uncovered.iter().filter(|c| c.targets.contains(&target)).count();
+				// The first candidate wins a tie
+				(count, std::cmp::Reverse(target))
+			})
+			.expect("the host is a candidate");
+		runs.push((best, Vec::new()));
+	}
+
+	let features = features.into_iter().collect::<Vec<_>>();
+	let s = if runs.len() == 1 { "" } else { "s" };
+	writeln!(stdout, "# Every declared dependency is checked by {} run{}:", runs.len(), s)?;
+	for (target, covered) in &runs {
+		let mut command = "cargo +nightly udeps --workspace --all-targets".to_owned();
+		if *target != 0 {
+			command += &format!(" --target {}", targets[*target].0);
+		}
+		if !features.is_empty() {
+			command += &format!(" --features {}", features.join(","));
+		}
+		writeln!(stdout, "{}", command)?;
+		let mut by_member = BTreeMap::new();
+		for conditional in covered {
+			by_member.entry(conditional.member).or_insert_with(Vec::new).push(conditional);
+		}
+		for (member, conditionals) in by_member {
+			let deps = conditionals
+				.iter()
+				.map(|c| format!("`{}` ({})", c.name, c.platform))
+				.collect::<Vec<_>>();
+			writeln!(stdout, "#   {}: {}", member, deps.join(", "))?;
+		}
+	}
+	for conditional in unreachable {
+		ws.gctx().shell().warn(format!(
+			"none of the targets checks `{}` of `{}`, declared for `{}`, pass a `--candidate` that does",
+			conditional.name, conditional.member, conditional.platform,
+		))?;
+	}
+	for description in host_only {
+		ws.gctx().shell().warn(format!("{} can only be checked on a host it is declared for", description))?;
+	}
+	stdout.flush()?;
+	Ok(())
+}
+
+fn describe(member :&Package, dep :&Dependency, platform :&str) -> String {
+	format!("the build dependency `{}` of `{}`, declared for `{}`,", dep.name_in_toml(), member.name(), platform)
+}
+
+/// The optional dependencies the feature enables, also through other features.
+fn enabled_deps(package :&Package, feature :&str) -> HashSet<InternedString> {
+	let feature_map = package.summary().features();
+	let mut deps = HashSet::new();
+	let mut seen = HashSet::new();
+	let mut stack = vec![InternedString::new(feature)];
+	while let Some(feature) = stack.pop() {
+		if !seen.insert(feature) {
+			continue;
+		}
+		for value in feature_map.get(&feature).into_iter().flatten() {
+			match value {
+				FeatureValue::Feature(feature) => stack.push(*feature),
+				FeatureValue::Dep { dep_name } => { deps.insert(*dep_name); },
+				FeatureValue::DepFeature { dep_name, weak : false, .. } => {
+					deps.insert(*dep_name);
+					// `dep/feature` also enables the feature of the same name, if there is one
+					stack.push(*dep_name);
+				},
+				FeatureValue::DepFeature { weak : true, .. } => (),
+			}
+		}
+	}
+	deps
+}
+
+/// A feature that enables the optional dependency, preferring its implicit feature.
+fn enabling_feature(package :&Package, dep_name :InternedString) -> Option<InternedString> {
+	let feature_map = package.summary().features();
+	if feature_map.contains_key(&dep_name) && enabled_deps(package, &dep_name).contains(&dep_name) {
+		return Some(dep_name);
+	}
+	feature_map
+		.keys()
+		.copied()
+		.find(|feature| enabled_deps(package, feature).contains(&dep_name))
+}
@@ -0,0 +1,33 @@
+//! Opt-outs in the sources of the packages, for dependencies that are kept on purpose.
+
+use std::path::PathBuf;
+
+use cargo::core::dependency::DepKind;
+use cargo::core::Package;
+
+/// The root of a target of `package` that can use dependencies of `kind` and whose crate
+/// attributes allow or expect the `unused_crate_dependencies` lint of rustc, relative to
+/// the package. Its author keeps such dependencies on purpose then.
+///
+/// Only attributes on a line of their own are found, like `#![allow(unused_crate_dependencies)]`
+/// or `#![expect(unused, unused_crate_dependencies)]`, and not those of `cfg_attr`.
+pub(crate) fn allowing_root(package :&Package, kind :DepKind) -> Option<PathBuf> {
+	package
+		.targets()
+		.iter()
+		.filter(|target| match kind {
+			DepKind::Normal => target.is_lib() || target.is_bin(),
+			DepKind::Development => target.is_test() || target.is_bench() || target.is_example(),
+			DepKind::Build => target.is_custom_build(),
+		})
+		.filter_map(|target| target.src_path().path())
+		.find(|path| std::fs::read_to_string(path).is_ok_and(|source| allows(&source)))
+		.map(|path| path.strip_prefix(package.root()).unwrap_or(path).to_owned())
+}
+
+/// Whether the crate attributes of `source` allow or expect `unused_crate_dependencies`.
+fn allows(source :&str) -> bool {
+	source.lines().map(str::trim).any(|line| {
+		(line.starts_with("#![allow(") || line.starts_with("#![expect(")) && line.contains("unused_crate_dependencies")
+	})
+}
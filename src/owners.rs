@@ -0,0 +1,85 @@
+//! Attribution of packages to the teams that own them.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use cargo::CargoResult;
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+
+#[derive(Debug, Default)]
+pub(crate) struct Owners {
+	/// Package name patterns with their team, the longest matching pattern wins.
+	by_name :Vec<(String, GlobMatcher, String)>,
+	/// The rules of a `CODEOWNERS` file, the last matching one wins.
+	codeowners :Vec<(GlobSet, Option<String>)>,
+}
+
+impl Owners {
+	/// `teams` maps team names to package name patterns, `codeowners` is
+	/// the path of a `CODEOWNERS` file in `root`, if any.
+	pub(crate) fn new(teams :&BTreeMap<String, Vec<String>>, codeowners :Option<&Path>, root :&Path) -> CargoResult<Self> {
+		let mut by_name = Vec::new();
+		for (team, patterns) in teams {
+			for pattern in patterns {
+				let matcher = Glob::new(pattern)
+					.map_err(|e| anyhow::anyhow!("invalid package name pattern `{}`: {}", pattern, e))?
+					.compile_matcher();
+				by_name.push((pattern.clone(), matcher, team.clone()));
+			}
+		}
+		let codeowners = match codeowners {
+			Some(path) => parse_codeowners(&cargo_util::paths::read(&root.join(path))?)?,
+			None => Vec::new(),
+		};
+		Ok(Self { by_name, codeowners })
+	}
+
+	/// The owner of the package with the given name, whose manifest is at
+	/// `manifest_path` relative to the root of the workspace.
+	pub(crate) fn owner_of(&self, name :&str, manifest_path :&Path) -> Option<String> {
+		let by_name = self.by_name
+			.iter()
+			.filter(|(_, matcher, _)| matcher.is_match(name))
+			.max_by_key(|(pattern, _, _)| pattern.len())
+			.map(|(_, _, team)| team.clone());
+		by_name.or_else(|| {
+			self.codeowners
+				.iter()
+				.rev()
+				.find(|(set, _)| set.is_match(manifest_path))
+				.and_then(|(_, owner)| owner.clone())
+		})
+	}
+}
+
+/// Parses the lines of a `CODEOWNERS` file, keeping the first owner of each.
+///
+/// Lines without owners take the ownership of the matching paths away.
+fn parse_codeowners(contents :&str) -> CargoResult<Vec<(GlobSet, Option<String>)>> {
+	let mut rules = Vec::new();
+	for line in contents.lines() {
+		let mut words = line.split('#').next().unwrap_or_default().split_whitespace();
+		let pattern = match words.next() {
+			Some(pattern) => pattern,
+			None => continue,
+		};
+		let owner = words.next().map(str::to_owned);
+		// Like in `.gitignore`, patterns that don't start with a `/` match in every directory,
+		// and everything in a matching directory is matched as well
+		let pattern = match pattern.strip_prefix('/') {
+			Some(anchored) => anchored.to_owned(),
+			None => format!("**/{}", pattern),
+		};
+		let pattern = pattern.trim_end_matches('/');
+		let mut set = GlobSetBuilder::new();
+		for glob in [pattern.to_owned(), format!("{}/**", pattern)] {
+			let glob = GlobBuilder::new(&glob)
+				.literal_separator(true)
+				.build()
+				.map_err(|e| anyhow::anyhow!("invalid `CODEOWNERS` pattern `{}`: {}", pattern, e))?;
+			set.add(glob);
+		}
+		rules.push((set.build()?, owner));
+	}
+	Ok(rules)
+}
@@ -0,0 +1,55 @@
+//! Entries of `[patch]` and `[replace]` that have no effect, for `--check-patches`.
+
+use cargo::core::{PackageId, PackageIdSpecQuery, Resolve, Workspace};
+use cargo::CargoResult;
+use cargo::sources::CRATES_IO_INDEX;
+use serde::{Deserialize, Serialize};
+
+/// An entry of `[patch]` or `[replace]` that the resolver didn't use.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub(crate) struct UnusedPatch {
+	/// The table of the entry, like `patch.crates-io` or `replace`.
+	pub(crate) table :String,
+	/// The key of the entry, like `foo` or, in `[replace]`, `foo:1.0.0`.
+	pub(crate) key :String,
+	/// The package a patch points at.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub(crate) package :Option<PackageId>,
+}
+
+/// The patches of the workspace that no dependency was resolved to, like ones of
+/// crates nothing depends on anymore or with versions the requirements don't match,
+/// and the replacements that match no package of the graph.
+pub(crate) fn find(ws :&Workspace<'_>, resolve :&Resolve) -> CargoResult<Vec<UnusedPatch>> {
+	let root_patch = ws.root_patch()?;
+	let mut unused = Vec::new();
+	for &package in resolve.unused_patches() {
+		let entry = root_patch.iter().find_map(|(url, deps)| {
+			deps.iter()
+				.find(|dep| {
+					dep.package_name() == package.name()
+						&& dep.source_id().canonical_url() == package.source_id().canonical_url()
+				})
+				.map(|dep| (url, dep.name_in_toml()))
+		});
+		let (table, key) = match entry {
+			Some((url, key)) if url.as_str() == CRATES_IO_INDEX => ("patch.crates-io".to_owned(), key),
+			Some((url, key)) => (format!("patch.\"{}\"", url), key),
+			None => ("patch".to_owned(), package.name()),
+		};
+		unused.push(UnusedPatch { table, key : key.to_string(), package : Some(package) });
+	}
+	for (spec, _) in ws.root_replace() {
+		if resolve.replacements().keys().any(|&id| spec.matches(id)) {
+			continue;
+		}
+		// Keys of crates.io are written like `foo:1.0.0`
+		let key = match (spec.url(), spec.partial_version()) {
+			(Some(url), Some(version)) if url.as_str() == CRATES_IO_INDEX => format!("{}:{}", spec.name(), version),
+			_ => spec.to_string(),
+		};
+		unused.push(UnusedPatch { table : "replace".to_owned(), key, package : None });
+	}
+	unused.sort();
+	Ok(unused)
+}
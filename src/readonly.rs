@@ -0,0 +1,56 @@
+//! Running from checkouts that can't be written to, like in build farms or Nix sandboxes.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use cargo::core::Workspace;
+use cargo::util::Filesystem;
+use cargo::CargoResult;
+
+use crate::ShellExt;
+
+/// Keeps the run from writing into the workspace if it isn't writable.
+///
+/// The target directory is moved to the user's cache directory, and if there is no
+/// `Cargo.lock` that could be read, the one that would be written is skipped.
+pub(crate) fn avoid_writes(ws :&mut Workspace<'_>) -> CargoResult<()> {
+	let target_dir = ws.target_dir().into_path_unlocked();
+	if !is_writable(&target_dir) {
+		let fallback = cache_dir()
+			.join("cargo-udeps")
+			.join(cargo::util::hex::short_hash(&ws.root()));
+		ws.gctx().shell().info(format_args!(
+			"`{}` is not writable, building in `{}` instead",
+			target_dir.display(),
+			fallback.display(),
+		))?;
+		ws.set_target_dir(Filesystem::new(fallback));
+	}
+	if !ws.root().join("Cargo.lock").exists() && !is_writable(ws.root()) {
+		ws.gctx().shell().info(format_args!(
+			"`{}` is not writable, not writing a `Cargo.lock`",
+			ws.root().display(),
+		))?;
+		ws.set_ignore_lock(true);
+	}
+	Ok(())
+}
+
+/// Whether files can be created in `dir`, or in its closest existing ancestor.
+fn is_writable(dir :&Path) -> bool {
+	match dir.ancestors().find(|dir| dir.exists()) {
+		Some(dir) => tempfile::tempfile_in(dir).is_ok(),
+		None => false,
+	}
+}
+
+fn cache_dir() -> PathBuf {
+	let dir = if cfg!(windows) {
+		env::var_os("LOCALAPPDATA").map(PathBuf::from)
+	} else {
+		env::var_os("XDG_CACHE_HOME")
+			.map(PathBuf::from)
+			.or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+	};
+	dir.filter(|dir| is_writable(dir)).unwrap_or_else(env::temp_dir)
+}
@@ -0,0 +1,55 @@
+//! Translation of paths between the place a build happened in and the local tree.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use cargo::CargoResult;
+
+/// A list of `FROM=TO` path prefix replacements, the first matching one wins.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PathRemap {
+	prefixes :Vec<(PathBuf, PathBuf)>,
+}
+
+impl PathRemap {
+	/// Parses `FROM=TO` pairs.
+	pub(crate) fn parse<'a>(pairs :impl IntoIterator<Item = &'a String>) -> CargoResult<Self> {
+		let prefixes = pairs
+			.into_iter()
+			.map(|pair| match pair.split_once('=') {
+				Some((from, to)) => Ok((from.into(), to.into())),
+				None => Err(anyhow::anyhow!("expected `FROM=TO`, found `{}`", pair)),
+			})
+			.collect::<CargoResult<_>>()?;
+		Ok(Self { prefixes })
+	}
+
+	pub(crate) fn extend(&mut self, other :Self) {
+		self.prefixes.extend(other.prefixes);
+	}
+
+	/// The remapping in the other direction.
+	pub(crate) fn inverse(&self) -> Self {
+		let prefixes = self.prefixes.iter().map(|(from, to)| (to.clone(), from.clone())).collect();
+		Self { prefixes }
+	}
+
+	pub(crate) fn path(&self, path :&Path) -> PathBuf {
+		self.prefixes
+			.iter()
+			.find_map(|(from, to)| Some(to.join(path.strip_prefix(from).ok()?)))
+			.unwrap_or_else(|| path.to_owned())
+	}
+
+	/// Remaps a path, or the path part of arguments like `--out-dir=PATH` or `dependency=PATH`.
+	pub(crate) fn arg(&self, arg :&OsStr) -> OsString {
+		let arg_str = match arg.to_str() {
+			Some(arg) => arg,
+			None => return arg.to_owned(),
+		};
+		match arg_str.split_once('=') {
+			Some((key, path)) => format!("{}={}", key, self.path(Path::new(path)).display()).into(),
+			None => self.path(Path::new(arg)).into(),
+		}
+	}
+}
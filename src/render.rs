@@ -0,0 +1,414 @@
+//! The formats the findings are printed in, picked with `--output` and the other output options.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::Path;
+
+use cargo::core::dependency;
+use cargo::core::package_id::PackageId;
+use nu_ansi_term::{Color, Style};
+
+use crate::{
+	diagnostic, duplicates, html, patches, sarif, summary, template, wrap,
+	DependencyGroup, IgnoredUnused, Outcome, OutcomeUnusedDeps, OutputKind, TargetUnused, UnanalyzedUnit,
+};
+
+/// How the human output is laid out.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Layout {
+	/// The width lines are wrapped to.
+	pub(crate) width: Option<usize>,
+	/// Whether the unused dependencies by target are listed as well.
+	pub(crate) verbose: bool,
+	pub(crate) color: bool,
+}
+
+/// What the report is asked for with the output options.
+pub(crate) struct Format<'a> {
+	pub(crate) output: OutputKind,
+	pub(crate) template: Option<&'a template::Template>,
+	/// With `--print-unused-names`, whether the names are prefixed with the names of their packages.
+	pub(crate) names: Option<bool>,
+	/// `--message-format short`.
+	pub(crate) short: bool,
+	/// Whether only the report is asked for, with `--quiet`.
+	pub(crate) quiet: bool,
+	pub(crate) layout: Layout,
+}
+
+impl Outcome {
+	/// Prints the report in the format that is asked for.
+	pub(crate) fn print_report(&self, format: &Format<'_>, root: &Path, cwd: &Path, stdout: impl Write) -> io::Result<()> {
+		if let Some(with_package) = format.names {
+			return self.print_names(with_package, stdout);
+		}
+		match (format.template, format.output) {
+			(Some(template), OutputKind::Human) => self.print_template(template, stdout),
+			// Nothing to report, and only the report is asked for
+			(None, OutputKind::Human) if format.quiet
+				&& self.unused_deps.is_empty()
+				&& self.unused_members.is_empty()
+				&& self.unused_features.is_empty()
+				&& self.duplicates.is_empty()
+				&& self.unused_patches.is_empty()
+				&& self.ignored.is_empty()
+				&& self.unanalyzed_units.is_empty() => Ok(()),
+			(None, OutputKind::Human) if format.short => self.print_short(stdout),
+			_ => self.print(format.output, format.layout, root, cwd, stdout),
+		}
+	}
+
+	fn print(&self, output: OutputKind, layout: Layout, root: &Path, cwd: &Path, stdout: impl Write) -> io::Result<()> {
+		match output {
+			OutputKind::Human => self.print_human(layout, root, cwd, stdout),
+			OutputKind::Json => self.print_json(stdout),
+			OutputKind::Diagnostic => diagnostic::print(self, root, cwd, stdout),
+			OutputKind::Sarif => sarif::print(self, root, stdout),
+			OutputKind::Markdown => summary::print(self, stdout),
+			OutputKind::Count => self.print_count(stdout),
+			OutputKind::Html => html::print(self, root, stdout),
+		}
+	}
+
+	/// Prints a line like `3 unused across 2 packages`, for `--output count`.
+	fn print_count(&self, mut stdout: impl Write) -> io::Result<()> {
+		let counts = self.unused_deps
+			.values()
+			.map(|deps| deps.normal.len() + deps.development.len() + deps.build.len())
+			.filter(|&count| count > 0)
+			.collect::<Vec<_>>();
+		let packages = if counts.len() == 1 { "package" } else { "packages" };
+		writeln!(stdout, "{} unused across {} {}", counts.iter().sum::<usize>(), counts.len(), packages)?;
+		stdout.flush()
+	}
+
+	/// Relative manifest paths are relative to `root`, and shown relative to `cwd` if possible.
+	///
+	/// With `verbose`, the dependencies that are unused with only one of several `--target`s are listed as well.
+	fn print_human(&self, layout: Layout, root: &Path, cwd: &Path, mut stdout: impl Write) -> io::Result<()> {
+		let Layout { width, verbose, color } = layout;
+		let paint = |style: Style, text: &str| if color {
+			style.paint(text).to_string()
+		} else {
+			text.to_owned()
+		};
+		let heading = |text: &str| paint(Style::new().bold(), text);
+		// Only the name, as the path of a package may be shortened when wrapping
+		let package = |id: &PackageId| format!("{}{}", paint(Style::new().bold(), &id.name()), &id.to_string()[id.name().len()..]);
+		if self.unused_deps.is_empty() {
+			writeln!(stdout, "{}", paint(Color::Green.normal(), "All deps seem to have been used."))?;
+		} else if !self.by_dependency.is_empty() {
+			writeln!(stdout, "{}", heading("unused dependencies by dependency:"))?;
+			for DependencyGroup { name, kind, count, packages } in &self.by_dependency {
+				let s = if *count == 1 { "" } else { "s" };
+				let line = format!("{} ({}), in {} package{}", paint(Color::Red.bold(), &format!("{:?}", name)), kind, count, s);
+				wrap::write_wrapped(&mut stdout, width, "", "  ", &line)?;
+				let mut packages = packages.iter().peekable();
+				while let Some(member) = packages.next() {
+					let (joint, continuation) = if packages.peek().is_some() {
+						('├', '│')
+					} else {
+						('└', ' ')
+					};
+					wrap::write_wrapped(
+						&mut stdout,
+						width,
+						&format!("{}─── ", joint),
+						&format!("{}     ", continuation),
+						&format!("`{}`", package(member)),
+					)?;
+				}
+			}
+
+			if let Some(note) = &self.note {
+				wrap::write_note(&mut stdout, width, note)?;
+			}
+		} else {
+			writeln!(stdout, "{}", heading("unused dependencies:"))?;
+
+			for (member, OutcomeUnusedDeps { manifest_path, normal, development, build, registries, renamed, warn_only, doc_only, dev_only, dev_only_targets, platforms, enabled_by, introduced, owner, info }) in &self.unused_deps {
+				fn edge_and_joint(p: bool) -> (char, char) {
+					if p {
+						(' ', '└')
+					} else {
+						('│', '├')
+					}
+				}
+
+				let mut line = format!("`{}`", package(member));
+				if let Some(owner) = owner {
+					line += &format!(" (owned by {})", owner);
+				}
+				wrap::write_wrapped(&mut stdout, width, "", "  ", &line)?;
+
+				// The lines the dependencies are declared on, so that editors can link them
+				let (manifest_path, shown_path) = diagnostic::manifest_paths(manifest_path, root, cwd);
+				let contents = std::fs::read_to_string(manifest_path).ok();
+				let doc = contents.as_deref().and_then(|contents| toml_edit::ImDocument::parse(contents).ok());
+				let location = |kinds: &[dependency::DepKind], dep: &str| {
+					let (contents, doc) = (contents.as_deref()?, doc.as_ref()?);
+					let line = kinds.iter().find_map(|&kind| diagnostic::declaration_line(contents, doc, kind, dep))?;
+					Some(format!("{}:{}", shown_path.display(), line))
+				};
+
+				use dependency::DepKind::{Build, Development, Normal};
+				for (deps, (edge, joint), prefix, kinds) in &[
+					(normal, edge_and_joint(development.is_empty() && build.is_empty() && doc_only.is_empty() && dev_only.is_empty()), "", &[Normal][..]),
+					(development, edge_and_joint(build.is_empty() && doc_only.is_empty() && dev_only.is_empty()), "dev-", &[Development]),
+					(build, edge_and_joint(doc_only.is_empty() && dev_only.is_empty()), "build-", &[Build]),
+					(doc_only, edge_and_joint(dev_only.is_empty()), "doc-only ", &[Normal, Development, Build]),
+					(dev_only, (' ', '└'), "dev-only ", &[Normal]),
+				] {
+					if !deps.is_empty() {
+						writeln!(stdout, "{}─── {}", joint, paint(Color::Cyan.normal(), &format!("{}dependencies", prefix)))?;
+						let mut deps = deps.iter().peekable();
+						while let Some(dep) = deps.next() {
+							let (joint, continuation) = if deps.peek().is_some() {
+								('├', '│')
+							} else {
+								('└', ' ')
+							};
+							let warns = kinds.iter().any(|&kind| warn_only.contains(kind, dep));
+							let style = if warns {
+								Color::Yellow.bold()
+							} else {
+								Color::Red.bold()
+							};
+							let mut line = paint(style, &format!("{:?}", dep));
+							if let Some(location) = location(kinds, dep) {
+								line += &format!(" ({})", location);
+							}
+							if let Some(package_name) = renamed.get(dep) {
+								line += &format!(" (package `{}`)", package_name);
+							}
+							if let Some(registry) = registries.get(dep) {
+								line += &format!(" (registry `{}`)", registry);
+							}
+							if let Some(platforms) = platforms.get(dep) {
+								line += &format!(" (only for {})", platforms);
+							}
+							if let Some(features) = enabled_by.get(dep) {
+								let s = if features.len() == 1 { "" } else { "s" };
+								let features = features.iter().map(|f| format!("`{}`", f)).collect::<Vec<_>>();
+								line += &format!(" (enabled by feature{} {})", s, features.join(", "));
+							}
+							if let Some(targets) = dev_only_targets.get(dep) {
+								let targets = targets.iter().map(String::as_str).collect::<Vec<_>>();
+								line += &format!(" (used by {})", targets.join(", "));
+							}
+							if warns {
+								line += " (warning only)";
+							}
+							if let Some(introduced) = introduced.get(dep) {
+								line += &format!(
+									" (added {} by {} in {:.7})",
+									introduced.date, introduced.author, introduced.commit,
+								);
+							}
+							if let Some(info) = info.get(dep) {
+								if let Some(description) = &info.description {
+									line += &format!(" - {}", description);
+								}
+								if let Some(repository) = &info.repository {
+									line += &format!(" <{}>", repository);
+								}
+							}
+							wrap::write_wrapped(
+								&mut stdout,
+								width,
+								&format!("{}    {}─── ", edge, joint),
+								&format!("{}    {}     ", edge, continuation),
+								&line,
+							)?;
+						}
+					}
+				}
+			}
+
+			if self.unused_deps.values().any(|unused_deps| unused_deps.owner.is_some()) {
+				writeln!(stdout, "{}", heading("unused dependencies by owner:"))?;
+				for (owner, count) in self.count_by_owner() {
+					let line = format!("{}: {}", owner.unwrap_or("(no owner)"), count);
+					wrap::write_wrapped(&mut stdout, width, "  ", "    ", &line)?;
+				}
+			}
+
+			if let Some(note) = &self.note {
+				wrap::write_note(&mut stdout, width, note)?;
+			}
+		}
+		if !self.unused_members.is_empty() {
+			writeln!(stdout, "{}", heading("workspace members that nothing depends on:"))?;
+			for member in &self.unused_members {
+				writeln!(stdout, "  `{} v{}`", member.name(), member.version())?;
+			}
+		}
+		if !self.unused_features.is_empty() {
+			writeln!(stdout, "{}", heading("features that nothing uses:"))?;
+			for (package, features) in &self.unused_features {
+				let line = format!("`{} v{}`: {}", package.name(), package.version(), features.join(", "));
+				wrap::write_wrapped(&mut stdout, width, "  ", "    ", &line)?;
+			}
+		}
+		if !self.duplicates.is_empty() {
+			writeln!(stdout, "{}", heading("crates in several versions:"))?;
+			for (name, versions) in &self.duplicates {
+				writeln!(stdout, "  `{}`", name)?;
+				for duplicates::Duplicate { package, members } in versions {
+					let members = members
+						.iter()
+						.map(|member| format!("`{} v{}`", member.name(), member.version()))
+						.collect::<Vec<_>>();
+					let line = format!("v{}, for {}", package.version(), members.join(", "));
+					wrap::write_wrapped(&mut stdout, width, "    ", "      ", &line)?;
+				}
+			}
+		}
+		if !self.unused_patches.is_empty() {
+			writeln!(stdout, "{}", heading("patches that have no effect:"))?;
+			for patches::UnusedPatch { table, key, package } in &self.unused_patches {
+				let line = match package {
+					Some(package) => format!("`{}` of `[{}]`, to `{} v{}`", key, table, package.name(), package.version()),
+					None => format!("`{}` of `[{}]`", key, table),
+				};
+				wrap::write_wrapped(&mut stdout, width, "  ", "    ", &line)?;
+			}
+		}
+		if !self.unanalyzed_units.is_empty() {
+			writeln!(stdout, "{}", heading("units that could not be analyzed, so their dependencies may be reported wrongly:"))?;
+			for UnanalyzedUnit { package, target, failed } in &self.unanalyzed_units {
+				let why = if *failed { "failed to compile" } else { "not built, as something it needs failed" };
+				let line = format!("`{} v{}`: {} ({})", package.name(), package.version(), target, why);
+				wrap::write_wrapped(&mut stdout, width, "  ", "    ", &line)?;
+			}
+		}
+		if !self.ignored.is_empty() {
+			writeln!(stdout, "{}", heading("ignored unused dependencies:"))?;
+			for (package, ignored) in &self.ignored {
+				writeln!(stdout, "  `{} v{}`", package.name(), package.version())?;
+				for IgnoredUnused { name, kind, reason } in ignored {
+					let line = format!("{:?} ({}): {}", name, kind, reason);
+					wrap::write_wrapped(&mut stdout, width, "    ", "      ", &line)?;
+				}
+			}
+		}
+		if verbose && !self.unused_by_target.is_empty() {
+			writeln!(stdout, "{}", heading("unused dependencies by target:"))?;
+			for (target, unused) in &self.unused_by_target {
+				writeln!(stdout, "  {}", target)?;
+				for TargetUnused { package, kind, name } in unused {
+					let line = format!("`{} v{}`: {:?} ({})", package.name(), package.version(), name, kind);
+					wrap::write_wrapped(&mut stdout, width, "    ", "      ", &line)?;
+				}
+			}
+		}
+		stdout.flush()
+	}
+
+	/// Prints one line per unused dependency, for `--message-format short`.
+	pub(crate) fn print_short(&self, mut stdout: impl Write) -> io::Result<()> {
+		for (member, unused) in &self.unused_deps {
+			for (kind, kind_name) in [(dependency::DepKind::Normal, "normal"), (dependency::DepKind::Development, "dev"), (dependency::DepKind::Build, "build")] {
+				for dep in unused.unused_deps(kind) {
+					let warn_only = if unused.warn_only.contains(kind, dep) { ", warning only" } else { "" };
+					writeln!(stdout, "{}: unused dependency `{}` ({}{})", member.name(), dep, kind_name, warn_only)?;
+				}
+			}
+		}
+		stdout.flush()
+	}
+
+	/// Prints the names of the unused dependencies, for `--print-unused-names`.
+	///
+	/// Each name is printed once, unless they are prefixed with the names of their packages.
+	fn print_names(&self, with_package: bool, mut stdout: impl Write) -> io::Result<()> {
+		let names = self.unused_deps
+			.iter()
+			.flat_map(|(member, unused)| {
+				unused.normal.iter().chain(&unused.development).chain(&unused.build).map(move |dep| {
+					if with_package {
+						format!("{}:{}", member.name(), dep)
+					} else {
+						dep.to_string()
+					}
+				})
+			})
+			.collect::<BTreeSet<_>>();
+		for name in names {
+			writeln!(stdout, "{}", name)?;
+		}
+		stdout.flush()
+	}
+
+	/// The number of unused dependencies per owner of their packages.
+	fn count_by_owner(&self) -> BTreeMap<Option<&str>, usize> {
+		let mut counts = BTreeMap::new();
+		for unused_deps in self.unused_deps.values() {
+			let count = unused_deps.normal.len() + unused_deps.development.len() + unused_deps.build.len();
+			*counts.entry(unused_deps.owner.as_deref()).or_default() += count;
+		}
+		counts
+	}
+
+	fn print_template(&self, template: &template::Template, mut stdout: impl Write) -> io::Result<()> {
+		write!(stdout, "{}", template.render(&self.template_context()))?;
+		stdout.flush()
+	}
+
+	/// The values available in `--format-template` templates.
+	fn template_context(&self) -> serde_json::Value {
+		let packages = self.unused_deps
+			.iter()
+			.map(|(id, unused)| {
+				let deps = [
+					(dependency::DepKind::Normal, "normal"),
+					(dependency::DepKind::Development, "development"),
+					(dependency::DepKind::Build, "build"),
+				]
+					.iter()
+					.flat_map(|&(kind, kind_name)| unused.unused_deps(kind).iter().map(move |dep| (kind, kind_name, dep)))
+					.map(|(kind, kind_name, dep)| serde_json::json!({
+						"name": dep,
+						"kind": kind_name,
+						"registry": unused.registries.get(dep),
+						"renamed": unused.renamed.get(dep),
+						"platform": unused.platforms.get(dep),
+						"enabled_by": unused.enabled_by.get(dep),
+						"warn_only": unused.warn_only.contains(kind, dep),
+						"introduced": unused.introduced.get(dep),
+						"info": unused.info.get(dep),
+					}))
+					.collect::<Vec<_>>();
+				serde_json::json!({
+					"package": id.to_string(),
+					"name": id.name(),
+					"version": id.version().to_string(),
+					"manifest_path": unused.manifest_path,
+					"owner": unused.owner,
+					"doc_only": unused.doc_only,
+					"dev_only": unused.dev_only,
+					"dev_only_targets": unused.dev_only_targets,
+					"deps": deps,
+				})
+			})
+			.collect::<Vec<_>>();
+		serde_json::json!({
+			"success": self.success,
+			"packages_checked": self.packages_checked,
+			"provenance": self.provenance,
+			"unused_count": packages.iter().map(|p| p["deps"].as_array().map_or(0, Vec::len)).sum::<usize>(),
+			"packages": packages,
+			"owners": self.count_by_owner()
+				.into_iter()
+				.map(|(owner, count)| serde_json::json!({ "owner": owner, "count": count }))
+				.collect::<Vec<_>>(),
+			"note": self.note,
+		})
+	}
+
+	pub(crate) fn print_json(&self, mut stdout: impl Write) -> io::Result<()> {
+		let json = serde_json::to_string(self).expect("should not fail");
+		writeln!(stdout, "{}", json)?;
+		stdout.flush()
+	}
+}
@@ -0,0 +1,138 @@
+//! The findings for tools that embed the analysis, like aggregators of several checks
+//! or editor extensions, instead of running `cargo udeps` and parsing its output.
+
+use std::ffi::OsString;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cargo::core::dependency::DepKind;
+use cargo::core::package_id::PackageId;
+use toml_edit::ImDocument;
+
+use crate::fix::dependency_key_span;
+use crate::Outcome;
+
+/// What to check, like the arguments of `cargo udeps`.
+///
+/// The common options have methods of their own, and all others can be passed with
+/// [`CheckOptions::arg`]. Output related options have no effect on the [`Report`].
+#[derive(Clone, Debug, Default)]
+pub struct CheckOptions {
+	pub(crate) args :Vec<OsString>,
+}
+
+impl CheckOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Like `--manifest-path`.
+	pub fn manifest_path(self, path :impl AsRef<Path>) -> Self {
+		self.arg("--manifest-path").arg(path.as_ref())
+	}
+
+	/// Like `--package`, which can be given several times.
+	pub fn package(self, spec :&str) -> Self {
+		self.arg("--package").arg(spec)
+	}
+
+	/// Like `--workspace`.
+	pub fn workspace(self) -> Self {
+		self.arg("--workspace")
+	}
+
+	/// Like `--all-targets`.
+	pub fn all_targets(self) -> Self {
+		self.arg("--all-targets")
+	}
+
+	/// Like `--features`.
+	pub fn features(self, features :&[&str]) -> Self {
+		self.arg("--features").arg(features.join(","))
+	}
+
+	/// Like `--all-features`.
+	pub fn all_features(self) -> Self {
+		self.arg("--all-features")
+	}
+
+	/// Like `--no-default-features`.
+	pub fn no_default_features(self) -> Self {
+		self.arg("--no-default-features")
+	}
+
+	/// Like `--target`.
+	pub fn target(self, triple :&str) -> Self {
+		self.arg("--target").arg(triple)
+	}
+
+	/// Any other argument of `cargo udeps`.
+	pub fn arg(mut self, arg :impl Into<OsString>) -> Self {
+		self.args.push(arg.into());
+		self
+	}
+}
+
+/// The unused dependencies that were found, by package.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Report {
+	/// Whether none of the unused dependencies make the check fail.
+	pub success :bool,
+	pub packages :Vec<PackageReport>,
+}
+
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct PackageReport {
+	pub package_id :PackageId,
+	pub manifest_path :PathBuf,
+	pub unused :Vec<UnusedDependency>,
+}
+
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct UnusedDependency {
+	/// The name in `Cargo.toml`, which differs from the package name for renamed dependencies.
+	pub name :String,
+	pub kind :DepKind,
+	/// The `cfg` or target of a dependency that is only declared for some platforms.
+	pub platform :Option<String>,
+	/// Whether the policy is to only warn about it.
+	pub warn_only :bool,
+	/// The byte range of its key in the manifest.
+	pub span :Option<Range<usize>>,
+}
+
+impl Report {
+	/// Relative manifest paths of the outcome are relative to `root`.
+	pub(crate) fn new(outcome :&Outcome, root :&Path) -> Self {
+		let packages = outcome.unused_deps
+			.iter()
+			.map(|(&package_id, unused_deps)| {
+				let manifest_path = root.join(&unused_deps.manifest_path);
+				let contents = std::fs::read_to_string(&manifest_path).ok();
+				let doc = contents.as_deref().and_then(|contents| ImDocument::parse(contents).ok());
+				let unused = [DepKind::Normal, DepKind::Development, DepKind::Build]
+					.into_iter()
+					.flat_map(|kind| unused_deps.unused_deps(kind).iter().map(move |&name| (kind, name)))
+					.map(|(kind, name)| UnusedDependency {
+						name : name.to_string(),
+						kind,
+						platform : unused_deps.platforms.get(&name).cloned(),
+						warn_only : unused_deps.warn_only.contains(kind, &name),
+						span : doc.as_ref().and_then(|doc| dependency_key_span(doc, kind, &name)),
+					})
+					.collect();
+				PackageReport { package_id, manifest_path, unused }
+			})
+			.filter(|package :&PackageReport| !package.unused.is_empty())
+			.collect();
+		Self { success : outcome.success, packages }
+	}
+
+	/// The number of unused dependencies of all packages.
+	pub fn unused_count(&self) -> usize {
+		self.packages.iter().map(|package| package.unused.len()).sum()
+	}
+}
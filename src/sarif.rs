@@ -0,0 +1,108 @@
+//! Rendering of the findings as SARIF 2.1.0, for code scanning services like the one of GitHub.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use cargo::core::dependency::DepKind;
+use serde_json::json;
+use toml_edit::ImDocument;
+
+use crate::diagnostic::line_and_column;
+use crate::fix::dependency_key_span;
+use crate::{explain, Outcome};
+
+const KINDS :[(DepKind, &str); 3] = [
+	(DepKind::Normal, "dependency"),
+	(DepKind::Development, "dev-dependency"),
+	(DepKind::Build, "build-dependency"),
+];
+
+/// Prints a SARIF log with a result for every unused dependency, located at its declaration.
+///
+/// Relative manifest paths are relative to `root`, which is the `%SRCROOT%` of the log.
+pub(crate) fn print(outcome :&Outcome, root :&Path, mut stdout :impl Write) -> io::Result<()> {
+	let rules = KINDS
+		.iter()
+		.map(|&(kind, _)| {
+			let code = explain::code(kind);
+			let explanation = explain::explanation(code).unwrap_or_default();
+			json!({
+				"id": code,
+				"shortDescription": { "text": explanation.lines().next().unwrap_or_default() },
+				"help": { "text": explanation },
+			})
+		})
+		.collect::<Vec<_>>();
+
+	let mut results = Vec::new();
+	for (member, unused) in &outcome.unused_deps {
+		let manifest_path = root.join(&unused.manifest_path);
+		let contents = std::fs::read_to_string(&manifest_path).ok();
+		let doc = contents.as_deref().and_then(|contents| ImDocument::parse(contents).ok());
+		let artifact_location = if Path::new(&unused.manifest_path).is_relative() {
+			json!({ "uri": uri(Path::new(&unused.manifest_path)), "uriBaseId": "%SRCROOT%" })
+		} else {
+			json!({ "uri": uri(&manifest_path) })
+		};
+		for (kind, kind_name) in KINDS {
+			for dep in unused.unused_deps(kind) {
+				let mut physical_location = json!({ "artifactLocation": artifact_location });
+				let span = doc.as_ref().and_then(|doc| dependency_key_span(doc, kind, dep));
+				if let (Some(contents), Some(span)) = (&contents, span) {
+					let (start_line, start_column) = line_and_column(contents, span.start);
+					let (end_line, end_column) = line_and_column(contents, span.end);
+					physical_location["region"] = json!({
+						"startLine": start_line,
+						"startColumn": start_column,
+						"endLine": end_line,
+						"endColumn": end_column,
+					});
+				}
+				let level = if unused.warn_only.contains(kind, dep) { "warning" } else { "error" };
+				results.push(json!({
+					"ruleId": explain::code(kind),
+					"level": level,
+					"message": {
+						"text": format!("`{}` is an unused {} of `{} v{}`", dep, kind_name, member.name(), member.version()),
+					},
+					"locations": [{ "physicalLocation": physical_location }],
+				}));
+			}
+		}
+	}
+
+	let log = json!({
+		"$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+		"version": "2.1.0",
+		"runs": [{
+			"tool": {
+				"driver": {
+					"name": "cargo-udeps",
+					"version": env!("CARGO_PKG_VERSION"),
+					"informationUri": env!("CARGO_PKG_REPOSITORY"),
+					"rules": rules,
+				},
+			},
+			"originalUriBaseIds": {
+				"%SRCROOT%": { "uri": format!("{}/", uri(root).trim_end_matches('/')) },
+			},
+			"columnKind": "unicodeCodePoints",
+			"results": results,
+		}],
+	});
+	serde_json::to_writer_pretty(&mut stdout, &log)?;
+	writeln!(stdout)?;
+	stdout.flush()
+}
+
+/// A relative URI reference for relative paths, and a `file` URI for absolute ones.
+pub(crate) fn uri(path :&Path) -> String {
+	let path_str = path.to_string_lossy().replace('\\', "/").replace(' ', "%20");
+	if !path.is_absolute() {
+		path_str
+	} else if path_str.starts_with('/') {
+		format!("file://{}", path_str)
+	} else {
+		format!("file:///{}", path_str)
+	}
+}
@@ -0,0 +1,61 @@
+//! The members with changes since a git revision, for `--since REV`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use cargo::core::{PackageId, Workspace};
+use cargo::CargoResult;
+
+/// The members with files that differ between `rev` and the working directory, including
+/// untracked files that aren't ignored. A file belongs to the member with the innermost
+/// directory that contains it. Changes to the manifest of the workspace or to `Cargo.lock`
+/// may affect any member, so all of them are returned then.
+pub(crate) fn changed_members(ws :&Workspace<'_>, rev :&str) -> CargoResult<HashSet<PackageId>> {
+	let repo = git2::Repository::discover(ws.root())
+		.with_context(|| format!("`--since` needs a git repository, but `{}` isn't in one", ws.root().display()))?;
+	let workdir = repo
+		.workdir()
+		.ok_or_else(|| anyhow::anyhow!("`--since` needs a git repository with a working directory"))?;
+	let workdir = canonicalize(workdir);
+	let tree = repo
+		.revparse_single(rev)
+		.and_then(|object| object.peel_to_tree())
+		.with_context(|| format!("could not find the git revision `{}`", rev))?;
+	let mut options = git2::DiffOptions::new();
+	options.include_untracked(true).recurse_untracked_dirs(true);
+	let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+	let mut members = ws
+		.members()
+		.map(|member| (canonicalize(member.root()), member.package_id()))
+		.collect::<Vec<_>>();
+	// Nested members come first, so that they get the files below them
+	members.sort_by_key(|(root, _)| std::cmp::Reverse(root.components().count()));
+	let target_dir = canonicalize(&ws.target_dir().into_path_unlocked());
+	let workspace_files = [canonicalize(ws.root_manifest()), canonicalize(&ws.root().join("Cargo.lock"))];
+
+	let mut changed = HashSet::new();
+	let paths = diff
+		.deltas()
+		.flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+		.flatten()
+		.map(|path| workdir.join(path));
+	for path in paths {
+		if workspace_files.contains(&path) {
+			return Ok(members.into_iter().map(|(_, id)| id).collect());
+		}
+		if path.starts_with(&target_dir) {
+			continue;
+		}
+		if let Some((_, id)) = members.iter().find(|(root, _)| path.starts_with(root)) {
+			changed.insert(*id);
+		}
+	}
+	Ok(changed)
+}
+
+/// The path with symbolic links resolved, so that the paths of git and cargo can be compared.
+fn canonicalize(path :&Path) -> PathBuf {
+	std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
@@ -0,0 +1,208 @@
+//! Findings of the packages that are printed as soon as all of their units are built, for `--stream`.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cargo::core::package_id::PackageId;
+use cargo::core::shell::Shell;
+use cargo::core::dependency::DepKind;
+use cargo::core::{Package, Resolve, SourceId, Workspace};
+use cargo::util::interning::InternedString;
+use cargo::CargoResult;
+use serde::Serialize;
+
+use crate::analysis::{self, Analysis};
+use crate::{
+	Backend, DependencyNames, ExecData, Lint, OptUdeps, Outcome, OutcomeUnusedDeps, PackageMetadataCargoUdeps,
+	Severity, SourceFilter, Usage, default_ignore_reason,
+};
+
+pub(crate) struct Stream {
+	json :bool,
+	backend :Backend,
+	/// The workspace root to make manifest paths relative to, with `--path-style relative`.
+	root :Option<PathBuf>,
+	dependency_names :Arc<HashMap<PackageId, DependencyNames>>,
+	workspace_metadata :Option<PackageMetadataCargoUdeps>,
+	/// Set with `--source`.
+	sources :Vec<SourceFilter>,
+	/// Unset with `--no-default-ignores`.
+	lib_name_fallback :bool,
+	/// Set with `--deny`, `--warn` and `--allow`.
+	lint_levels :HashMap<Lint, Severity>,
+	members :HashMap<PackageId, Member>,
+	/// The number of units of each package that are still to be built.
+	pending :HashMap<PackageId, usize>,
+}
+
+/// What is needed of a member to tell which of its dependencies to report.
+struct Member {
+	manifest_path :PathBuf,
+	metadata :Option<PackageMetadataCargoUdeps>,
+	sources :HashMap<(DepKind, InternedString), SourceId>,
+	/// The dependencies that the default ignores leave out, like in the report.
+	default_ignored :HashSet<(DepKind, InternedString)>,
+}
+
+/// The JSON line of a package, with a `reason` like the messages of cargo.
+#[derive(Serialize)]
+struct Message<'a> {
+	reason :&'static str,
+	package_id :PackageId,
+	unused_deps :&'a OutcomeUnusedDeps,
+}
+
+/// A line to print, to stdout for JSON and to stderr otherwise.
+pub(crate) enum Line {
+	Stdout(String),
+	Stderr(String),
+}
+
+impl Stream {
+	pub(crate) fn new(
+		json :bool,
+		opt :&OptUdeps,
+		ws :&Workspace<'_>,
+		resolve :&Resolve,
+		packages :&HashMap<PackageId, &Package>,
+		root :Option<PathBuf>,
+		dependency_names :Arc<HashMap<PackageId, DependencyNames>>,
+	) -> CargoResult<Self> {
+		let members = ws
+			.members()
+			.filter(|member| dependency_names.contains_key(&member.package_id()))
+			.map(|member| {
+				// Reversed, so that the first declaration wins like in the report
+				let sources = member
+					.dependencies()
+					.iter()
+					.rev()
+					.map(|dep| ((dep.kind(), dep.name_in_toml()), dep.source_id()))
+					.collect();
+				let default_ignored = if opt.no_default_ignores {
+					HashSet::new()
+				} else {
+					member
+						.dependencies()
+						.iter()
+						.filter(|dep| {
+							let dependency = resolve
+								.deps(member.package_id())
+								.find(|(_, deps)| deps.iter().any(|d| d.kind() == dep.kind() && d.name_in_toml() == dep.name_in_toml()))
+								.and_then(|(id, _)| packages.get(&id).copied());
+							default_ignore_reason(member, dep.kind(), dependency).is_some()
+						})
+						.map(|dep| (dep.kind(), dep.name_in_toml()))
+						.collect()
+				};
+				let member_info = Member {
+					manifest_path : member.manifest_path().to_owned(),
+					metadata : PackageMetadataCargoUdeps::of_package(member)?,
+					sources,
+					default_ignored,
+				};
+				Ok((member.package_id(), member_info))
+			})
+			.collect::<CargoResult<_>>()?;
+		Ok(Self {
+			json,
+			backend : opt.backend,
+			root,
+			sources : opt.source.clone(),
+			lib_name_fallback : !opt.no_default_ignores,
+			lint_levels : opt.lint_levels(),
+			dependency_names,
+			workspace_metadata : PackageMetadataCargoUdeps::of_workspace(ws)?,
+			members,
+			pending : HashMap::new(),
+		})
+	}
+
+	/// Counts a unit of `pkg` that is going to be built.
+	pub(crate) fn expect(&mut self, pkg :PackageId) {
+		*self.pending.entry(pkg).or_default() += 1;
+	}
+}
+
+/// Takes note that a unit of `pkg` was built, and returns the findings of `pkg`
+/// if it was the last one.
+///
+/// Ignores and policies are applied like for the report, but the registries,
+/// owners and commits of the dependencies are left out.
+pub(crate) fn unit_built(data :&mut ExecData, pkg :PackageId) -> CargoResult<Vec<Line>> {
+	let pending = match data.stream.as_mut().and_then(|stream| stream.pending.get_mut(&pkg)) {
+		Some(pending) => pending,
+		None => return Ok(Vec::new()),
+	};
+	*pending -= 1;
+	if *pending > 0 {
+		return Ok(Vec::new());
+	}
+
+	let data = &*data;
+	let stream = data.stream.as_ref().expect("should be streaming");
+	let (names, member) = match (stream.dependency_names.get(&pkg), stream.members.get(&pkg)) {
+		(Some(names), Some(member)) => (names, member),
+		_ => return Ok(Vec::new()),
+	};
+	// The messages of reading the analysis can't be shown from here
+	let mut shell = Shell::from_write(Box::new(io::sink()));
+	let lib_stems = Analysis::lib_stems(data);
+	let mut usage = Usage::new([(&pkg, names)], stream.lib_name_fallback);
+	for cmd_info in data.relevant_cmd_infos.iter().filter(|cmd_info| cmd_info.pkg == pkg) {
+		// Skipped units are warned about in the end, when the analysis is collected again
+		let unit = match analysis::Unit::collect(cmd_info, data, stream.backend, &mut shell) {
+			Err(e) if !data.strict && e.is::<analysis::MissingDepinfo>() => continue,
+			unit => unit?,
+		};
+		usage.add_unit(&unit, &lib_stems, names);
+	}
+	let mut unused_deps = OutcomeUnusedDeps::new(&member.manifest_path)?;
+	for (kind, _, dependency) in usage.unused() {
+		if !SourceFilter::any_matches(&stream.sources, member.sources.get(&(kind, dependency)).copied()) {
+			continue;
+		}
+		if member.default_ignored.contains(&(kind, dependency)) {
+			continue;
+		}
+		let severity = Severity::of_unused(
+			member.metadata.as_ref(),
+			stream.workspace_metadata.as_ref(),
+			&stream.lint_levels,
+			kind,
+			dependency,
+		);
+		if let Some(severity) = severity {
+			unused_deps.unused_deps_mut(kind).insert(dependency);
+			if severity == Severity::Warn {
+				unused_deps.warn_only.insert(kind, dependency);
+			}
+		}
+	}
+
+	if let Some(root) = &stream.root {
+		unused_deps.relativize(root);
+	}
+
+	if stream.json {
+		let message = Message { reason : "package-checked", package_id : pkg, unused_deps : &unused_deps };
+		return Ok(vec![Line::Stdout(serde_json::to_string(&message)?)]);
+	}
+	let outcome = Outcome {
+		unused_deps : [(pkg, unused_deps)].into_iter().collect(),
+		..Outcome::default()
+	};
+	let mut short = Vec::new();
+	outcome.print_short(&mut short)?;
+	let warning = if data.supports_color {
+		nu_ansi_term::Color::Yellow.bold().paint("warning:").to_string()
+	} else {
+		"warning:".to_owned()
+	};
+	Ok(String::from_utf8_lossy(&short)
+		.lines()
+		.map(|line| Line::Stderr(format!("{} {}", warning, line)))
+		.collect())
+}
@@ -0,0 +1,79 @@
+//! The markdown summary of the findings for GitHub Actions jobs, written to `$GITHUB_STEP_SUMMARY`,
+//! and printed with `--output markdown`.
+
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use cargo::core::dependency::DepKind;
+
+use crate::Outcome;
+
+/// Appends the summary to the file, like the steps of a job are expected to.
+pub(crate) fn append(outcome :&Outcome, path :&Path) -> io::Result<()> {
+	let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+	file.write_all(markdown(outcome).as_bytes())?;
+	file.flush()
+}
+
+pub(crate) fn print(outcome :&Outcome, mut stdout :impl Write) -> io::Result<()> {
+	stdout.write_all(markdown(outcome).as_bytes())?;
+	stdout.flush()
+}
+
+fn markdown(outcome :&Outcome) -> String {
+	let mut md = "## cargo-udeps\n\n".to_owned();
+	let count = outcome.unused_deps
+		.values()
+		.map(|deps| deps.normal.len() + deps.development.len() + deps.build.len())
+		.sum::<usize>();
+	if count == 0 {
+		writeln!(md, "All deps of the {} checked packages seem to have been used.\n", outcome.packages_checked).unwrap();
+		return md;
+	}
+	let packages = outcome.unused_deps
+		.values()
+		.filter(|deps| !(deps.normal.is_empty() && deps.development.is_empty() && deps.build.is_empty()))
+		.count();
+	writeln!(
+		md,
+		"{} unused dependenc{} in {} of the {} checked packages.\n",
+		count,
+		if count == 1 { "y" } else { "ies" },
+		packages,
+		outcome.packages_checked,
+	).unwrap();
+	md += "| Package | Normal | Development | Build |\n";
+	md += "| --- | ---: | ---: | ---: |\n";
+	for (id, deps) in &outcome.unused_deps {
+		writeln!(
+			md,
+			"| `{} v{}` | {} | {} | {} |",
+			id.name(), id.version(), deps.normal.len(), deps.development.len(), deps.build.len(),
+		).unwrap();
+	}
+	md += "\n";
+	for (id, deps) in &outcome.unused_deps {
+		let commands = [(DepKind::Normal, ""), (DepKind::Development, " --dev"), (DepKind::Build, " --build")]
+			.into_iter()
+			.filter(|(kind, _)| !deps.unused_deps(*kind).is_empty())
+			.map(|(kind, flag)| {
+				let names = deps.unused_deps(kind).iter().map(|dep| dep.as_str()).collect::<Vec<_>>();
+				format!("cargo remove --package {}{} {}", id.name(), flag, names.join(" "))
+			})
+			.collect::<Vec<_>>();
+		if commands.is_empty() {
+			continue;
+		}
+		writeln!(md, "### `{} v{}` ({})\n", id.name(), id.version(), deps.manifest_path).unwrap();
+		for (kind, kind_name) in [(DepKind::Normal, "normal"), (DepKind::Development, "dev"), (DepKind::Build, "build")] {
+			for dep in deps.unused_deps(kind) {
+				let warn_only = if deps.warn_only.contains(kind, dep) { ", warning only" } else { "" };
+				writeln!(md, "- `{}` ({}{})", dep, kind_name, warn_only).unwrap();
+			}
+		}
+		writeln!(md, "\n```sh\n{}\n```\n", commands.join("\n")).unwrap();
+	}
+	md
+}
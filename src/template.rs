@@ -0,0 +1,141 @@
+//! A small handlebars-like template language for custom reports.
+//!
+//! Supported are `{{path.to.value}}`, `{{#each list}}..{{/each}}` and
+//! `{{#if value}}..{{else}}..{{/if}}`. Inside of `each`, the names are
+//! looked up in the current element first, and `{{this}}` is the element.
+
+use cargo::CargoResult;
+use serde_json::Value;
+
+enum Node {
+	Text(String),
+	Var(String),
+	Each(String, Vec<Node>),
+	If(String, Vec<Node>, Vec<Node>),
+}
+
+pub(crate) struct Template {
+	nodes :Vec<Node>,
+}
+
+impl Template {
+	pub(crate) fn parse(source :&str) -> CargoResult<Self> {
+		let mut rest = source;
+		let (nodes, end) = parse_nodes(&mut rest)?;
+		match end {
+			None => Ok(Self { nodes }),
+			Some(tag) => Err(anyhow::anyhow!("unexpected `{{{{{}}}}}` in template", tag)),
+		}
+	}
+
+	pub(crate) fn render(&self, context :&Value) -> String {
+		let mut out = String::new();
+		render_nodes(&self.nodes, &[context], &mut out);
+		out
+	}
+}
+
+/// Parses nodes until the end of the input or a closing tag,
+/// which is returned.
+fn parse_nodes(rest :&mut &str) -> CargoResult<(Vec<Node>, Option<String>)> {
+	let mut nodes = Vec::new();
+	loop {
+		let start = match rest.find("{{") {
+			Some(start) => start,
+			None => {
+				if !rest.is_empty() {
+					nodes.push(Node::Text(rest.to_string()));
+				}
+				*rest = "";
+				return Ok((nodes, None));
+			},
+		};
+		if start > 0 {
+			nodes.push(Node::Text(rest[..start].to_string()));
+		}
+		let end = rest[start..]
+			.find("}}")
+			.ok_or_else(|| anyhow::anyhow!("unclosed `{{{{` in template"))?;
+		let tag = rest[start + 2..start + end].trim().to_owned();
+		*rest = &rest[start + end + 2..];
+		if let Some(path) = tag.strip_prefix("#each ") {
+			let (body, end) = parse_nodes(rest)?;
+			expect_end(end, "/each")?;
+			nodes.push(Node::Each(path.trim().to_owned(), body));
+		} else if let Some(path) = tag.strip_prefix("#if ") {
+			let (then, end) = parse_nodes(rest)?;
+			let otherwise = if end.as_deref() == Some("else") {
+				let (otherwise, end) = parse_nodes(rest)?;
+				expect_end(end, "/if")?;
+				otherwise
+			} else {
+				expect_end(end, "/if")?;
+				Vec::new()
+			};
+			nodes.push(Node::If(path.trim().to_owned(), then, otherwise));
+		} else if tag.starts_with('/') || tag == "else" {
+			return Ok((nodes, Some(tag)));
+		} else {
+			nodes.push(Node::Var(tag));
+		}
+	}
+}
+
+fn expect_end(end :Option<String>, expected :&str) -> CargoResult<()> {
+	match end {
+		Some(end) if end == expected => Ok(()),
+		Some(end) => Err(anyhow::anyhow!("expected `{{{{{}}}}}` in template, found `{{{{{}}}}}`", expected, end)),
+		None => Err(anyhow::anyhow!("missing `{{{{{}}}}}` in template", expected)),
+	}
+}
+
+/// Looks `path` up in the innermost scope that has its first component.
+fn lookup<'a>(scopes :&[&'a Value], path :&str) -> Option<&'a Value> {
+	if path == "this" {
+		return scopes.last().copied();
+	}
+	let mut components = path.split('.');
+	let first = components.next()?;
+	let mut value = scopes.iter().rev().find_map(|scope| scope.get(first))?;
+	for component in components {
+		value = value.get(component)?;
+	}
+	Some(value)
+}
+
+fn is_truthy(value :Option<&Value>) -> bool {
+	match value {
+		None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+		Some(Value::Number(n)) => n.as_f64() != Some(0.0),
+		Some(Value::String(s)) => !s.is_empty(),
+		Some(Value::Array(a)) => !a.is_empty(),
+		Some(Value::Object(o)) => !o.is_empty(),
+		Some(Value::Bool(true)) => true,
+	}
+}
+
+fn render_nodes(nodes :&[Node], scopes :&[&Value], out :&mut String) {
+	for node in nodes {
+		match node {
+			Node::Text(text) => out.push_str(text),
+			Node::Var(path) => match lookup(scopes, path) {
+				None | Some(Value::Null) => (),
+				Some(Value::String(s)) => out.push_str(s),
+				Some(value) => out.push_str(&value.to_string()),
+			},
+			Node::Each(path, body) => {
+				if let Some(Value::Array(elements)) = lookup(scopes, path) {
+					for element in elements {
+						let mut scopes = scopes.to_vec();
+						scopes.push(element);
+						render_nodes(body, &scopes, out);
+					}
+				}
+			},
+			Node::If(path, then, otherwise) => {
+				let branch = if is_truthy(lookup(scopes, path)) { then } else { otherwise };
+				render_nodes(branch, scopes, out);
+			},
+		}
+	}
+}
@@ -0,0 +1,72 @@
+//! How long the phases of a run and the work on the single crates took, for `--timings`.
+
+use std::time::{Duration, Instant};
+
+use cargo::core::package_id::PackageId;
+use cargo::core::shell::Shell;
+use cargo::CargoResult;
+
+#[derive(Debug, Default)]
+pub(crate) struct Timings {
+	phases :Vec<(&'static str, Duration)>,
+	crates :Vec<CrateTiming>,
+}
+
+#[derive(Debug)]
+struct CrateTiming {
+	pkg :PackageId,
+	/// The name of the target, like `foo` or `build-script-build`.
+	target :String,
+	/// `rustc`, `doc-cfg` or `analysis`
+	work :&'static str,
+	duration :Duration,
+}
+
+impl Timings {
+	/// Records how long the phase that began at `start` took.
+	pub(crate) fn phase(&mut self, name :&'static str, start :Instant) {
+		self.phases.push((name, start.elapsed()));
+	}
+
+	/// Records how long `work` on the `target` of `pkg` took.
+	pub(crate) fn record(&mut self, pkg :PackageId, target :&str, work :&'static str, duration :Duration) {
+		self.crates.push(CrateTiming { pkg, target : target.to_owned(), work, duration });
+	}
+
+	pub(crate) fn extend(&mut self, other :Timings) {
+		self.phases.extend(other.phases);
+		self.crates.extend(other.crates);
+	}
+
+	/// Prints the phase totals and the `top` slowest crates to stderr.
+	pub(crate) fn print(&self, top :usize, shell :&mut Shell) -> CargoResult<()> {
+		let err = shell.err();
+		writeln!(err, "Timings:")?;
+		for (name, duration) in &self.phases {
+			writeln!(err, "  {:<10} {:>8.2}s", name, duration.as_secs_f64())?;
+		}
+		if self.crates.is_empty() {
+			return Ok(());
+		}
+		let mut crates = self.crates.iter().collect::<Vec<_>>();
+		// Ties are broken by name, so that the list is stable
+		crates.sort_by(|a, b| b.duration.cmp(&a.duration)
+			.then_with(|| (a.pkg, &a.target, a.work).cmp(&(b.pkg, &b.target, b.work))));
+		writeln!(err, "Slowest crates:")?;
+		for timing in crates.iter().take(top) {
+			writeln!(
+				err,
+				"  {:>8.2}s  {:<8}  {} v{} ({})",
+				timing.duration.as_secs_f64(),
+				timing.work,
+				timing.pkg.name(),
+				timing.pkg.version(),
+				timing.target,
+			)?;
+		}
+		if crates.len() > top {
+			writeln!(err, "  ... and {} more", crates.len() - top)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,150 @@
+//! Making sure that a nightly rustc is used, which `-Z binary-dep-depinfo` needs.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use cargo::util::context::{ConfigRelativePath, ConfigValue, Definition, GlobalContext};
+use cargo::util::Rustc;
+use cargo::CargoResult;
+
+/// The toolchain that is installed if the rustc in use is not a nightly one.
+const TOOLCHAIN :&str = "nightly";
+
+/// Switches to the nightly toolchain of rustup if the rustc in use is not a nightly one,
+/// installing it if `install` is given or the user agrees to it.
+pub(crate) fn ensure_nightly(config :&mut GlobalContext, install :bool) -> CargoResult<()> {
+	// Allows `-Z` flags on any channel
+	if config.get_env_os("RUSTC_BOOTSTRAP").is_some() {
+		return Ok(());
+	}
+	let rustc = rustc_in_use(config)?;
+	let pre = rustc.version.pre.as_str();
+	if pre.starts_with("nightly") || pre.starts_with("dev") {
+		return Ok(());
+	}
+	let not_nightly = format!("`cargo-udeps` needs a nightly rustc, but `{}` is {}", rustc.path.display(), rustc.version);
+	if config.get_env_os("RUSTC").is_some() {
+		return Err(anyhow::anyhow!("{}, as set by `$RUSTC`", not_nightly));
+	}
+
+	let path = match nightly_rustc() {
+		Some(path) => path,
+		None => {
+			if !rustup_available() {
+				return Err(anyhow::anyhow!("{}\nInstall a nightly toolchain and run `cargo +{} udeps`", not_nightly, TOOLCHAIN));
+			}
+			if !install && !confirm(config, &not_nightly)? {
+				return Err(anyhow::anyhow!(
+					"{}\nRun `rustup toolchain install {}`, or pass `--install-toolchain`",
+					not_nightly,
+					TOOLCHAIN,
+				));
+			}
+			config.shell().status("Installing", format!("the {} toolchain", TOOLCHAIN))?;
+			let status = Command::new("rustup")
+				.args(["toolchain", "install", TOOLCHAIN, "--profile", "minimal"])
+				// Keep stdout for the report
+				.stdout(io::stderr())
+				.status()?;
+			if !status.success() {
+				return Err(anyhow::anyhow!("could not install the {} toolchain: {}", TOOLCHAIN, status));
+			}
+			nightly_rustc().ok_or_else(|| anyhow::anyhow!("could not find rustc of the {} toolchain", TOOLCHAIN))?
+		},
+	};
+	config.shell().note(format!("using the rustc of the {} toolchain, `{}`", TOOLCHAIN, path.display()))?;
+	set_build_tool(config, "rustc", &path)?;
+	// `--doctests` needs a nightly rustdoc as well
+	if let Some(rustdoc) = rustup_which(&["--toolchain", TOOLCHAIN, "rustdoc"]) {
+		set_build_tool(config, "rustdoc", &rustdoc)?;
+	}
+	Ok(())
+}
+
+/// The rustc that cargo would use, which is looked up like cargo does, but without the wrappers.
+///
+/// `GlobalContext::load_global_rustc` can't be used, as it caches the `build` config,
+/// which [`set_build_tool`] couldn't change anymore.
+fn rustc_in_use(config :&GlobalContext) -> CargoResult<Rustc> {
+	let path = match config.get_env_os("RUSTC") {
+		Some(path) => PathBuf::from(path),
+		None => config.get::<Option<ConfigRelativePath>>("build.rustc")?
+			.map_or_else(|| "rustc".into(), |path| path.resolve_program(config)),
+	};
+	Rustc::new(path.clone(), None, None, &path, None, config)
+}
+
+/// Sets a tool of the `build` config, like `--config build.rustc=…` does.
+///
+/// Cargo passes `build.rustc` on to build scripts as `$RUSTC`. Unlike setting the variables,
+/// this leaves the environment of the context alone, which programs calling [`crate::check`]
+/// may have filled themselves.
+fn set_build_tool(config :&mut GlobalContext, tool :&str, path :&Path) -> CargoResult<()> {
+	let definition = Definition::Cli(None);
+	let value = ConfigValue::String(path.display().to_string(), definition.clone());
+	let build = config.values_mut()?
+		.entry("build".to_owned())
+		.or_insert_with(|| ConfigValue::Table(HashMap::new(), definition));
+	match build {
+		ConfigValue::Table(table, _) => {
+			table.insert(tool.to_owned(), value);
+			Ok(())
+		},
+		_ => Err(anyhow::anyhow!("expected a table for `build`, but found {}", build.desc())),
+	}
+}
+
+/// Makes sure that build scripts and the like don't get the current executable as `$CARGO`
+/// when a rustup toolchain is chosen (like with `cargo +nightly udeps`), as cargo would
+/// point them to it if `$CARGO` isn't set.
+///
+/// The `cargo-udeps` binary sets `$CARGO` to the cargo of the toolchain before it creates the
+/// context. Cargo only reads it from the environment of the context, which can't be added to
+/// without replacing the whole of it, so programs calling [`crate::check`] have to set it themselves.
+pub(crate) fn check_cargo_env(config :&GlobalContext) -> CargoResult<()> {
+	if config.get_env_os(cargo::CARGO_ENV).is_some() {
+		return Ok(());
+	}
+	match config.get_env_os("RUSTUP_TOOLCHAIN") {
+		Some(toolchain) => Err(anyhow::anyhow!(
+			"`${}` is not set, so build scripts would get this program instead of the cargo of the {:?} toolchain; \
+			set it to the path that `rustup which cargo` prints",
+			cargo::CARGO_ENV,
+			toolchain,
+		)),
+		None => Ok(()),
+	}
+}
+
+fn rustup_which(args :&[&str]) -> Option<PathBuf> {
+	let output = Command::new("rustup").arg("which").args(args).output().ok()?;
+	if !output.status.success() {
+		return None;
+	}
+	Some(String::from_utf8(output.stdout).ok()?.trim().into())
+}
+
+/// The path of the rustc of the nightly toolchain, if it's installed.
+fn nightly_rustc() -> Option<PathBuf> {
+	rustup_which(&["--toolchain", TOOLCHAIN, "rustc"])
+}
+
+fn rustup_available() -> bool {
+	Command::new("rustup").arg("--version").output().is_ok_and(|output| output.status.success())
+}
+
+/// Asks whether to install the toolchain, if there is someone to ask.
+fn confirm(config :&GlobalContext, reason :&str) -> CargoResult<bool> {
+	if !io::stdin().is_terminal() || !config.shell().is_err_tty() {
+		return Ok(false);
+	}
+	let mut shell = config.shell();
+	shell.warn(reason)?;
+	write!(shell.err(), "Install the {} toolchain with rustup now? [y/N] ", TOOLCHAIN)?;
+	shell.err().flush()?;
+	let mut answer = String::new();
+	io::stdin().lock().read_line(&mut answer)?;
+	Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
@@ -0,0 +1,109 @@
+//! Checking again whenever the manifests or sources of the workspace change, for `--watch`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use cargo::util::command_prelude::{ArgMatches, ArgMatchesExt};
+use cargo::util::context::GlobalContext;
+use cargo::CargoResult;
+
+use crate::{OptUdeps, ShellExt};
+
+/// How often the files are looked at.
+const INTERVAL :Duration = Duration::from_millis(500);
+
+/// Runs the check, and again after every change, until the process is stopped.
+///
+/// Errors of a check, like those of a manifest that is being edited, are shown
+/// without ending the watch. Units that didn't change are taken from the cache.
+pub(crate) fn run<W :Write>(opt :&OptUdeps, config :&mut GlobalContext, mut stdout :W, clap_matches :&ArgMatches) -> CargoResult<i32> {
+	loop {
+		let mut report = None;
+		if let Err(e) = opt.check(config, &mut stdout, clap_matches, &mut report) {
+			cargo::display_error(&e, &mut config.shell());
+		}
+		let roots = roots(config, clap_matches);
+		let mut seen = snapshot(&roots);
+		config.shell().info("Watching for changes of the manifests and sources, press Ctrl-C to stop")?;
+		let changed = loop {
+			thread::sleep(INTERVAL);
+			let current = snapshot(&roots);
+			if let Some(changed) = first_change(&seen, &current) {
+				let changed = changed.to_owned();
+				// Editors and `git checkout` change several files one after the other
+				seen = current;
+				loop {
+					thread::sleep(INTERVAL);
+					let current = snapshot(&roots);
+					if current == seen {
+						break;
+					}
+					seen = current;
+				}
+				break changed;
+			}
+		};
+		config.shell().info(format_args!("`{}` changed, checking again", changed.display()))?;
+	}
+}
+
+/// The directories to watch, and the target directory that is left out.
+struct Roots {
+	dirs :Vec<PathBuf>,
+	target_dir :Option<PathBuf>,
+}
+
+fn roots(config :&GlobalContext, clap_matches :&ArgMatches) -> Roots {
+	match clap_matches.workspace(config) {
+		Ok(ws) => {
+			let mut dirs = vec![ws.root().to_owned()];
+			// Members can be outside of the workspace root
+			dirs.extend(ws.members().map(|member| member.root().to_owned()).filter(|root| !root.starts_with(ws.root())));
+			Roots { dirs, target_dir : Some(ws.target_dir().into_path_unlocked()) }
+		},
+		// The manifest is broken, it will be looked at once it changes
+		Err(_) => Roots { dirs : vec![config.cwd().to_owned()], target_dir : None },
+	}
+}
+
+/// The modification times of the manifests and Rust sources.
+fn snapshot(roots :&Roots) -> BTreeMap<PathBuf, SystemTime> {
+	fn visit(dir :&Path, roots :&Roots, snapshot :&mut BTreeMap<PathBuf, SystemTime>) {
+		let Ok(entries) = std::fs::read_dir(dir) else {
+			return;
+		};
+		for entry in entries.flatten() {
+			let path = entry.path();
+			let Ok(metadata) = entry.metadata() else {
+				continue;
+			};
+			if metadata.is_dir() {
+				let hidden = entry.file_name().to_string_lossy().starts_with('.');
+				if !hidden && roots.target_dir.as_deref() != Some(&path) {
+					visit(&path, roots, snapshot);
+				}
+			} else if entry.file_name() == "Cargo.toml" || path.extension().is_some_and(|ext| ext == "rs") {
+				if let Ok(modified) = metadata.modified() {
+					snapshot.insert(path, modified);
+				}
+			}
+		}
+	}
+	let mut snapshot = BTreeMap::new();
+	for dir in &roots.dirs {
+		visit(dir, roots, &mut snapshot);
+	}
+	snapshot
+}
+
+/// A file that was added, changed or removed.
+fn first_change<'a>(before :&'a BTreeMap<PathBuf, SystemTime>, after :&'a BTreeMap<PathBuf, SystemTime>) -> Option<&'a Path> {
+	after
+		.iter()
+		.find(|(path, modified)| before.get(*path) != Some(modified))
+		.or_else(|| before.iter().find(|(path, _)| !after.contains_key(*path)))
+		.map(|(path, _)| path.as_path())
+}
@@ -0,0 +1,183 @@
+//! Why a dependency is considered used or unused, for `--explain DEPENDENCY`.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::Write;
+
+use cargo::core::dependency::DepKind;
+use cargo::core::{Package, PackageId};
+use cargo::util::interning::InternedString;
+use cargo::CargoResult;
+
+use crate::Backend;
+
+/// A unit that was found to use a dependency.
+#[derive(Clone, Debug)]
+pub(crate) struct Evidence {
+	/// The target of the unit, as described to users.
+	pub(crate) target :String,
+	/// The name the dependency was passed to the unit with, if it was found by its `--extern` path.
+	pub(crate) extern_crate_name :Option<String>,
+	/// The file stem of the artifact, like `libfoo-0123456789abcdef`.
+	pub(crate) stem :String,
+	pub(crate) matched :Matched,
+}
+
+/// How an artifact the unit depended on was matched to a dependency.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Matched {
+	ExternPath,
+	PackageId,
+	LibName,
+}
+
+/// What the units did with the dependencies, which is otherwise forgotten
+/// once it is known which of them are used.
+#[derive(Debug, Default)]
+pub(crate) struct Trace {
+	evidence :HashMap<(DepKind, PackageId, InternedString), Vec<Evidence>>,
+	/// The targets of the units the dependencies were passed to with `--extern`.
+	passed_to :HashMap<(DepKind, PackageId, InternedString), BTreeSet<String>>,
+}
+
+impl Trace {
+	pub(crate) fn record(
+		&mut self,
+		kind :DepKind,
+		pkg :PackageId,
+		target :&str,
+		used :impl IntoIterator<Item = (InternedString, Evidence)>,
+		passed :&[InternedString],
+	) {
+		for (dependency, evidence) in used {
+			self.evidence.entry((kind, pkg, dependency)).or_default().push(evidence);
+		}
+		for &dependency in passed {
+			self.passed_to.entry((kind, pkg, dependency)).or_default().insert(target.to_owned());
+		}
+	}
+}
+
+/// How a dependency ended up, besides being used or not.
+pub(crate) struct Status {
+	pub(crate) used :bool,
+	/// Whether it was passed to any of the units, or can't be used at all.
+	pub(crate) checked :bool,
+	/// Whether it has no library, and can't be used.
+	pub(crate) non_lib :bool,
+	/// Whether it is only used when checked with `--cfg doc`.
+	pub(crate) doc_only :bool,
+	/// Whether it is ignored by the metadata or `--allow`.
+	pub(crate) ignored :bool,
+}
+
+/// Prints, for every checked package with a dependency called `name`, whether it was found
+/// to be used and by what. `status` is asked about each of these dependencies.
+///
+/// Dependencies are looked up by the name they are declared with, and by their package name.
+/// `loads` is asked whether the first dependency of a package depends, directly or not,
+/// on the second, so that loading it also puts the second one in the depinfo.
+pub(crate) fn print(
+	name :&str,
+	packages :&[&Package],
+	trace :&Trace,
+	backend :Backend,
+	mut status :impl FnMut(DepKind, PackageId, InternedString) -> CargoResult<Status>,
+	mut loads :impl FnMut(PackageId, InternedString, InternedString) -> bool,
+	mut stdout :impl Write,
+) -> CargoResult<()> {
+	let mut found = false;
+	for package in packages {
+		let mut seen = HashSet::new();
+		let dependencies = package
+			.dependencies()
+			.iter()
+			.filter(|dep| dep.name_in_toml() == name || dep.package_name() == name)
+			.filter(|dep| seen.insert((dep.kind(), dep.name_in_toml())));
+		for dep in dependencies {
+			found = true;
+			let key = (dep.kind(), package.package_id(), dep.name_in_toml());
+			let kind = match dep.kind() {
+				DepKind::Normal => "normal",
+				DepKind::Development => "development",
+				DepKind::Build => "build",
+			};
+			let Status { used, checked, non_lib, doc_only, ignored } = status(key.0, key.1, key.2)?;
+			let verdict = if used {
+				"used"
+			} else if doc_only {
+				"used only when checked with `--cfg doc`"
+			} else if checked {
+				"unused"
+			} else {
+				"not checked"
+			};
+			writeln!(stdout, "`{}` ({}) of `{}`: {}", dep.name_in_toml(), kind, package.package_id(), verdict)?;
+			for evidence in trace.evidence.get(&key).into_iter().flatten() {
+				let how = match evidence.matched {
+					Matched::ExternPath => "its `--extern` path",
+					Matched::PackageId => "the artifacts of the build",
+					Matched::LibName => "its library name, as the artifact is unknown",
+				};
+				let found_in = match backend {
+					Backend::Depinfo => format!("`{}` is in the depinfo", evidence.stem),
+					Backend::Lint => format!("`{}` isn't reported by the lint", evidence.stem),
+				};
+				match &evidence.extern_crate_name {
+					Some(extern_crate_name) => writeln!(
+						stdout,
+						"  {} uses it as `{}`: {}, matched by {}",
+						evidence.target, extern_crate_name, found_in, how,
+					)?,
+					None => writeln!(stdout, "  {} uses it: {}, matched by {}", evidence.target, found_in, how)?,
+				}
+				if backend == Backend::Depinfo {
+					// The other dependencies the unit used that bring this one along, like facades
+					// that re-export it. Their users load it, whether they use it themselves or not.
+					let through = trace.evidence
+						.iter()
+						.filter(|((_, id, other), others)| {
+							*id == key.1 && *other != key.2 && others.iter().any(|e| e.target == evidence.target)
+						})
+						.map(|((_, _, other), _)| *other)
+						.collect::<BTreeSet<_>>();
+					for other in through.into_iter().filter(|&other| loads(key.1, other, key.2)) {
+						writeln!(
+							stdout,
+							"    `{}` depends on it too, so it is in the depinfo even if it is only used \
+							through what `{}` re-exports (`--backend lint` tells these apart)",
+							other, other,
+						)?;
+					}
+				}
+			}
+			if !used {
+				if non_lib {
+					writeln!(stdout, "  it has no library that Rust code can use, and such dependencies are regarded as unused")?;
+				} else if let Some(targets) = trace.passed_to.get(&key) {
+					let targets = targets.iter().map(String::as_str).collect::<Vec<_>>();
+					writeln!(stdout, "  it is passed with `--extern` to {}, but none of them use it", targets.join(", "))?;
+				}
+				if !checked {
+					writeln!(
+						stdout,
+						"  it isn't passed to any of the checked units, it may be optional, for another platform, \
+						or only needed by targets that weren't checked (see `--all-targets`)",
+					)?;
+				}
+			}
+			if ignored {
+				writeln!(stdout, "  it is ignored, so it isn't reported either way")?;
+			}
+		}
+	}
+	if !found {
+		let codes = crate::explain::codes().collect::<Vec<_>>();
+		return Err(anyhow::anyhow!(
+			"`{}` is neither a dependency of the checked packages nor a kind of finding, which are: {}",
+			name,
+			codes.join(", "),
+		));
+	}
+	stdout.flush()?;
+	Ok(())
+}
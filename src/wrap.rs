@@ -0,0 +1,133 @@
+//! Fitting the human output into the width of the terminal.
+
+use std::io::{self, Write};
+
+/// Lines aren't made narrower than this, however deep they are indented.
+const MIN_TEXT_WIDTH :usize = 16;
+
+/// Writes `text` as a line starting with `first_prefix`, continued on lines
+/// starting with `rest_prefix` where it would get wider than `width`.
+///
+/// Words that don't fit into a line of their own, like long paths, are
+/// shortened in the middle, unless they are locations like `(Cargo.toml:8)`,
+/// which editors and terminals can only link in full. The escape sequences of
+/// colored words don't count towards the width, and are kept as they are.
+pub(crate) fn write_wrapped(
+	mut out :impl Write,
+	width :Option<usize>,
+	first_prefix :&str,
+	rest_prefix :&str,
+	text :&str,
+) -> io::Result<()> {
+	let width = match width {
+		Some(width) => width,
+		None => return writeln!(out, "{}{}", first_prefix, text),
+	};
+	let mut line = first_prefix.to_owned();
+	let mut line_width = visible_width(first_prefix);
+	let mut text_width = width.saturating_sub(line_width).max(MIN_TEXT_WIDTH);
+	let mut empty = true;
+	for word in text.split(' ') {
+		let word_width = visible_width(word);
+		if !empty && line_width + 1 + word_width > width {
+			writeln!(out, "{}", line)?;
+			line = rest_prefix.to_owned();
+			line_width = visible_width(rest_prefix);
+			text_width = width.saturating_sub(line_width).max(MIN_TEXT_WIDTH);
+			empty = true;
+		}
+		if !empty {
+			line.push(' ');
+			line_width += 1;
+		}
+		if word_width > text_width && !is_location(word) {
+			line += &shorten(word, text_width);
+			line_width += text_width;
+		} else {
+			line += word;
+			line_width += word_width;
+		}
+		empty = false;
+	}
+	writeln!(out, "{}", line)
+}
+
+/// Writes the lines of a note like `Note: ..` with continuation lines indented
+/// by six spaces, keeping the indentation when wrapping.
+pub(crate) fn write_note(mut out :impl Write, width :Option<usize>, note :&str) -> io::Result<()> {
+	for line in note.lines() {
+		let text = line.strip_prefix("Note: ").unwrap_or_else(|| line.trim_start());
+		let prefix = &line[..line.len() - text.len()];
+		write_wrapped(&mut out, width, prefix, "      ", text)?;
+	}
+	Ok(())
+}
+
+/// A part of a word, either a character that is shown or an ANSI escape sequence.
+enum Segment<'a> {
+	Visible(char),
+	Escape(&'a str),
+}
+
+/// Splits `text` into the characters that are shown and the escape sequences
+/// like `\x1b[1;31m` between them.
+fn segments(text :&str) -> impl Iterator<Item = Segment<'_>> {
+	let mut rest = text;
+	std::iter::from_fn(move || {
+		let c = rest.chars().next()?;
+		let len = if c == '\x1b' {
+			rest.find(|c :char| c.is_ascii_alphabetic()).map_or(rest.len(), |end| end + 1)
+		} else {
+			c.len_utf8()
+		};
+		let (segment, remaining) = rest.split_at(len);
+		rest = remaining;
+		Some(if c == '\x1b' { Segment::Escape(segment) } else { Segment::Visible(c) })
+	})
+}
+
+/// The number of characters of `text`, without the ones of ANSI escape sequences.
+fn visible_width(text :&str) -> usize {
+	segments(text).filter(|segment| matches!(segment, Segment::Visible(_))).count()
+}
+
+/// Whether `word` is a location like `(path/to/Cargo.toml:8)`.
+fn is_location(word :&str) -> bool {
+	let visible = segments(word)
+		.filter_map(|segment| match segment {
+			Segment::Visible(c) => Some(c),
+			Segment::Escape(_) => None,
+		})
+		.collect::<String>();
+	let visible = visible.trim_matches(|c| matches!(c, '(' | ')' | ',' | '`'));
+	let mut parts = visible.rsplit(':');
+	let line = parts.next().unwrap_or_default();
+	!line.is_empty() && line.chars().all(|c| c.is_ascii_digit()) && parts.next().is_some_and(|path| !path.is_empty())
+}
+
+/// Replaces the middle of `word` with `…`, so that `width` characters of it are shown.
+///
+/// Escape sequences are never cut, and those of the middle are kept, so that
+/// colors are reset like without shortening.
+fn shorten(word :&str, width :usize) -> String {
+	let tail = (width - 1) / 2;
+	let head = width - 1 - tail;
+	let len = visible_width(word);
+	let mut shortened = String::new();
+	let mut index = 0;
+	for segment in segments(word) {
+		match segment {
+			Segment::Escape(escape) => shortened += escape,
+			Segment::Visible(c) => {
+				if index == head {
+					shortened.push('…');
+				}
+				if index < head || index >= len - tail {
+					shortened.push(c);
+				}
+				index += 1;
+			},
+		}
+	}
+	shortened
+}
@@ -0,0 +1,395 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "apply"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+# Used by the library.
+if_chain = "1.0.0"
+maplit = "1.0.2"
+
+[dev-dependencies]
+matches = "0.1.8"
+
+[target.'cfg(unix)'.dependencies]
+maplit = "1.0.2"
+"#;
+
+// Produced on another machine, hence the foreign path.
+static REPORT_JSON :&str = r#"{
+	"success": false,
+	"unused_deps": {
+		"apply 0.0.1 (path+file:///home/runner/work/apply)": {
+			"manifest_path": "/home/runner/work/apply/Cargo.toml",
+			"normal": ["maplit"],
+			"development": ["matches"],
+			"build": []
+		}
+	},
+	"note": null
+}"#;
+
+#[test]
+fn apply() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_apply_apply")?
+		.cargo_toml(CARGO_TOML)?
+		.file("report.json", REPORT_JSON)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--allow-no-vcs");
+	let (code, stdout_masked) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!("", stdout_masked);
+	assert_eq!(
+		r#"[workspace]
+[package]
+name = "apply"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+# Used by the library.
+if_chain = "1.0.0"
+"#,
+		runner.read_file("Cargo.toml")?,
+	);
+	Ok(())
+}
+
+#[test]
+fn apply_dry_run() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_apply_apply_dry_run")?
+		.cargo_toml(CARGO_TOML)?
+		.file("report.json", REPORT_JSON)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--dry-run");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(CARGO_TOML, runner.read_file("Cargo.toml")?);
+	Ok(())
+}
+
+fn git(runner :&Runner, args :&[&str]) -> CargoResult<()> {
+	let status = std::process::Command::new("git")
+		.args(["-c", "user.name=cargo-udeps", "-c", "user.email=cargo-udeps@example.com"])
+		.args(args)
+		.current_dir(runner.path())
+		.status()?;
+	if !status.success() {
+		return Err(anyhow::anyhow!("`git {}` failed: {}", args.join(" "), status));
+	}
+	Ok(())
+}
+
+#[test]
+fn apply_dirty() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_apply_apply_dirty")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?;
+	git(&runner, &["init", "-q"])?;
+	git(&runner, &["add", "Cargo.toml"])?;
+	git(&runner, &["commit", "-q", "-m", "initial"])?;
+	let runner = runner
+		.cargo_toml(&format!("{}\n# work in progress\n", CARGO_TOML))?
+		.file("report.json", REPORT_JSON)?
+		.arg("apply")
+		.arg("report.json");
+	let err = runner.run().unwrap_err();
+	assert!(err.to_string().contains("Cargo.toml (dirty)"), "{}", err);
+	assert_eq!(format!("{}\n# work in progress\n", CARGO_TOML), runner.read_file("Cargo.toml")?);
+
+	let (code, _) = runner.arg("--allow-dirty").run()?;
+	assert_eq!(0, code);
+	Ok(())
+}
+
+static LOCK_FILE_CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "apply-lock-file"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+a = { path = "a" }
+b = { path = "b" }
+"#;
+
+static LOCK_FILE_CARGO_LOCK :&str = r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "a"
+version = "0.0.1"
+
+[[package]]
+name = "apply-lock-file"
+version = "0.0.1"
+dependencies = [
+ "a",
+ "b",
+]
+
+[[package]]
+name = "b"
+version = "0.0.1"
+"#;
+
+static LOCK_FILE_REPORT_JSON :&str = r#"{
+		"success": false,
+		"unused_deps": {
+			"apply-lock-file 0.0.1 (path+file:///elsewhere)": {
+				"manifest_path": "/elsewhere/Cargo.toml",
+				"normal": ["b"],
+				"development": [],
+				"build": []
+			}
+		},
+		"note": null
+	}"#;
+
+fn lock_file_runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(LOCK_FILE_CARGO_TOML)?
+		.file("Cargo.lock", LOCK_FILE_CARGO_LOCK)?
+		.file("report.json", LOCK_FILE_REPORT_JSON)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("a", "")?
+		.path_dep("b", "")?)
+}
+
+#[test]
+fn apply_lock_file() -> CargoResult<()> {
+	let runner = lock_file_runner("cargo_udeps_test_apply_apply_lock_file")?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--allow-no-vcs");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "a"
+version = "0.0.1"
+
+[[package]]
+name = "apply-lock-file"
+version = "0.0.1"
+dependencies = [
+ "a",
+]
+"#,
+		runner.read_file("Cargo.lock")?,
+	);
+	Ok(())
+}
+
+#[test]
+fn apply_locked() -> CargoResult<()> {
+	// As a member, `b` stays in the lock file, but the root package doesn't depend on it anymore
+	let cargo_toml = LOCK_FILE_CARGO_TOML.replace("[workspace]", "[workspace]\nmembers = [\"a\", \"b\"]");
+	let runner = lock_file_runner("cargo_udeps_test_apply_apply_locked")?
+		.cargo_toml(&cargo_toml)?
+		.arg("--locked")
+		.arg("apply")
+		.arg("report.json")
+		.arg("--allow-no-vcs");
+	let err = runner.run().unwrap_err();
+	assert!(format!("{:#}", err).contains("--locked"), "{:#}", err);
+	assert!(err.to_string().contains("the manifests were left unchanged"), "{}", err);
+	assert_eq!(cargo_toml, runner.read_file("Cargo.toml")?);
+	assert_eq!(LOCK_FILE_CARGO_LOCK, runner.read_file("Cargo.lock")?);
+	Ok(())
+}
+
+#[test]
+fn apply_prune_workspace_deps() -> CargoResult<()> {
+	static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+
+[workspace.dependencies]
+# Shared by all members.
+if_chain = "1.0.0"
+maplit = "1.0.2"
+"#;
+
+	static REPORT_JSON :&str = r#"{
+		"success": false,
+		"unused_deps": {
+			"a 0.0.1 (path+file:///elsewhere/a)": {
+				"manifest_path": "/elsewhere/a/Cargo.toml",
+				"normal": ["if_chain", "maplit"],
+				"development": [],
+				"build": []
+			},
+			"b 0.0.1 (path+file:///elsewhere/b)": {
+				"manifest_path": "/elsewhere/b/Cargo.toml",
+				"normal": ["maplit"],
+				"development": [],
+				"build": []
+			}
+		},
+		"note": null
+	}"#;
+
+	static SUB_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+if_chain.workspace = true
+maplit = { workspace = true }
+"#;
+
+	let runner = Runner::new("cargo_udeps_test_apply_apply_prune_workspace_deps")?
+		.cargo_toml(CARGO_TOML)?
+		.file("report.json", REPORT_JSON)?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", &SUB_CARGO_TOML.replace("{}", "a"))?
+		.file("./a/src/lib.rs", "")?
+		.dir("./b/src")?
+		.file("./b/Cargo.toml", &SUB_CARGO_TOML.replace("{}", "b"))?
+		.file("./b/src/lib.rs", "")?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--prune-workspace-deps")
+		.arg("--allow-no-vcs");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		r#"[workspace]
+members = ["a", "b"]
+
+[workspace.dependencies]
+# Shared by all members.
+if_chain = "1.0.0"
+"#,
+		runner.read_file("Cargo.toml")?,
+	);
+	assert_eq!(
+		r#"[package]
+name = "b"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+if_chain.workspace = true
+"#,
+		runner.read_file("b/Cargo.toml")?,
+	);
+	Ok(())
+}
+
+#[test]
+fn apply_protected_registry() -> CargoResult<()> {
+	static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "apply-protected-registry"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[package.metadata.cargo-udeps]
+protected-registries = ["internal"]
+
+[dependencies]
+internal-dep = { version = "1.0.0", registry = "internal" }
+maplit = "1.0.2"
+"#;
+
+	static CONFIG_TOML :&str = r#"[registries]
+internal = { index = "sparse+https://registry.invalid/index/" }
+"#;
+
+	static REPORT_JSON :&str = r#"{
+		"success": false,
+		"unused_deps": {
+			"apply-protected-registry 0.0.1 (path+file:///elsewhere)": {
+				"manifest_path": "/elsewhere/Cargo.toml",
+				"normal": ["internal-dep", "maplit"],
+				"development": [],
+				"build": [],
+				"registries": {"internal-dep": "internal"}
+			}
+		},
+		"note": null
+	}"#;
+
+	let runner = Runner::new("cargo_udeps_test_apply_apply_protected_registry")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./.cargo")?
+		.file("./.cargo/config.toml", CONFIG_TOML)?
+		.file("report.json", REPORT_JSON)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--allow-no-vcs");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		CARGO_TOML.replace("maplit = \"1.0.2\"\n", ""),
+		runner.read_file("Cargo.toml")?,
+	);
+
+	let runner = runner.arg("--allow-registry").arg("internal");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		r#"[workspace]
+[package]
+name = "apply-protected-registry"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[package.metadata.cargo-udeps]
+protected-registries = ["internal"]
+"#,
+		runner.read_file("Cargo.toml")?,
+	);
+	Ok(())
+}
+
+#[test]
+fn apply_no_vcs() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_apply_apply_no_vcs")?
+		.cargo_toml(CARGO_TOML)?
+		.file("report.json", REPORT_JSON)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("apply")
+		.arg("report.json");
+	let err = runner.run().unwrap_err();
+	assert!(err.to_string().contains("pass `--allow-no-vcs`"), "{}", err);
+	assert_eq!(CARGO_TOML, runner.read_file("Cargo.toml")?);
+
+	let (code, _) = runner.arg("--allow-no-vcs").run()?;
+	assert_eq!(0, code);
+	Ok(())
+}
+
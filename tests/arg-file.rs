@@ -0,0 +1,62 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "../unused" }
+"#;
+
+static ARGS :&str = "--workspace\r\n--exclude\r\nb\r\n\r\n--all-targets\r\n";
+
+static EXPECTED :&str = r#"unused dependencies:
+`a v0.0.1 (██████████/a)`
+└─── dependencies
+     └─── "unused" (a/Cargo.toml:7)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.path_dep("unused", "")?
+		.file("./args.txt", ARGS)?;
+	for member in ["a", "b"] {
+		runner = runner
+			.dir(&format!("./{}/src", member))?
+			.file(&format!("./{}/Cargo.toml", member), &MEMBER_CARGO_TOML.replace("{}", member))?
+			.file(&format!("./{}/src/lib.rs", member), "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn arg_file() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_arg_file")?.arg("@args.txt").run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn missing_arg_file() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_arg_file_missing")?
+		.arg("@missing.txt")
+		.run()
+		.unwrap_err();
+	assert_eq!("could not read the argument file `missing.txt`", err.to_string());
+	Ok(())
+}
@@ -0,0 +1,30 @@
+mod runner;
+
+use std::process::Command;
+
+use cargo::CargoResult;
+
+use crate::runner::Runner;
+
+#[test]
+fn backtrace_with_very_verbose() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_backtrace")?
+		.cargo_toml("[package]\nname = \"backtrace\"\n")?;
+	let stderr = |args :&[&str]| -> CargoResult<String> {
+		let output = Command::new(env!("CARGO_BIN_EXE_cargo-udeps"))
+			.arg("udeps")
+			.args(args)
+			.current_dir(runner.path())
+			.env_remove("RUST_BACKTRACE")
+			.env_remove("RUST_LIB_BACKTRACE")
+			.output()?;
+		assert_eq!(Some(101), output.status.code());
+		Ok(String::from_utf8(output.stderr)?)
+	};
+
+	let stderr_vv = stderr(&["-vv"])?;
+	assert!(stderr_vv.contains("note: backtrace of the error:\n"), "{}", stderr_vv);
+	let stderr_v = stderr(&["-v"])?;
+	assert!(!stderr_v.contains("backtrace of the error"), "{}", stderr_v);
+	Ok(())
+}
@@ -0,0 +1,106 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "baseline"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+old = { path = "old" }
+
+[dev-dependencies]
+old-dev = { path = "old-dev" }
+"#;
+
+static BASELINE :&str = r#"{
+  "unused_deps": {
+    "baseline": {
+      "normal": [
+        "old"
+      ],
+      "development": [
+        "old-dev"
+      ]
+    }
+  }
+}
+"#;
+
+fn runner(prefix :&str, cargo_toml :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(cargo_toml)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?;
+	for name in ["old", "old-dev", "new"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner.arg("--all-targets"))
+}
+
+#[test]
+fn write_baseline() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_baseline_write", CARGO_TOML)?
+		.arg("--write-baseline")
+		.arg("baseline.json");
+	let (code, _) = runner.run()?;
+	// Writing it doesn't change the outcome
+	assert_eq!(1, code);
+	assert_eq!(BASELINE, runner.read_file("baseline.json")?);
+	Ok(())
+}
+
+#[test]
+fn baseline() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_baseline", CARGO_TOML)?
+		.file("baseline.json", BASELINE)?
+		.arg("--baseline")
+		.arg("baseline.json")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout);
+	assert!(stderr.contains("info: 2 unused dependencies are in the baseline and not reported\n"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn new_unused_dependency() -> CargoResult<()> {
+	let cargo_toml = CARGO_TOML.replace("[dev-dependencies]", "new = { path = \"new\" }\n\n[dev-dependencies]");
+	let (code, stdout) = runner("cargo_udeps_test_baseline_new", &cargo_toml)?
+		.file("baseline.json", BASELINE)?
+		.arg("--baseline")
+		.arg("baseline.json")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!([["new"], []]), serde_json::json!([unused_deps["normal"], unused_deps["development"]]));
+	Ok(())
+}
+
+#[test]
+fn no_longer_unused() -> CargoResult<()> {
+	let cargo_toml = CARGO_TOML.replace("old-dev = { path = \"old-dev\" }\n", "");
+	let (code, _, stderr) = runner("cargo_udeps_test_baseline_no_longer_unused", &cargo_toml)?
+		.file("baseline.json", BASELINE)?
+		.arg("--baseline")
+		.arg("baseline.json")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert!(
+		stderr.contains("info: `old-dev` (Development) of `baseline` is in the baseline, but no longer unused\n"),
+		"{}",
+		stderr,
+	);
+	Ok(())
+}
@@ -0,0 +1,55 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "blame"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "unused" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`blame v0.0.1 (██████████)`
+├─── dependencies
+│    └─── "unused" (Cargo.toml:10) (added 2021-06-01 12:00:00 by Jane Doe in {commit})
+└─── dev-dependencies
+     └─── "uncommitted" (Cargo.toml:2)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn blame() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_blame")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.path_dep("uncommitted", "")?
+		.arg("--all-targets")
+		.arg("--blame");
+
+	let repo = git2::Repository::init(runner.path())?;
+	let mut index = repo.index()?;
+	index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+	let tree = repo.find_tree(index.write_tree()?)?;
+	let signature = git2::Signature::new("Jane Doe", "jane@example.com", &git2::Time::new(1_622_548_800, 0))?;
+	let commit = repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])?;
+
+	std::fs::write(
+		runner.path().join("Cargo.toml"),
+		format!("[dev-dependencies]\nuncommitted = {{ path = \"uncommitted\" }}\n{}", CARGO_TOML),
+	)?;
+	let (code, stdout_masked) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED.replace("{commit}", &commit.to_string()[..7]), stdout_masked);
+	Ok(())
+}
@@ -0,0 +1,96 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "build_script_metadata"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+foo-sys = { path = "foo-sys" }
+bar-sys = { path = "bar-sys" }
+"#;
+
+/// Only reads the metadata of `foo-sys`.
+static BUILD_RS :&str = r#"fn main() {
+	let root = std::env::var("DEP_FOO_ROOT").unwrap();
+	println!("cargo:rustc-env=FOO_ROOT={}", root);
+}
+"#;
+
+static SYS_CARGO_TOML :&str = r#"[package]
+name = "{}-sys"
+version = "0.0.1"
+edition = "2021"
+publish = false
+links = "{}"
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.file("./build.rs", BUILD_RS)?;
+	for name in ["foo", "bar"] {
+		runner = runner
+			.dir(&format!("./{}-sys/src", name))?
+			.file(&format!("./{}-sys/Cargo.toml", name), &SYS_CARGO_TOML.replace("{}", name))?
+			.file(&format!("./{}-sys/build.rs", name), "fn main() {\n\tprintln!(\"cargo:root=/opt\");\n}\n")?
+			.file(&format!("./{}-sys/src/lib.rs", name), "")?;
+	}
+	Ok(runner.arg("--output").arg("json"))
+}
+
+fn unused(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok(unused_deps["normal"].clone())
+}
+
+#[test]
+fn build_script_metadata() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_build_script_metadata")?.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!(["bar-sys"]), unused(&stdout)?);
+	assert!(
+		stderr.contains("Not reporting `foo-sys` (Normal), it is used indirectly: the build script reads its `links` metadata (`DEP_FOO_*`)"),
+		"{}",
+		stderr,
+	);
+	Ok(())
+}
+
+#[test]
+fn build_script_metadata_no_default_ignores() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_build_script_metadata_no_default_ignores")?
+		.arg("--no-default-ignores")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!(["bar-sys", "foo-sys"]), unused(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn build_script_metadata_stream() -> CargoResult<()> {
+	let (code, _, stderr) = runner("cargo_udeps_test_build_script_metadata_stream")?
+		.arg("--stream")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	// The runner's shell writes cargo's stdout together with stderr
+	let message = stderr
+		.lines()
+		.find(|line| line.starts_with(r#"{"reason":"package-checked""#))
+		.unwrap_or_else(|| panic!("should stream the package: {}", stderr));
+	let message = serde_json::from_str::<serde_json::Value>(message)?;
+	// Like the report
+	assert_eq!(serde_json::json!(["bar-sys"]), message["unused_deps"]["normal"]);
+	Ok(())
+}
@@ -0,0 +1,78 @@
+mod runner;
+
+use std::process::Command;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "build_std"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+"#;
+
+static LIB_RS :&str = "pub use used::ANSWER;\n";
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?
+		.path_dep("used", "pub const ANSWER :u32 = 42;\n")?
+		.path_dep("unused", "")?)
+}
+
+fn host_triple() -> CargoResult<String> {
+	let version = Command::new(std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()))
+		.arg("-vV")
+		.output()?
+		.stdout;
+	String::from_utf8(version)?
+		.lines()
+		.find_map(|l| l.strip_prefix("host: "))
+		.map(str::to_owned)
+		.ok_or_else(|| anyhow::anyhow!("could not determine the host triple"))
+}
+
+static EXPECTED :&str = r#"unused dependencies:
+`build_std v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+#[ignore = "needs the rust-src component"]
+fn build_std() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_build_std")?
+		.arg("-Zbuild-std=core,alloc,std")
+		// `-Z build-std` requires an explicit target
+		.arg("--target")
+		.arg(host_triple()?)
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn extern_options() -> CargoResult<()> {
+	// Private dependencies are passed as `--extern priv:name=..`
+	let (code, stdout_masked) = runner("cargo_udeps_test_build_std_extern_options")?
+		.arg("-Zpublic-dependency")
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
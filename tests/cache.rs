@@ -0,0 +1,83 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "cache"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+later = { path = "later" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`cache v0.0.1 (██████████)`
+└─── dependencies
+     └─── "later" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub fn answer() -> u32 {\n\tused::value()\n}\n")?;
+	for dep in ["used", "later"] {
+		runner = runner
+			.path_dep(dep, "pub fn value() -> u32 { 42 }\n")?;
+	}
+	Ok(runner.arg("--all-targets"))
+}
+
+#[test]
+fn cache() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_cache")?;
+	let (code, stdout_masked, stderr) = runner.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	assert!(stderr.contains("Checking cache v0.0.1"), "{}", stderr);
+
+	// Nothing changed, so nothing is rebuilt
+	let (code, stdout_masked, stderr) = runner.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	assert!(!stderr.contains("Checking cache v0.0.1"), "{}", stderr);
+
+	// Changes are picked up
+	let runner = runner.file("./src/lib.rs", "pub fn answer() -> u32 {\n\tused::value() + later::value()\n}\n")?;
+	let (code, stdout_masked, stderr) = runner.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout_masked);
+	assert!(stderr.contains("Checking cache v0.0.1"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn no_cache() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_no_cache")?;
+	runner.run()?;
+	let (code, stdout_masked, stderr) = runner.arg("--no-cache").run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	assert!(stderr.contains("Checking cache v0.0.1"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn cache_lint_backend() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_cache_lint_backend")?.arg("--backend").arg("lint");
+	runner.run()?;
+	let (code, stdout_masked, stderr) = runner.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	assert!(!stderr.contains("Checking cache v0.0.1"), "{}", stderr);
+	Ok(())
+}
@@ -0,0 +1,59 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "cargo_flags"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+unused = { path = "unused" }
+
+[profile.ci]
+inherits = "dev"
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--output")
+		.arg("count"))
+}
+
+#[test]
+fn custom_profile() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_cargo_flags_custom_profile")?
+		.arg("--profile")
+		.arg("ci")
+		.arg("--jobs")
+		.arg("1");
+	let (code, stdout) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!("1 unused across 1 package\n", stdout);
+	assert!(runner.path().join("target").join("ci").is_dir());
+	Ok(())
+}
+
+#[test]
+fn config() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_cargo_flags_config")?
+		.arg("--config")
+		.arg("build.target-dir = \"elsewhere\"")
+		.arg("--keep-going");
+	let (code, stdout) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!("1 unused across 1 package\n", stdout);
+	assert!(runner.path().join("elsewhere").join("debug").is_dir());
+	assert!(!runner.path().join("target").exists());
+	Ok(())
+}
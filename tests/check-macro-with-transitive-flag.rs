@@ -56,7 +56,7 @@ fn macro_call_with_unused_transitive_flag() -> CargoResult<()> {
 └─── dependencies
      └─── "strum"
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
@@ -0,0 +1,65 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[package]
+name = "ci_matrix"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+extra = { path = "extra", optional = true }
+
+[target.'cfg(windows)'.dependencies]
+for-windows = { path = "for-windows" }
+
+[target.'cfg(target_os = "haiku")'.dependencies]
+for-haiku = { path = "for-haiku" }
+
+[features]
+more = ["dep:extra"]
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?;
+	for name in ["extra", "for-windows", "for-haiku"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn ci_matrix() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_ci_matrix")?.arg("ci-matrix").run_with_stderr()?;
+	assert_eq!(0, code, "{}", stderr);
+	let lines = stdout.lines().collect::<Vec<_>>();
+	assert_eq!("# Every declared dependency is checked by 2 runs:", lines[0]);
+	assert!(lines[1].ends_with(" --all-targets --features ci_matrix/more"), "{}", stdout);
+	assert!(lines[2].contains(" --target x86_64-pc-windows-msvc "), "{}", stdout);
+	assert_eq!("#   ci_matrix: `for-windows` (cfg(windows))", lines[3]);
+	assert!(stderr.contains("none of the targets checks `for-haiku` of `ci_matrix`"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn candidate() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_ci_matrix_candidate")?
+		.arg("ci-matrix")
+		.arg("--candidate")
+		.arg("x86_64-unknown-haiku")
+		.run_with_stderr()?;
+	assert_eq!(0, code, "{}", stderr);
+	assert!(stdout.starts_with("# Every declared dependency is checked by 3 runs:"), "{}", stdout);
+	assert!(stdout.contains(" --target x86_64-unknown-haiku "), "{}", stdout);
+	assert!(stdout.contains("#   ci_matrix: `for-haiku` (cfg(target_os = \"haiku\"))"), "{}", stdout);
+	assert!(!stderr.contains("none of the targets"), "{}", stderr);
+	Ok(())
+}
@@ -0,0 +1,130 @@
+mod runner;
+
+use std::sync::{Arc, Mutex};
+
+use cargo::core::compiler::{CompileMode, DefaultExecutor, Executor};
+use cargo::core::manifest::Target;
+use cargo::core::package_id::PackageId;
+use cargo::core::shell::Shell;
+use cargo::core::Workspace;
+use cargo::ops::CompileOptions;
+use cargo::util::context::GlobalContext;
+use cargo::CargoResult;
+use cargo_util::ProcessBuilder;
+use cargo_udeps::Collector;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "collector"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`collector v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused" (Cargo.toml:9)
+Note: These dependencies might be used by other targets.
+      To find dependencies that are not used by any target, enable `--all-targets`.
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used::ANSWER;\n")?
+		.path_dep("used", "pub const ANSWER :u32 = 42;\n")?
+		.path_dep("unused", "")?)
+}
+
+#[test]
+fn build_elsewhere_and_report() -> CargoResult<()> {
+	let analysis = tempfile::Builder::new().prefix("cargo_udeps_test_collector").tempdir()?;
+
+	// Some other tool, driving cargo itself
+	let builder = runner("cargo_udeps_test_collector_builder")?;
+	let cargo_home = cargo::util::homedir(builder.path()).unwrap();
+	let mut gctx = GlobalContext::new(Shell::from_write(Box::new(vec![])), builder.path().to_owned(), cargo_home);
+	gctx.configure(0, true, None, false, false, false, &None, &["binary-dep-depinfo".to_owned()], &[])?;
+	let ws = Workspace::new(&builder.path().join("Cargo.toml"), &gctx)?;
+	let compile_opts = CompileOptions::new(&gctx, CompileMode::Check { test : false })?;
+	let collector = Arc::new(Collector::new(&ws, &compile_opts)?.wrapping(Arc::new(DefaultExecutor)));
+	let exec :Arc<dyn Executor> = collector.clone();
+	cargo::ops::compile_with_exec(&ws, &compile_opts, &exec)?;
+	collector.export_analysis(analysis.path(), &mut gctx.shell())?;
+
+	let (code, stdout_masked) = runner("cargo_udeps_test_collector_reporter")?
+		.arg("--import-analysis")
+		.arg(analysis.path())
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
+
+/// Runs the commands like a build system that invokes rustc itself, keeping them.
+#[derive(Default)]
+struct External {
+	invocations :Mutex<Vec<(ProcessBuilder, PackageId, Target, CompileMode)>>,
+}
+
+impl Executor for External {
+	fn exec(&self, cmd :&ProcessBuilder, id :PackageId, target :&Target,
+			mode :CompileMode, on_stdout_line :&mut dyn FnMut(&str) -> CargoResult<()>,
+			on_stderr_line :&mut dyn FnMut(&str) -> CargoResult<()>) -> CargoResult<()> {
+		self.invocations.lock().unwrap().push((cmd.clone(), id, target.clone(), mode));
+		DefaultExecutor.exec(cmd, id, target, mode, on_stdout_line, on_stderr_line)
+	}
+}
+
+#[test]
+fn record_elsewhere_and_report() -> CargoResult<()> {
+	let analysis = tempfile::Builder::new().prefix("cargo_udeps_test_collector_record").tempdir()?;
+
+	let builder = runner("cargo_udeps_test_collector_record_builder")?;
+	let cargo_home = cargo::util::homedir(builder.path()).unwrap();
+	let mut gctx = GlobalContext::new(Shell::from_write(Box::new(vec![])), builder.path().to_owned(), cargo_home);
+	gctx.configure(0, true, None, false, false, false, &None, &["binary-dep-depinfo".to_owned()], &[])?;
+	let ws = Workspace::new(&builder.path().join("Cargo.toml"), &gctx)?;
+	let compile_opts = CompileOptions::new(&gctx, CompileMode::Check { test : false })?;
+	let external = Arc::new(External::default());
+	let exec :Arc<dyn Executor> = external.clone();
+	cargo::ops::compile_with_exec(&ws, &compile_opts, &exec)?;
+
+	// The build ran without the collector, which only gets the invocations
+	let collector = Collector::new(&ws, &compile_opts)?;
+	for (cmd, id, target, mode) in external.invocations.lock().unwrap().iter() {
+		collector.record(cmd, *id, target, *mode)?;
+	}
+	collector.export_analysis(analysis.path(), &mut gctx.shell())?;
+
+	let (code, stdout_masked) = runner("cargo_udeps_test_collector_record_reporter")?
+		.arg("--import-analysis")
+		.arg(analysis.path())
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn needs_binary_dep_depinfo() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_collector_no_depinfo")?;
+	let cargo_home = cargo::util::homedir(runner.path()).unwrap();
+	let gctx = GlobalContext::new(Shell::from_write(Box::new(vec![])), runner.path().to_owned(), cargo_home);
+	let ws = Workspace::new(&runner.path().join("Cargo.toml"), &gctx)?;
+	let compile_opts = CompileOptions::new(&gctx, CompileMode::Check { test : false })?;
+	let err = Collector::new(&ws, &compile_opts).err().unwrap();
+	assert_eq!("collecting dependency information needs `-Z binary-dep-depinfo`", err.to_string());
+	Ok(())
+}
@@ -0,0 +1,77 @@
+mod runner;
+
+use std::process::Command;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "color"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+dep = { path = "dep" }
+"#;
+
+static PLAIN :&str = r#"unused dependencies:
+`color v0.0.1 (██████████)`
+└─── dependencies
+     └─── "dep" (Cargo.toml:10)
+"#;
+
+static COLORED :&str = "\x1b[1munused dependencies:\x1b[0m
+`\x1b[1mcolor\x1b[0m v0.0.1 (██████████)`
+└─── \x1b[36mdependencies\x1b[0m
+     └─── \x1b[1;31m\"dep\"\x1b[0m (Cargo.toml:10)
+";
+
+/// Runs the binary, as the shell of the tests never supports color, and returns
+/// the report without the notes.
+fn run(runner :&Runner, args :&[&str], envs :&[(&str, &str)]) -> CargoResult<String> {
+	let output = Command::new(env!("CARGO_BIN_EXE_cargo-udeps"))
+		.arg("udeps")
+		.args(args)
+		.current_dir(runner.path())
+		.env_remove("COLUMNS")
+		.env_remove("NO_COLOR")
+		.env_remove("CLICOLOR_FORCE")
+		.env_remove("CARGO_TERM_COLOR")
+		.envs(envs.iter().copied())
+		.output()?;
+	assert_eq!(Some(1), output.status.code(), "{}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8(output.stdout)?.replace(&*runner.path().to_string_lossy(), "██████████");
+	Ok(stdout.lines().take_while(|line| !line.starts_with("Note:")).map(|line| line.to_owned() + "\n").collect())
+}
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("dep", "")?)
+}
+
+#[test]
+fn color_flag() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_color_flag")?;
+	assert_eq!(COLORED, run(&runner, &["--color", "always"], &[])?);
+	assert_eq!(PLAIN, run(&runner, &["--color", "never"], &[("CLICOLOR_FORCE", "1")])?);
+	// Not a terminal
+	assert_eq!(PLAIN, run(&runner, &["--color", "auto"], &[])?);
+	Ok(())
+}
+
+#[test]
+fn color_env() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_color_env")?;
+	assert_eq!(COLORED, run(&runner, &[], &[("CLICOLOR_FORCE", "1")])?);
+	assert_eq!(PLAIN, run(&runner, &[], &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")])?);
+	assert_eq!(COLORED, run(&runner, &[], &[("CARGO_TERM_COLOR", "always")])?);
+	Ok(())
+}
@@ -0,0 +1,76 @@
+mod runner;
+
+use std::env;
+use std::process::Command;
+
+use cargo::CargoResult;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["crates/*"]
+[package]
+name = "compat"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+static NEW_CARGO_TOML :&str = r#"[package]
+name = "new"
+version = "0.0.1"
+edition = "2024"
+publish = false
+"#;
+
+fn installed_minor() -> CargoResult<u32> {
+	let output = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into())).arg("-V").output()?;
+	let version = String::from_utf8(output.stdout)?;
+	version
+		.split_whitespace()
+		.nth(1)
+		.and_then(|version| version.split('.').nth(1)?.parse().ok())
+		.ok_or_else(|| anyhow::anyhow!("could not read the version of cargo: {}", version))
+}
+
+#[test]
+fn edition_2024_member() -> CargoResult<()> {
+	if installed_minor()? < 85 {
+		return Ok(());
+	}
+	let runner = Runner::new("cargo_udeps_test_compat_edition_2024")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.dir("./crates/new/src")?
+		.file("./crates/new/Cargo.toml", NEW_CARGO_TOML)?
+		.file("./crates/new/src/lib.rs", "")?;
+	let output = Command::new(env!("CARGO_BIN_EXE_cargo-udeps"))
+		.arg("udeps")
+		.current_dir(runner.path())
+		.output()?;
+	assert_eq!(Some(101), output.status.code());
+	let stderr = String::from_utf8(output.stderr)?;
+	let warning = format!(
+		"warning: `{}` uses the 2024 edition (1.85), which the installed cargo",
+		runner.path().join("crates/new/Cargo.toml").display(),
+	);
+	assert!(stderr.contains(&warning), "{}", stderr);
+	assert!(!stderr.contains("resolver"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn no_warning_without_newer_features() -> CargoResult<()> {
+	let (code, _, stderr) = Runner::new("cargo_udeps_test_compat_none")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.dir("./crates/old/src")?
+		.file("./crates/old/Cargo.toml", &NEW_CARGO_TOML.replace("new", "old").replace("2024", "2018"))?
+		.file("./crates/old/src/lib.rs", "")?
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert!(!stderr.contains("cargo-udeps` is built with"), "{}", stderr);
+	Ok(())
+}
@@ -0,0 +1,109 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["app", "tool"]
+
+[workspace.metadata.cargo-udeps.ignore]
+normal = ["a"]
+
+[workspace.metadata.cargo-udeps.lints]
+unused-normal = "warn"
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+a = { path = "../a" }
+b = { path = "../b" }
+"#;
+
+fn runner(prefix :&str, config_file :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.file("./.cargo-udeps.toml", config_file)?;
+	for name in ["app", "tool"] {
+		runner = runner
+			.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &MEMBER_CARGO_TOML.replace("{}", name))?
+			.file(&format!("./{}/src/lib.rs", name), "")?;
+	}
+	for name in ["a", "b"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+/// The unused normal dependencies by package name.
+fn unused(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let unused = report["unused_deps"]
+		.as_object()
+		.unwrap()
+		.iter()
+		.map(|(id, deps)| (id.split(' ').next().unwrap().to_owned(), deps["normal"].clone()))
+		.collect::<serde_json::Map<_, _>>();
+	Ok(unused.into())
+}
+
+#[test]
+fn config_file_over_metadata() -> CargoResult<()> {
+	// `ignore` replaces the one of the table, `lints` is left as it is
+	let (code, stdout) = runner("cargo_udeps_test_config_file_over_metadata", "output = \"json\"\n\n[ignore]\nnormal = [\"b\"]\n")?
+		.arg("--workspace")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!(serde_json::json!({ "app": ["a"], "tool": ["a"] }), unused(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn config_file_exclude() -> CargoResult<()> {
+	let (_, stdout) = runner("cargo_udeps_test_config_file_exclude", "output = \"json\"\nexclude = [\"tool\"]\n")?
+		.arg("--workspace")
+		.run()?;
+	assert_eq!(serde_json::json!({ "app": ["b"] }), unused(&stdout)?);
+	// Unless asked for by name
+	let (_, stdout) = runner("cargo_udeps_test_config_file_exclude_by_name", "output = \"json\"\nexclude = [\"tool\"]\n")?
+		.arg("-p")
+		.arg("tool")
+		.run()?;
+	assert_eq!(serde_json::json!({ "tool": ["b"] }), unused(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn config_file_command_line_wins() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_config_file_command_line_wins", "output = \"json\"\nbackend = \"lint\"\n")?
+		.arg("--workspace");
+	let (_, stdout) = runner.run()?;
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	assert_eq!("lint", report["provenance"]["backend"]);
+	let (_, stdout) = runner
+		.arg("--backend")
+		.arg("depinfo")
+		.arg("--output")
+		.arg("count")
+		.run()?;
+	assert_eq!("2 unused across 2 packages\n", stdout);
+	Ok(())
+}
+
+#[test]
+fn config_file_invalid() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_config_file_invalid", "output = \"yaml\"\n")?
+		.run()
+		.unwrap_err();
+	assert!(err.to_string().starts_with("could not parse `"), "{}", err);
+	assert!(err.to_string().ends_with(".cargo-udeps.toml`"), "{}", err);
+	Ok(())
+}
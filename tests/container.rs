@@ -0,0 +1,68 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "container"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`container v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used::ANSWER;\n")?
+		.path_dep("used", "pub const ANSWER :u32 = 42;\n")?
+		.path_dep("unused", "")?)
+}
+
+#[test]
+fn container_command() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_container_command")?
+		.arg("--container-command")
+		.arg("env")
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn container_path() -> CargoResult<()> {
+	// The symlink plays the role of the directory the workspace is mounted at
+	let runner = runner("cargo_udeps_test_container_path")?;
+	let mounted = tempfile::Builder::new().prefix("cargo_udeps_test_container_path_mount").tempdir()?;
+	let mount = mounted.path().join("project");
+	std::os::unix::fs::symlink(runner.path(), &mount)?;
+	let map = format!("{}={}", runner.path().display(), mount.display());
+	let (code, stdout_masked) = runner
+		.arg("--container-command")
+		.arg("env")
+		.arg("--container-path")
+		.arg(map)
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
@@ -0,0 +1,99 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+exclude = ["bar"]
+"#;
+
+static A_CARGO_TOML :&str = r#"[package]
+name = "a"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+foo = { path = "../bar", package = "bar" }
+"#;
+
+static B_CARGO_TOML :&str = r#"[package]
+name = "b"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+bar = { path = "../bar" }
+"#;
+
+fn runner(prefix :&str, a_lib_rs :&str, b_lib_rs :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", A_CARGO_TOML)?
+		.file("./a/src/lib.rs", a_lib_rs)?
+		.dir("./b/src")?
+		.file("./b/Cargo.toml", B_CARGO_TOML)?
+		.file("./b/src/lib.rs", b_lib_rs)?
+		.path_dep("bar", "")?
+		.arg("--workspace")
+		.arg("--all-targets"))
+}
+
+#[test]
+fn renamed_in_one_member_unused() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_cross_renamed_a", "", "pub use bar as _;\n")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`a v0.0.1 (██████████/a)`
+└─── dependencies
+     └─── "foo" (a/Cargo.toml:7) (package `bar`)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn unrenamed_in_other_member_unused() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_cross_renamed_b", "pub use foo as _;\n", "")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`b v0.0.1 (██████████/b)`
+└─── dependencies
+     └─── "bar" (b/Cargo.toml:7)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn ignored_by_the_name_in_the_manifest() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_cross_renamed_ignore", "", "")?
+		.cargo_toml(&format!("{}[workspace.metadata.cargo-udeps.ignore]\nnormal = [\"foo\"]\n", CARGO_TOML))?;
+	let (code, stdout_masked) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`b v0.0.1 (██████████/b)`
+└─── dependencies
+     └─── "bar" (b/Cargo.toml:7)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
@@ -0,0 +1,79 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "default-ignores"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+"#;
+
+/// An analysis like older versions wrote it, without the `--extern` paths and
+/// with the artifact of the dependency unknown.
+static ANALYSIS :&str = r#"{
+	"units": [{
+		"package": { "name": "default-ignores", "version": "0.0.1" },
+		"extern_crate_names": ["used"],
+		"used_stems": ["libused-0123456789abcdef"]
+	}],
+	"lib_stems": {}
+}"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("used", "")?
+		.dir("./analysis")?
+		.file("./analysis/udeps-analysis.json", ANALYSIS)?
+		.arg("--all-targets")
+		.arg("--import-analysis")
+		.arg("analysis"))
+}
+
+#[test]
+fn print_default_ignores() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_print_default_ignores")?
+		.arg("--print-default-ignores")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		r#"Heuristics that can hide unused dependencies:
+  lib-name-fallback: Dependencies whose artifacts aren't known, like in analyses of older versions, are matched by their library name. Of several dependencies with the same library name, all count as used if one of them is (see `cargo udeps --explain ambiguous`).
+  crate-level-allow: Dependencies are not reported if a target that can use them allows or expects the `unused_crate_dependencies` lint of rustc in its root, like `#![allow(unused_crate_dependencies)]`, as they are kept on purpose then.
+  build-script-metadata: Normal dependencies with `links` are not reported if the build script of the package mentions their `DEP_<LINKS>_` variables, as it gets their metadata through them. Only the source file of the build script is searched, not the modules it pulls in with `mod` or `include!`.
+
+Pass `--no-default-ignores` to turn them off.
+"#,
+		stdout,
+	);
+	Ok(())
+}
+
+#[test]
+fn lib_name_fallback() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_lib_name_fallback")?.run()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout);
+	Ok(())
+}
+
+#[test]
+fn no_default_ignores() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_no_default_ignores")?
+		.arg("--no-default-ignores")
+		.arg("--message-format")
+		.arg("short")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("default-ignores: unused dependency `used` (normal)\n", stdout);
+	Ok(())
+}
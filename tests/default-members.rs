@@ -0,0 +1,111 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["app", "xtask"]
+default-members = ["app"]
+exclude = ["unused"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "../unused" }
+"#;
+
+static EXPECTED_DEFAULT :&str = r#"unused dependencies:
+`app v0.0.1 (██████████/app)`
+└─── dependencies
+     └─── "unused" (app/Cargo.toml:7)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+static EXPECTED_ALL :&str = r#"unused dependencies:
+`app v0.0.1 (██████████/app)`
+└─── dependencies
+     └─── "unused" (app/Cargo.toml:7)
+`xtask v0.0.1 (██████████/xtask)`
+└─── dependencies
+     └─── "unused" (xtask/Cargo.toml:7)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+static EXPECTED_XTASK :&str = r#"unused dependencies:
+`xtask v0.0.1 (██████████/xtask)`
+└─── dependencies
+     └─── "unused" (xtask/Cargo.toml:7)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.path_dep("unused", "")?
+		.arg("--all-targets");
+	for member in ["app", "xtask"] {
+		runner = runner
+			.dir(&format!("./{}/src", member))?
+			.file(&format!("./{}/Cargo.toml", member), &MEMBER_CARGO_TOML.replace("{}", member))?
+			.file(&format!("./{}/src/main.rs", member), "fn main() {}\n")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn default_members() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_default_members")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED_DEFAULT, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn include_excluded_members() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_include_excluded_members")?
+		.arg("--include-excluded-members")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED_ALL, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn package_from_root() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_default_members_package")?
+		.arg("-p")
+		.arg("xtask")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED_XTASK, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn package_not_member() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_default_members_not_member")?
+		.arg("-p")
+		.arg("unused");
+	let err = runner.run().unwrap_err();
+	assert_eq!(
+		format!(
+			"package `unused` is not a member of the workspace `{}`, and only members can be checked\n  \
+			the members are `app`, `xtask`",
+			runner.path().display(),
+		),
+		err.to_string(),
+	);
+	Ok(())
+}
@@ -0,0 +1,47 @@
+mod runner;
+
+use cargo::core::dependency::DepKind;
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+/// Checks the workspace in `fixtures/detection/<fixture>` with both backends and
+/// compares the unused dependencies of the report with `expected`.
+fn check(fixture :&str, expected :&[(DepKind, &str, Option<&str>)]) -> CargoResult<()> {
+	for backend in ["depinfo", "lint"] {
+		let report = Runner::new(&format!("cargo_udeps_test_detection_{}_{}", fixture, backend))?
+			.fixture(&format!("detection/{}", fixture))?
+			.arg("--all-targets")
+			.arg("--backend")
+			.arg(backend)
+			.check()?;
+		let found = report.packages
+			.iter()
+			.flat_map(|package| &package.unused)
+			.map(|dep| (dep.kind, dep.name.as_str(), dep.platform.as_deref()))
+			.collect::<Vec<_>>();
+		assert_eq!(expected, found, "with the {} backend", backend);
+		assert_eq!(expected.is_empty(), report.success);
+	}
+	Ok(())
+}
+
+macro_rules! fixture {
+	($(#[$attr:meta])* $name:ident, $expected:expr) => {
+		$(#[$attr])*
+		#[test]
+		fn $name() -> CargoResult<()> {
+			check(stringify!($name), $expected)
+		}
+	};
+}
+
+fixture!(unused_normal, &[(DepKind::Normal, "unused", None)]);
+fixture!(unused_build, &[(DepKind::Build, "unused", None)]);
+// Reported by the key in `Cargo.toml`, not by the package
+fixture!(renamed, &[(DepKind::Normal, "unused-alias", None)]);
+fixture!(test_only, &[(DepKind::Development, "unused", None)]);
+fixture!(#[cfg(unix)] cfg_gated, &[(DepKind::Normal, "unused", Some("cfg(unix)"))]);
+// `used` is only expanded through `#[derive]`
+fixture!(proc_macro, &[(DepKind::Normal, "unused", None)]);
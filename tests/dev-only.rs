@@ -0,0 +1,128 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "dev_only"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+for-tests = { path = "for-tests" }
+[dev-dependencies]
+bare = { path = "bare" }
+"#;
+
+static LIB_RS :&str = r#"pub fn answer() -> u32 {
+	used::value()
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn answer() {
+		assert_eq!(super::answer(), for_tests::value());
+	}
+}
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for dep in ["used", "for-tests", "bare"] {
+		runner = runner
+			.path_dep(dep, "pub fn value() -> u32 { 42 }\n")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn dev_only_human() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_dev_only_human")?
+		.arg("--all-targets")
+		.arg("--no-wrap")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`dev_only v0.0.1 (██████████)`
+├─── dev-dependencies
+│    └─── "bare" (Cargo.toml:11)
+└─── dev-only dependencies
+     └─── "for-tests" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn dev_only_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_dev_only_json")?
+		.arg("--all-targets")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!([]), unused_deps["normal"]);
+	assert_eq!(serde_json::json!(["bare"]), unused_deps["development"]);
+	assert_eq!(serde_json::json!(["for-tests"]), unused_deps["dev_only"]);
+	Ok(())
+}
+
+#[test]
+fn tests_only() -> CargoResult<()> {
+	// Without the library checked on its own, nothing tells the dependency is only used by tests
+	let (code, stdout) = runner("cargo_udeps_test_dev_only_tests_only")?
+		.arg("--lib")
+		.arg("--profile")
+		.arg("test")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!(["bare"]), unused_deps["development"]);
+	assert!(unused_deps.get("dev_only").is_none());
+	Ok(())
+}
+
+#[test]
+fn show_unused_by_target() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_dev_only_show_unused_by_target")?
+		.dir("./examples")?
+		.file("./examples/demo.rs", "fn main() {\n\tprintln!(\"{}\", for_tests::value());\n}\n")?
+		.arg("--all-targets")
+		.arg("--show-unused-by-target")
+		.arg("--no-wrap")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`dev_only v0.0.1 (██████████)`
+├─── dev-dependencies
+│    └─── "bare" (Cargo.toml:11)
+└─── dev-only dependencies
+     └─── "for-tests" (Cargo.toml:9) (used by example "demo", lib (unit tests))
+Note: The dev-only dependencies can be moved to `[dev-dependencies]`.
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
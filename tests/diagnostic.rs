@@ -0,0 +1,86 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["member"]
+
+[package]
+name = "diagnostic"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+a = { path = "a" }
+
+[target.'cfg(all())'.dev-dependencies]
+"b" = { path = "b" }
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "member"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[build-dependencies]
+  a = { path = "../a" }
+"#;
+
+static EXPECTED :&str = r#"warning[unused-normal]: unused dependency `a`
+  --> Cargo.toml:11:1
+   |
+11 | a = { path = "a" }
+   | ^
+   |
+   = note: `a` is a dependency of `diagnostic v0.0.1`
+
+warning[unused-dev]: unused dependency `b`
+  --> Cargo.toml:14:1
+   |
+14 | "b" = { path = "b" }
+   | ^^^
+   |
+   = note: `b` is a dev-dependency of `diagnostic v0.0.1`
+
+warning[unused-build]: unused dependency `a`
+ --> member/Cargo.toml:8:3
+  |
+8 |   a = { path = "../a" }
+  |   ^
+  |
+  = note: `a` is a build-dependency of `member v0.0.1`
+
+warning: 3 unused dependencies
+Some warnings have detailed explanations: unused-build, unused-dev, unused-normal.
+For more information about a warning, try `cargo udeps --explain unused-build`.
+"#;
+
+#[test]
+fn points_at_the_manifest() -> CargoResult<()> {
+	let mut runner = Runner::new("cargo_udeps_test_diagnostic")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.dir("./member/src")?
+		.file("./member/Cargo.toml", MEMBER_CARGO_TOML)?
+		.file("./member/src/lib.rs", "")?
+		.file("./member/build.rs", "fn main() {}\n")?;
+	for name in ["a", "b"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	let (code, stdout_masked) = runner
+		.arg("--workspace")
+		.arg("--all-targets")
+		.arg("--output")
+		.arg("diagnostic")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
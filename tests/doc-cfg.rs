@@ -0,0 +1,85 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "doc_cfg"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+for-docs = { path = "for-docs" }
+bare = { path = "bare" }
+"#;
+
+static LIB_RS :&str = r#"/// See [`for_docs::documented`].
+#[cfg(docsrs)]
+pub fn documented() {
+	for_docs::documented()
+}
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?
+		.path_dep("for-docs", "pub fn documented() {}\n")?
+		.path_dep("bare", "")?
+		.arg("--all-targets"))
+}
+
+static EXPECTED :&str = r#"unused dependencies:
+`doc_cfg v0.0.1 (██████████)`
+├─── dependencies
+│    └─── "bare" (Cargo.toml:9)
+└─── doc-only dependencies
+     └─── "for-docs" (Cargo.toml:8)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn doc_cfg_human() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_doc_cfg_human")?
+		.arg("--doc-cfg")
+		.arg("--no-wrap")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn doc_cfg_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_doc_cfg_json")?
+		.arg("--doc-cfg")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!(["bare"]), unused_deps["normal"]);
+	assert_eq!(serde_json::json!(["for-docs"]), unused_deps["doc_only"]);
+	Ok(())
+}
+
+#[test]
+fn without_doc_cfg() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_without_doc_cfg")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!(["bare", "for-docs"]), unused_deps["normal"]);
+	assert!(unused_deps.get("doc_only").is_none());
+	Ok(())
+}
@@ -0,0 +1,91 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "doctests"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+used = { path = "used" }
+
+[dev-dependencies]
+doc-helper = { path = "doc-helper" }
+unused-dev = { path = "unused-dev" }
+"#;
+
+/// `doc-helper` is only used by the doctest.
+static LIB_RS :&str = r#"/// ```
+/// doc_helper::f();
+/// doctests::f();
+/// ```
+pub fn f() {
+	used::f();
+}
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for name in ["used", "doc-helper", "unused-dev"] {
+		runner = runner
+			.path_dep(name, "pub fn f() {}\n")?;
+	}
+	Ok(runner.arg("--all-targets").arg("--output").arg("json"))
+}
+
+fn note(stdout :&str) -> CargoResult<String> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	Ok(report["note"].as_str().unwrap_or_default().to_owned())
+}
+
+fn unused(stdout :&str) -> CargoResult<(serde_json::Value, serde_json::Value)> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok((unused_deps["normal"].clone(), unused_deps["development"].clone()))
+}
+
+#[test]
+fn doctests_not_compiled() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_doctests_not_compiled")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!((serde_json::json!([]), serde_json::json!(["doc-helper", "unused-dev"])), unused(&stdout)?);
+	assert!(note(&stdout)?.contains("only detected with `--doctests`"), "{}", stdout);
+	Ok(())
+}
+
+#[test]
+fn doctests_compiled() -> CargoResult<()> {
+	for backend in ["depinfo", "lint"] {
+		let (code, stdout) = runner(&format!("cargo_udeps_test_doctests_compiled_{}", backend))?
+			.arg("--backend")
+			.arg(backend)
+			.arg("--doctests")
+			.run()?;
+		assert_eq!(1, code);
+		assert_eq!((serde_json::json!([]), serde_json::json!(["unused-dev"])), unused(&stdout)?);
+		assert!(!note(&stdout)?.contains("doc-tests"), "{}", stdout);
+	}
+	Ok(())
+}
+
+#[test]
+fn doctests_not_compiling() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_doctests_not_compiling")?
+		.file("./src/lib.rs", "/// ```\n/// doc_helper::missing();\n/// ```\npub fn f() {\n\tused::f();\n}\n")?
+		.arg("--doctests")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!((serde_json::json!([]), serde_json::json!(["doc-helper", "unused-dev"])), unused(&stdout)?);
+	assert!(stderr.contains("the doctests of `doctests` don't compile, their dependencies are not analyzed"), "{}", stderr);
+	Ok(())
+}
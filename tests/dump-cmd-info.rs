@@ -0,0 +1,52 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "dump-cmd-info"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+dep = { path = "dep" }
+"#;
+
+#[test]
+fn dump_cmd_info() -> CargoResult<()> {
+	let (code, _, stderr) = Runner::new("cargo_udeps_test_dump_cmd_info")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("dep", "")?
+		.arg("--dump-cmd-info")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	let dumps = stderr
+		.lines()
+		.filter_map(|line| line.strip_prefix("info: captured "))
+		.map(serde_json::from_str::<serde_json::Value>)
+		.collect::<Result<Vec<_>, _>>()?;
+	let dump = |crate_name :&str| {
+		dumps
+			.iter()
+			.find(|dump| dump["crate_name"] == crate_name)
+			.unwrap_or_else(|| panic!("should dump `{}`: {}", crate_name, stderr))
+	};
+
+	let member = dump("dump_cmd_info");
+	assert_eq!("lib", member["crate_type"]);
+	assert_eq!(false, member["cap_lints_allow"]);
+	assert_eq!(serde_json::json!(["dep"]), member["extern_crate_names"]);
+	let extern_stems = member["extern_stems"].as_object().unwrap();
+	assert_eq!(1, extern_stems.len());
+	assert!(extern_stems.keys().all(|stem| stem.starts_with("libdep-")), "{:?}", extern_stems);
+	assert!(member["out_dir"].as_str().unwrap().starts_with("██████████/target/"), "{}", member);
+
+	let dep = dump("dep");
+	assert_eq!(serde_json::json!([]), dep["extern_crate_names"]);
+	Ok(())
+}
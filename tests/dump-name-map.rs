@@ -0,0 +1,60 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "dump-name-map"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+alias = { package = "real-name", path = "real-name" }
+[build-dependencies]
+builder = { path = "builder" }
+"#;
+
+#[test]
+fn dump_name_map() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_dump_name_map")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("real-name", "")?
+		.path_dep("builder", "")?
+		.arg("--all-targets")
+		.arg("--dump-name-map")
+		.arg("names.json");
+	let (code, _) = runner.run()?;
+	assert_eq!(1, code);
+	let dump = runner.read_file("names.json")?.replace(&*runner.path().to_string_lossy(), "██████████");
+	assert_eq!(
+		serde_json::json!({
+			"dump-name-map v0.0.1 (██████████)": {
+				"normal": {
+					"by_extern_crate_name": { "alias": "alias" },
+					"by_lib_name": { "real_name": ["alias"] },
+					"by_package_id": { "real-name v0.0.1 (██████████/real-name)": "alias" },
+					"non_lib": [],
+				},
+				"development": {
+					"by_extern_crate_name": {},
+					"by_lib_name": {},
+					"by_package_id": {},
+					"non_lib": [],
+				},
+				"build": {
+					"by_extern_crate_name": { "builder": "builder" },
+					"by_lib_name": { "builder": ["builder"] },
+					"by_package_id": { "builder v0.0.1 (██████████/builder)": "builder" },
+					"non_lib": [],
+				},
+			},
+		}),
+		serde_json::from_str::<serde_json::Value>(&dump)?,
+	);
+	Ok(())
+}
@@ -0,0 +1,117 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b", "c"]
+exclude = ["foo1", "foo2", "mid"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+"#;
+
+static FOO_CARGO_TOML :&str = r#"[package]
+name = "foo"
+version = "{}"
+edition = "2021"
+publish = false
+"#;
+
+static MID_CARGO_TOML :&str = r#"[package]
+name = "mid"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+foo = { path = "../foo2" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./mid/src")?
+		.file("./mid/Cargo.toml", MID_CARGO_TOML)?
+		.file("./mid/src/lib.rs", "pub use foo as _;\n")?;
+	// `b` gets the second version of `foo` through `mid`
+	for (name, dependency, path) in [("a", "foo", "foo1"), ("b", "mid", "mid"), ("c", "foo", "foo1")] {
+		let cargo_toml = format!("{}{} = {{ path = \"../{}\" }}\n", MEMBER_CARGO_TOML.replace("{}", name), dependency, path);
+		runner = runner
+			.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &cargo_toml)?
+			.file(&format!("./{}/src/lib.rs", name), &format!("pub use {} as _;\n", dependency))?;
+	}
+	for (dir, version) in [("foo1", "1.0.0"), ("foo2", "2.0.0")] {
+		runner = runner
+			.dir(&format!("./{}/src", dir))?
+			.file(&format!("./{}/Cargo.toml", dir), &FOO_CARGO_TOML.replace("{}", version))?
+			.file(&format!("./{}/src/lib.rs", dir), "")?;
+	}
+	Ok(runner.arg("--workspace").arg("--check-duplicates"))
+}
+
+#[test]
+fn duplicates_human() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_duplicates_human")?.run()?;
+	// Duplicates don't make the run fail
+	assert_eq!(0, code);
+	assert_eq!(
+		"All deps seem to have been used.\n\
+		crates in several versions:\n  \
+		`foo`\n    \
+		v1.0.0, for `a v0.0.1`, `c v0.0.1`\n    \
+		v2.0.0, for `b v0.0.1`\n",
+		stdout,
+	);
+	Ok(())
+}
+
+#[test]
+fn duplicates_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_duplicates_json")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(0, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let short = |id :&serde_json::Value| id.as_str().unwrap().split(' ').take(2).collect::<Vec<_>>().join(" ");
+	let versions = report["duplicates"]["foo"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|version| {
+			let members = version["members"].as_array().unwrap().iter().map(short).collect::<Vec<_>>();
+			(short(&version["package"]), members)
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			("foo 1.0.0".to_owned(), vec!["a 0.0.1".to_owned(), "c 0.0.1".to_owned()]),
+			("foo 2.0.0".to_owned(), vec!["b 0.0.1".to_owned()]),
+		],
+		versions,
+	);
+	Ok(())
+}
+
+#[test]
+fn duplicates_none() -> CargoResult<()> {
+	let (code, stdout) = Runner::new("cargo_udeps_test_duplicates_none")?
+		.cargo_toml("[workspace]\n[package]\nname = \"single\"\nversion = \"0.0.1\"\nedition = \"2021\"\npublish = false\n")?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("--check-duplicates")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout);
+	Ok(())
+}
@@ -0,0 +1,83 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "enrich"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+described = { path = "described" }
+bare = { path = "bare" }
+"#;
+
+static DESCRIBED_CARGO_TOML :&str = r#"[package]
+name = "described"
+version = "0.0.1"
+edition = "2018"
+publish = false
+description = """
+Does things,
+and more things"""
+repository = "https://example.com/described"
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.dir("./described/src")?
+		.file("./described/Cargo.toml", DESCRIBED_CARGO_TOML)?
+		.file("./described/src/lib.rs", "")?
+		.path_dep("bare", "")?
+		.arg("--all-targets")
+		.arg("--enrich"))
+}
+
+static EXPECTED :&str = r#"unused dependencies:
+`enrich v0.0.1 (██████████)`
+└─── dependencies
+     ├─── "bare" (Cargo.toml:9)
+     └─── "described" (Cargo.toml:8) - Does things, and more things <https://example.com/described>
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn enrich_human() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_enrich_human")?
+		.arg("--no-wrap")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn enrich_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_enrich_json")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(
+		serde_json::json!({
+			"bare" : {},
+			"described" : {
+				"description" : "Does things, and more things",
+				"repository" : "https://example.com/described",
+			},
+		}),
+		unused_deps["info"],
+	);
+	Ok(())
+}
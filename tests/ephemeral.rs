@@ -0,0 +1,56 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "ephemeral"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "unused" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`ephemeral v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused" (Cargo.toml:8)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets")
+		.arg("--ephemeral"))
+}
+
+#[test]
+fn ephemeral() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_ephemeral")?;
+	let (code, stdout_masked) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	assert!(!runner.path().join("target").exists());
+	Ok(())
+}
+
+#[test]
+fn ephemeral_with_target_dir() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_ephemeral_with_target_dir")?
+		.arg("--target-dir")
+		.arg("target")
+		.run()
+		.unwrap_err();
+	assert!(err.to_string().contains("cannot be used with"), "{}", err);
+	Ok(())
+}
@@ -0,0 +1,113 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "explain"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+#[test]
+fn explain() -> CargoResult<()> {
+	let (code, stdout) = Runner::new("cargo_udeps_test_explain")?
+		.cargo_toml(CARGO_TOML)?
+		.arg("--explain")
+		.arg("unused-dev")
+		.run()?;
+	assert_eq!(0, code);
+	assert!(stdout.starts_with("A dependency of `[dev-dependencies]` is not used"), "{}", stdout);
+	assert!(stdout.contains("`--all-targets`"), "{}", stdout);
+	Ok(())
+}
+
+#[test]
+fn explain_unknown() -> CargoResult<()> {
+	// Anything that isn't a code is looked up as a dependency after the check
+	let err = Runner::new("cargo_udeps_test_explain_unknown")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("--explain")
+		.arg("unused")
+		.run()
+		.unwrap_err();
+	assert_eq!(
+		"`unused` is neither a dependency of the checked packages nor a kind of finding, \
+		which are: unused-normal, unused-dev, unused-build, non-lib, ambiguous",
+		err.to_string(),
+	);
+	Ok(())
+}
+
+static DEPENDENCY_CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "explain_dependency"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+used = { path = "used" }
+renamed = { path = "unused", package = "unused" }
+
+[dev-dependencies]
+testing = { path = "testing" }
+"#;
+
+fn dependency_runner(prefix :&str, dependency :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(DEPENDENCY_CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used::f;\n")?;
+	for name in ["used", "unused", "testing"] {
+		runner = runner
+			.path_dep(name, "pub fn f() {}\n")?;
+	}
+	Ok(runner.arg("--explain").arg(dependency))
+}
+
+#[test]
+fn explain_used_dependency() -> CargoResult<()> {
+	let (code, stdout) = dependency_runner("cargo_udeps_test_explain_used_dependency", "used")?.run()?;
+	assert_eq!(0, code);
+	let mut lines = stdout.lines();
+	assert_eq!(Some("`used` (normal) of `explain_dependency v0.0.1 (██████████)`: used"), lines.next());
+	let evidence = lines.next().unwrap();
+	assert!(evidence.starts_with("  lib uses it as `used`: `libused-"), "{}", stdout);
+	assert!(evidence.ends_with("` is in the depinfo, matched by its `--extern` path"), "{}", stdout);
+	assert_eq!(None, lines.next());
+	Ok(())
+}
+
+#[test]
+fn explain_unused_dependency() -> CargoResult<()> {
+	// Looked up by its package name, as it is renamed
+	let (code, stdout) = dependency_runner("cargo_udeps_test_explain_unused_dependency", "unused")?.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		"`renamed` (normal) of `explain_dependency v0.0.1 (██████████)`: unused\n  \
+		it is passed with `--extern` to lib, but none of them use it\n",
+		stdout,
+	);
+	Ok(())
+}
+
+#[test]
+fn explain_unchecked_dependency() -> CargoResult<()> {
+	let (code, stdout) = dependency_runner("cargo_udeps_test_explain_unchecked_dependency", "testing")?.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		"`testing` (development) of `explain_dependency v0.0.1 (██████████)`: not checked\n  \
+		it isn't passed to any of the checked units, it may be optional, for another platform, \
+		or only needed by targets that weren't checked (see `--all-targets`)\n",
+		stdout,
+	);
+	Ok(())
+}
@@ -0,0 +1,79 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "feature_combinations"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+bare = { path = "bare" }
+net = { path = "net", optional = true }
+stdio = { path = "stdio" }
+
+[features]
+server = ["dep:net"]
+minimal = []
+"#;
+
+static LIB_RS :&str = r#"#[cfg(feature = "server")]
+pub use net::listen;
+
+#[cfg(not(feature = "minimal"))]
+pub use stdio::listen as print;
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for name in ["bare", "net", "stdio"] {
+		runner = runner
+			.path_dep(name, "pub fn listen() {}\n")?;
+	}
+	Ok(runner.arg("--output").arg("json"))
+}
+
+fn unused_normal(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok(unused_deps["normal"].clone())
+}
+
+#[test]
+fn all_features() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_combinations_all_features")?
+		.arg("--all-features")
+		.run()?;
+	assert_eq!(1, code);
+	// `stdio` is only used without `minimal`
+	assert_eq!(serde_json::json!(["bare", "stdio"]), unused_normal(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn feature_combinations() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_feature_combinations")?
+		.arg("--check-feature-combinations")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!(["bare"]), unused_normal(&stdout)?);
+	for description in [
+		"no features",
+		"the default features",
+		"all features",
+		"the feature `feature_combinations/minimal`",
+		"the feature `feature_combinations/server`",
+	] {
+		assert!(stderr.contains(&format!("Checking with {}", description)), "{}", stderr);
+	}
+	Ok(())
+}
@@ -0,0 +1,84 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[workspace.metadata.cargo-udeps.feature-sets]
+server = ["server"]
+client = ["client"]
+
+[package]
+name = "feature_sets"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+bare = { path = "bare" }
+net = { path = "net", optional = true }
+wasm = { path = "wasm", optional = true }
+
+[features]
+server = ["dep:net"]
+client = ["dep:net", "dep:wasm"]
+"#;
+
+static LIB_RS :&str = r#"#[cfg(feature = "server")]
+pub use net::listen;
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for name in ["bare", "net", "wasm"] {
+		runner = runner
+			.path_dep(name, "pub fn listen() {}\n")?;
+	}
+	Ok(runner.arg("--output").arg("json"))
+}
+
+fn unused_normal(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok(unused_deps["normal"].clone())
+}
+
+#[test]
+fn all_feature_sets() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_all_feature_sets")?
+		.arg("--feature-set")
+		.arg("all")
+		.run()?;
+	assert_eq!(1, code);
+	// `net` is used with `server`, although not with `client`
+	assert_eq!(serde_json::json!(["bare", "wasm"]), unused_normal(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn one_feature_set() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_one_feature_set")?
+		.arg("--feature-set")
+		.arg("client")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!(["bare", "net", "wasm"]), unused_normal(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn unknown_feature_set() -> CargoResult<()> {
+	let result = runner("cargo_udeps_test_unknown_feature_set")?
+		.arg("--feature-set")
+		.arg("embedded")
+		.run();
+	let error = result.expect_err("should fail").to_string();
+	assert_eq!("no feature set `embedded`, expected `all` or one of: client, server", error);
+	Ok(())
+}
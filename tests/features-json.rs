@@ -0,0 +1,43 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "features_json"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[features]
+default = ["std"]
+std = []
+extra = []
+unused-feature = []
+"#;
+
+#[test]
+fn features_and_resolver() -> CargoResult<()> {
+	let (code, stdout_masked) = Runner::new("cargo_udeps_test_features_json")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("--features")
+		.arg("extra")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		concat!(
+			r#"{"success":true,"packages_checked":1,"provenance":██████████,"resolver":"1","#,
+			r#""features":{"features_json 0.0.1 (path+file://██████████)":["default","extra","std"]},"#,
+			r#""unused_deps":{},"note":null}"#,
+			"\n",
+		),
+		runner::mask_provenance(&stdout_masked),
+	);
+	Ok(())
+}
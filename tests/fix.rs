@@ -0,0 +1,74 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "fix"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+# Used by the library
+used = { path = "used" }
+renamed = { package = "bare", path = "bare" }
+
+[build-dependencies]
+unused-build = { path = "unused-build" } # never needed
+"#;
+
+static EXPECTED_CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "fix"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+# Used by the library
+used = { path = "used" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used::f;\n")?
+		.file("./build.rs", "fn main() {}\n")?;
+	for name in ["used", "bare", "unused-build"] {
+		runner = runner
+			.path_dep(name, "pub fn f() {}\n")?;
+	}
+	Ok(runner.arg("--all-targets").arg("--fix").arg("--allow-no-vcs"))
+}
+
+#[test]
+fn fix() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_fix")?;
+	let (code, _, stderr) = runner.run_with_stderr()?;
+	// The exit code is about what was found
+	assert_eq!(1, code);
+	assert!(stderr.contains("Removing renamed from dependencies of `fix`"), "{}", stderr);
+	assert!(stderr.contains("Removing unused-build from build-dependencies of `fix`"), "{}", stderr);
+	assert_eq!(EXPECTED_CARGO_TOML, runner.read_file("Cargo.toml")?);
+
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	Ok(())
+}
+
+#[test]
+fn fix_dry_run() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_fix_dry_run")?.arg("--dry-run");
+	let (code, _, stderr) = runner.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert!(stderr.contains("Removing renamed from dependencies of `fix`"), "{}", stderr);
+	assert_eq!(CARGO_TOML, runner.read_file("Cargo.toml")?);
+	Ok(())
+}
@@ -0,0 +1,2 @@
+#[derive(used::Answer)]
+pub struct Used;
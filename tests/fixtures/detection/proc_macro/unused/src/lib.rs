@@ -0,0 +1,8 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+
+#[proc_macro_derive(Answer)]
+pub fn answer(_ :TokenStream) -> TokenStream {
+	TokenStream::new()
+}
@@ -0,0 +1 @@
+pub use used_alias::f;
@@ -0,0 +1,4 @@
+#[test]
+fn t() {
+	used::f();
+}
@@ -0,0 +1,63 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "format_template"
+version = "0.0.1"
+[dependencies]
+byteorder = "1.0.0"
+[dev-dependencies]
+unused = { path = "unused" }
+"#;
+
+static TEMPLATE :&str = r#"{{#if success}}:tada: all {{packages_checked}} packages are clean{{else}}:warning: {{unused_count}} unused dependencies
+{{#each packages}}*{{name}}* {{version}}
+{{#each deps}}• `{{name}}` ({{kind}}) in {{manifest_path}}
+{{/each}}{{/each}}See https://dashboard.example/udeps{{/if}}
+"#;
+
+#[test]
+fn format_template() -> CargoResult<()> {
+	let (code, stdout_masked) = Runner::new("cargo_udeps_test_format_template")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.file("./report.hbs", TEMPLATE)?
+		.arg("--all-targets")
+		.arg("--format-template")
+		.arg("report.hbs")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#":warning: 2 unused dependencies
+*format_template* 0.0.1
+• `byteorder` (normal) in Cargo.toml
+• `unused` (development) in Cargo.toml
+See https://dashboard.example/udeps
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn invalid_template() -> CargoResult<()> {
+	let err = Runner::new("cargo_udeps_test_invalid_template")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.file("./report.hbs", "{{#each packages}}{{name}}")?
+		.arg("--format-template")
+		.arg("report.hbs")
+		.run()
+		.unwrap_err();
+	assert_eq!("missing `{{/each}}` in template", err.root_cause().to_string());
+	Ok(())
+}
@@ -0,0 +1,50 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+// No `Cargo.toml`, as no workspace is loaded
+
+#[test]
+fn generate_completion() -> CargoResult<()> {
+	for (shell, start) in [
+		("bash", "_cargo() {"),
+		("zsh", "#compdef cargo"),
+		("fish", "# Print an optspec"),
+		("powershell", "\nusing namespace System.Management.Automation"),
+	] {
+		let (code, stdout) = Runner::new("cargo_udeps_test_generate_completion")?
+			.arg("--generate-completion")
+			.arg(shell)
+			.run()?;
+		assert_eq!(0, code);
+		assert!(stdout.starts_with(start), "{}", stdout);
+		assert!(stdout.contains("generate-completion"), "{}", stdout);
+	}
+	Ok(())
+}
+
+#[test]
+fn generate_completion_unknown_shell() -> CargoResult<()> {
+	let err = Runner::new("cargo_udeps_test_generate_completion_unknown_shell")?
+		.arg("--generate-completion")
+		.arg("tcsh")
+		.run()
+		.unwrap_err();
+	assert!(err.to_string().contains("invalid value 'tcsh'"), "{}", err);
+	Ok(())
+}
+
+#[test]
+fn generate_manpage() -> CargoResult<()> {
+	let (code, stdout) = Runner::new("cargo_udeps_test_generate_manpage")?
+		.arg("--generate-manpage")
+		.run()?;
+	assert_eq!(0, code);
+	assert!(stdout.contains(".TH cargo-udeps 1"), "{}", stdout);
+	assert!(stdout.contains("\\fBcargo udeps\\fR"), "{}", stdout);
+	assert!(stdout.contains("\\-\\-generate\\-manpage"), "{}", stdout);
+	Ok(())
+}
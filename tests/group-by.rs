@@ -0,0 +1,94 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b", "c"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+common = { path = "../common" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies by dependency:
+"common" (normal), in 3 packages
+├─── `a v0.0.1 (██████████/a)`
+├─── `b v0.0.1 (██████████/b)`
+└─── `c v0.0.1 (██████████/c)`
+"rare" (development), in 1 package
+└─── `b v0.0.1 (██████████/b)`
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+/// `common` is unused in every member, `rare` only in `b`.
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?.cargo_toml(CARGO_TOML)?;
+	for name in ["a", "b", "c"] {
+		let mut cargo_toml = MEMBER_CARGO_TOML.replace("{}", name);
+		if name == "b" {
+			cargo_toml += "\n[dev-dependencies]\nrare = { path = \"../rare\" }\n";
+		}
+		runner = runner
+			.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &cargo_toml)?
+			.file(&format!("./{}/src/lib.rs", name), "")?;
+	}
+	for name in ["common", "rare"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner.arg("--workspace").arg("--all-targets").arg("--group-by").arg("dependency"))
+}
+
+#[test]
+fn group_by_dependency_human() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_group_by_dependency_human")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout);
+	Ok(())
+}
+
+#[test]
+fn group_by_dependency_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_group_by_dependency_json")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let groups = report["by_dependency"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|group| {
+			let packages = group["packages"]
+				.as_array()
+				.unwrap()
+				.iter()
+				.map(|id| id.as_str().unwrap().split(' ').next().unwrap().to_owned())
+				.collect::<Vec<_>>();
+			(group["name"].as_str().unwrap().to_owned(), group["kind"].as_str().unwrap().to_owned(), group["count"].as_u64().unwrap(), packages)
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			("common".to_owned(), "normal".to_owned(), 3, vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]),
+			("rare".to_owned(), "development".to_owned(), 1, vec!["b".to_owned()]),
+		],
+		groups,
+	);
+	// Still listed by package as well
+	assert_eq!(3, report["unused_deps"].as_object().unwrap().len());
+	Ok(())
+}
@@ -0,0 +1,53 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "history"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "unused" }
+"#;
+
+#[test]
+fn history_and_trend() -> CargoResult<()> {
+	let history = tempfile::Builder::new().prefix("cargo_udeps_test_history").tempdir()?;
+	let history_file = history.path().join("udeps-history.jsonl");
+
+	let runner = Runner::new("cargo_udeps_test_history")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--history-file")
+		.arg(&history_file);
+	assert_eq!(1, runner.run()?.0);
+	std::fs::write(runner.path().join("src").join("lib.rs"), "pub use unused as _;\n")?;
+	assert_eq!(0, runner.run()?.0);
+
+	let (code, stdout) = Runner::new("cargo_udeps_test_history_trend")?
+		.arg("trend")
+		.arg(&history_file)
+		.run()?;
+	assert_eq!(0, code);
+	// Strip the timestamps
+	let lines = stdout
+		.lines()
+		.map(|line| line.strip_prefix("Trend: ").map_or_else(|| &line[19..], |_| line))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			"     1 unused (1 normal, 0 dev, 0 build) in 1 packages",
+			"     0 unused (0 normal, 0 dev, 0 build) in 1 packages  -1",
+			"Trend: going down (-1 over 2 runs)",
+		],
+		lines,
+	);
+	Ok(())
+}
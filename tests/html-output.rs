@@ -0,0 +1,74 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["used"]
+
+[package]
+name = "html_output"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+bare = { path = "bare" }
+used = { path = "used" }
+
+[dev-dependencies]
+bare-dev = { path = "bare-dev" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used as _;\n")?
+		.arg("--output")
+		.arg("html");
+	for name in ["bare", "bare-dev", "used"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn output_html() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_output_html")?
+		.arg("--workspace")
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(stdout.starts_with("<!DOCTYPE html>\n"), "{}", stdout);
+	assert!(stdout.ends_with("</body>\n</html>\n"), "{}", stdout);
+	for fragment in [
+		"<p>2 unused dependencies in 1 of the 4 checked packages.</p>",
+		"<tr><td><a href=\"#package-0\">html_output v0.0.1</a></td><td class=\"count\">1</td><td class=\"count\">1</td>\
+			<td class=\"count\">0</td><td class=\"chart\"><span class=\"bar normal\" style=\"width: 50.0%\"></span>\
+			<span class=\"bar dev\" style=\"width: 50.0%\"></span></td></tr>",
+		"<section id=\"package-0\">\n<h2>html_output v0.0.1</h2>\n<p><a href=\"Cargo.toml\">Cargo.toml</a></p>",
+		"<h3>dependencies</h3>\n<ul>\n<li><code>bare</code>, <a href=\"Cargo.toml\">Cargo.toml:11</a></li>\n</ul>",
+		"<h3>dev-dependencies</h3>\n<ul>\n<li><code>bare-dev</code>, <a href=\"Cargo.toml\">Cargo.toml:15</a></li>\n</ul>",
+	] {
+		assert!(stdout.contains(fragment), "{}\n\n{}", fragment, stdout);
+	}
+	// Packages without findings have no section
+	assert!(!stdout.contains("<h2>used v0.0.1</h2>"), "{}", stdout);
+	Ok(())
+}
+
+#[test]
+fn output_html_nothing_unused() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_output_html_nothing_unused")?
+		.arg("-p")
+		.arg("used")
+		.run()?;
+	assert_eq!(0, code);
+	assert!(stdout.contains("<p>All deps of the 1 checked packages seem to have been used.</p>\n</body>"), "{}", stdout);
+	assert!(!stdout.contains("<table>"), "{}", stdout);
+	Ok(())
+}
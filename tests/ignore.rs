@@ -37,10 +37,10 @@ matches = "0.1.8"
 		r#"unused dependencies:
 `ignore-if-chain v0.0.0 (██████████)`
 └─── dependencies
-     ├─── "maplit"
-     └─── "matches"
+     ├─── "maplit" (Cargo.toml:13)
+     └─── "matches" (Cargo.toml:14)
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
@@ -81,10 +81,10 @@ matches = "0.1.8"
 		r#"unused dependencies:
 `ignore-workspace v0.0.0 (██████████)`
 └─── dependencies
-     ├─── "maplit"
-     └─── "matches"
+     ├─── "maplit" (Cargo.toml:14)
+     └─── "matches" (Cargo.toml:15)
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
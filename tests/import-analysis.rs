@@ -0,0 +1,59 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "import_analysis"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`import_analysis v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used::ANSWER;\n")?
+		.path_dep("used", "pub const ANSWER :u32 = 42;\n")?
+		.path_dep("unused", "")?)
+}
+
+#[test]
+fn export_and_import() -> CargoResult<()> {
+	let analysis = tempfile::Builder::new().prefix("cargo_udeps_test_import_analysis").tempdir()?;
+
+	let (code, stdout_masked) = runner("cargo_udeps_test_import_analysis_builder")?
+		.arg("--all-targets")
+		.arg("--export-analysis")
+		.arg(analysis.path())
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+
+	// The same workspace in another place, which has never been built
+	let runner = runner("cargo_udeps_test_import_analysis_analyzer")?
+		.arg("--all-targets")
+		.arg("--import-analysis")
+		.arg(analysis.path());
+	let (code, stdout_masked) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	assert!(!runner.path().join("target").join("debug").join("deps").exists());
+	Ok(())
+}
@@ -0,0 +1,87 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+static EXPECTED :&str = r#"[workspace]
+members = ["a", "b"]
+
+[workspace.metadata.cargo-udeps]
+# The teams that own the members, by package name patterns
+#owners = { my-team = ["a", "b"] }
+# Or look the owners up in a CODEOWNERS file
+#codeowners = ".github/CODEOWNERS"
+# Keep `cargo udeps apply` from removing crates of these registries
+#protected-registries = []
+
+# Dependencies that are used in ways `cargo-udeps` can't detect, like only in doc-tests
+#[workspace.metadata.cargo-udeps.ignore]
+#normal = []
+#development = []
+#build = []
+
+# Recommended for CI, with `cargo +nightly udeps --workspace --all-targets`:
+# fail on new unused dependencies, but only warn about those that are known
+# to be hard to detect, and keep `apply` from removing them
+#[workspace.metadata.cargo-udeps.policy]
+#some-crate = { severity = "warn", auto-fix = false }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?.cargo_toml(CARGO_TOML)?;
+	for name in ["a", "b"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn init() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_init")?.arg("init");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(EXPECTED, runner.read_file("Cargo.toml")?);
+
+	let err = runner.run().unwrap_err();
+	assert!(err.to_string().contains("already has a `workspace.metadata.cargo-udeps` table"), "{}", err);
+	Ok(())
+}
+
+#[test]
+fn init_dry_run() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_init_dry_run")?.arg("init").arg("--dry-run");
+	let (code, stdout) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(EXPECTED.strip_prefix(CARGO_TOML).unwrap().trim_start(), stdout);
+	assert_eq!(CARGO_TOML, runner.read_file("Cargo.toml")?);
+	Ok(())
+}
+
+#[test]
+fn init_package() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_init_package")?
+		.cargo_toml(&MEMBER_CARGO_TOML.replace("{}", "init_package"))?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("init");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	let cargo_toml = runner.read_file("Cargo.toml")?;
+	assert!(cargo_toml.contains("\n\n[package.metadata.cargo-udeps]\n# Keep"), "{}", cargo_toml);
+	assert!(!cargo_toml.contains("owners"), "{}", cargo_toml);
+	Ok(())
+}
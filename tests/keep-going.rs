@@ -0,0 +1,97 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "keep_going"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+for-lib = { path = "for-lib" }
+for-bin = { path = "for-bin" }
+unused = { path = "unused" }
+"#;
+
+/// The binary doesn't compile, so that `for-bin` can't be found to be used.
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src/bin")?
+		.file("./src/lib.rs", "pub use for_lib as _;\n")?
+		.file("./src/bin/broken.rs", "use for_bin as _;\n\nfn main() {\n\tlet x :u32 = \"not a number\";\n}\n")?;
+	for name in ["for-lib", "for-bin", "unused"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn without_keep_going() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_without_keep_going")?.run().unwrap_err();
+	assert!(err.to_string().contains("job failed"), "{}", err);
+	Ok(())
+}
+
+#[test]
+fn keep_going_human() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_keep_going_human")?
+		.arg("--keep-going")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert!(stdout.contains(
+		"units that could not be analyzed, so their dependencies may be reported wrongly:\n  \
+		`keep_going v0.0.1`: bin \"broken\" (failed to compile)\n",
+	), "{}", stdout);
+	assert!(stderr.contains("the report only covers the units that were built"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn keep_going_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_keep_going_json")?
+		.arg("--keep-going")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	// Only used by the binary that failed
+	assert_eq!(serde_json::json!(["for-bin", "unused"]), unused_deps["normal"]);
+	let unanalyzed = report["unanalyzed_units"].as_array().unwrap();
+	assert_eq!(1, unanalyzed.len());
+	assert_eq!(serde_json::json!("bin \"broken\""), unanalyzed[0]["target"]);
+	assert_eq!(serde_json::json!(true), unanalyzed[0]["failed"]);
+	assert_eq!(serde_json::json!(false), report["success"]);
+	Ok(())
+}
+
+#[test]
+fn keep_going_dependents() -> CargoResult<()> {
+	// The binary isn't built, as the library it needs fails
+	let (code, stdout) = runner("cargo_udeps_test_keep_going_dependents")?
+		.file("./src/lib.rs", "pub fn broken() -> u32 {\n\t\"not a number\"\n}\n")?
+		.file("./src/bin/broken.rs", "fn main() {}\n")?
+		.arg("--keep-going")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let unanalyzed = report["unanalyzed_units"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|unit| (unit["target"].as_str().unwrap().to_owned(), unit["failed"].as_bool().unwrap()))
+		.collect::<Vec<_>>();
+	assert_eq!(vec![("bin \"broken\"".to_owned(), false), ("lib".to_owned(), true)], unanalyzed);
+	Ok(())
+}
@@ -0,0 +1,54 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "lib_examples"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dev-dependencies]
+used-by-plugin = { path = "used-by-plugin" }
+used-by-helper = { path = "used-by-helper" }
+unused = { path = "unused" }
+[[example]]
+name = "plugin"
+crate-type = ["lib"]
+[[example]]
+name = "helper"
+crate-type = ["rlib", "staticlib"]
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`lib_examples v0.0.1 (██████████)`
+└─── dev-dependencies
+     └─── "unused" (Cargo.toml:10)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn lib_examples() -> CargoResult<()> {
+	let mut runner = Runner::new("cargo_udeps_test_lib_examples")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		// An example with its own modules
+		.dir("./examples/plugin")?
+		.file("./examples/plugin/main.rs", "mod registry;\n")?
+		.file("./examples/plugin/registry.rs", "pub use used_by_plugin as _;\n")?
+		.file("./examples/helper.rs", "pub use used_by_helper as _;\n")?;
+	for dep in ["used-by-plugin", "used-by-helper", "unused"] {
+		runner = runner
+			.path_dep(dep, "")?;
+	}
+	let (code, stdout_masked) = runner.arg("--all-targets").run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
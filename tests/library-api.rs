@@ -0,0 +1,73 @@
+mod runner;
+
+use cargo::core::dependency::DepKind;
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "library_api"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+[dev-dependencies]
+bare = { path = "bare" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub fn answer() -> u32 {\n\tused::value()\n}\n")?;
+	for dep in ["used", "unused", "bare"] {
+		runner = runner
+			.path_dep(dep, "pub fn value() -> u32 { 42 }\n")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn library_api() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_library_api")?.arg("--all-targets");
+	let report = runner.check()?;
+	assert!(!report.success);
+	assert_eq!(2, report.unused_count());
+	let [package] = report.packages.as_slice() else {
+		panic!("expected one package: {:?}", report.packages);
+	};
+	assert_eq!("library_api", package.package_id.name().as_str());
+	assert_eq!(runner.path().join("Cargo.toml"), package.manifest_path);
+	let manifest = runner.read_file("Cargo.toml")?;
+	let found = package.unused
+		.iter()
+		.map(|dep| (dep.name.as_str(), dep.kind, &manifest[dep.span.clone().expect("should have a span")]))
+		.collect::<Vec<_>>();
+	assert_eq!(vec![("unused", DepKind::Normal, "unused"), ("bare", DepKind::Development, "bare")], found);
+	Ok(())
+}
+
+#[test]
+fn library_api_prints_nothing() -> CargoResult<()> {
+	// Output options are accepted, but the report is returned all the same
+	let report = runner("cargo_udeps_test_library_api_prints_nothing")?
+		.arg("--output")
+		.arg("json")
+		.check()?;
+	assert_eq!(1, report.unused_count());
+	Ok(())
+}
+
+#[test]
+fn library_api_subcommand() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_library_api_subcommand")?
+		.arg("init")
+		.check()
+		.expect_err("subcommands are no checks");
+	assert!(err.to_string().contains("can't be used for a check"), "{}", err);
+	Ok(())
+}
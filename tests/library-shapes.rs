@@ -0,0 +1,91 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "library_shapes"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+cdylib-only = { path = "cdylib-only" }
+staticlib-only = { path = "staticlib-only" }
+bin-only = { path = "bin-only" }
+macro-only = { path = "macro-only" }
+rlib-and-cdylib = { path = "rlib-and-cdylib" }
+"#;
+
+static LIB_RS :&str = r#"macro_only::make!();
+
+pub use rlib_and_cdylib::f;
+"#;
+
+static HELPER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?
+		.dir("./bin-only/src")?
+		.file("./bin-only/Cargo.toml", &HELPER_CARGO_TOML.replace("{}", "bin-only"))?
+		.file("./bin-only/src/main.rs", "fn main() {}\n")?
+		.dir("./macro-only/src")?
+		.file("./macro-only/Cargo.toml", &(HELPER_CARGO_TOML.replace("{}", "macro-only") + "\n[lib]\nproc-macro = true\n"))?
+		.file(
+			"./macro-only/src/lib.rs",
+			"#[proc_macro]\npub fn make(_ :proc_macro::TokenStream) -> proc_macro::TokenStream {\n\tproc_macro::TokenStream::new()\n}\n",
+		)?;
+	for (name, crate_types) in [
+		("cdylib-only", r#"["cdylib"]"#),
+		("staticlib-only", r#"["staticlib"]"#),
+		("rlib-and-cdylib", r#"["rlib", "cdylib"]"#),
+	] {
+		let cargo_toml = format!("{}\n[lib]\ncrate-type = {}\n", HELPER_CARGO_TOML.replace("{}", name), crate_types);
+		runner = runner
+			.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &cargo_toml)?
+			.file(&format!("./{}/src/lib.rs", name), "pub fn f() {}\n")?;
+	}
+	Ok(runner.arg("--output").arg("json"))
+}
+
+#[test]
+fn libraries_rust_code_cant_use() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_library_shapes")?.run_with_stderr()?;
+	// Declared, but can't be used by the package
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!(["bin-only", "cdylib-only", "staticlib-only"]), unused_deps["normal"]);
+	for (name, crate_type) in [("cdylib-only", "cdylib"), ("staticlib-only", "staticlib")] {
+		let warning = format!("warning: the library of `{} v0.0.1 (██████████/{})` is only a `{}`", name, name, crate_type);
+		assert!(stderr.contains(&warning), "{}", stderr);
+	}
+	assert!(!stderr.contains("rlib-and-cdylib v0.0.1 (██████████/rlib-and-cdylib)` is only"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn libraries_rust_code_cant_use_lint_backend() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_library_shapes_lint")?
+		.arg("--backend")
+		.arg("lint")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!(["bin-only", "cdylib-only", "staticlib-only"]), unused_deps["normal"]);
+	Ok(())
+}
@@ -0,0 +1,102 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "lint_backend"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+[dev-dependencies]
+for-tests = { path = "for-tests" }
+bare = { path = "bare" }
+[build-dependencies]
+for-build = { path = "for-build" }
+unused-build = { path = "unused-build" }
+"#;
+
+// Allowing the lint for rustc doesn't hide anything (unlike the opt-out of
+// `#![allow(unused_crate_dependencies)]` on a line of its own)
+static LIB_RS :&str = r#"#![cfg_attr(all(), allow(unused_crate_dependencies))]
+
+pub fn answer() -> u32 {
+	used::value()
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn answer() {
+		assert_eq!(super::answer(), for_tests::value());
+	}
+}
+"#;
+
+static BUILD_RS :&str = "fn main() {\n\tfor_build::value();\n}\n";
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?
+		.file("./build.rs", BUILD_RS)?;
+	for dep in ["used", "unused", "for-tests", "bare", "for-build", "unused-build"] {
+		runner = runner
+			.path_dep(dep, "pub fn value() -> u32 { 42 }\n")?;
+	}
+	Ok(runner.arg("--backend").arg("lint"))
+}
+
+#[test]
+fn lint_backend() -> CargoResult<()> {
+	let (code, stdout_masked, stderr) = runner("cargo_udeps_test_lint_backend")?
+		.arg("--all-targets")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	// The warnings of the lint are not shown
+	assert!(!stderr.contains("unused in"), "{}", stderr);
+	assert_eq!(
+		r#"unused dependencies:
+`lint_backend v0.0.1 (██████████)`
+├─── dependencies
+│    └─── "unused" (Cargo.toml:9)
+├─── dev-dependencies
+│    └─── "bare" (Cargo.toml:12)
+└─── build-dependencies
+     └─── "unused-build" (Cargo.toml:15)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn lint_backend_provenance() -> CargoResult<()> {
+	let (_, stdout) = runner("cargo_udeps_test_lint_backend_provenance")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	assert_eq!("lint", report["provenance"]["backend"]);
+	Ok(())
+}
+
+#[test]
+fn lint_backend_doc_cfg() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_lint_backend_doc_cfg")?
+		.arg("--doc-cfg")
+		.run()
+		.expect_err("`--doc-cfg` is not supported with the lint");
+	assert!(err.to_string().contains("`--doc-cfg` needs `--backend depinfo`"), "{}", err);
+	Ok(())
+}
@@ -0,0 +1,103 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "lint_levels"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+normal = { path = "normal" }
+
+[dev-dependencies]
+dev = { path = "dev" }
+
+[build-dependencies]
+build = { path = "build" }
+"#;
+
+fn runner(prefix :&str, cargo_toml :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(cargo_toml)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.file("./build.rs", "fn main() {}\n")?;
+	for name in ["normal", "dev", "build"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner.arg("--all-targets"))
+}
+
+#[test]
+fn command_line() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_lint_levels_command_line", CARGO_TOML)?
+		.arg("--deny")
+		.arg("unused-normal")
+		.arg("--warn")
+		.arg("unused-dev")
+		.arg("--allow")
+		.arg("unused-build")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(stdout_masked.contains("└─── \"normal\" (Cargo.toml:10)\n"), "{}", stdout_masked);
+	assert!(stdout_masked.contains("└─── \"dev\" (Cargo.toml:13) (warning only)\n"), "{}", stdout_masked);
+	assert!(!stdout_masked.contains("\"build\""), "{}", stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn only_warnings() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_lint_levels_only_warnings", CARGO_TOML)?
+		.arg("--warn")
+		.arg("unused-normal")
+		.arg("--warn")
+		.arg("unused-dev")
+		.arg("--warn")
+		.arg("unused-build")
+		.run()?;
+	assert_eq!(0, code);
+	assert!(stdout_masked.contains("└─── \"build\" (Cargo.toml:16) (warning only)\n"), "{}", stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn metadata() -> CargoResult<()> {
+	let cargo_toml = CARGO_TOML.replace(
+		"[package]",
+		"[workspace.metadata.cargo-udeps.lints]\nunused-normal = \"warn\"\nunused-dev = \"warn\"\nunused-build = \"allow\"\n\n[package]",
+	);
+	let (code, _) = runner("cargo_udeps_test_lint_levels_metadata", &cargo_toml)?.run()?;
+	assert_eq!(0, code);
+
+	// The command line takes precedence
+	let (code, stdout_masked) = runner("cargo_udeps_test_lint_levels_metadata_deny", &cargo_toml)?
+		.arg("--deny")
+		.arg("unused-normal")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(stdout_masked.contains("└─── \"normal\" (Cargo.toml:15)\n"), "{}", stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn same_name_different_kinds() -> CargoResult<()> {
+	let cargo_toml = CARGO_TOML.replace("dev = { path = \"dev\" }", "normal = { path = \"normal\" }");
+	let (code, stdout_masked) = runner("cargo_udeps_test_lint_levels_same_name", &cargo_toml)?
+		.arg("--deny")
+		.arg("unused-normal")
+		.arg("--warn")
+		.arg("unused-dev")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(stdout_masked.contains("└─── \"normal\" (Cargo.toml:10)\n"), "{}", stdout_masked);
+	assert!(stdout_masked.contains("└─── \"normal\" (Cargo.toml:13) (warning only)\n"), "{}", stdout_masked);
+	Ok(())
+}
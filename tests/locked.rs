@@ -0,0 +1,78 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "locked"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+unused = { path = "unused" }
+"#;
+
+static CARGO_LOCK :&str = r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "locked"
+version = "0.0.1"
+dependencies = [
+ "unused",
+]
+
+[[package]]
+name = "unused"
+version = "0.0.1"
+"#;
+
+fn runner(prefix :&str, cargo_toml :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(cargo_toml)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?)
+}
+
+#[test]
+fn locked_up_to_date() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_locked_up_to_date", CARGO_TOML)?
+		.file("Cargo.lock", CARGO_LOCK)?
+		.arg("--locked")
+		.arg("--offline");
+	let (code, _) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!(CARGO_LOCK, runner.read_file("Cargo.lock")?);
+	Ok(())
+}
+
+#[test]
+fn locked_would_change() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_locked_would_change", CARGO_TOML)?
+		.arg("--locked")
+		.run()
+		.expect_err("there is no `Cargo.lock` yet");
+	assert!(err.to_string().contains("--locked was passed"), "{}", err);
+	Ok(())
+}
+
+#[test]
+fn offline() -> CargoResult<()> {
+	let cargo_toml = CARGO_TOML.replace(
+		"unused = { path = \"unused\" }",
+		"unused = { path = \"unused\" }\ncargo-udeps-test-not-downloaded = \"1\"",
+	);
+	let err = runner("cargo_udeps_test_offline", &cargo_toml)?
+		.arg("--offline")
+		.run()
+		.expect_err("the registry can't be reached");
+	assert!(format!("{:?}", err).contains("offline"), "{:?}", err);
+	Ok(())
+}
@@ -0,0 +1,47 @@
+mod runner;
+
+use cargo::CargoResult;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "lockfile_version"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+static CARGO_LOCK :&str = r#"version = 99
+
+[[package]]
+name = "lockfile_version"
+version = "0.0.1"
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.file("Cargo.lock", CARGO_LOCK)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?)
+}
+
+#[test]
+fn newer_lockfile() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_newer_lockfile")?.run().unwrap_err();
+	assert!(err.to_string().contains("has version 99"), "{}", err);
+	assert!(err.to_string().contains("--allow-newer-lockfile"), "{}", err);
+	Ok(())
+}
+
+#[test]
+fn allow_newer_lockfile() -> CargoResult<()> {
+	// The embedded cargo gets to look at the lockfile, and fails in its own way
+	let err = runner("cargo_udeps_test_allow_newer_lockfile")?
+		.arg("--allow-newer-lockfile")
+		.run()
+		.unwrap_err();
+	assert!(!err.to_string().contains("--allow-newer-lockfile"), "{}", err);
+	Ok(())
+}
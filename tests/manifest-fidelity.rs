@@ -0,0 +1,65 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+/// Applies a report listing the given `(kind, name)` pairs to the
+/// `Cargo.toml` of the fixture and compares the result with `expected.toml`.
+fn check(fixture :&str, cargo_toml :&str, expected :&str, unused :&[(&str, &str)]) -> CargoResult<()> {
+	let names = |kind :&str| {
+		unused
+			.iter()
+			.filter(|(k, _)| *k == kind)
+			.map(|(_, name)| format!("{:?}", name))
+			.collect::<Vec<_>>()
+			.join(", ")
+	};
+	let report = format!(
+		r#"{{"success": false, "note": null, "unused_deps": {{"fixture 0.0.0 (path+file:///elsewhere)": {{
+			"manifest_path": "/elsewhere/Cargo.toml",
+			"normal": [{}],
+			"development": [{}],
+			"build": [{}]
+		}}}}}}"#,
+		names("normal"),
+		names("development"),
+		names("build"),
+	);
+	let runner = Runner::new(&format!("cargo_udeps_test_manifest_fidelity_{}", fixture))?
+		.cargo_toml(cargo_toml)?
+		.file("report.json", &report)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--allow-no-vcs");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(expected, runner.read_file("Cargo.toml")?);
+	Ok(())
+}
+
+macro_rules! fixture {
+	($name:ident, $unused:expr) => {
+		#[test]
+		fn $name() -> CargoResult<()> {
+			check(
+				stringify!($name),
+				include_str!(concat!("fixtures/manifest-fidelity/", stringify!($name), "/Cargo.toml")),
+				include_str!(concat!("fixtures/manifest-fidelity/", stringify!($name), "/expected.toml")),
+				$unused,
+			)
+		}
+	};
+}
+
+fixture!(comments, &[("normal", "env_logger"), ("normal", "serde"), ("development", "matches")]);
+fixture!(subtables, &[("normal", "winapi")]);
+fixture!(dotted, &[("normal", "log"), ("development", "matches")]);
+fixture!(inline, &[("normal", "nix"), ("development", "matches")]);
+fixture!(inheritance, &[("normal", "serde"), ("build", "log")]);
+fixture!(targets, &[("normal", "libc"), ("development", "tempfile"), ("build", "cc")]);
+fixture!(features, &[("normal", "serde"), ("normal", "serde_json"), ("normal", "native-tls")]);
+fixture!(dev_features, &[("normal", "serde")]);
@@ -0,0 +1,9 @@
+use cargo_udeps::bench::Matching;
+use pretty_assertions::assert_eq;
+
+#[test]
+fn bench_matching() {
+	// Every other dependency of each member is used
+	assert_eq!(3 * 2, Matching::new(3, 4).run());
+	assert_eq!(0, Matching::new(3, 0).run());
+}
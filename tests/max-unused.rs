@@ -0,0 +1,56 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "max_unused"
+version = "0.0.1"
+[dependencies]
+byteorder = "1.0.0"
+[dev-dependencies]
+unused = { path = "unused" }
+"#;
+
+fn run(prefix :&str, limits :&[&str]) -> CargoResult<i32> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets");
+	for limit in limits {
+		runner = runner.arg("--max-unused").arg(*limit);
+	}
+	let (code, stdout_masked) = runner.run()?;
+	// The findings are reported either way
+	assert!(stdout_masked.contains(r#""byteorder""#), "{}", stdout_masked);
+	Ok(code)
+}
+
+#[test]
+fn total() -> CargoResult<()> {
+	assert_eq!(0, run("cargo_udeps_test_max_unused_total_within", &["2"])?);
+	assert_eq!(1, run("cargo_udeps_test_max_unused_total_exceeded", &["1"])?);
+	Ok(())
+}
+
+#[test]
+fn per_kind() -> CargoResult<()> {
+	assert_eq!(0, run("cargo_udeps_test_max_unused_per_kind_within", &["normal=1", "development=1", "build=0"])?);
+	assert_eq!(1, run("cargo_udeps_test_max_unused_per_kind_exceeded", &["normal=1", "development=0"])?);
+	Ok(())
+}
+
+#[test]
+fn invalid() -> CargoResult<()> {
+	let err = run("cargo_udeps_test_max_unused_invalid", &["dev=1"]).unwrap_err();
+	assert_eq!(
+		"unknown dependency kind `dev`, expected `normal`, `development` or `build`",
+		err.to_string(),
+	);
+	Ok(())
+}
@@ -0,0 +1,105 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[package]
+name = "message_format_json"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+a = { path = "a" }
+[dev-dependencies]
+b = { path = "b" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?;
+	for name in ["a", "b"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+/// Checks that every line of `stdout` is JSON, like with cargo, and returns the messages of the findings.
+fn messages(stdout :&str) -> Vec<&str> {
+	for line in stdout.lines() {
+		assert!(serde_json::from_str::<serde_json::Value>(line).is_ok(), "not JSON: {}", line);
+	}
+	stdout
+		.lines()
+		.filter(|line| line.starts_with(r#"{"reason":"udeps-unused-dependency""#))
+		.collect()
+}
+
+#[test]
+fn message_per_finding() -> CargoResult<()> {
+	let (code, stdout_masked, stderr) = runner("cargo_udeps_test_message_format_json")?
+		.arg("--all-targets")
+		.arg("--message-format")
+		.arg("json")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		vec![
+			r#"{"reason":"udeps-unused-dependency","package_id":"message_format_json 0.0.1 (path+file://██████████)","manifest_path":"Cargo.toml","name":"a","kind":"normal","level":"error","code":"unused-normal","span":{"file_name":"Cargo.toml","byte_start":105,"byte_end":106,"line_start":7,"line_end":7,"column_start":1,"column_end":2}}"#,
+			r#"{"reason":"udeps-unused-dependency","package_id":"message_format_json 0.0.1 (path+file://██████████)","manifest_path":"Cargo.toml","name":"b","kind":"development","level":"error","code":"unused-dev","span":{"file_name":"Cargo.toml","byte_start":143,"byte_end":144,"line_start":9,"line_end":9,"column_start":1,"column_end":2}}"#,
+		],
+		messages(&stdout_masked),
+	);
+	// The human report goes to stderr
+	assert!(stderr.contains("unused dependencies:\n"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn absolute_paths() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_message_format_json_absolute")?
+		.arg("--all-targets")
+		.arg("--message-format")
+		.arg("json")
+		.arg("--path-style")
+		.arg("absolute")
+		.run()?;
+	assert_eq!(1, code);
+	let messages = messages(&stdout_masked);
+	assert_eq!(2, messages.len(), "{}", stdout_masked);
+	for message in messages {
+		let message = serde_json::from_str::<serde_json::Value>(message)?;
+		assert_eq!(serde_json::json!("██████████/Cargo.toml"), message["manifest_path"]);
+		assert_eq!(serde_json::json!("██████████/Cargo.toml"), message["span"]["file_name"]);
+	}
+	Ok(())
+}
+
+#[test]
+fn json_report_stays_on_stdout() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_message_format_json_report")?
+		.arg("--all-targets")
+		.arg("--message-format")
+		.arg("json")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(2, messages(&stdout).len(), "{}", stdout);
+	assert!(stdout.lines().last().unwrap().starts_with(r#"{"success":false,"#), "{}", stdout);
+	Ok(())
+}
+
+#[test]
+fn no_messages_without_json() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_message_format_json_human")?
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(!stdout_masked.contains("udeps-unused-dependency"), "{}", stdout_masked);
+	Ok(())
+}
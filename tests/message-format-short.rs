@@ -0,0 +1,80 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["member"]
+[package]
+name = "message_format_short"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+a = { path = "a" }
+b = { path = "b" }
+[dev-dependencies]
+c = { path = "c" }
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "member"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[build-dependencies]
+a = { path = "../a" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.dir("./member/src")?
+		.file("./member/Cargo.toml", MEMBER_CARGO_TOML)?
+		.file("./member/src/lib.rs", "")?
+		.file("./member/build.rs", "fn main() {}\n")?;
+	for name in ["a", "b", "c"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn one_line_per_finding() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_message_format_short")?
+		.arg("--workspace")
+		.arg("--all-targets")
+		.arg("--message-format")
+		.arg("short")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"member: unused dependency `a` (build)
+message_format_short: unused dependency `a` (normal)
+message_format_short: unused dependency `b` (normal)
+message_format_short: unused dependency `c` (dev)
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn nothing_if_all_used() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_message_format_short_used")?
+		.file("./src/lib.rs", "pub use a::*;\npub use b::*;\n#[cfg(test)]\nuse c as _;\n")?
+		.file("./member/build.rs", "use a as _;\nfn main() {}\n")?
+		.arg("--workspace")
+		.arg("--all-targets")
+		.arg("--message-format")
+		.arg("short")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!("", stdout_masked);
+	Ok(())
+}
@@ -0,0 +1,104 @@
+#![cfg(unix)]
+
+mod runner;
+
+use std::os::unix::fs::PermissionsExt;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "missing_depinfo"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+"#;
+
+/// Does `{}` to the `.d` file of the checked crate after rustc wrote it.
+static WRAPPER :&str = r#"#!/bin/sh
+"$@" || exit $?
+prev=
+for arg in "$@"; do
+	case "$prev" in
+		--out-dir) out="$arg" ;;
+		--crate-name) name="$arg" ;;
+	esac
+	prev="$arg"
+done
+if [ "$name" = "missing_depinfo" ]; then
+	for d in "$out"/missing_depinfo-*.d; do
+		{}
+	done
+fi
+"#;
+
+/// Renames the file, like a wrapper that changes `-C extra-filename` would.
+static RENAME :&str = r#"mv "$d" "$out/missing_depinfo-0123456789abcdef.d""#;
+
+/// Leaves a rule for another file only, like one of an earlier build.
+static TRUNCATE :&str = r#"echo "other.rmeta: src/lib.rs" > "$d""#;
+
+fn runner(prefix :&str, action :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used as _;\n")?
+		.file("./wrapper.sh", &WRAPPER.replace("{}", action))?;
+	for name in ["used", "unused"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	let wrapper = runner.path().join("wrapper.sh");
+	std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755))?;
+	Ok(runner.env("RUSTC_WORKSPACE_WRAPPER", &wrapper.to_string_lossy()))
+}
+
+#[test]
+fn missing_depinfo_skipped() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_missing_depinfo_skipped", RENAME)?.run_with_stderr()?;
+	// The dependencies of the skipped unit aren't checked
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout);
+	assert!(stderr.contains("/missing_depinfo-"), "{}", stderr);
+	for expected in [
+		".d` is missing for lib of `missing_depinfo v0.0.1 (██████████)`\n",
+		"  other dep-info files of the crate in its directory: missing_depinfo-0123456789abcdef.d\n",
+		"  the unit is skipped, so its dependencies may be reported wrongly, unless another unit uses them",
+	] {
+		assert!(stderr.contains(expected), "{}\n\n{}", expected, stderr);
+	}
+	Ok(())
+}
+
+#[test]
+fn stale_depinfo_skipped() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_stale_depinfo_skipped", TRUNCATE)?.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout);
+	for expected in [
+		".d` has no rule for the artifact of lib of `missing_depinfo v0.0.1 (██████████)`\n",
+		"  there are no other dep-info files of the crate in its directory\n",
+	] {
+		assert!(stderr.contains(expected), "{}\n\n{}", expected, stderr);
+	}
+	Ok(())
+}
+
+#[test]
+fn missing_depinfo_strict() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_missing_depinfo_strict", RENAME)?
+		.arg("--strict")
+		.run()
+		.unwrap_err()
+		.to_string();
+	assert!(err.contains(".d` is missing for lib of `missing_depinfo v0.0.1"), "{}", err);
+	Ok(())
+}
@@ -0,0 +1,92 @@
+mod runner;
+
+use std::process::Command;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "multi_target"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+native = { path = "native" }
+wasm = { path = "wasm" }
+unused = { path = "unused" }
+"#;
+
+static LIB_RS :&str = r#"#[cfg(not(target_arch = "wasm32"))]
+pub use native::f;
+#[cfg(target_arch = "wasm32")]
+pub use wasm::f;
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for name in ["native", "wasm", "unused"] {
+		runner = runner
+			.path_dep(name, "#![no_std]\npub fn f() {}\n")?;
+	}
+	Ok(runner.arg("--output").arg("json"))
+}
+
+fn host() -> String {
+	let output = Command::new("rustc").arg("-vV").output().unwrap();
+	let output = String::from_utf8(output.stdout).unwrap();
+	output.lines().find_map(|line| line.strip_prefix("host: ")).unwrap().to_owned()
+}
+
+fn unused_normal(report :&serde_json::Value) -> serde_json::Value {
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	unused_deps["normal"].clone()
+}
+
+#[test]
+fn one_target() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_multi_target_one")?
+		.arg("--target")
+		.arg(host())
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	assert_eq!(serde_json::json!(["unused", "wasm"]), unused_normal(&report));
+	// There is nothing to break down
+	assert_eq!(serde_json::Value::Null, report["unused_by_target"]);
+	Ok(())
+}
+
+#[test]
+#[ignore = "needs the wasm32-unknown-unknown target"]
+fn several_targets() -> CargoResult<()> {
+	let host = host();
+	let (code, stdout) = runner("cargo_udeps_test_multi_target_several")?
+		.arg("--target")
+		.arg(&host)
+		.arg("--target")
+		.arg("wasm32-unknown-unknown")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	// `native` and `wasm` are each used with one of the targets
+	assert_eq!(serde_json::json!(["unused"]), unused_normal(&report));
+	let names = |target :&str| {
+		report["unused_by_target"][target]
+			.as_array()
+			.unwrap()
+			.iter()
+			.map(|unused| unused["name"].as_str().unwrap().to_owned())
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(vec!["unused", "wasm"], names(&host));
+	assert_eq!(vec!["native", "unused"], names("wasm32-unknown-unknown"));
+	Ok(())
+}
@@ -0,0 +1,57 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+exclude = ["foo1", "foo2"]
+[package]
+name = "multiple_versions"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+foo = { path = "foo1" }
+foo2 = { path = "foo2", package = "foo" }
+"#;
+
+static FOO_CARGO_TOML :&str = r#"[package]
+name = "foo"
+version = "{}"
+edition = "2018"
+publish = false
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`multiple_versions v0.0.1 (██████████)`
+└─── dependencies
+     └─── "foo" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn versions_are_told_apart() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_multiple_versions")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use foo2 as _;\n")?
+		.dir("./foo1/src")?
+		.file("./foo1/Cargo.toml", &FOO_CARGO_TOML.replace("{}", "1.0.0"))?
+		.file("./foo1/src/lib.rs", "")?
+		.dir("./foo2/src")?
+		.file("./foo2/Cargo.toml", &FOO_CARGO_TOML.replace("{}", "2.0.0"))?
+		.file("./foo2/src/lib.rs", "")?
+		.arg("--all-targets");
+	// The second time, the dependencies are not rebuilt, so only the `--extern` paths of
+	// the package itself tell which version of `foo` it uses
+	for _ in 0..2 {
+		let (code, stdout_masked) = runner.run()?;
+		assert_eq!(1, code);
+		assert_eq!(EXPECTED, stdout_masked);
+	}
+	Ok(())
+}
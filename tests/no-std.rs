@@ -0,0 +1,104 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "firmware"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+hal = { path = "hal" }
+unused = { path = "unused" }
+[target.'cfg(target_os = "none")'.dependencies]
+board = { path = "board" }
+flasher = { path = "flasher" }
+[target.'cfg(not(target_os = "none"))'.dependencies]
+simulator = { path = "simulator" }
+"#;
+
+static LIB_RS :&str = r#"#![no_std]
+
+#[cfg(target_os = "none")]
+use board as _;
+
+pub fn init() -> u32 {
+	hal::init()
+}
+"#;
+
+static DEP_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for dep in ["hal", "unused", "board", "simulator"] {
+		runner = runner
+			.path_dep(dep, "#![no_std]\npub fn init() -> u32 { 0 }\n")?;
+	}
+	// A tool that is only of use when building for the device
+	runner = runner
+		.dir("./flasher/src")?
+		.file("./flasher/Cargo.toml", &DEP_CARGO_TOML.replace("{}", "flasher"))?
+		.file("./flasher/src/main.rs", "fn main() {}\n")?;
+	Ok(runner)
+}
+
+#[test]
+fn host() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_no_std_host")?
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`firmware v0.0.1 (██████████)`
+└─── dependencies
+     ├─── "simulator" (Cargo.toml:14) (only for cfg(not(target_os = "none")))
+     └─── "unused" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+#[ignore = "needs the thumbv7em-none-eabihf target"]
+fn thumbv7em() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_no_std_thumbv7em")?
+		.arg("--target")
+		.arg("thumbv7em-none-eabihf")
+		.arg("--lib")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`firmware v0.0.1 (██████████)`
+└─── dependencies
+     ├─── "flasher"
+     └─── "unused" (Cargo.toml:9)
+Note: These dependencies might be used by other targets.
+Note: Some dependencies are non-library packages.
+      `cargo-udeps` regards them as unused.
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
@@ -30,13 +30,13 @@ fn without_all_targets() -> CargoResult<()> {
 		r#"unused dependencies:
 `non_lib_build_dep v0.0.0 (██████████)`
 └─── build-dependencies
-     └─── "diffr"
+     └─── "diffr" (Cargo.toml:8)
 Note: These dependencies might be used by other targets.
       To find dependencies that are not used by any target, enable `--all-targets`.
 Note: Some dependencies are non-library packages.
       `cargo-udeps` regards them as unused.
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
@@ -59,11 +59,11 @@ fn with_all_targets() -> CargoResult<()> {
 		r#"unused dependencies:
 `non_lib_build_dep v0.0.0 (██████████)`
 └─── build-dependencies
-     └─── "diffr"
+     └─── "diffr" (Cargo.toml:8)
 Note: Some dependencies are non-library packages.
       `cargo-udeps` regards them as unused.
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
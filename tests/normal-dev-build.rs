@@ -40,13 +40,13 @@ fn without_all_targets() -> CargoResult<()> {
 		r#"unused dependencies:
 `normal_dev_build v0.0.1 (██████████)`
 ├─── dependencies
-│    └─── "if_chain"
+│    └─── "if_chain" (Cargo.toml:10)
 └─── build-dependencies
-     └─── "matches"
+     └─── "matches" (Cargo.toml:16)
 Note: These dependencies might be used by other targets.
       To find dependencies that are not used by any target, enable `--all-targets`.
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
@@ -70,13 +70,13 @@ fn with_all_targets() -> CargoResult<()> {
 		r#"unused dependencies:
 `normal_dev_build v0.0.1 (██████████)`
 ├─── dependencies
-│    └─── "if_chain"
+│    └─── "if_chain" (Cargo.toml:10)
 ├─── dev-dependencies
-│    └─── "maplit"
+│    └─── "maplit" (Cargo.toml:13)
 └─── build-dependencies
-     └─── "matches"
+     └─── "matches" (Cargo.toml:16)
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
@@ -0,0 +1,50 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "on_unused"
+version = "0.0.1"
+[dependencies]
+byteorder = "1.0.0"
+"#;
+
+static HOOK :&str = r#"cat > stdin.json && cp "$CARGO_UDEPS_REPORT" file.json"#;
+
+#[cfg(unix)]
+#[test]
+fn unused() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_on_unused")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("--all-targets")
+		.arg("--on-unused")
+		.arg(HOOK);
+	let (code, _) = runner.run()?;
+	assert_eq!(1, code);
+	let stdin = runner.read_file("stdin.json")?;
+	assert!(stdin.contains(r#""normal":["byteorder"]"#), "{}", stdin);
+	assert_eq!(stdin, runner.read_file("file.json")?);
+	Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn all_used() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_on_unused_all_used")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "extern crate byteorder;\n")?
+		.arg("--all-targets")
+		.arg("--on-unused")
+		.arg(HOOK);
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert!(!runner.path().join("stdin.json").exists());
+	Ok(())
+}
@@ -0,0 +1,83 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "optional_features"
+version = "0.0.1"
+edition = "2021"
+publish = false
+[dependencies]
+explicit = { path = "explicit", optional = true }
+implicit = { path = "implicit", optional = true }
+bare = { path = "bare", optional = true }
+[features]
+extra = ["dep:explicit"]
+fancy = ["implicit", "explicit?/more"]
+everything = ["extra", "fancy"]
+"#;
+
+static HELPER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+[features]
+more = []
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?;
+	for dep in ["explicit", "implicit", "bare"] {
+		runner = runner
+			.dir(&format!("./{}/src", dep))?
+			.file(&format!("./{}/Cargo.toml", dep), &HELPER_CARGO_TOML.replace("{}", dep))?
+			.file(&format!("./{}/src/lib.rs", dep), "")?;
+	}
+	Ok(runner.arg("--all-targets").arg("--all-features"))
+}
+
+#[test]
+fn optional_features_human() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_optional_features_human")?
+		.arg("--no-wrap")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`optional_features v0.0.1 (██████████)`
+└─── dependencies
+     ├─── "bare" (Cargo.toml:10)
+     ├─── "explicit" (Cargo.toml:8) (enabled by features `extra`, `fancy`)
+     └─── "implicit" (Cargo.toml:9) (enabled by feature `fancy`)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn optional_features_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_optional_features_json")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(
+		serde_json::json!({ "explicit" : ["extra", "fancy"], "implicit" : ["fancy"] }),
+		unused_deps["enabled_by"],
+	);
+	Ok(())
+}
@@ -0,0 +1,77 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["crates/*", "tools/*"]
+[workspace.metadata.cargo-udeps]
+codeowners = ".github/CODEOWNERS"
+[workspace.metadata.cargo-udeps.owners]
+storage = ["storage-*"]
+storage-core = ["storage-core"]
+"#;
+
+static CODEOWNERS :&str = r#"# Everything else
+*            @org/platform
+/tools/      @org/devex  @someone
+/tools/misc  # not owned
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "../../unused" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`cli v0.0.1 (██████████/tools/cli)` (owned by @org/devex)
+└─── dependencies
+     └─── "unused" (tools/cli/Cargo.toml:7)
+`misc v0.0.1 (██████████/tools/misc)`
+└─── dependencies
+     └─── "unused" (tools/misc/Cargo.toml:7)
+`server v0.0.1 (██████████/crates/server)` (owned by @org/platform)
+└─── dependencies
+     └─── "unused" (crates/server/Cargo.toml:7)
+`storage-core v0.0.1 (██████████/crates/storage-core)` (owned by storage-core)
+└─── dependencies
+     └─── "unused" (crates/storage-core/Cargo.toml:7)
+`storage-s3 v0.0.1 (██████████/crates/storage-s3)` (owned by storage)
+└─── dependencies
+     └─── "unused" (crates/storage-s3/Cargo.toml:7)
+unused dependencies by owner:
+  (no owner): 1
+  @org/devex: 1
+  @org/platform: 1
+  storage: 1
+  storage-core: 1
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn owners() -> CargoResult<()> {
+	let mut runner = Runner::new("cargo_udeps_test_owners")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./.github")?
+		.file("./.github/CODEOWNERS", CODEOWNERS)?
+		.path_dep("unused", "")?;
+	for member in ["crates/server", "crates/storage-core", "crates/storage-s3", "tools/cli", "tools/misc"] {
+		let name = member.rsplit('/').next().unwrap();
+		runner = runner
+			.dir(&format!("./{}/src", member))?
+			.file(&format!("./{}/Cargo.toml", member), &MEMBER_CARGO_TOML.replace("{}", name))?
+			.file(&format!("./{}/src/lib.rs", member), "")?;
+	}
+	let (code, stdout_masked) = runner.arg("--workspace").arg("--all-targets").run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	Ok(())
+}
@@ -0,0 +1,92 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+resolver = "2"
+"#;
+
+static A_CARGO_TOML :&str = r#"[package]
+name = "a"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+x = { path = "../x", optional = true }
+[features]
+with-x = ["dep:x"]
+"#;
+
+static B_CARGO_TOML :&str = r#"[package]
+name = "b"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[features]
+other = []
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`a v0.0.1 (██████████/a)`
+└─── dependencies
+     └─── "x" (a/Cargo.toml:7) (enabled by feature `with-x`)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", A_CARGO_TOML)?
+		.file("./a/src/lib.rs", "")?
+		.dir("./b/src")?
+		.file("./b/Cargo.toml", B_CARGO_TOML)?
+		.file("./b/src/lib.rs", "")?
+		.path_dep("x", "")?)
+}
+
+#[test]
+fn member_feature() -> CargoResult<()> {
+	for feature in ["a/with-x", "a?/with-x"] {
+		let (code, stdout_masked) = runner("cargo_udeps_test_package_features")?
+			.arg("--workspace")
+			.arg("--all-targets")
+			.arg("-F")
+			.arg(feature)
+			.run()?;
+		assert_eq!(1, code, "{}", feature);
+		assert_eq!(EXPECTED, stdout_masked, "{}", feature);
+	}
+	Ok(())
+}
+
+#[test]
+fn without_the_feature() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_package_features_none")?
+		.arg("--workspace")
+		.arg("--all-targets")
+		.arg("-F")
+		.arg("b/other")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn unknown_feature() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_package_features_unknown")?
+		.arg("--workspace")
+		.arg("-F")
+		.arg("b/with-x")
+		.run()
+		.unwrap_err();
+	assert!(err.to_string().contains("none of the selected packages contains these features: b/with-x"), "{}", err);
+	Ok(())
+}
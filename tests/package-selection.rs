@@ -0,0 +1,79 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b", "broken"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+unused = { path = "../unused" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.path_dep("unused", "")?;
+	for member in ["a", "b", "broken"] {
+		runner = runner
+			.dir(&format!("./{}/src", member))?
+			.file(&format!("./{}/Cargo.toml", member), &MEMBER_CARGO_TOML.replace("{}", member))?
+			.file(&format!("./{}/src/lib.rs", member), "")?;
+	}
+	// Members that aren't selected aren't compiled, so this doesn't fail the run
+	Ok(runner.file("./broken/src/lib.rs", "compile_error!(\"not selected\");\n")?.arg("--all-targets"))
+}
+
+fn members(runner :&Runner) -> CargoResult<Vec<String>> {
+	let report = runner.check()?;
+	Ok(report.packages.iter().map(|package| package.package_id.name().to_string()).collect())
+}
+
+#[test]
+fn package() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_package_selection_package")?.arg("-p").arg("b");
+	assert_eq!(vec!["b"], members(&runner)?);
+	Ok(())
+}
+
+#[test]
+fn exclude() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_package_selection_exclude")?
+		.arg("--workspace")
+		.arg("--exclude")
+		.arg("broken");
+	assert_eq!(vec!["a", "b"], members(&runner)?);
+	Ok(())
+}
+
+#[test]
+fn exclude_with_check_workspace_members() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_package_selection_members")?
+		.arg("--workspace")
+		.arg("--exclude")
+		.arg("broken")
+		.arg("--exclude")
+		.arg("b")
+		.arg("--check-workspace-members")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let name = |id :&str| id.split(' ').next().unwrap().to_owned();
+	let unused_deps = report["unused_deps"].as_object().unwrap().keys().map(|id| name(id)).collect::<Vec<_>>();
+	assert_eq!(vec!["a"], unused_deps);
+	// `a` is the only member left, which nothing selected depends on
+	let unused_members = report["unused_members"].as_array().unwrap().iter().map(|id| name(id.as_str().unwrap())).collect::<Vec<_>>();
+	assert_eq!(vec!["a"], unused_members);
+	Ok(())
+}
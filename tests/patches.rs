@@ -0,0 +1,126 @@
+mod runner;
+
+use cargo::CargoResult;
+use cargo::util::IntoUrl;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+exclude = ["upstream"]
+
+[package]
+name = "patches"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+used = { git = "{upstream}" }
+
+[patch."{upstream}"]
+used = { path = "used" }
+
+[patch.crates-io]
+stale = { path = "stale" }
+"#;
+
+static REPLACE_CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "replace"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[replace]
+"old:1.0.0" = { path = "old" }
+"#;
+
+static HELPER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "1.0.0"
+edition = "2021"
+publish = false
+"#;
+
+/// `used` is patched with a copy of the crate of a git repository, `stale` is a patch of
+/// crates.io, which nothing depends on.
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used as _;\n")?
+		.dir("./upstream/used/src")?
+		.file("./upstream/used/Cargo.toml", &HELPER_CARGO_TOML.replace("{}", "used"))?
+		.file("./upstream/used/src/lib.rs", "")?;
+	let upstream = runner.path().join("upstream").join("used");
+	let repo = git2::Repository::init(&upstream)?;
+	let mut index = repo.index()?;
+	index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+	let tree = repo.find_tree(index.write_tree()?)?;
+	let signature = git2::Signature::new("Jane Doe", "jane@example.com", &git2::Time::new(1_622_548_800, 0))?;
+	repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])?;
+	let url = upstream.as_path().into_url()?;
+	runner = runner.cargo_toml(&CARGO_TOML.replace("{upstream}", url.as_str()))?;
+	for name in ["used", "stale"] {
+		runner = runner
+			.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &HELPER_CARGO_TOML.replace("{}", name))?
+			.file(&format!("./{}/src/lib.rs", name), "")?;
+	}
+	Ok(runner.arg("--check-patches"))
+}
+
+#[test]
+fn unused_patches_human() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_unused_patches_human")?.run()?;
+	// Patches don't make the run fail
+	assert_eq!(0, code);
+	assert_eq!(
+		"All deps seem to have been used.\n\
+		patches that have no effect:\n  \
+		`stale` of `[patch.crates-io]`, to `stale v1.0.0`\n",
+		stdout,
+	);
+	Ok(())
+}
+
+#[test]
+fn unused_patches_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_unused_patches_json")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(0, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	assert_eq!(
+		serde_json::json!([{
+			"table": "patch.crates-io",
+			"key": "stale",
+			"package": "stale 1.0.0 (path+file://██████████/stale)",
+		}]),
+		report["unused_patches"],
+	);
+	Ok(())
+}
+
+#[test]
+fn unused_replace() -> CargoResult<()> {
+	let (code, stdout) = Runner::new("cargo_udeps_test_unused_replace")?
+		.cargo_toml(REPLACE_CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.dir("./old/src")?
+		.file("./old/Cargo.toml", &HELPER_CARGO_TOML.replace("{}", "old"))?
+		.file("./old/src/lib.rs", "")?
+		.arg("--check-patches")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		"All deps seem to have been used.\n\
+		patches that have no effect:\n  \
+		`old:1.0.0` of `[replace]`\n",
+		stdout,
+	);
+	Ok(())
+}
@@ -0,0 +1,79 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a"]
+"#;
+
+static A_CARGO_TOML :&str = r#"[package]
+name = "a"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "../unused" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", A_CARGO_TOML)?
+		.file("./a/src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets"))
+}
+
+fn manifest_path(json :&str) -> String {
+	let report = serde_json::from_str::<serde_json::Value>(json).unwrap();
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	unused_deps["manifest_path"].as_str().unwrap().to_owned()
+}
+
+#[test]
+fn relative_by_default() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_path_style_relative")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("a/Cargo.toml", manifest_path(&stdout));
+	Ok(())
+}
+
+#[test]
+fn absolute() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_path_style_absolute")?
+		.arg("--output")
+		.arg("json")
+		.arg("--path-style")
+		.arg("absolute")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("██████████/a/Cargo.toml", manifest_path(&stdout));
+	Ok(())
+}
+
+#[test]
+fn diagnostic() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_path_style_diagnostic_relative")?
+		.arg("--output")
+		.arg("diagnostic")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(stdout.contains(" --> a/Cargo.toml:7:1\n"), "{}", stdout);
+
+	let (code, stdout) = runner("cargo_udeps_test_path_style_diagnostic_absolute")?
+		.arg("--output")
+		.arg("diagnostic")
+		.arg("--path-style")
+		.arg("absolute")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(stdout.contains(" --> ██████████/a/Cargo.toml:7:1\n"), "{}", stdout);
+	Ok(())
+}
@@ -0,0 +1,115 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[workspace.metadata.cargo-udeps.policy]
+byteorder = { severity = "warn" }
+unused = { severity = "allow" }
+
+[package]
+name = "policy"
+version = "0.0.1"
+publish = false
+[dependencies]
+byteorder = "1.0.0"
+unused = { path = "unused" }
+"#;
+
+#[test]
+fn severity() -> CargoResult<()> {
+	let (code, stdout_masked) = Runner::new("cargo_udeps_test_policy_severity")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		r#"unused dependencies:
+`policy v0.0.1 (██████████)`
+└─── dependencies
+     └─── "byteorder" (Cargo.toml:11) (warning only)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn package_policy_takes_precedence() -> CargoResult<()> {
+	let cargo_toml = format!(
+		"{}[package.metadata.cargo-udeps.policy]\nbyteorder = {{ severity = \"error\" }}\n",
+		CARGO_TOML,
+	);
+	let (code, stdout_masked) = Runner::new("cargo_udeps_test_policy_precedence")?
+		.cargo_toml(&cargo_toml)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert!(stdout_masked.contains("└─── \"byteorder\" (Cargo.toml:11)\n"), "{}", stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn auto_fix() -> CargoResult<()> {
+	static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "policy"
+version = "0.0.1"
+publish = false
+[package.metadata.cargo-udeps.policy]
+openssl-sys = { auto-fix = false }
+[dependencies]
+maplit = "1.0.2"
+openssl-sys = "0.9"
+"#;
+
+	static REPORT_JSON :&str = r#"{
+	"success": false,
+	"unused_deps": {
+		"policy 0.0.1 (path+file:///home/runner/work/policy)": {
+			"manifest_path": "/home/runner/work/policy/Cargo.toml",
+			"normal": ["maplit", "openssl-sys"],
+			"development": [],
+			"build": []
+		}
+	},
+	"note": null
+}"#;
+
+	let runner = Runner::new("cargo_udeps_test_policy_auto_fix")?
+		.cargo_toml(CARGO_TOML)?
+		.file("report.json", REPORT_JSON)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--allow-no-vcs");
+	let (code, _) = runner.run()?;
+	assert_eq!(0, code);
+	assert_eq!(
+		r#"[workspace]
+[package]
+name = "policy"
+version = "0.0.1"
+publish = false
+[package.metadata.cargo-udeps.policy]
+openssl-sys = { auto-fix = false }
+[dependencies]
+openssl-sys = "0.9"
+"#,
+		runner.read_file("Cargo.toml")?,
+	);
+	Ok(())
+}
@@ -0,0 +1,63 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+"#;
+
+static A_CARGO_TOML :&str = r#"[package]
+name = "a"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+shared = { path = "../shared" }
+only-a = { path = "../only-a" }
+"#;
+
+static B_CARGO_TOML :&str = r#"[package]
+name = "b"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dev-dependencies]
+shared = { path = "../shared" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", A_CARGO_TOML)?
+		.file("./a/src/lib.rs", "")?
+		.dir("./b/src")?
+		.file("./b/Cargo.toml", B_CARGO_TOML)?
+		.file("./b/src/lib.rs", "")?;
+	for name in ["shared", "only-a"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner.arg("--workspace").arg("--all-targets").arg("--print-unused-names"))
+}
+
+#[test]
+fn print_unused_names() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_print_unused_names")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!("only-a\nshared\n", stdout);
+	Ok(())
+}
+
+#[test]
+fn print_unused_names_with_package() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_print_unused_names_with_package")?
+		.arg("--with-package")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("a:only-a\na:shared\nb:shared\n", stdout);
+	Ok(())
+}
@@ -0,0 +1,75 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["macros", "user"]
+"#;
+
+static MACROS_CARGO_TOML :&str = r#"[package]
+name = "macros"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[lib]
+proc-macro = true
+[dependencies]
+helper = { path = "../helper" }
+unused-helper = { path = "../unused-helper" }
+"#;
+
+static MACROS_LIB_RS :&str = r#"extern crate proc_macro;
+
+use proc_macro::TokenStream;
+
+#[proc_macro]
+pub fn answer(_ :TokenStream) -> TokenStream {
+	helper::ANSWER.parse().unwrap()
+}
+"#;
+
+static USER_CARGO_TOML :&str = r#"[package]
+name = "user"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+macros = { path = "../macros" }
+"#;
+
+static USER_LIB_RS :&str = r#"pub fn answer() -> u32 {
+	macros::answer!()
+}
+"#;
+
+#[test]
+fn proc_macro_member() -> CargoResult<()> {
+	let (code, stdout_masked) = Runner::new("cargo_udeps_test_proc_macro_member")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./macros/src")?
+		.file("./macros/Cargo.toml", MACROS_CARGO_TOML)?
+		.file("./macros/src/lib.rs", MACROS_LIB_RS)?
+		.dir("./user/src")?
+		.file("./user/Cargo.toml", USER_CARGO_TOML)?
+		.file("./user/src/lib.rs", USER_LIB_RS)?
+		.path_dep("helper", "pub const ANSWER :&str = \"42\";\n")?
+		.path_dep("unused-helper", "")?
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`macros v0.0.1 (██████████/macros)`
+└─── dependencies
+     └─── "unused-helper" (macros/Cargo.toml:10)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
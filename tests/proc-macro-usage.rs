@@ -0,0 +1,131 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "proc_macro_usage"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+derive-only = { path = "derive-only" }
+attribute = { path = "attribute" }
+function-like = { path = "function-like" }
+unused-derive = { path = "unused-derive" }
+runtime = { path = "runtime" }
+
+[dev-dependencies]
+dev-derive = { path = "dev-derive" }
+"#;
+
+static LIB_RS :&str = r#"#[derive(derive_only::Answer)]
+pub struct Used;
+
+#[attribute::keep]
+pub fn kept() {}
+
+function_like::make!();
+
+// The derive of `runtime` is re-exported by it, like `serde` does with `serde_derive`
+#[derive(runtime::Answer)]
+pub struct AlsoUsed;
+"#;
+
+static TEST_RS :&str = r#"#[derive(dev_derive::Answer)]
+struct InTests;
+
+#[test]
+fn t() {
+	let _ = InTests;
+}
+"#;
+
+static MACRO_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[lib]
+proc-macro = true
+"#;
+
+static MACRO_LIB_RS :&str = r#"extern crate proc_macro;
+
+use proc_macro::TokenStream;
+
+#[proc_macro_derive(Answer)]
+pub fn answer(_ :TokenStream) -> TokenStream {
+	TokenStream::new()
+}
+
+#[proc_macro_attribute]
+pub fn keep(_ :TokenStream, item :TokenStream) -> TokenStream {
+	item
+}
+
+#[proc_macro]
+pub fn make(_ :TokenStream) -> TokenStream {
+	TokenStream::new()
+}
+"#;
+
+static RUNTIME_CARGO_TOML :&str = r#"[package]
+name = "runtime"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+runtime-derive = { path = "../runtime-derive" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?
+		.dir("./tests")?
+		.file("./tests/t.rs", TEST_RS)?
+		.dir("./runtime/src")?
+		.file("./runtime/Cargo.toml", RUNTIME_CARGO_TOML)?
+		.file("./runtime/src/lib.rs", "pub use runtime_derive::Answer;\n")?;
+	for name in ["derive-only", "attribute", "function-like", "unused-derive", "dev-derive", "runtime-derive"] {
+		runner = runner
+			.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &MACRO_CARGO_TOML.replace("{}", name))?
+			.file(&format!("./{}/src/lib.rs", name), MACRO_LIB_RS)?;
+	}
+	Ok(runner.arg("--all-targets").arg("--output").arg("json"))
+}
+
+fn unused(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok(serde_json::json!([unused_deps["normal"], unused_deps["development"]]))
+}
+
+#[test]
+fn proc_macro_usage() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_proc_macro_usage")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!([["unused-derive"], []]), unused(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn proc_macro_usage_lint_backend() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_proc_macro_usage_lint")?
+		.arg("--backend")
+		.arg("lint")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!([["unused-derive"], []]), unused(&stdout)?);
+	Ok(())
+}
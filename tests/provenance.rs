@@ -0,0 +1,34 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "provenance"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+#[test]
+fn versions_in_json() -> CargoResult<()> {
+	let (code, stdout) = Runner::new("cargo_udeps_test_provenance")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(0, code);
+	let report :serde_json::Value = serde_json::from_str(&stdout)?;
+	let provenance = &report["provenance"];
+	assert_eq!(env!("CARGO_PKG_VERSION"), provenance["cargo_udeps"]);
+	assert_eq!(cargo::version().to_string(), provenance["cargo"]);
+	assert!(provenance["rustc"].as_str().is_some_and(|v| v.contains("nightly") || v.contains("dev")));
+	assert!(provenance["rustc_commit"].is_string());
+	assert_eq!("depinfo", provenance["backend"]);
+	Ok(())
+}
@@ -0,0 +1,66 @@
+#![cfg(unix)]
+
+mod runner;
+
+use std::fs::{self, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::env;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "read-only"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "unused" }
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`read-only v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused"
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn read_only() -> CargoResult<()> {
+	let cache = tempfile::Builder::new().prefix("cargo_udeps_test_read_only_cache").tempdir()?;
+	env::set_var("XDG_CACHE_HOME", cache.path());
+	let runner = Runner::new("cargo_udeps_test_read_only")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets");
+	let set_read_only = |read_only :bool| -> CargoResult<()> {
+		let mode = if read_only { 0o555 } else { 0o755 };
+		for dir in [".", "src", "unused", "unused/src"] {
+			fs::set_permissions(runner.path().join(dir), Permissions::from_mode(mode))?;
+		}
+		Ok(())
+	};
+	set_read_only(true)?;
+	if fs::write(runner.path().join("probe"), "").is_ok() {
+		// Permissions don't apply, for example to root
+		fs::remove_file(runner.path().join("probe"))?;
+		set_read_only(false)?;
+		return Ok(());
+	}
+	let result = runner.run();
+	set_read_only(false)?;
+	let (code, stdout_masked) = result?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout_masked);
+	assert!(!runner.path().join("target").exists());
+	assert!(!runner.path().join("Cargo.lock").exists());
+	assert!(cache.path().join("cargo-udeps").exists());
+	Ok(())
+}
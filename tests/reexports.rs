@@ -0,0 +1,136 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["facade"]
+
+[package]
+name = "reexports"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+facade = { path = "facade" }
+inner-a = { path = "inner-a" }
+inner-c = { path = "inner-c" }
+"#;
+
+static FACADE_CARGO_TOML :&str = r#"[package]
+name = "facade"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+inner-a = { path = "../inner-a" }
+inner-b = { path = "../inner-b" }
+inner-c = { path = "../inner-c" }
+unused = { path = "../unused" }
+"#;
+
+/// Nothing but re-exports, of each kind.
+static FACADE_LIB_RS :&str = r#"pub use inner_a::A;
+pub extern crate inner_b;
+#[doc(hidden)]
+pub use inner_c::*;
+"#;
+
+/// `reexports` depends on `inner-a` and `inner-c` directly, but only uses them through `facade`.
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub fn f() -> facade::A {\n\tfacade::c();\n\tfacade::A\n}\n")?
+		.dir("./facade/src")?
+		.file("./facade/Cargo.toml", FACADE_CARGO_TOML)?
+		.file("./facade/src/lib.rs", FACADE_LIB_RS)?;
+	for (name, lib_rs) in [
+		("inner-a", "pub struct A;\n"),
+		("inner-b", "pub struct B;\n"),
+		("inner-c", "pub struct C;\n\npub fn c() {}\n"),
+		("unused", ""),
+	] {
+		runner = runner
+			.path_dep(name, lib_rs)?;
+	}
+	Ok(runner)
+}
+
+fn unused_normal(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok(unused_deps["normal"].clone())
+}
+
+#[test]
+fn reexports_of_facade() -> CargoResult<()> {
+	// `pub use`, `pub extern crate` and glob re-exports all use the crate
+	for backend in ["depinfo", "lint"] {
+		let (code, stdout) = runner(&format!("cargo_udeps_test_reexports_of_facade_{}", backend))?
+			.arg("--backend")
+			.arg(backend)
+			.arg("-p")
+			.arg("facade")
+			.arg("--output")
+			.arg("json")
+			.run()?;
+		assert_eq!(1, code);
+		assert_eq!(serde_json::json!(["unused"]), unused_normal(&stdout)?);
+	}
+	Ok(())
+}
+
+#[test]
+fn used_through_facade_lint() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_used_through_facade_lint")?
+		.arg("--backend")
+		.arg("lint")
+		.arg("-p")
+		.arg("reexports")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!(["inner-a", "inner-c"]), unused_normal(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn used_through_facade_depinfo() -> CargoResult<()> {
+	// The depinfo has every crate that is loaded, which includes the dependencies of
+	// `facade`, so `--explain` says where else they come from
+	let (code, stdout) = runner("cargo_udeps_test_used_through_facade_depinfo")?
+		.arg("-p")
+		.arg("reexports")
+		.arg("--explain")
+		.arg("inner-c")
+		.run()?;
+	assert_eq!(0, code);
+	let mut lines = stdout.lines();
+	assert_eq!(Some("`inner-c` (normal) of `reexports v0.0.1 (██████████)`: used"), lines.next());
+	let evidence = lines.next().unwrap();
+	assert!(evidence.starts_with("  lib uses it as `inner_c`: `libinner_c-"), "{}", stdout);
+	assert_eq!(
+		Some(
+			"    `facade` depends on it too, so it is in the depinfo even if it is only used \
+			through what `facade` re-exports (`--backend lint` tells these apart)",
+		),
+		lines.next(),
+	);
+	assert_eq!(None, lines.next());
+
+	// `facade` itself depends on nothing that `reexports` uses
+	let (_, stdout) = runner("cargo_udeps_test_used_through_facade_depinfo_facade")?
+		.arg("-p")
+		.arg("reexports")
+		.arg("--explain")
+		.arg("facade")
+		.run()?;
+	assert_eq!(2, stdout.lines().count(), "{}", stdout);
+	Ok(())
+}
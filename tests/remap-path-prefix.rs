@@ -0,0 +1,82 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "remap"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+"#;
+
+fn runner(prefix :&str, cargo_toml :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(cargo_toml)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used::ANSWER;\n")?
+		.path_dep("used", "pub const ANSWER :u32 = 42;\n")?
+		.path_dep("unused", "")?)
+}
+
+#[cfg(unix)]
+#[test]
+fn remap_path_prefix() -> CargoResult<()> {
+	// The files of the build are looked up through a symlink
+	let runner = runner("cargo_udeps_test_remap_path_prefix", CARGO_TOML)?;
+	let checkout = tempfile::Builder::new().prefix("cargo_udeps_test_remap_path_prefix_checkout").tempdir()?;
+	let link = checkout.path().join("project");
+	std::os::unix::fs::symlink(runner.path(), &link)?;
+	let remap = format!("{}={}", runner.path().display(), link.display());
+	let (code, stdout_masked) = runner
+		.arg("--remap-path-prefix")
+		.arg(remap)
+		.arg("--all-targets")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`remap v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+#[test]
+fn remap_path_prefix_metadata() -> CargoResult<()> {
+	// Files that are remapped to a place where they don't exist can't be found
+	let cargo_toml = format!(
+		"{}[workspace.metadata.cargo-udeps]\nremap-path-prefix = [\"/=/nonexistent/\"]\n",
+		CARGO_TOML,
+	);
+	let err = runner("cargo_udeps_test_remap_path_prefix_metadata", &cargo_toml)?
+		.arg("--all-targets")
+		.arg("--strict")
+		.run()
+		.unwrap_err()
+		.to_string();
+	// Either of the units of the lib may come first
+	assert!(err.contains(".d` is missing for lib"), "{}", err);
+	assert!(err.contains("of `remap v0.0.1"), "{}", err);
+	assert!(err.contains("if `--remap-path-prefix` doesn't match the paths of the build"), "{}", err);
+	// Without `--strict`, the units are skipped
+	let (code, stdout, stderr) = runner("cargo_udeps_test_remap_path_prefix_metadata_skipped", &cargo_toml)?
+		.arg("--all-targets")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout);
+	assert!(stderr.contains("the unit is skipped"), "{}", stderr);
+	Ok(())
+}
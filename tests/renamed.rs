@@ -0,0 +1,88 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "renamed"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+used-normal = { package = "alpha", path = "alpha" }
+unused-normal = { package = "beta", path = "beta" }
+
+[dev-dependencies]
+used_dev = { package = "gamma", path = "gamma" }
+unused_dev = { package = "delta", path = "delta" }
+
+[build-dependencies]
+usedbuild = { package = "epsilon", path = "epsilon" }
+unusedbuild = { package = "zeta", path = "zeta" }
+"#;
+
+static LIB_RS :&str = "pub use used_normal::f;\n";
+static TEST_RS :&str = "#[test]\nfn t() {\n\tused_dev::f();\n}\n";
+static BUILD_RS :&str = "fn main() {\n\tusedbuild::f();\n}\n";
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?
+		.dir("./tests")?
+		.file("./tests/t.rs", TEST_RS)?
+		.file("./build.rs", BUILD_RS)?;
+	for name in ["alpha", "beta", "gamma", "delta", "epsilon", "zeta"] {
+		runner = runner
+			.path_dep(name, "pub fn f() {}\n")?;
+	}
+	Ok(runner.arg("--all-targets").arg("--output").arg("json"))
+}
+
+fn unused(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok(serde_json::json!([unused_deps["normal"], unused_deps["development"], unused_deps["build"]]))
+}
+
+#[test]
+fn renamed() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_renamed")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!([["unused-normal"], ["unused_dev"], ["unusedbuild"]]), unused(&stdout)?);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(
+		serde_json::json!({ "unused-normal": "beta", "unused_dev": "delta", "unusedbuild": "zeta" }),
+		unused_deps["renamed"],
+	);
+	Ok(())
+}
+
+#[test]
+fn renamed_lint_backend() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_renamed_lint_backend")?
+		.arg("--backend")
+		.arg("lint")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!([["unused-normal"], ["unused_dev"], ["unusedbuild"]]), unused(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn ignored_by_the_name_in_the_manifest() -> CargoResult<()> {
+	let cargo_toml = format!(
+		"{}[package.metadata.cargo-udeps.ignore]\nnormal = [\"unused-normal\"]\ndevelopment = [\"unused_dev\"]\nbuild = [\"unusedbuild\"]\n",
+		CARGO_TOML,
+	);
+	let (code, _) = runner("cargo_udeps_test_renamed_ignored")?.cargo_toml(&cargo_toml)?.run()?;
+	assert_eq!(0, code);
+	Ok(())
+}
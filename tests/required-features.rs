@@ -0,0 +1,89 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "required_features"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+only-tool = { path = "only-tool" }
+parser = { path = "parser", optional = true }
+
+[dev-dependencies]
+only-demo = { path = "only-demo" }
+
+[features]
+cli = ["dep:parser"]
+demo = []
+
+[[bin]]
+name = "tool"
+required-features = ["cli"]
+
+[[example]]
+name = "demo"
+required-features = ["demo"]
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src/bin")?
+		.file("./src/lib.rs", "")?
+		.file("./src/bin/tool.rs", "use only_tool as _;\nuse parser as _;\n\nfn main() {}\n")?
+		.dir("./examples")?
+		.file("./examples/demo.rs", "use only_demo as _;\n\nfn main() {}\n")?;
+	for name in ["only-tool", "parser", "only-demo"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner.arg("--output").arg("json"))
+}
+
+/// The unused normal dependencies and dev-dependencies, or `None` if there are none.
+fn unused(stdout :&str) -> CargoResult<Option<(serde_json::Value, serde_json::Value)>> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let unused_deps = report["unused_deps"].as_object().unwrap().values().next();
+	Ok(unused_deps.map(|unused_deps| (unused_deps["normal"].clone(), unused_deps["development"].clone())))
+}
+
+#[test]
+fn required_features_skipped() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_required_features_skipped")?.run()?;
+	// `tool` isn't checked, so `only-tool` is reported
+	assert_eq!(1, code);
+	assert_eq!(Some((serde_json::json!(["only-tool"]), serde_json::json!([]))), unused(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn required_features_included() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_required_features_included")?
+		.arg("--include-required-features")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!(None, unused(&stdout)?);
+	// Examples aren't checked by default
+	assert!(stderr.contains("Enabling `cli` of `required_features`, which its targets require"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn required_features_included_all_targets() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_required_features_all_targets")?
+		.arg("--include-required-features")
+		.arg("--all-targets")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!(None, unused(&stdout)?);
+	assert!(stderr.contains("Enabling `cli`, `demo` of `required_features`, which its targets require"), "{}", stderr);
+	Ok(())
+}
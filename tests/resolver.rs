@@ -0,0 +1,87 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+resolver = "{}"
+"#;
+
+static A_CARGO_TOML :&str = r#"[package]
+name = "a"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[build-dependencies]
+tool = { path = "../tool", optional = true }
+[features]
+with-tool = ["tool"]
+"#;
+
+// Only the tests of `b` enable the feature
+static B_CARGO_TOML :&str = r#"[package]
+name = "b"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+a = { path = "../a" }
+[dev-dependencies]
+a = { path = "../a", features = ["with-tool"] }
+"#;
+
+static TOOL_CARGO_TOML :&str = r#"[package]
+name = "tool"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+static UNIFIED :&str = r#"unused dependencies:
+`a v0.0.1 (██████████/a)`
+└─── build-dependencies
+     └─── "tool" (a/Cargo.toml:7) (enabled by feature `with-tool`)
+Note: These dependencies might be used by other targets.
+      To find dependencies that are not used by any target, enable `--all-targets`.
+Note: Some dependencies are non-library packages.
+      `cargo-udeps` regards them as unused.
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn run(resolver :&str, prefix :&str) -> CargoResult<(i32, String)> {
+	Runner::new(prefix)?
+		.cargo_toml(&CARGO_TOML.replace("{}", resolver))?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", A_CARGO_TOML)?
+		.file("./a/src/lib.rs", "")?
+		.file("./a/build.rs", "fn main() {}\n")?
+		.dir("./b/src")?
+		.file("./b/Cargo.toml", B_CARGO_TOML)?
+		.file("./b/src/lib.rs", "pub use a as _;\n")?
+		.dir("./tool/src")?
+		.file("./tool/Cargo.toml", TOOL_CARGO_TOML)?
+		.file("./tool/src/main.rs", "fn main() {}\n")?
+		.arg("--workspace")
+		.run()
+}
+
+#[test]
+fn resolver_1_unifies_dev_features() -> CargoResult<()> {
+	let (code, stdout_masked) = run("1", "cargo_udeps_test_resolver_1")?;
+	assert_eq!(1, code);
+	assert_eq!(UNIFIED, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn resolver_2_only_enables_dev_features_for_tests() -> CargoResult<()> {
+	let (code, stdout_masked) = run("2", "cargo_udeps_test_resolver_2")?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout_masked);
+	Ok(())
+}
@@ -1,12 +1,15 @@
 #![allow(dead_code)]
 
+use std::cell::RefCell;
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
+use std::rc::Rc;
 use std::{env, fs, io, str};
 
 use anyhow::Context;
 use cargo::core::shell::Shell;
+use cargo::util::context::GlobalContext;
 use cargo::{CargoResult, CliError};
 use tempfile::TempDir;
 
@@ -18,7 +21,7 @@ fn set_rustc_env() -> CargoResult<()> {
 	let toolchain =
 		env::var("CARGO_UDEPS_TEST_TOOLCHAIN").unwrap_or_else(|_| DEFAULT_TOOLCHAIN.to_owned());
 	let Output { status, stdout, .. } = Command::new("rustup")
-		.args(&["which", "rustc"])
+		.args(["which", "rustc"])
 		.env("RUSTUP_TOOLCHAIN", &toolchain)
 		.output()?;
 	if !status.success() {
@@ -34,12 +37,16 @@ pub(crate) struct Runner {
 	cwd :TempDir,
 	cargo_home :PathBuf,
 	args :Vec<OsString>,
+	/// Environment variables that are set for the run only.
+	envs :Vec<(String, String)>,
 }
 
 impl Runner {
 	pub(crate) fn new(prefix :&str) -> CargoResult<Self> {
 		if env::var_os(SET_RUSTC_ENV).is_none() {
 			set_rustc_env()?;
+			// The human output is wrapped to it
+			env::remove_var("COLUMNS");
 		}
 		let cwd = tempfile::Builder::new().prefix(prefix).tempdir()?;
 		let cargo_home = cargo::util::homedir(cwd.as_ref())
@@ -49,6 +56,7 @@ impl Runner {
 			cwd,
 			cargo_home,
 			args,
+			envs : Vec::new(),
 		})
 	}
 
@@ -68,21 +76,70 @@ impl Runner {
 		Ok(self)
 	}
 
+	/// Adds the package `name` in `./<name>`, for a path dependency, with the given `lib.rs`.
+	pub(crate) fn path_dep(self, name :&str, lib_rs :&str) -> io::Result<Self> {
+		let cargo_toml = format!(
+			"[package]\nname = \"{}\"\nversion = \"0.0.1\"\nedition = \"2018\"\npublish = false\n",
+			name,
+		);
+		self.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &cargo_toml)?
+			.file(&format!("./{}/src/lib.rs", name), lib_rs)
+	}
+
+	/// Copies the workspace in `tests/fixtures/<name>` into the temporary directory.
+	pub(crate) fn fixture(self, name :&str) -> io::Result<Self> {
+		fn copy(from :&Path, to :&Path) -> io::Result<()> {
+			fs::create_dir_all(to)?;
+			for entry in fs::read_dir(from)? {
+				let entry = entry?;
+				if entry.file_type()?.is_dir() {
+					copy(&entry.path(), &to.join(entry.file_name()))?;
+				} else {
+					fs::copy(entry.path(), to.join(entry.file_name()))?;
+				}
+			}
+			Ok(())
+		}
+		let from = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name);
+		copy(&from, self.cwd.path())?;
+		Ok(self)
+	}
+
 	pub(crate) fn arg<S: Into<OsString>>(mut self, arg: S) -> Self {
 		self.args.push(arg.into());
 		self
 	}
 
-	pub(crate) fn run(self) -> CargoResult<(i32, String)> {
+	pub(crate) fn env(mut self, key :&str, value :&str) -> Self {
+		self.envs.push((key.to_owned(), value.to_owned()));
+		self
+	}
+
+	pub(crate) fn path(&self) -> &Path {
+		self.cwd.path()
+	}
+
+	pub(crate) fn read_file(&self, file_name :&str) -> io::Result<String> {
+		fs::read_to_string(self.cwd.path().join(file_name))
+	}
+
+	pub(crate) fn run(&self) -> CargoResult<(i32, String)> {
+		let (code, stdout, _) = self.run_with_stderr()?;
+		Ok((code, stdout))
+	}
+
+	/// Like [`Runner::run`], but also returns what was written to stderr.
+	pub(crate) fn run_with_stderr(&self) -> CargoResult<(i32, String, String)> {
 		let mut stdout = vec![];
+		let stderr_buf = SharedBuf::default();
 		let stderr = if std::env::var("UDEPS_VERBOSE_TEST").is_ok() {
 			Shell::new()
 		} else {
 			eprintln!("Please set the UDEPS_VERBOSE_TEST environment variable to enable more verbose logging");
-			Shell::from_write(Box::new(vec![]))
+			Shell::from_write(Box::new(stderr_buf.clone()))
 		};
-		let mut config = cargo::util::context::GlobalContext::new(stderr,
-			self.cwd.path().to_owned(), self.cargo_home.clone());
+		let mut config = self.config(stderr);
 		let code = match cargo_udeps::run(self.args.clone(), &mut config, &mut stdout) {
 			Ok(()) => 0,
 			Err(CliError {
@@ -94,6 +151,51 @@ impl Runner {
 		};
 		let cwd_lossy = self.cwd.path().to_string_lossy();
 		let stdout = str::from_utf8(&stdout)?.replace(&*cwd_lossy, "██████████");
-		Ok((code, stdout))
+		let stderr = String::from_utf8(stderr_buf.0.borrow().clone())?.replace(&*cwd_lossy, "██████████");
+		Ok((code, stdout, stderr))
+	}
+
+	/// Checks through the library API, with the arguments given to [`Runner::arg`].
+	pub(crate) fn check(&self) -> CargoResult<cargo_udeps::Report> {
+		let options = self.args[2..]
+			.iter()
+			.fold(cargo_udeps::CheckOptions::new(), |options, arg| options.arg(arg));
+		cargo_udeps::check(&options, &mut self.config(Shell::from_write(Box::new(io::sink()))))
+	}
+
+	fn config(&self, shell :Shell) -> GlobalContext {
+		let mut config = GlobalContext::new(shell, self.cwd.path().to_owned(), self.cargo_home.clone());
+		// Tests running in GitHub Actions shouldn't write to the summary of the job,
+		// and the artifacts of the tests belong in their own directory
+		let envs = env::vars()
+			.filter(|(key, _)| !["GITHUB_STEP_SUMMARY", "CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"].contains(&&**key))
+			.chain(self.envs.iter().cloned())
+			.collect();
+		config.set_env(envs);
+		config
+	}
+}
+
+#[derive(Clone, Default)]
+struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for SharedBuf {
+	fn write(&mut self, buf :&[u8]) -> io::Result<usize> {
+		self.0.borrow_mut().write(buf)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// Masks the `provenance` of a JSON report, whose versions differ between machines.
+pub fn mask_provenance(json :&str) -> String {
+	match json.find(r#""provenance":{"#) {
+		Some(start) => {
+			let end = start + json[start..].find('}').expect("should be closed") + 1;
+			format!("{}\"provenance\":██████████{}", &json[..start], &json[end..])
+		},
+		None => json.to_owned(),
 	}
 }
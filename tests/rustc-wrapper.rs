@@ -0,0 +1,105 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "rustc_wrapper"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+used = { path = "used" }
+flagged = { path = "flagged" }
+unused = { path = "unused" }
+"#;
+
+static LIB_RS :&str = r#"pub use used as _;
+
+#[cfg(udeps_test_flag)]
+pub use flagged as _;
+"#;
+
+/// Records the crates it compiles, like a cache would see them.
+static WRAPPER :&str = r#"#!/bin/sh
+log="$(dirname "$0")/wrapper.log"
+prev=
+for arg in "$@"; do
+	if [ "$prev" = "--crate-name" ]; then
+		echo "$arg" >> "$log"
+	fi
+	prev="$arg"
+done
+exec "$@"
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for name in ["used", "flagged", "unused"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner.arg("--output").arg("json"))
+}
+
+fn unused_normal(stdout :&str) -> CargoResult<serde_json::Value> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	Ok(unused_deps["normal"].clone())
+}
+
+#[cfg(unix)]
+#[test]
+fn rustc_wrapper_bypassed_for_checked_units() -> CargoResult<()> {
+	use std::os::unix::fs::PermissionsExt;
+
+	for backend in ["depinfo", "lint"] {
+		let runner = runner(&format!("cargo_udeps_test_rustc_wrapper_{}", backend))?
+			.file("./wrapper.sh", WRAPPER)?;
+		let wrapper = runner.path().join("wrapper.sh");
+		std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755))?;
+		let runner = runner
+			.arg("--backend")
+			.arg(backend)
+			.env("RUSTC_WRAPPER", &wrapper.to_string_lossy())
+			.env("RUSTFLAGS", "--cfg udeps_test_flag");
+		let (code, stdout) = runner.run()?;
+		assert_eq!(1, code);
+		assert_eq!(serde_json::json!(["unused"]), unused_normal(&stdout)?);
+		// The dependencies still go through the wrapper, the checked package doesn't
+		let log = runner.read_file("wrapper.log")?;
+		// `___` is the crate of cargo's queries of the target information
+		let mut compiled = log.lines().filter(|&name| name != "___").collect::<Vec<_>>();
+		compiled.sort();
+		assert_eq!(vec!["flagged", "unused", "used"], compiled);
+	}
+	Ok(())
+}
+
+#[test]
+fn rustflags_are_kept() -> CargoResult<()> {
+	// `flagged` is only used with the `--cfg` of `RUSTFLAGS`
+	for backend in ["depinfo", "lint"] {
+		let (code, stdout) = runner(&format!("cargo_udeps_test_rustflags_{}", backend))?
+			.arg("--backend")
+			.arg(backend)
+			.env("RUSTFLAGS", "--cfg udeps_test_flag")
+			.run()?;
+		assert_eq!(1, code);
+		assert_eq!(serde_json::json!(["unused"]), unused_normal(&stdout)?);
+		let (_, stdout) = runner(&format!("cargo_udeps_test_rustflags_none_{}", backend))?
+			.arg("--backend")
+			.arg(backend)
+			.run()?;
+		assert_eq!(serde_json::json!(["flagged", "unused"]), unused_normal(&stdout)?);
+	}
+	Ok(())
+}
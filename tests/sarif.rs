@@ -0,0 +1,101 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["member"]
+
+[workspace.metadata.cargo-udeps.policy]
+b = { severity = "warn" }
+
+[package]
+name = "sarif"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[dependencies]
+a = { path = "a" }
+
+[dev-dependencies]
+"b" = { path = "b" }
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "member"
+version = "0.0.1"
+edition = "2018"
+publish = false
+
+[build-dependencies]
+  a = { path = "../a" }
+"#;
+
+#[test]
+fn results_point_at_the_manifest() -> CargoResult<()> {
+	let mut runner = Runner::new("cargo_udeps_test_sarif")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.dir("./member/src")?
+		.file("./member/Cargo.toml", MEMBER_CARGO_TOML)?
+		.file("./member/src/lib.rs", "")?
+		.file("./member/build.rs", "fn main() {}\n")?;
+	for name in ["a", "b"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	let (code, stdout_masked) = runner
+		.arg("--workspace")
+		.arg("--all-targets")
+		.arg("--output")
+		.arg("sarif")
+		.run()?;
+	assert_eq!(1, code);
+
+	let log = serde_json::from_str::<serde_json::Value>(&stdout_masked)?;
+	assert_eq!("2.1.0", log["version"]);
+	let run = &log["runs"][0];
+	assert_eq!("cargo-udeps", run["tool"]["driver"]["name"]);
+	let rules = run["tool"]["driver"]["rules"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|rule| rule["id"].as_str().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(vec!["unused-normal", "unused-dev", "unused-build"], rules);
+
+	let results = run["results"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|result| {
+			let location = &result["locations"][0]["physicalLocation"];
+			(
+				result["ruleId"].as_str().unwrap().to_owned(),
+				result["level"].as_str().unwrap().to_owned(),
+				location["artifactLocation"]["uri"].as_str().unwrap().to_owned(),
+				location["region"]["startLine"].as_u64().unwrap(),
+				location["region"]["startColumn"].as_u64().unwrap(),
+				location["region"]["endColumn"].as_u64().unwrap(),
+			)
+		})
+		.collect::<Vec<_>>();
+	let expected = [
+		("unused-build", "error", "member/Cargo.toml", 8, 3, 4),
+		("unused-normal", "error", "Cargo.toml", 14, 1, 2),
+		("unused-dev", "warning", "Cargo.toml", 17, 1, 4),
+	]
+	.iter()
+	.map(|&(rule, level, uri, line, start, end)| (rule.to_owned(), level.to_owned(), uri.to_owned(), line, start, end))
+	.collect::<Vec<_>>();
+	assert_eq!(expected, results);
+	assert_eq!(
+		"`a` is an unused dependency of `sarif v0.0.1`",
+		run["results"][1]["message"]["text"],
+	);
+	Ok(())
+}
@@ -0,0 +1,62 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+"#;
+
+static A_CARGO_TOML :&str = r#"[package]
+name = "a"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+b = { path = "../b" }
+c = { path = "../c" }
+"#;
+
+static B_CARGO_TOML :&str = r#"[package]
+name = "b"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+c = { path = "../c" }
+"#;
+
+#[test]
+fn lib_of_one_member() -> CargoResult<()> {
+	// `b` is built as a dependency of `a`, but not analyzed.
+	let (code, stdout_masked) = Runner::new("cargo_udeps_test_selected_targets_lib_of_one_member")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", A_CARGO_TOML)?
+		.file("./a/src/lib.rs", "pub use b;\n")?
+		.file("./a/src/main.rs", "use c as _;\nfn main() {}\n")?
+		.dir("./b/src")?
+		.file("./b/Cargo.toml", B_CARGO_TOML)?
+		.file("./b/src/lib.rs", "")?
+		.path_dep("c", "")?
+		.arg("-p")
+		.arg("a")
+		.arg("--lib")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`a v0.0.1 (██████████/a)`
+└─── dependencies
+     └─── "c" (a/Cargo.toml:8)
+Note: These dependencies might be used by other targets.
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
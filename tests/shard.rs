@@ -0,0 +1,147 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b", "c", "d"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+helper = { path = "../helper" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.path_dep("helper", "")?;
+	for member in ["a", "b", "c", "d"] {
+		runner = runner
+			.dir(&format!("./{}/src", member))?
+			.file(&format!("./{}/Cargo.toml", member), &MEMBER_CARGO_TOML.replace("{}", member))?
+			.file(&format!("./{}/src/lib.rs", member), "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn shards_merge_to_the_full_report() -> CargoResult<()> {
+	let (code, full) = runner("cargo_udeps_test_shard_full")?
+		.arg("--all-targets")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+
+	let reports = tempfile::Builder::new().prefix("cargo_udeps_test_shard_reports").tempdir()?;
+	let mut merge = runner("cargo_udeps_test_shard_merge")?.arg("merge");
+	let mut checked = 0;
+	let mut shard_dirs = Vec::new();
+	for shard in 1..=3 {
+		let runner = runner("cargo_udeps_test_shard")?
+			.arg("--all-targets")
+			.arg("--output")
+			.arg("json")
+			.arg("--shard")
+			.arg(format!("{}/3", shard));
+		let (_, report) = runner.run()?;
+		checked += report.matches("[\"helper\"]").count();
+		// Package IDs with masked paths can't be parsed
+		let report = report.replace("██████████", &runner.path().to_string_lossy());
+		let path = reports.path().join(format!("{}.json", shard));
+		std::fs::write(&path, report)?;
+		merge = merge.arg(path);
+		shard_dirs.push(runner);
+	}
+	assert_eq!(4, checked);
+
+	let (code, mut merged) = merge.run()?;
+	for dir in &shard_dirs {
+		merged = merged.replace(&*dir.path().to_string_lossy(), "██████████");
+	}
+	assert_eq!(1, code);
+	assert_eq!(full, merged);
+	Ok(())
+}
+
+#[test]
+fn invalid_shard() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_shard_invalid")?
+		.arg("--shard")
+		.arg("4/3")
+		.run()
+		.unwrap_err();
+	assert!(err.to_string().contains("expected `K/N`"), "{}", err);
+	Ok(())
+}
+
+/// A report with the unused normal dependencies of `a`, and of `b` if it was checked.
+fn report(a :&[&str], b :Option<&[&str]>) -> String {
+	let unused = |name :&str, deps :&[&str]| serde_json::json!({
+		"manifest_path": format!("/ws/{}/Cargo.toml", name),
+		"normal": deps,
+		"development": [],
+		"build": [],
+	});
+	let mut unused_deps = serde_json::Map::new();
+	let mut features = serde_json::Map::new();
+	for (name, deps) in [("a", Some(a)), ("b", b)] {
+		let Some(deps) = deps else {
+			continue;
+		};
+		let id = format!("{} 0.0.1 (path+file:///ws/{})", name, name);
+		features.insert(id.clone(), serde_json::json!([]));
+		if !deps.is_empty() {
+			unused_deps.insert(id, unused(name, deps));
+		}
+	}
+	let success = unused_deps.is_empty();
+	serde_json::json!({
+		"success": success,
+		"packages_checked": features.len(),
+		"features": features,
+		"unused_deps": unused_deps,
+		"note": null,
+	}).to_string()
+}
+
+#[test]
+fn merge_overlapping_reports() -> CargoResult<()> {
+	let (code, merged) = runner("cargo_udeps_test_shard_merge_overlapping")?
+		// Both check `a`, and only the first one `b`
+		.file("./first.json", &report(&["helper", "other"], Some(&["helper"])))?
+		.file("./second.json", &report(&["helper", "third"], None))?
+		.arg("merge")
+		.arg("first.json")
+		.arg("second.json")
+		.run()?;
+	assert_eq!(1, code);
+	let merged = serde_json::from_str::<serde_json::Value>(&merged)?;
+	assert_eq!(serde_json::json!(false), merged["success"]);
+	assert_eq!(serde_json::json!(2), merged["packages_checked"]);
+	let unused_deps = merged["unused_deps"].as_object().unwrap();
+	assert_eq!(2, unused_deps.len(), "{}", merged);
+	assert_eq!(serde_json::json!(["helper"]), unused_deps["a 0.0.1 (path+file:///ws/a)"]["normal"]);
+	assert_eq!(serde_json::json!(["helper"]), unused_deps["b 0.0.1 (path+file:///ws/b)"]["normal"]);
+
+	// What a later run finds used is not reported anymore
+	let (code, merged) = runner("cargo_udeps_test_shard_merge_overlapping_used")?
+		.file("./first.json", &report(&["helper"], None))?
+		.file("./second.json", &report(&[], None))?
+		.arg("merge")
+		.arg("first.json")
+		.arg("second.json")
+		.run()?;
+	assert_eq!(0, code);
+	let merged = serde_json::from_str::<serde_json::Value>(&merged)?;
+	assert_eq!(serde_json::json!(true), merged["success"]);
+	assert_eq!(serde_json::json!({}), merged["unused_deps"]);
+	Ok(())
+}
@@ -0,0 +1,97 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "show_ignored"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+kept = { path = "kept" }
+
+[dev-dependencies]
+listed = { path = "listed" }
+unused-dev = { path = "unused-dev" }
+
+[package.metadata.cargo-udeps.ignore]
+development = ["listed"]
+"#;
+
+static EXPECTED :&str = r#"unused dependencies:
+`show_ignored v0.0.1 (██████████)`
+└─── dev-dependencies
+     └─── "unused-dev" (Cargo.toml:14)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+ignored unused dependencies:
+  `show_ignored v0.0.1`
+    "kept" (normal): `src/lib.rs` allows `unused_crate_dependencies`
+    "listed" (development): it is in `package.metadata.cargo-udeps.ignore`
+"#;
+
+/// The library keeps its dependencies on purpose, which doesn't cover the dev-dependencies.
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "#![allow(unused_crate_dependencies)]\n")?;
+	for name in ["kept", "listed", "unused-dev"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner.arg("--all-targets"))
+}
+
+#[test]
+fn source_opt_out() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_source_opt_out")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, unused_deps) = report["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(serde_json::json!([]), unused_deps["normal"]);
+	assert_eq!(serde_json::json!(["unused-dev"]), unused_deps["development"]);
+	// Only listed with `--show-ignored`
+	assert_eq!(serde_json::Value::Null, report["ignored"]);
+	Ok(())
+}
+
+#[test]
+fn show_ignored_human() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_show_ignored_human")?
+		.arg("--show-ignored")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED, stdout);
+	Ok(())
+}
+
+#[test]
+fn show_ignored_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_show_ignored_json")?
+		.arg("--show-ignored")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let (_, ignored) = report["ignored"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(
+		&serde_json::json!([
+			{ "name" : "kept", "kind" : "normal", "reason" : "`src/lib.rs` allows `unused_crate_dependencies`" },
+			{ "name" : "listed", "kind" : "development", "reason" : "it is in `package.metadata.cargo-udeps.ignore`" },
+		]),
+		ignored,
+	);
+	Ok(())
+}
@@ -0,0 +1,116 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+exclude = ["unused"]
+"#;
+
+static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+unused = { path = "../unused" }
+"#;
+
+/// Both members have an unused dependency, and everything is committed.
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.path_dep("unused", "")?;
+	for name in ["a", "b"] {
+		runner = runner
+			.dir(&format!("./{}/src", name))?
+			.file(&format!("./{}/Cargo.toml", name), &MEMBER_CARGO_TOML.replace("{}", name))?
+			.file(&format!("./{}/src/lib.rs", name), "")?;
+	}
+	let repo = git2::Repository::init(runner.path())?;
+	let mut index = repo.index()?;
+	index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+	index.write()?;
+	let tree = repo.find_tree(index.write_tree()?)?;
+	let signature = git2::Signature::new("Jane Doe", "jane@example.com", &git2::Time::new(1_622_548_800, 0))?;
+	repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])?;
+	Ok(runner.arg("--workspace").arg("--output").arg("json"))
+}
+
+/// The names of the packages with unused dependencies.
+fn reported(stdout :&str) -> CargoResult<Vec<String>> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let mut names = report["unused_deps"]
+		.as_object()
+		.unwrap()
+		.keys()
+		.map(|id| id.split(' ').next().unwrap().to_owned())
+		.collect::<Vec<_>>();
+	names.sort();
+	Ok(names)
+}
+
+#[test]
+fn since_changed_file() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_since_changed_file")?
+		.file("./b/src/lib.rs", "pub fn changed() {}\n")?
+		.arg("--since")
+		.arg("HEAD")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(vec!["b"], reported(&stdout)?);
+	assert!(stderr.contains("Checking `b`, which changed since `HEAD`"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn since_untracked_file() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_since_untracked_file")?
+		.file("./a/src/new.rs", "")?
+		.arg("--since")
+		.arg("HEAD")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(vec!["a"], reported(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn since_workspace_manifest() -> CargoResult<()> {
+	// The manifest of the workspace may change any member
+	let (code, stdout) = runner("cargo_udeps_test_since_workspace_manifest")?
+		.file("./Cargo.toml", &format!("{}resolver = \"2\"\n", CARGO_TOML))?
+		.arg("--since")
+		.arg("HEAD")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(vec!["a", "b"], reported(&stdout)?);
+	Ok(())
+}
+
+#[test]
+fn since_nothing_changed() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_since_nothing_changed")?
+		.arg("--since")
+		.arg("HEAD")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!(Vec::<String>::new(), reported(&stdout)?);
+	assert!(stderr.contains("No members changed since `HEAD`"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn since_unknown_revision() -> CargoResult<()> {
+	let err = runner("cargo_udeps_test_since_unknown_revision")?
+		.arg("--since")
+		.arg("nonexistent")
+		.run()
+		.unwrap_err();
+	assert_eq!("could not find the git revision `nonexistent`", err.to_string());
+	Ok(())
+}
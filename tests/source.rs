@@ -0,0 +1,123 @@
+mod runner;
+
+use std::fs;
+use std::path::Path;
+
+use cargo::util::IntoUrl;
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "source"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+from-path = { path = "from-path" }
+from-git = { git = "{url}" }
+"#;
+
+static LIB_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+/// A git repository with the `from-git` package.
+fn git_repo() -> CargoResult<TempDir> {
+	let dir = tempfile::Builder::new().prefix("cargo_udeps_test_source_git").tempdir()?;
+	fs::create_dir(dir.path().join("src"))?;
+	fs::write(dir.path().join("Cargo.toml"), LIB_CARGO_TOML.replace("{}", "from-git"))?;
+	fs::write(dir.path().join("src/lib.rs"), "")?;
+	let repo = git2::Repository::init(dir.path())?;
+	let mut index = repo.index()?;
+	index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+	let tree = repo.find_tree(index.write_tree()?)?;
+	let signature = git2::Signature::now("Jane Doe", "jane@example.com")?;
+	repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])?;
+	Ok(dir)
+}
+
+fn runner(prefix :&str, repo :&Path) -> CargoResult<Runner> {
+	let url = repo.into_url()?;
+	Ok(Runner::new(prefix)?
+		.cargo_toml(&CARGO_TOML.replace("{url}", url.as_str()))?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("from-path", "")?
+		.arg("--all-targets")
+		.arg("--message-format")
+		.arg("short"))
+}
+
+#[test]
+fn all_sources() -> CargoResult<()> {
+	let repo = git_repo()?;
+	let (code, stdout) = runner("cargo_udeps_test_source_all", repo.path())?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		"source: unused dependency `from-git` (normal)\nsource: unused dependency `from-path` (normal)\n",
+		stdout,
+	);
+	Ok(())
+}
+
+#[test]
+fn only_git() -> CargoResult<()> {
+	let repo = git_repo()?;
+	let (code, stdout) = runner("cargo_udeps_test_source_git", repo.path())?
+		.arg("--source")
+		.arg("git")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("source: unused dependency `from-git` (normal)\n", stdout);
+	Ok(())
+}
+
+#[test]
+fn only_path() -> CargoResult<()> {
+	let repo = git_repo()?;
+	let (code, stdout) = runner("cargo_udeps_test_source_path", repo.path())?
+		.arg("--source")
+		.arg("path")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("source: unused dependency `from-path` (normal)\n", stdout);
+	Ok(())
+}
+
+#[test]
+fn several_sources() -> CargoResult<()> {
+	let repo = git_repo()?;
+	let (code, _) = runner("cargo_udeps_test_source_several", repo.path())?
+		.arg("--source")
+		.arg("crates-io")
+		.arg("--source")
+		.arg("registry:my-registry")
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(0, code);
+	Ok(())
+}
+
+#[test]
+fn invalid_source() -> CargoResult<()> {
+	let repo = git_repo()?;
+	let err = runner("cargo_udeps_test_source_invalid", repo.path())?
+		.arg("--source")
+		.arg("svn")
+		.run()
+		.unwrap_err();
+	assert!(
+		err.to_string().contains("expected `crates-io`, `git`, `path` or `registry:<NAME>`, found `svn`"),
+		"{}",
+		err,
+	);
+	Ok(())
+}
@@ -0,0 +1,43 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "status_file"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "unused" }
+"#;
+
+fn runner(prefix :&str, lib_rs :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", lib_rs)?
+		.path_dep("unused", "")?
+		.arg("--status-file")
+		.arg("status.json"))
+}
+
+#[test]
+fn unused() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_status_file_unused", "")?;
+	let (code, _) = runner.run()?;
+	assert_eq!(1, code);
+	assert_eq!("{\"errors\":0,\"unused\":1}\n", runner.read_file("status.json")?);
+	Ok(())
+}
+
+#[test]
+fn error() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_status_file_error", "fn main( {}\n")?;
+	runner.run().unwrap_err();
+	assert_eq!("{\"errors\":1,\"unused\":null}\n", runner.read_file("status.json")?);
+	Ok(())
+}
@@ -0,0 +1,88 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "step_summary"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+bare = { path = "bare" }
+[dev-dependencies]
+bare-dev = { path = "bare-dev" }
+"#;
+
+static EXPECTED :&str = r#"# Earlier step
+
+## cargo-udeps
+
+2 unused dependencies in 1 of the 1 checked packages.
+
+| Package | Normal | Development | Build |
+| --- | ---: | ---: | ---: |
+| `step_summary v0.0.1` | 1 | 1 | 0 |
+
+### `step_summary v0.0.1` (Cargo.toml)
+
+- `bare` (normal)
+- `bare-dev` (dev)
+
+```sh
+cargo remove --package step_summary bare
+cargo remove --package step_summary --dev bare-dev
+```
+
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.arg("--all-targets");
+	for name in ["bare", "bare-dev"] {
+		runner = runner
+			.path_dep(name, "")?;
+	}
+	Ok(runner)
+}
+
+#[test]
+fn step_summary() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_step_summary")?.file("./summary.md", "# Earlier step\n\n")?;
+	let summary_path = runner.path().join("summary.md");
+	let runner = runner.env("GITHUB_STEP_SUMMARY", summary_path.to_str().unwrap());
+	let (code, stdout) = runner.run()?;
+	assert_eq!(1, code);
+	assert!(stdout.contains("\"bare-dev\""), "{}", stdout);
+	assert_eq!(EXPECTED, runner.read_file("summary.md")?);
+	Ok(())
+}
+
+#[test]
+fn output_markdown() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_output_markdown")?
+		.arg("--output")
+		.arg("markdown")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED.strip_prefix("# Earlier step\n\n").unwrap(), stdout);
+	Ok(())
+}
+
+#[test]
+fn output_count() -> CargoResult<()> {
+	// `--report` is the same as `--output`
+	let (code, stdout) = runner("cargo_udeps_test_output_count")?
+		.arg("--report")
+		.arg("count")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("2 unused across 1 package\n", stdout);
+	Ok(())
+}
@@ -0,0 +1,102 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["a", "b"]
+"#;
+
+static A_CARGO_TOML :&str = r#"[package]
+name = "a"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+helper = { path = "../helper" }
+ignored = { path = "../ignored" }
+[package.metadata.cargo-udeps.ignore]
+normal = ["ignored"]
+"#;
+
+static B_CARGO_TOML :&str = r#"[package]
+name = "b"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+helper = { path = "../helper" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./a/src")?
+		.file("./a/Cargo.toml", A_CARGO_TOML)?
+		.file("./a/src/lib.rs", "")?
+		.dir("./b/src")?
+		.file("./b/Cargo.toml", B_CARGO_TOML)?
+		.file("./b/src/lib.rs", "pub use helper;\n")?
+		.path_dep("helper", "")?
+		.path_dep("ignored", "")?
+		.arg("--all-targets")
+		.arg("--stream"))
+}
+
+#[test]
+fn stream_human() -> CargoResult<()> {
+	let (code, _, stderr) = runner("cargo_udeps_test_stream_human")?.run_with_stderr()?;
+	assert_eq!(1, code);
+	let warnings = stderr.lines().filter(|line| line.starts_with("warning: a:")).collect::<Vec<_>>();
+	assert_eq!(vec!["warning: a: unused dependency `helper` (normal)"], warnings);
+	assert!(!stderr.contains("warning: b:"), "{}", stderr);
+	// Printed while building, rather than after it
+	let warning = stderr.find("warning: a:").unwrap();
+	let finished = stderr.find("Finished").expect("should finish the build");
+	assert!(warning < finished, "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn stream_json() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_stream_json")?
+		.arg("--output")
+		.arg("json")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	// The report is unaffected
+	assert!(stdout.starts_with(r#"{"success":false,"#), "{}", stdout);
+	// The runner's shell writes cargo's stdout together with stderr
+	let messages = stderr
+		.lines()
+		.filter(|line| line.starts_with(r#"{"reason":"package-checked""#))
+		.map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+		.collect::<Vec<_>>();
+	let message = |name :&str| {
+		messages
+			.iter()
+			.find(|message| message["package_id"].as_str().unwrap().starts_with(&format!("{} ", name)))
+			.unwrap_or_else(|| panic!("should stream `{}`: {}", name, stderr))
+	};
+	assert_eq!(serde_json::json!(["helper"]), message("a")["unused_deps"]["normal"]);
+	assert_eq!(serde_json::json!([]), message("b")["unused_deps"]["normal"]);
+	Ok(())
+}
+
+#[test]
+fn stream_crate_level_allow() -> CargoResult<()> {
+	let runner = runner("cargo_udeps_test_stream_crate_level_allow")?
+		.file("./a/src/lib.rs", "#![allow(unused_crate_dependencies)]\n")?;
+	let (code, stdout, stderr) = runner.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!("All deps seem to have been used.\n", stdout);
+	// Like the report, the stream leaves out what the package keeps on purpose
+	assert!(!stderr.contains("warning: a:"), "{}", stderr);
+
+	let (code, _, stderr) = runner.arg("--no-default-ignores").run_with_stderr()?;
+	assert_eq!(1, code);
+	assert!(stderr.contains("warning: a: unused dependency `helper` (normal)"), "{}", stderr);
+	Ok(())
+}
@@ -0,0 +1,129 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "target_specific"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dev-dependencies]
+used-dev = { path = "used-dev" }
+
+[target.'cfg(all())'.dev-dependencies]
+unused-dev = { path = "unused-dev" }
+used-dev = { path = "used-dev" }
+
+[target.'cfg(all())'.build-dependencies]
+unused-build = { path = "unused-build" }
+
+[target.'cfg(any())'.build-dependencies]
+other-platform = { path = "other-platform" }
+"#;
+
+static EXPECTED_CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "target_specific"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dev-dependencies]
+used-dev = { path = "used-dev" }
+
+[target.'cfg(all())'.dev-dependencies]
+used-dev = { path = "used-dev" }
+
+[target.'cfg(any())'.build-dependencies]
+other-platform = { path = "other-platform" }
+"#;
+
+static EXPECTED_HUMAN :&str = r#"unused dependencies:
+`target_specific v0.0.1 (██████████)`
+├─── dev-dependencies
+│    └─── "unused-dev" (Cargo.toml:13) (only for cfg(all()))
+└─── build-dependencies
+     └─── "unused-build" (Cargo.toml:17) (only for cfg(all()))
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.dir("./tests")?
+		.file("./src/lib.rs", "")?
+		.file("./build.rs", "fn main() {}\n")?
+		.file("./tests/used.rs", "#[test]\nfn used() {\n\tused_dev::used();\n}\n")?;
+	for name in ["used-dev", "unused-dev", "unused-build", "other-platform"] {
+		runner = runner
+			.path_dep(name, "pub fn used() {}\n")?;
+	}
+	Ok(runner.arg("--all-targets"))
+}
+
+#[test]
+fn target_specific_human() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_target_specific_human")?
+		.arg("--no-wrap")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED_HUMAN, stdout);
+	// `used-dev` is declared twice, which is fine
+	assert!(!stderr.contains("duplicate package"), "{}", stderr);
+	Ok(())
+}
+
+#[test]
+fn target_specific_apply() -> CargoResult<()> {
+	let (code, report) = runner("cargo_udeps_test_target_specific_apply")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let json = serde_json::from_str::<serde_json::Value>(&report)?;
+	let (_, unused_deps) = json["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	assert_eq!(
+		serde_json::json!({ "unused-build" : "cfg(all())", "unused-dev" : "cfg(all())" }),
+		unused_deps["platforms"],
+	);
+
+	let runner = runner("cargo_udeps_test_target_specific_apply")?
+		.file("./report.json", &report)?
+		.arg("apply")
+		.arg("report.json")
+		.arg("--allow-no-vcs");
+	let (code, _, stderr) = runner.run_with_stderr()?;
+	assert_eq!(0, code, "{}", stderr);
+	assert!(stderr.contains("Removing unused-dev from target.'cfg(all())'.dev-dependencies"), "{}", stderr);
+	assert!(stderr.contains("Removing unused-build from target.'cfg(all())'.build-dependencies"), "{}", stderr);
+	assert_eq!(EXPECTED_CARGO_TOML, runner.read_file("Cargo.toml")?);
+	Ok(())
+}
+
+#[test]
+#[cfg(not(windows))]
+fn other_platform_normal() -> CargoResult<()> {
+	let cargo_toml = format!("{}\n[target.'cfg(windows)'.dependencies]\nwindows-only = {{ path = \"windows-only\" }}\n", CARGO_TOML);
+	let (code, report) = runner("cargo_udeps_test_target_specific_other_platform")?
+		.cargo_toml(&cargo_toml)?
+		.path_dep("windows-only", "")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	let json = serde_json::from_str::<serde_json::Value>(&report)?;
+	let (_, unused_deps) = json["unused_deps"].as_object().unwrap().iter().next().unwrap();
+	// It isn't compiled for this platform, so it can't be told whether it is used
+	assert_eq!(serde_json::json!([]), unused_deps["normal"]);
+	Ok(())
+}
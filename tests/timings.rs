@@ -0,0 +1,64 @@
+mod runner;
+
+use cargo::CargoResult;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "timings"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "unused" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets"))
+}
+
+#[test]
+fn timings() -> CargoResult<()> {
+	let (code, _, stderr) = runner("cargo_udeps_test_timings")?
+		.arg("--timings")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	let timings = &stderr[stderr.find("Timings:\n").expect("should print the timings")..];
+	let lines = timings.lines().collect::<Vec<_>>();
+	assert!(lines[1].trim_start().starts_with("resolve "), "{}", timings);
+	assert!(lines[2].trim_start().starts_with("build "), "{}", timings);
+	assert!(lines[3].trim_start().starts_with("analysis "), "{}", timings);
+	assert!(lines[4].trim_start().starts_with("report "), "{}", timings);
+	assert_eq!("Slowest crates:", lines[5]);
+	let crates = &lines[6..];
+	assert!(crates.iter().any(|l| l.contains("rustc     unused v0.0.1 (unused)")), "{}", timings);
+	assert!(crates.iter().any(|l| l.contains("analysis  timings v0.0.1 (timings)")), "{}", timings);
+	Ok(())
+}
+
+#[test]
+fn timings_top() -> CargoResult<()> {
+	let (code, _, stderr) = runner("cargo_udeps_test_timings_top")?
+		.arg("--timings=1")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	let timings = &stderr[stderr.find("Slowest crates:\n").expect("should print the slowest crates")..];
+	let lines = timings.lines().collect::<Vec<_>>();
+	assert_eq!(3, lines.len(), "{}", timings);
+	assert!(lines[2].starts_with("  ... and "), "{}", timings);
+	Ok(())
+}
+
+#[test]
+fn no_timings() -> CargoResult<()> {
+	let (code, _, stderr) = runner("cargo_udeps_test_no_timings")?.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert!(!stderr.contains("Timings:"), "{}", stderr);
+	Ok(())
+}
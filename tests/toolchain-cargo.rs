@@ -0,0 +1,54 @@
+mod runner;
+
+use std::env;
+use std::process::Command;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "toolchain_cargo"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+static BUILD_RS :&str = r#"fn main() {
+	let cargo = std::env::var("CARGO").unwrap();
+	std::fs::write(concat!(env!("CARGO_MANIFEST_DIR"), "/cargo.txt"), cargo).unwrap();
+}
+"#;
+
+#[test]
+fn cargo_of_rustup_toolchain() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_toolchain_cargo")?
+		.cargo_toml(CARGO_TOML)?
+		.file("build.rs", BUILD_RS)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?;
+	let output = Command::new("rustup").args(["which", "--toolchain", "nightly", "cargo"]).output()?;
+	if !output.status.success() {
+		// Not installed with rustup
+		return Ok(());
+	}
+
+	// Like run by a cargo that doesn't set `$CARGO`
+	let udeps = Command::new(env!("CARGO_BIN_EXE_cargo-udeps"))
+		.arg("udeps")
+		.current_dir(runner.path())
+		.env_remove("CARGO")
+		.env("RUSTUP_TOOLCHAIN", "nightly")
+		.output()?;
+	assert_eq!(Some(0), udeps.status.code(), "{}", String::from_utf8_lossy(&udeps.stderr));
+	assert_eq!(String::from_utf8(output.stdout)?.trim(), runner.read_file("cargo.txt")?);
+
+	// The environment of a context that is passed in is left alone
+	env::remove_var("CARGO");
+	env::set_var("RUSTUP_TOOLCHAIN", "nightly");
+	let err = runner.run().unwrap_err();
+	assert!(err.to_string().starts_with("`$CARGO` is not set"), "{}", err);
+	Ok(())
+}
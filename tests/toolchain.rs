@@ -0,0 +1,72 @@
+mod runner;
+
+use std::env;
+use std::process::Command;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "toolchain"
+version = "0.0.1"
+edition = "2018"
+publish = false
+"#;
+
+static STABLE_RUSTC :&str = r#"#!/bin/sh
+cat <<EOF
+rustc 1.80.0 (051478957 2024-07-21)
+binary: rustc
+commit-hash: 051478957371ee0084a7c0913941d2a8c4757bb9
+commit-date: 2024-07-21
+host: x86_64-unknown-linux-gnu
+release: 1.80.0
+LLVM version: 18.1.7
+EOF
+"#;
+
+#[cfg(unix)]
+#[test]
+fn stable_rustc() -> CargoResult<()> {
+	use std::os::unix::fs::PermissionsExt;
+
+	let runner = Runner::new("cargo_udeps_test_toolchain")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.file("rustc", STABLE_RUSTC)?;
+	let rustc = runner.path().join("rustc");
+	std::fs::set_permissions(&rustc, std::fs::Permissions::from_mode(0o755))?;
+	let nightly_rustc = env::var_os("RUSTC");
+
+	// An explicitly chosen rustc is not replaced
+	env::set_var("RUSTC", &rustc);
+	let err = runner.run().unwrap_err();
+	assert!(err.to_string().contains("needs a nightly rustc"), "{}", err);
+	assert!(err.to_string().ends_with("as set by `$RUSTC`"), "{}", err);
+
+	// The one of rustup is
+	let stable_installed = Command::new("rustup")
+		.args(["which", "--toolchain", "stable", "rustc"])
+		.output()
+		.is_ok_and(|output| output.status.success());
+	if stable_installed {
+		let toolchain = env::var_os("RUSTUP_TOOLCHAIN");
+		env::remove_var("RUSTC");
+		env::set_var("RUSTUP_TOOLCHAIN", "stable");
+		let (code, stdout) = runner.run()?;
+		assert_eq!(0, code);
+		assert_eq!("All deps seem to have been used.\n", stdout);
+		match toolchain {
+			Some(toolchain) => env::set_var("RUSTUP_TOOLCHAIN", toolchain),
+			None => env::remove_var("RUSTUP_TOOLCHAIN"),
+		}
+	}
+	if let Some(nightly_rustc) = nightly_rustc {
+		env::set_var("RUSTC", nightly_rustc);
+	}
+	Ok(())
+}
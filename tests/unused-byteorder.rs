@@ -28,11 +28,11 @@ fn without_all_targets() -> CargoResult<()> {
 		r#"unused dependencies:
 `unused_byteorder v0.0.1 (██████████)`
 └─── dependencies
-     └─── "byteorder"
+     └─── "byteorder" (Cargo.toml:6)
 Note: These dependencies might be used by other targets.
       To find dependencies that are not used by any target, enable `--all-targets`.
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
@@ -55,9 +55,9 @@ fn with_all_targets() -> CargoResult<()> {
 		r#"unused dependencies:
 `unused_byteorder v0.0.1 (██████████)`
 └─── dependencies
-     └─── "byteorder"
+     └─── "byteorder" (Cargo.toml:6)
 Note: They might be false-positive.
-      For example, `cargo-udeps` cannot detect usage of crates that are only used in doc-tests.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
       To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
 "#,
 		stdout_masked,
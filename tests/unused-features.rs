@@ -0,0 +1,101 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "unused_features"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+dep = { path = "dep", optional = true }
+
+[features]
+default = ["in-cfg"]
+in-cfg = []
+in-cfg-attr = []
+in-macro = []
+in-build-script = []
+with-dep = ["dep:dep"]
+alias = ["in-cfg", "in-macro"]
+unused = []
+alias-of-unused = ["unused"]
+"#;
+
+static LIB_RS :&str = r#"#[cfg(feature = "in-cfg")]
+pub fn f() {}
+
+#[cfg_attr(feature="in-cfg-attr", derive(Debug))]
+pub struct S;
+
+pub fn g() -> bool {
+	cfg!(feature = "in-macro")
+}
+
+// A comment about feature "unused" isn't a use
+"#;
+
+static BUILD_RS :&str = r#"fn main() {
+	if std::env::var_os("CARGO_FEATURE_IN_BUILD_SCRIPT").is_some() {
+		println!("cargo:rustc-cfg=in_build_script");
+	}
+}
+"#;
+
+static DEP_CARGO_TOML :&str = r#"[package]
+name = "dep"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[features]
+never = []
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?
+		.file("./build.rs", BUILD_RS)?
+		// The features of packages in subdirectories are their own
+		.dir("./dep/src")?
+		.file("./dep/Cargo.toml", DEP_CARGO_TOML)?
+		.file("./dep/src/lib.rs", "#[cfg(feature = \"unused\")]\npub fn f() {}\n")?
+		.arg("--check-features"))
+}
+
+#[test]
+fn unused_features_human() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_unused_features_human")?.run()?;
+	// Unused features don't make the run fail
+	assert_eq!(0, code);
+	assert_eq!(
+		"All deps seem to have been used.\nfeatures that nothing uses:\n  `unused_features v0.0.1`: alias-of-unused, unused\n",
+		stdout,
+	);
+	Ok(())
+}
+
+#[test]
+fn unused_features_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_unused_features_json")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(0, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let unused_features = report["unused_features"].as_object().unwrap();
+	let features = unused_features
+		.iter()
+		.map(|(id, features)| (id.split(' ').next().unwrap().to_owned(), features.clone()))
+		.collect::<Vec<_>>();
+	assert_eq!(vec![("unused_features".to_owned(), serde_json::json!(["alias-of-unused", "unused"]))], features);
+	Ok(())
+}
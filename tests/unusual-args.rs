@@ -0,0 +1,42 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "unusual_args"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+used = { path = "used" }
+unused = { path = "unused" }
+"#;
+
+#[test]
+fn equals_sign_in_extern_paths() -> CargoResult<()> {
+	let mut runner = Runner::new("cargo_udeps_test_unusual_args_equals_sign")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "pub use used::f;\n")?;
+	for name in ["used", "unused"] {
+		runner = runner
+			.path_dep(name, "pub fn f() {}\n")?;
+	}
+	// Like `--extern used=/…/key=value/debug/deps/libused-….rmeta`
+	let target_dir = runner.path().join("key=value");
+	let (code, stdout) = runner
+		.arg("--target-dir")
+		.arg(target_dir)
+		.arg("--output")
+		.arg("count")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!("1 unused across 1 package\n", stdout);
+	Ok(())
+}
@@ -48,3 +48,25 @@ fn with_all_targets() -> CargoResult<()> {
     assert_eq!("All deps seem to have been used.\n", stdout_masked);
     Ok(())
 }
+
+#[test]
+fn json() -> CargoResult<()> {
+    let (code, stdout_masked) =
+        Runner::new("cargo_udeps_test_usesiso639dash1_json")?
+            .cargo_toml(CARGO_TOML)?
+            .dir("./src")?
+            .file("./src/lib.rs", LIB_RS)?
+            .arg("--output")
+            .arg("json")
+            .run()?;
+    assert_eq!(0, code);
+    assert_eq!(
+        concat!(
+            "{\"success\":true,\"packages_checked\":1,\"provenance\":██████████,\"resolver\":\"2\",",
+            "\"features\":{\"usesiso639dash1 0.0.1 (path+file://██████████)\":[]},",
+            "\"unused_deps\":{},\"note\":null}\n",
+        ),
+        runner::mask_provenance(&stdout_masked),
+    );
+    Ok(())
+}
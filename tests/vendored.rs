@@ -0,0 +1,114 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "vendored"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+used-vendored = "0.1.0"
+unused-vendored = "0.1.0"
+"#;
+
+static CONFIG_TOML :&str = r#"[source.crates-io]
+replace-with = "vendored-sources"
+
+[source.vendored-sources]
+directory = "vendor"
+"#;
+
+static VENDORED_CARGO_TOML :&str = r#"[package]
+name = "{}"
+version = "0.1.0"
+edition = "2018"
+"#;
+
+static CHECKSUM_JSON :&str = r#"{"files":{},"package":null}"#;
+
+static LIB_RS :&str = "pub use used_vendored;\n";
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./.cargo")?
+		.file("./.cargo/config.toml", CONFIG_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", LIB_RS)?;
+	for name in ["used-vendored", "unused-vendored"] {
+		runner = runner
+			.dir(&format!("./vendor/{}/src", name))?
+			.file(&format!("./vendor/{}/Cargo.toml", name), &VENDORED_CARGO_TOML.replace("{}", name))?
+			.file(&format!("./vendor/{}/.cargo-checksum.json", name), CHECKSUM_JSON)?
+			.file(&format!("./vendor/{}/src/lib.rs", name), "")?;
+	}
+	// Everything has to come from `vendor`
+	Ok(runner.arg("--offline"))
+}
+
+#[test]
+fn vendored() -> CargoResult<()> {
+	let (code, stdout_masked) = runner("cargo_udeps_test_vendored_vendored")?.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		r#"unused dependencies:
+`vendored v0.0.1 (██████████)`
+└─── dependencies
+     └─── "unused-vendored" (Cargo.toml:9)
+Note: These dependencies might be used by other targets.
+      To find dependencies that are not used by any target, enable `--all-targets`.
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#,
+		stdout_masked,
+	);
+	Ok(())
+}
+
+/// The unused normal dependencies and the registries in the JSON report.
+fn unused(stdout :&str) -> CargoResult<(serde_json::Value, serde_json::Value)> {
+	let report = serde_json::from_str::<serde_json::Value>(stdout)?;
+	let unused_deps = &report["unused_deps"]["vendored 0.0.1 (path+file://██████████)"];
+	Ok((unused_deps["normal"].clone(), unused_deps["registries"].clone()))
+}
+
+#[test]
+fn vendored_is_from_crates_io() -> CargoResult<()> {
+	// Reported as the crates.io package it replaces, rather than one of another registry
+	let (code, stdout_masked) = runner("cargo_udeps_test_vendored_crates_io")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(
+		(serde_json::json!(["unused-vendored"]), serde_json::Value::Null),
+		unused(&stdout_masked)?,
+	);
+
+	let (code, stdout_masked) = runner("cargo_udeps_test_vendored_source_crates_io")?
+		.arg("--output")
+		.arg("json")
+		.arg("--source")
+		.arg("crates-io")
+		.run()?;
+	assert_eq!(1, code);
+	assert_eq!(serde_json::json!(["unused-vendored"]), unused(&stdout_masked)?.0);
+
+	// The directory source that replaces crates.io doesn't count as a path
+	let (code, stdout_masked, stderr) = runner("cargo_udeps_test_vendored_source_path")?
+		.arg("--output")
+		.arg("json")
+		.arg("--source")
+		.arg("path")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!(serde_json::Value::Null, unused(&stdout_masked)?.0);
+	assert!(stderr.contains("Not reporting `unused-vendored` (Normal), it isn't from a `--source`"), "{}", stderr);
+	Ok(())
+}
@@ -0,0 +1,59 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "verbosity"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+dep = { path = "dep" }
+"#;
+
+fn runner(prefix :&str, lib_rs :&str) -> CargoResult<Runner> {
+	Ok(Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", lib_rs)?
+		.path_dep("dep", "pub fn f() {}\n")?)
+}
+
+#[test]
+fn quiet_success() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_verbosity_quiet_success", "pub use dep::f;\n")?
+		.arg("-q")
+		.run_with_stderr()?;
+	assert_eq!(0, code);
+	assert_eq!("", stdout);
+	assert_eq!("", stderr);
+	Ok(())
+}
+
+#[test]
+fn quiet_failure() -> CargoResult<()> {
+	let (code, stdout, stderr) = runner("cargo_udeps_test_verbosity_quiet_failure", "")?
+		.arg("-q")
+		.run_with_stderr()?;
+	assert_eq!(1, code);
+	assert!(stdout.starts_with("unused dependencies:\n"), "{}", stdout);
+	assert_eq!("", stderr);
+	Ok(())
+}
+
+#[test]
+fn loading_details_only_when_verbose() -> CargoResult<()> {
+	let (_, _, stderr) = runner("cargo_udeps_test_verbosity_normal", "")?.run_with_stderr()?;
+	assert!(!stderr.contains("Loading depinfo"), "{}", stderr);
+
+	let (_, _, stderr) = runner("cargo_udeps_test_verbosity_verbose", "")?.arg("-v").run_with_stderr()?;
+	assert!(stderr.contains("info: Loading depinfo from"), "{}", stderr);
+	assert!(stderr.contains("Running `"), "{}", stderr);
+	Ok(())
+}
@@ -0,0 +1,86 @@
+mod runner;
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+
+[package]
+name = "watch"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+dep = { path = "dep" }
+"#;
+
+#[test]
+fn watch() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_watch")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("dep", "pub fn f() {}\n")?;
+	// The watch only ends when the process is stopped, so it runs in a process of its own
+	let mut child = Command::new(env!("CARGO_BIN_EXE_cargo-udeps"))
+		.args(["udeps", "--watch"])
+		.current_dir(runner.path())
+		.env_remove("COLUMNS")
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()?;
+	let (sender, receiver) = mpsc::channel();
+	let stdout = BufReader::new(child.stdout.take().unwrap());
+	let stderr = BufReader::new(child.stderr.take().unwrap());
+	for lines in [Box::new(stdout) as Box<dyn BufRead + Send>, Box::new(stderr)] {
+		let sender = sender.clone();
+		thread::spawn(move || {
+			for line in lines.lines().map_while(Result::ok) {
+				if sender.send(line).is_err() {
+					break;
+				}
+			}
+		});
+	}
+	let next_line = |pred :&dyn Fn(&str) -> bool| loop {
+		match receiver.recv_timeout(Duration::from_secs(300)) {
+			Ok(line) if pred(&line) => return Some(line),
+			Ok(_) => (),
+			Err(_) => return None,
+		}
+	};
+	let is_report = |line :&str| line.starts_with("unused dependencies:") || line.starts_with("All deps");
+	// Changes are only noticed once the watch has started
+	let is_watching = |line :&str| line.starts_with("info: Watching");
+
+	let first = next_line(&is_report);
+	next_line(&is_watching);
+	// Kept, as dropping the runner removes the directory
+	let _runner = runner.file("./src/lib.rs", "pub use dep::f;\n")?;
+	let second = next_line(&is_report);
+	child.kill()?;
+	child.wait()?;
+	assert_eq!(Some("unused dependencies:"), first.as_deref());
+	assert_eq!(Some("All deps seem to have been used."), second.as_deref());
+	Ok(())
+}
+
+#[test]
+fn watch_conflicts_with_fix() -> CargoResult<()> {
+	let err = Runner::new("cargo_udeps_test_watch_fix")?
+		.arg("--watch")
+		.arg("--fix")
+		.run()
+		.expect_err("should conflict");
+	assert!(err.to_string().contains("cannot be used with"), "{}", err);
+	Ok(())
+}
@@ -0,0 +1,66 @@
+mod runner;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+members = ["app", "core", "testing", "orphan"]
+"#;
+
+static APP_CARGO_TOML :&str = r#"[package]
+name = "app"
+version = "0.0.1"
+edition = "2021"
+publish = false
+
+[dependencies]
+core = { path = "../core" }
+
+[dev-dependencies]
+testing = { path = "../testing" }
+"#;
+
+fn runner(prefix :&str) -> CargoResult<Runner> {
+	let mut runner = Runner::new(prefix)?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./app/src")?
+		.file("./app/Cargo.toml", APP_CARGO_TOML)?
+		.file("./app/src/main.rs", "fn main() {\n\tcore::f();\n}\n\n#[test]\nfn t() {\n\ttesting::f();\n}\n")?;
+	for name in ["core", "testing", "orphan"] {
+		runner = runner
+			.path_dep(name, "pub fn f() {}\n")?;
+	}
+	Ok(runner.arg("--workspace").arg("--all-targets").arg("--check-workspace-members"))
+}
+
+#[test]
+fn workspace_members_human() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_workspace_members_human")?.run()?;
+	// Unused members don't make the run fail
+	assert_eq!(0, code);
+	assert_eq!(
+		"All deps seem to have been used.\nworkspace members that nothing depends on:\n  `orphan v0.0.1`\n",
+		stdout,
+	);
+	Ok(())
+}
+
+#[test]
+fn workspace_members_json() -> CargoResult<()> {
+	let (code, stdout) = runner("cargo_udeps_test_workspace_members_json")?
+		.arg("--output")
+		.arg("json")
+		.run()?;
+	assert_eq!(0, code);
+	let report = serde_json::from_str::<serde_json::Value>(&stdout)?;
+	let members = report["unused_members"]
+		.as_array()
+		.unwrap()
+		.iter()
+		.map(|id| id.as_str().unwrap().split(' ').take(2).collect::<Vec<_>>().join(" "))
+		.collect::<Vec<_>>();
+	assert_eq!(vec!["orphan 0.0.1"], members);
+	Ok(())
+}
@@ -0,0 +1,151 @@
+mod runner;
+
+use std::env;
+use std::process::Command;
+
+use cargo::CargoResult;
+use pretty_assertions::assert_eq;
+
+use crate::runner::Runner;
+
+static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "wrap"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "unused" }
+unused-too = { path = "unused-too" }
+[package.metadata.cargo-udeps.policy]
+unused = { severity = "warn" }
+"#;
+
+static EXPECTED_WRAPPED :&str = r#"unused dependencies:
+`wrap v0.0.1
+  ████…████
+└─── dependencies
+     ├─── "unused"
+     │     (Cargo.toml:8)
+     │     (warning
+     │     only)
+     └─── "unused-too"
+           (Cargo.toml:9)
+Note: They might be
+      false-positive.
+      For example,
+      crates that
+      are only used
+      in doc-tests
+      are only
+      detected with
+      `--doctests`.
+      To ignore some
+      dependencies,
+      write
+      `package…ignore`
+      in Cargo.toml.
+"#;
+
+static EXPECTED_NOT_WRAPPED :&str = r#"unused dependencies:
+`wrap v0.0.1 (██████████)`
+└─── dependencies
+     ├─── "unused" (Cargo.toml:8) (warning only)
+     └─── "unused-too" (Cargo.toml:9)
+Note: They might be false-positive.
+      For example, crates that are only used in doc-tests are only detected with `--doctests`.
+      To ignore some dependencies, write `package.metadata.cargo-udeps.ignore` in Cargo.toml.
+"#;
+
+#[test]
+fn wrap() -> CargoResult<()> {
+	let runner = Runner::new("cargo_udeps_test_wrap")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.path_dep("unused-too", "")?
+		.arg("--all-targets");
+	env::set_var("COLUMNS", "20");
+
+	let (code, stdout) = runner.run()?;
+	assert_eq!(1, code);
+	// The path has been shortened to 18 characters before it could be masked
+	let word = format!("({})`", runner.path().display()).chars().collect::<Vec<_>>();
+	let shortened = format!(
+		"{}…{}",
+		word[..9].iter().collect::<String>(),
+		word[word.len() - 8..].iter().collect::<String>(),
+	);
+	assert_eq!(EXPECTED_WRAPPED, stdout.replace(&shortened, "████…████"));
+
+	let (code, stdout_masked) = runner.arg("--no-wrap").run()?;
+	assert_eq!(1, code);
+	assert_eq!(EXPECTED_NOT_WRAPPED, stdout_masked);
+	Ok(())
+}
+
+#[test]
+fn wrap_colored() -> CargoResult<()> {
+	static CARGO_TOML :&str = r#"[workspace]
+[package]
+name = "wrap_colored"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+a-very-long-dependency-name = { path = "a-very-long-dependency-name" }
+"#;
+
+	let runner = Runner::new("cargo_udeps_test_wrap_colored")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./src")?
+		.file("./src/lib.rs", "")?
+		.path_dep("a-very-long-dependency-name", "")?;
+	// The shell of the tests never supports color
+	let output = Command::new(env!("CARGO_BIN_EXE_cargo-udeps"))
+		.args(["udeps", "--all-targets"])
+		.current_dir(runner.path())
+		.env_remove("NO_COLOR")
+		.env("COLUMNS", "20")
+		.env("CARGO_TERM_COLOR", "always")
+		.output()?;
+	assert_eq!(Some(1), output.status.code(), "{}", String::from_utf8_lossy(&output.stderr));
+	let stdout = String::from_utf8(output.stdout)?;
+	// Shortened to 16 characters that are shown, with the colors intact
+	assert!(stdout.contains("     └─── \x1b[1;31m\"a-very-…y-name\"\x1b[0m\n"), "{:?}", stdout);
+	Ok(())
+}
+
+#[test]
+fn wrap_location() -> CargoResult<()> {
+	static CARGO_TOML :&str = r#"[workspace]
+members = ["member-with-a-long-name"]
+"#;
+
+	static MEMBER_CARGO_TOML :&str = r#"[package]
+name = "member-with-a-long-name"
+version = "0.0.1"
+edition = "2018"
+publish = false
+[dependencies]
+unused = { path = "../unused" }
+"#;
+
+	let runner = Runner::new("cargo_udeps_test_wrap_location")?
+		.cargo_toml(CARGO_TOML)?
+		.dir("./member-with-a-long-name/src")?
+		.file("./member-with-a-long-name/Cargo.toml", MEMBER_CARGO_TOML)?
+		.file("./member-with-a-long-name/src/lib.rs", "")?
+		.path_dep("unused", "")?
+		.arg("--all-targets")
+		.arg("--path-style")
+		.arg("relative");
+	env::set_var("COLUMNS", "20");
+
+	let (code, stdout) = runner.run()?;
+	assert_eq!(1, code);
+	// Too wide, but left whole so that it can be followed
+	assert!(stdout.contains("\n           (member-with-a-long-name/Cargo.toml:7)\n"), "{}", stdout);
+	Ok(())
+}